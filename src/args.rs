@@ -1,13 +1,19 @@
 use crate::data::args::DataArgs;
+use crate::error::MintError;
 use crate::layout::args::LayoutArgs;
 use crate::output::args::OutputArgs;
-use clap::Parser;
+use crate::project::ProjectConfig;
+use clap::builder::Resettable;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Command, CommandFactory, FromArgMatches, Id, Parser};
+
+const DATASOURCE_IDS: &[&str] = &["xlsx", "postgres", "http", "json"];
+const VERSION_IDS: &[&str] = &["version", "variant", "version_matrix"];
 
 // Top-level CLI parser. Sub-sections are flattened from sub-Args structs.
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[command(
     author,
-    version,
     about = "Build flash blocks from layout files and data sources (Excel, Postgres, or REST)",
     after_help = "For more information, visit https://crates.io/crates/mint-cli"
 )]
@@ -21,3 +27,157 @@ pub struct Args {
     #[command(flatten)]
     pub output: OutputArgs,
 }
+
+impl Args {
+    /// Builds the CLI command with defaults taken from the project config.
+    ///
+    /// A default data source or version stack satisfies the usual requirement
+    /// that both are given together.
+    pub fn command_with_project(project: Option<&ProjectConfig>) -> Command {
+        let mut cmd = Args::command();
+        let Some(project) = project else {
+            return cmd;
+        };
+
+        if let Some(defines) = &project.define {
+            cmd = cmd.mut_arg("define", |a| a.default_values(defines.clone()));
+        }
+        if let Some(regions) = &project.region {
+            cmd = cmd.mut_arg("regions", |a| a.default_values(regions.clone()));
+        }
+        for (id, value) in project.defaults() {
+            cmd = cmd.mut_arg(id, |a| a.default_value(value));
+            let satisfied = if VERSION_IDS.contains(&id) {
+                DATASOURCE_IDS
+            } else if DATASOURCE_IDS.contains(&id) {
+                VERSION_IDS
+            } else {
+                &[]
+            };
+            for other in satisfied {
+                cmd = cmd.mut_arg(*other, |a| a.requires(Resettable::<Id>::Reset));
+            }
+        }
+        cmd
+    }
+
+    /// Extracts args from matches built by [`Args::command_with_project`],
+    /// dropping project defaults that a command-line flag replaces.
+    pub fn from_project_matches(matches: &ArgMatches) -> Result<Self, MintError> {
+        let mut args =
+            Args::from_arg_matches(matches).map_err(|e| MintError::ProjectConfig(e.to_string()))?;
+        let from_cli = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+        if DATASOURCE_IDS.iter().any(|id| from_cli(id)) {
+            let data = &mut args.data;
+            for (id, value) in [
+                ("xlsx", &mut data.xlsx),
+                ("postgres", &mut data.postgres),
+                ("http", &mut data.http),
+                ("json", &mut data.json),
+            ] {
+                if !from_cli(id) {
+                    *value = None;
+                }
+            }
+        }
+        if (from_cli("variant") || from_cli("version_matrix")) && !from_cli("version") {
+            args.data.version = None;
+        }
+        Ok(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli_definition_is_valid() {
+        Args::command().debug_assert();
+    }
+
+    fn parse(project: &str, argv: &[&str]) -> Result<Args, MintError> {
+        let project: ProjectConfig = toml::from_str(project).unwrap();
+        let matches = Args::command_with_project(Some(&project))
+            .try_get_matches_from(argv)
+            .map_err(|e| MintError::ProjectConfig(e.to_string()))?;
+        Args::from_project_matches(&matches)
+    }
+
+    #[test]
+    fn project_defaults_fill_unset_flags() {
+        let args = parse(
+            "xlsx = \"data.xlsx\"\nversion = \"Debug/Default\"\nformat = \"mot\"",
+            &["mint", "layout.toml"],
+        )
+        .unwrap();
+        assert_eq!(args.data.xlsx.as_deref(), Some("data.xlsx"));
+        assert_eq!(args.data.get_version_list(), ["Debug", "Default"]);
+        assert_eq!(args.output.format, crate::output::args::OutputFormat::Mot);
+    }
+
+    #[test]
+    fn command_line_flags_override_project_defaults() {
+        let args = parse(
+            "xlsx = \"data.xlsx\"\nversion = \"Default\"\nrecord_width = 16",
+            &[
+                "mint",
+                "layout.toml",
+                "--json",
+                "{}",
+                "--variant",
+                "A",
+                "--record-width",
+                "8",
+            ],
+        )
+        .unwrap();
+        assert_eq!(args.data.xlsx, None);
+        assert_eq!(args.data.json.as_deref(), Some("{}"));
+        assert_eq!(args.data.get_version_list(), ["A"]);
+        assert_eq!(args.output.record_width, 8);
+    }
+
+    #[test]
+    fn project_defines_apply_unless_given_on_command_line() {
+        let project = "define = [\"FAMILY=pro\", \"CHANNELS:int=4\"]";
+        let args = parse(project, &["mint", "layout.toml.j2"]).unwrap();
+        assert_eq!(args.layout.define.len(), 2);
+        assert_eq!(args.layout.define[0], ("FAMILY".into(), "pro".into()));
+        assert_eq!(args.layout.define[1], ("CHANNELS".into(), 4.into()));
+
+        let args = parse(project, &["mint", "layout.toml.j2", "-D", "FAMILY=lite"]).unwrap();
+        assert_eq!(args.layout.define, [("FAMILY".into(), "lite".into())]);
+    }
+
+    #[test]
+    fn version_matrix_replaces_project_version() {
+        let args = parse(
+            "json = \"{}\"\nversion = \"Default\"",
+            &[
+                "mint",
+                "layout.toml",
+                "--version-matrix",
+                "Default; VarA/Default ;VarB/Default",
+            ],
+        )
+        .unwrap();
+        assert_eq!(args.data.version, None);
+        assert_eq!(
+            args.data.get_version_matrix(),
+            ["Default", "VarA/Default", "VarB/Default"]
+        );
+        assert_eq!(args.data.get_version_list(), ["Default", "VarA", "VarB"]);
+    }
+
+    #[test]
+    fn project_source_without_version_fails_only_when_read() {
+        let args = parse("json = \"{}\"", &["mint", "layout.toml", "--fmt"]).unwrap();
+        let err = crate::data::create_data_source(&args.data)
+            .err()
+            .expect("no version")
+            .to_string();
+        assert!(err.contains("no version was given"), "{err}");
+    }
+}
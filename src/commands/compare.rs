@@ -0,0 +1,231 @@
+use super::stats::FieldChange;
+use super::{BlockBuildResult, build_bytestreams, resolve_blocks};
+use crate::args::Args;
+use crate::data::DataSource;
+use crate::error::MintError;
+use crate::layout::ScalarType;
+use crate::output::annotate::read_values;
+use crate::output::error::OutputError;
+use crate::output::image::Image;
+use crate::output::{self, CrcCheck};
+use indicatif::ProgressBar;
+
+/// Leaf whose bytes in the read-back image differ from the reference build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    pub field: String,
+    pub address: u32,
+    pub expected: Vec<u8>,
+    /// Bytes found in the image; shorter than `expected` where the image ends.
+    pub actual: Vec<u8>,
+    /// Set when `actual` is `expected` with every element byte-swapped, the
+    /// usual sign of a wrong `endianness`.
+    pub byte_swapped: Option<EndianReadings>,
+}
+
+/// Values of a field's actual bytes read in either byte order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndianReadings {
+    pub little: String,
+    pub big: String,
+}
+
+/// Result of checking one block of a read-back image.
+#[derive(Debug, Clone)]
+pub struct BlockComparison {
+    pub name: String,
+    pub start_address: u32,
+    /// Whether the image covers the whole block.
+    pub covered: bool,
+    /// Stored vs. recomputed CRC; `None` if the block has no CRC or is not covered.
+    pub crc: Option<CrcCheck>,
+    pub differences: Vec<FieldDiff>,
+}
+
+impl BlockComparison {
+    pub fn matches(&self) -> bool {
+        self.covered && self.differences.is_empty() && self.crc.is_none_or(|c| c.matches())
+    }
+}
+
+/// Maps the raw image at `--compare` (loaded at `--base-address`) onto the
+/// layout, verifying each block's CRC and diffing each field against the
+/// reference build from the given data source.
+pub fn compare(
+    args: &Args,
+    data_source: Option<&dyn DataSource>,
+) -> Result<Vec<BlockComparison>, MintError> {
+    let Some(path) = args.output.compare.as_ref() else {
+        return Ok(Vec::new());
+    };
+    let dump = std::fs::read(path)
+        .map_err(|e| OutputError::FileError(format!("failed to read {}: {}", path.display(), e)))?;
+
+    let (resolved_blocks, layouts) = resolve_blocks(&args.layout)?;
+    let results = build_bytestreams(
+        &resolved_blocks,
+        &layouts,
+        data_source,
+        &args.layout.conversion_policy(),
+        false,
+        &ProgressBar::hidden(),
+        None,
+    )?;
+
+    let mut comparisons = Vec::with_capacity(results.len());
+    for result in results {
+        let layout = &layouts[&result.block_names.file];
+        let block = &layout.blocks[&result.block_names.name];
+        let range = &result.data_range;
+
+        let start = (range.start_address as u64).checked_sub(args.output.base_address as u64);
+        let device = |len: usize| -> &[u8] {
+            let Some(start) = start.map(|s| s as usize).filter(|&s| s < dump.len()) else {
+                return &[];
+            };
+            &dump[start..(start + len).min(dump.len())]
+        };
+
+        let allocated = range.allocated_size as usize;
+        let device_block = device(allocated);
+        let covered = device_block.len() == allocated;
+        let crc = if covered {
+            output::verify_crc(
+                device_block,
+                result.payload_len,
+                &block.header,
+                &layout.settings,
+            )?
+        } else {
+            None
+        };
+
+        let mut expected = range.image();
+        let mut actual = device(expected.len()).to_vec();
+        if layout.settings.word_addressing.enabled() {
+            let word_size = layout.settings.word_addressing.word_size();
+            unswap(&mut expected, word_size);
+            unswap(&mut actual, word_size);
+        }
+        let differences = result
+            .spans
+            .iter()
+            .filter_map(|span| {
+                let want = &expected[span.offset..span.offset + span.len];
+                let got = actual.get(span.offset..).unwrap_or_default();
+                let got = &got[..span.len.min(got.len())];
+                (want != got).then(|| FieldDiff {
+                    field: span.path.clone(),
+                    address: range.start_address + span.offset as u32,
+                    expected: want.to_vec(),
+                    actual: got.to_vec(),
+                    byte_swapped: byte_swapped(want, got, span.scalar_type),
+                })
+            })
+            .collect();
+
+        comparisons.push(BlockComparison {
+            name: result.stat.name,
+            start_address: range.start_address,
+            covered,
+            crc,
+            differences,
+        });
+    }
+    Ok(comparisons)
+}
+
+/// Both readings of `actual` if it is `expected` with each element of a
+/// multi-byte type reversed.
+fn byte_swapped(expected: &[u8], actual: &[u8], scalar_type: ScalarType) -> Option<EndianReadings> {
+    let size = scalar_type.size_bytes();
+    if size == 1 || expected.len() != actual.len() || !expected.len().is_multiple_of(size) {
+        return None;
+    }
+    expected
+        .chunks(size)
+        .zip(actual.chunks(size))
+        .all(|(e, a)| e.iter().eq(a.iter().rev()))
+        .then(|| EndianReadings {
+            little: read_values(actual, scalar_type, true),
+            big: read_values(actual, scalar_type, false),
+        })
+}
+
+/// Fields, and CRCs, whose built bytes differ from the `--baseline` image.
+pub(super) fn baseline_changes(results: &[BlockBuildResult], baseline: &Image) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    for result in results {
+        let range = &result.data_range;
+        let start = range.start_address as u64;
+        let mut new = range.bytestream.clone();
+        let mut old: Vec<Option<u8>> = (0..new.len() as u64)
+            .map(|i| baseline.byte_at(start + i))
+            .collect();
+        if result.word_addressing.enabled() {
+            unswap(&mut new, result.word_addressing.word_size());
+            unswap(&mut old, result.word_addressing.word_size());
+        }
+        for span in &result.spans {
+            let want = &new[span.offset..span.offset + span.len];
+            let had = &old[span.offset..span.offset + span.len];
+            if want.iter().zip(had).any(|(w, h)| Some(*w) != *h) {
+                changes.push(FieldChange {
+                    block: result.stat.name.clone(),
+                    field: span.path.clone(),
+                    address: range.start_address + span.offset as u32,
+                    old: had.to_vec(),
+                    new: want.to_vec(),
+                });
+            }
+        }
+
+        let crc_old: Vec<Option<u8>> = (0..range.crc_bytestream.len() as u64)
+            .map(|i| baseline.byte_at(range.crc_address as u64 + i))
+            .collect();
+        if crc_old
+            .iter()
+            .zip(&range.crc_bytestream)
+            .any(|(h, w)| *h != Some(*w))
+        {
+            changes.push(FieldChange {
+                block: result.stat.name.clone(),
+                field: "crc".to_string(),
+                address: range.crc_address,
+                old: crc_old,
+                new: range.crc_bytestream.clone(),
+            });
+        }
+    }
+    changes
+}
+
+/// Reverses the word-addressing byte swap so spans index the layout order.
+pub(super) fn unswap<T>(bytes: &mut [T], word_size: u32) {
+    for chunk in bytes.chunks_exact_mut(word_size as usize) {
+        chunk.reverse();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swapped_elements_are_read_in_both_orders() {
+        let readings = byte_swapped(&[0xFE, 0xFF], &[0xFF, 0xFE], ScalarType::I16).unwrap();
+        assert_eq!(
+            (readings.little.as_str(), readings.big.as_str()),
+            ("-257", "-2")
+        );
+
+        let one = 1.0f32.to_le_bytes();
+        let mut swapped = one;
+        swapped.reverse();
+        let readings = byte_swapped(&one, &swapped, ScalarType::F32).unwrap();
+        assert_eq!(readings.big, "1");
+
+        assert!(byte_swapped(&[1, 2, 3, 4], &[2, 1, 3, 5], ScalarType::U16).is_none());
+        assert!(byte_swapped(&[1, 2], &[2, 1], ScalarType::U8).is_none());
+    }
+}
@@ -0,0 +1,23 @@
+use super::graph::BuildGraph;
+use super::{block_key, resolve_blocks};
+use crate::args::Args;
+use crate::error::MintError;
+use crate::layout::contract;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// JSON Schema of the data source names the selected blocks, and the blocks they
+/// reference, read. See [`contract::data_contract`].
+pub fn data_contract(args: &Args) -> Result<Value, MintError> {
+    let (resolved_blocks, layouts) = resolve_blocks(&args.layout)?;
+    let graph = BuildGraph::new(&resolved_blocks, &layouts)?;
+
+    // Requested blocks first, then the blocks they reference.
+    let mut seen = HashSet::new();
+    let blocks = resolved_blocks
+        .iter()
+        .chain(graph.waves().iter().flatten())
+        .filter(|b| seen.insert(block_key(&b.file, &b.name)))
+        .map(|b| (b.label.as_str(), &layouts[&b.file].blocks[&b.name]));
+    Ok(contract::data_contract(blocks))
+}
@@ -0,0 +1,174 @@
+use super::{BlockKey, ResolvedBlock, block_key};
+use crate::layout::block::Config;
+use crate::layout::error::LayoutError;
+use std::collections::{HashMap, HashSet};
+
+/// Blocks to build, with the blocks they reference, ordered into waves.
+///
+/// A block references another through an embedded `block`, `crc_of`, or a
+/// field reference. Every reference of a block in a wave is in an earlier wave,
+/// so the blocks of one wave are independent and can be built in parallel.
+pub(super) struct BuildGraph {
+    waves: Vec<Vec<ResolvedBlock>>,
+    dependencies: HashMap<BlockKey, Vec<BlockKey>>,
+}
+
+impl BuildGraph {
+    /// Adds the blocks `blocks` reference, transitively, and orders them all.
+    pub(super) fn new(
+        blocks: &[ResolvedBlock],
+        layouts: &HashMap<String, Config>,
+    ) -> Result<Self, LayoutError> {
+        let mut all = blocks.to_vec();
+        let mut seen: HashSet<BlockKey> = all.iter().map(|b| block_key(&b.file, &b.name)).collect();
+        let mut dependencies: HashMap<BlockKey, Vec<BlockKey>> = HashMap::new();
+        let mut i = 0;
+        while i < all.len() {
+            let file = all[i].file.clone();
+            let block = layouts[&file]
+                .blocks
+                .get(&all[i].name)
+                .ok_or_else(|| LayoutError::BlockNotFound(format!("{}@{}", all[i].name, file)))?;
+            let deps = block.dependencies();
+            for dep in &deps {
+                if seen.insert(block_key(&file, dep)) {
+                    all.push(ResolvedBlock {
+                        label: dep.clone(),
+                        name: dep.clone(),
+                        file: file.clone(),
+                    });
+                }
+            }
+            dependencies.insert(
+                block_key(&file, &all[i].name),
+                deps.iter().map(|d| block_key(&file, d)).collect(),
+            );
+            i += 1;
+        }
+
+        let mut waves = Vec::new();
+        let mut placed: HashSet<BlockKey> = HashSet::new();
+        let mut pending = all;
+        while !pending.is_empty() {
+            let (ready, waiting): (Vec<_>, Vec<_>) = pending.into_iter().partition(|b| {
+                dependencies[&block_key(&b.file, &b.name)]
+                    .iter()
+                    .all(|dep| placed.contains(dep))
+            });
+            if ready.is_empty() {
+                let names: Vec<&str> = in_cycles(&waiting, &dependencies)
+                    .map(|b| b.label.as_str())
+                    .collect();
+                return Err(LayoutError::DependencyCycle(names.join(", ")));
+            }
+            placed.extend(ready.iter().map(|b| block_key(&b.file, &b.name)));
+            waves.push(ready);
+            pending = waiting;
+        }
+        Ok(Self {
+            waves,
+            dependencies,
+        })
+    }
+
+    /// Blocks grouped into build order; see [`BuildGraph`].
+    pub(super) fn waves(&self) -> &[Vec<ResolvedBlock>] {
+        &self.waves
+    }
+
+    /// Blocks that `block` references.
+    pub(super) fn dependencies(&self, block: &ResolvedBlock) -> &[BlockKey] {
+        &self.dependencies[&block_key(&block.file, &block.name)]
+    }
+}
+
+/// Narrows blocks that could not be ordered to those on a cycle, dropping blocks
+/// that only wait on one.
+fn in_cycles<'a>(
+    blocks: &'a [ResolvedBlock],
+    dependencies: &HashMap<BlockKey, Vec<BlockKey>>,
+) -> impl Iterator<Item = &'a ResolvedBlock> {
+    let mut remaining: HashSet<BlockKey> =
+        blocks.iter().map(|b| block_key(&b.file, &b.name)).collect();
+    loop {
+        let referenced: HashSet<&BlockKey> = remaining
+            .iter()
+            .flat_map(|key| &dependencies[key])
+            .filter(|dep| remaining.contains(*dep))
+            .collect();
+        let unreferenced: Vec<BlockKey> = remaining
+            .iter()
+            .filter(|key| !referenced.contains(key))
+            .cloned()
+            .collect();
+        if unreferenced.is_empty() {
+            break;
+        }
+        for key in unreferenced {
+            remaining.remove(&key);
+        }
+    }
+    blocks
+        .iter()
+        .filter(move |b| remaining.contains(&block_key(&b.file, &b.name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(text: &str) -> HashMap<String, Config> {
+        let config: Config = toml::from_str(text).unwrap();
+        HashMap::from([("a.toml".to_string(), config)])
+    }
+
+    fn resolved(name: &str) -> ResolvedBlock {
+        ResolvedBlock {
+            name: name.to_string(),
+            file: "a.toml".to_string(),
+            label: name.to_string(),
+        }
+    }
+
+    fn block(name: &str, refs: &[&str]) -> String {
+        let mut text = format!(
+            "[{name}.header]\nstart_address = 0\nlength = 4\n[{name}.data]\nid = {{ value = 0, type = \"u8\" }}\n"
+        );
+        for r in refs {
+            text.push_str(&format!("{r} = {{ block = \"{r}\" }}\n"));
+        }
+        text
+    }
+
+    #[test]
+    fn orders_references_into_earlier_waves() {
+        let text = format!(
+            "[settings]\nendianness = \"little\"\n{}{}{}",
+            block("app", &["cal", "ids"]),
+            block("cal", &["ids"]),
+            block("ids", &[])
+        );
+        let graph = BuildGraph::new(&[resolved("app")], &layout(&text)).unwrap();
+        let waves: Vec<Vec<&str>> = graph
+            .waves()
+            .iter()
+            .map(|w| w.iter().map(|b| b.name.as_str()).collect())
+            .collect();
+        assert_eq!(waves, [vec!["ids"], vec!["cal"], vec!["app"]]);
+        assert_eq!(graph.dependencies(&resolved("cal")).len(), 1);
+    }
+
+    #[test]
+    fn cycle_error_names_only_blocks_on_the_cycle() {
+        let text = format!(
+            "[settings]\nendianness = \"little\"\n{}{}{}",
+            block("app", &["a"]),
+            block("a", &["b"]),
+            block("b", &["a"])
+        );
+        let err = BuildGraph::new(&[resolved("app")], &layout(&text))
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "Block dependency cycle between: a, b.");
+    }
+}
@@ -0,0 +1,39 @@
+use crate::error::MintError;
+use crate::shell::shell_command;
+
+/// Runs `command` through the platform shell with `env` added to its
+/// environment. A non-zero exit status is an error.
+pub fn run_hook(command: &str, env: &[(&str, String)]) -> Result<(), MintError> {
+    let status = shell_command(command)
+        .envs(env.iter().map(|(k, v)| (k, v)))
+        .status()
+        .map_err(|e| MintError::Hook {
+            command: command.to_string(),
+            message: e.to_string(),
+        })?;
+    if !status.success() {
+        return Err(MintError::Hook {
+            command: command.to_string(),
+            message: status.to_string(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_hook_passes_env_and_reports_failure() {
+        run_hook(
+            "test \"$MINT_OUTPUT\" = fw.hex",
+            &[("MINT_OUTPUT", "fw.hex".into())],
+        )
+        .expect("env visible to hook");
+
+        let err = run_hook("exit 3", &[]).unwrap_err().to_string();
+        assert!(err.contains("exit 3"), "{err}");
+        assert!(err.contains("exit status: 3"), "{err}");
+    }
+}
@@ -0,0 +1,200 @@
+use super::compare::unswap;
+use super::{build_bytestreams, resolve_blocks};
+use crate::args::Args;
+use crate::data::error::DataError;
+use crate::data::{DataSource, SourceKind};
+use crate::error::MintError;
+use crate::layout::settings::Endianness;
+use crate::layout::value::{DataValue, ValueSource};
+use crate::layout::{LeafEntry, ScalarType, SizeSource};
+use crate::output::annotate::read_raw;
+use crate::output::error::OutputError;
+use crate::output::image::Image;
+use indicatif::ProgressBar;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Reads the value of every `name` leaf from the image at `path`, keyed by
+/// data source name, for `--image-source`.
+///
+/// Leaves with `transform` or `map`, and `names` or `bitmap` sources, are not
+/// read back; `base` provides their values, and the size of any field that
+/// depends on them. With `--image-schema`, each versioned block is read
+/// through its migration from that version.
+pub fn image_values(
+    args: &Args,
+    path: &Path,
+    base: Option<&dyn DataSource>,
+) -> Result<HashMap<String, Value>, MintError> {
+    let image = Image::read(path)?;
+    let (resolved_blocks, layouts) = resolve_blocks(&args.layout)?;
+
+    let mut shapes = HashMap::new();
+    for layout in layouts.values() {
+        for block in layout.blocks.values() {
+            for (_, leaf) in block.leaves()? {
+                if let Some(name) = leaf.image_name() {
+                    shapes.insert(name.to_string(), leaf.source_size().0);
+                }
+            }
+        }
+    }
+    let shape_source = ShapeSource {
+        shapes: &shapes,
+        base: base.map(|b| Box::new(b) as Box<dyn DataSource>),
+    };
+    let results = build_bytestreams(
+        &resolved_blocks,
+        &layouts,
+        Some(&shape_source),
+        &args.layout.conversion_policy(),
+        false,
+        &ProgressBar::hidden(),
+        None,
+    )?;
+
+    let mut values = HashMap::new();
+    for result in results {
+        let block = &layouts[&result.block_names.file].blocks[&result.block_names.name];
+        let leaves: HashMap<String, &LeafEntry> = block.leaves()?.into_iter().collect();
+        let migration = match args.data.image_schema {
+            Some(version) => block.migration(version)?,
+            None => None,
+        };
+        let start = result.data_range.start_address as u64;
+        let little = matches!(result.endianness, Endianness::Little);
+
+        for span in &result.spans {
+            let Some((leaf, name)) = leaves
+                .get(&span.path)
+                .and_then(|leaf| Some((leaf, leaf.image_name()?)))
+            else {
+                continue;
+            };
+            let (offset, field_path) = match migration {
+                Some(migration) => match migration.offset(&span.path, span.offset) {
+                    Some(offset) => (offset, migration.old_path(&span.path)),
+                    None => continue,
+                },
+                None => (span.offset, span.path.as_str()),
+            };
+            let address = start + offset as u64;
+            // Fields span whole words, so each can be unswapped on its own.
+            let mut field: Vec<Option<u8>> = (0..span.len as u64)
+                .map(|i| image.byte_at(address + i))
+                .collect();
+            if result.word_addressing.enabled() {
+                unswap(&mut field, result.word_addressing.word_size());
+            }
+            let field: Vec<u8> = field.into_iter().collect::<Option<_>>().ok_or_else(|| {
+                OutputError::FileError(format!(
+                    "{} does not cover field '{}' at 0x{:X}",
+                    path.display(),
+                    field_path,
+                    address
+                ))
+            })?;
+            let decoded = field
+                .chunks(span.scalar_type.size_bytes())
+                .map(|chunk| decode(chunk, span.scalar_type, little))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| {
+                    OutputError::FileError(format!(
+                        "field '{}' at 0x{:X} holds a NaN or infinite value",
+                        field_path, address
+                    ))
+                })?;
+            values.insert(name.to_string(), leaf.source_value(decoded));
+        }
+    }
+    Ok(values)
+}
+
+/// JSON value of one element; `None` for a float JSON cannot hold.
+fn decode(chunk: &[u8], scalar_type: ScalarType, little: bool) -> Option<Value> {
+    let raw = read_raw(chunk, little);
+    let bits = chunk.len() as u32 * 8;
+    match scalar_type {
+        ScalarType::F32 => {
+            serde_json::Number::from_f64(f32::from_bits(raw as u32).into()).map(Value::Number)
+        }
+        ScalarType::F64 => serde_json::Number::from_f64(f64::from_bits(raw)).map(Value::Number),
+        t if t.is_signed() => Some(Value::from(((raw << (64 - bits)) as i64) >> (64 - bits))),
+        _ => Some(Value::from(raw)),
+    }
+}
+
+/// Stands in for the data source while fields are located: each name read
+/// from the image gets zeros in the shape of its leaf, and any other name
+/// comes from `base`.
+struct ShapeSource<'a> {
+    shapes: &'a HashMap<String, Option<SizeSource>>,
+    base: Option<Box<dyn DataSource + 'a>>,
+}
+
+impl ShapeSource<'_> {
+    fn base(&self, name: &str) -> Result<&dyn DataSource, DataError> {
+        self.base.as_deref().ok_or_else(|| {
+            DataError::MiscError(format!(
+                "'{}' is not read from --image-source and no data source provides it",
+                name
+            ))
+        })
+    }
+}
+
+impl DataSource for ShapeSource<'_> {
+    fn retrieve_single_value(&self, name: &str) -> Result<DataValue, DataError> {
+        match self.shapes.get(name) {
+            Some(_) => Ok(DataValue::U64(0)),
+            None => self.base(name)?.retrieve_single_value(name),
+        }
+    }
+
+    fn retrieve_1d_array_or_string(&self, name: &str) -> Result<ValueSource, DataError> {
+        match self.shapes.get(name) {
+            Some(Some(SizeSource::OneD(len))) => {
+                Ok(ValueSource::Array(vec![DataValue::U64(0); *len]))
+            }
+            Some(_) => Ok(ValueSource::Single(DataValue::U64(0))),
+            None => self.base(name)?.retrieve_1d_array_or_string(name),
+        }
+    }
+
+    fn retrieve_1d_array(&self, name: &str) -> Result<ValueSource, DataError> {
+        match self.shapes.get(name) {
+            Some(_) => self.retrieve_1d_array_or_string(name),
+            None => self.base(name)?.retrieve_1d_array(name),
+        }
+    }
+
+    fn retrieve_2d_array(&self, name: &str) -> Result<Vec<Vec<DataValue>>, DataError> {
+        match self.shapes.get(name) {
+            Some(Some(SizeSource::TwoD([rows, cols]))) => {
+                Ok(vec![vec![DataValue::U64(0); *cols]; *rows])
+            }
+            Some(_) => Ok(vec![vec![DataValue::U64(0)]]),
+            None => self.base(name)?.retrieve_2d_array(name),
+        }
+    }
+
+    fn retrieve_map(&self, table: &str) -> Result<HashMap<String, DataValue>, DataError> {
+        self.base(table)?.retrieve_map(table)
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.base.as_ref().map(|b| b.names()).unwrap_or_default()
+    }
+
+    fn select(&self, source: SourceKind) -> Result<Box<dyn DataSource + '_>, DataError> {
+        let base = match &self.base {
+            Some(base) => Some(base.select(source)?),
+            None => None,
+        };
+        Ok(Box::new(ShapeSource {
+            shapes: self.shapes,
+            base,
+        }))
+    }
+}
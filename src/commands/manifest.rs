@@ -0,0 +1,182 @@
+use super::{build, open_data_source};
+use crate::args::Args;
+use crate::data::{self, DataSource};
+use crate::error::MintError;
+use crate::output::checksums::sha256_hex;
+use crate::project::ProjectConfig;
+use crate::warnings;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// Record of a build written by `--manifest`, enough for `--rebuild` to run
+/// it again and check that it reproduces the same bytes.
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    mint_version: String,
+    /// Command-line arguments, without the program name.
+    args: Vec<String>,
+    /// `mint.toml` settings in effect, with unset keys left out.
+    project: Option<Map<String, Value>>,
+    /// SHA-256 of each layout and data file the build read.
+    inputs: IndexMap<String, String>,
+    /// Values fetched from an `--http` or `--postgres` source, replayed as `--json`.
+    data: Option<Map<String, Value>>,
+    /// SHA-256 of each file the build wrote.
+    outputs: IndexMap<String, String>,
+}
+
+fn hash_file(path: &Path) -> Result<String, MintError> {
+    std::fs::read(path)
+        .map(|bytes| sha256_hex(&bytes))
+        .map_err(|e| MintError::Manifest(format!("failed to read {}: {}", path.display(), e)))
+}
+
+/// Files the build reads, in a fixed order, that the manifest pins by hash.
+/// `--http` and `--postgres` configs are left out as their values are
+/// snapshotted instead.
+fn input_files(args: &Args) -> Vec<PathBuf> {
+    let json_file = |input: &Option<String>| input.clone().filter(|s| s.ends_with(".json"));
+    let mut files: Vec<PathBuf> = args
+        .layout
+        .blocks
+        .iter()
+        .map(|b| PathBuf::from(&b.file))
+        .collect();
+    files.extend(args.data.xlsx.iter().map(PathBuf::from));
+    files.extend(json_file(&args.data.json).map(PathBuf::from));
+    files.extend(json_file(&args.data.overrides).map(PathBuf::from));
+    files.extend(args.data.image_source.iter().cloned());
+    files.extend(args.output.baseline.iter().cloned());
+    let mut seen = std::collections::HashSet::new();
+    files.retain(|file| seen.insert(file.clone()));
+    files
+}
+
+/// Command line and `mint.toml` a build was started with, for `--manifest`.
+pub struct Invocation {
+    /// Command-line arguments, without the program name.
+    pub argv: Vec<String>,
+    pub project: Option<ProjectConfig>,
+}
+
+/// Writes a manifest of the build just run with `args` to `path`; `outputs`
+/// are the files the build wrote.
+pub(super) fn write_manifest(
+    path: &Path,
+    invocation: &Invocation,
+    args: &Args,
+    data_source: Option<&dyn DataSource>,
+    outputs: &[PathBuf],
+) -> Result<(), MintError> {
+    let fetched = args.data.http.is_some() || args.data.postgres.is_some();
+    let sources = [
+        args.data.xlsx.is_some(),
+        args.data.postgres.is_some(),
+        args.data.http.is_some(),
+        args.data.json.is_some(),
+    ];
+    if fetched && sources.iter().filter(|&&given| given).count() > 1 {
+        return Err(MintError::Manifest(
+            "an --http or --postgres source can only be snapshotted as the only data source"
+                .to_string(),
+        ));
+    }
+    let data = match (fetched, data_source) {
+        (true, Some(source)) => Some(data::snapshot_document(source)?),
+        _ => None,
+    };
+
+    let project = invocation.project.as_ref().map(|project| {
+        let mut map = match serde_json::to_value(project) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+        map.retain(|_, value| !value.is_null());
+        map
+    });
+    let hashes = |files: &[PathBuf]| {
+        files
+            .iter()
+            .map(|file| Ok((file.display().to_string(), hash_file(file)?)))
+            .collect::<Result<IndexMap<_, _>, MintError>>()
+    };
+    let manifest = Manifest {
+        mint_version: env!("CARGO_PKG_VERSION").to_string(),
+        args: invocation.argv.clone(),
+        project,
+        inputs: hashes(&input_files(args))?,
+        data,
+        outputs: hashes(outputs)?,
+    };
+
+    let text = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| MintError::Manifest(format!("failed to serialize manifest: {}", e)))?;
+    std::fs::write(path, text + "\n")
+        .map_err(|e| MintError::Manifest(format!("failed to write {}: {}", path.display(), e)))
+}
+
+/// Runs the build recorded in the manifest at `path` again, from the same
+/// working directory, and returns the number of outputs that came out
+/// byte-identical. Changed inputs and differing outputs are errors. Hooks
+/// are not run.
+pub fn rebuild(path: &Path) -> Result<usize, MintError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| MintError::Manifest(format!("failed to read {}: {}", path.display(), e)))?;
+    let manifest: Manifest = serde_json::from_str(&text)
+        .map_err(|e| MintError::Manifest(format!("failed to parse {}: {}", path.display(), e)))?;
+    if manifest.mint_version != env!("CARGO_PKG_VERSION") {
+        warnings::warn(format!(
+            "{} was written by mint {}; rebuilding with {}",
+            path.display(),
+            manifest.mint_version,
+            env!("CARGO_PKG_VERSION")
+        ));
+    }
+
+    let changed: Vec<&str> = manifest
+        .inputs
+        .iter()
+        .filter(|(file, hash)| hash_file(Path::new(file)).ok().as_ref() != Some(*hash))
+        .map(|(file, _)| file.as_str())
+        .collect();
+    if !changed.is_empty() {
+        return Err(MintError::Manifest(format!(
+            "inputs changed since the manifest was written: {}",
+            changed.join(", ")
+        )));
+    }
+
+    let project: Option<ProjectConfig> = manifest
+        .project
+        .map(|map| serde_json::from_value(Value::Object(map)))
+        .transpose()
+        .map_err(|e| MintError::Manifest(format!("invalid recorded mint.toml: {}", e)))?;
+    let matches = Args::command_with_project(project.as_ref())
+        .try_get_matches_from(std::iter::once("mint".to_string()).chain(manifest.args))
+        .map_err(|e| MintError::Manifest(format!("recorded arguments do not parse: {}", e)))?;
+    let mut args = Args::from_project_matches(&matches)?;
+    args.output.manifest = None;
+    args.output.pre_build = None;
+    args.output.post_build = None;
+    if let Some(data) = manifest.data {
+        args.data.json = Some(Value::Object(data).to_string());
+        args.data.http = None;
+        args.data.postgres = None;
+    }
+
+    let data_source = open_data_source(&args)?;
+    build(&args, data_source.as_deref())?;
+
+    let differing: Vec<String> = manifest
+        .outputs
+        .iter()
+        .filter(|(file, hash)| hash_file(Path::new(file)).ok().as_ref() != Some(*hash))
+        .map(|(file, _)| file.clone())
+        .collect();
+    match differing.is_empty() {
+        true => Ok(manifest.outputs.len()),
+        false => Err(MintError::RebuildMismatch(differing)),
+    }
+}
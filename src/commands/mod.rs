@@ -1,28 +1,64 @@
+mod compare;
+mod contract;
+mod graph;
+pub mod hooks;
+mod image_source;
+mod manifest;
+mod packing;
 pub mod stats;
+mod view;
 mod writer;
 
 use crate::args::Args;
-use crate::data::DataSource;
+use crate::data::{self, DataSource, TimedDataSource};
 use crate::error::MintError;
 use crate::layout;
-use crate::layout::args::BlockNames;
-use crate::layout::block::Config;
+use crate::layout::args::{BlockNames, LayoutArgs};
+use crate::layout::block::{Config, FieldSpan};
 use crate::layout::error::LayoutError;
-use crate::layout::settings::Endianness;
+use crate::layout::policy::ConversionPolicy;
+use crate::layout::refs::BlockRefs;
+use crate::layout::settings::{EccConfig, Endianness, Settings, WordAddressing};
 use crate::layout::used_values::{NoopValueSink, ValueCollector};
 use crate::output;
+use crate::output::annotate::{self, AnnotatedBlock};
+use crate::output::args::OutputFormat;
+use crate::output::ecc;
+use crate::output::emitter::{EmitterRegistry, ImageEmitter};
 use crate::output::error::OutputError;
-use crate::output::{DataRange, OutputFile};
+use crate::output::flash_script::{self, FlashTool};
+use crate::output::gdb::{self, GdbBlock};
+use crate::output::image::{self, Image};
+use crate::output::linker;
+use crate::output::regions;
+use crate::output::split;
+use crate::output::{BlockRegion, DataRange};
+use crate::visuals;
+use crate::warnings;
+use graph::BuildGraph;
+use indexmap::IndexMap;
+use indicatif::ProgressBar;
 use rayon::prelude::*;
-use stats::{BlockStat, BuildStats};
+use stats::{BlockStat, BuildStats, FieldStat, SkippedBlock};
 use std::collections::{HashMap, HashSet};
-use std::time::Instant;
-use writer::write_output;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use writer::{write_bytes, write_streamed};
+
+pub use compare::{BlockComparison, EndianReadings, FieldDiff, compare};
+pub use contract::data_contract;
+pub use image_source::image_values;
+use manifest::write_manifest;
+pub use manifest::{Invocation, rebuild};
+pub use packing::{PackingReport, PackingSuggestion, suggest_packing};
+pub use view::{ViewField, view_fields};
 
 #[derive(Debug, Clone)]
 struct ResolvedBlock {
     name: String,
     file: String,
+    /// Name used in stats and messages; `file:block` when the name is ambiguous.
+    label: String,
 }
 
 struct BlockBuildResult {
@@ -30,16 +66,31 @@ struct BlockBuildResult {
     data_range: DataRange,
     stat: BlockStat,
     used_values: Option<serde_json::Value>,
+    /// Leaf spans within the bytestream, before word-addressing swaps.
+    spans: Vec<FieldSpan>,
+    /// Payload length in bytes before CRC padding.
+    payload_len: usize,
+    /// Word size the data range bytes are swapped by.
+    word_addressing: WordAddressing,
+    endianness: Endianness,
+    /// `header.format` override for the block's `--block-out-dir` file.
+    format: Option<OutputFormat>,
+    /// ECC applied on output, with the block's padding byte.
+    ecc: Option<(EccConfig, u8)>,
 }
 
 fn resolve_blocks(
-    block_args: &[BlockNames],
+    args: &LayoutArgs,
 ) -> Result<(Vec<ResolvedBlock>, HashMap<String, Config>), LayoutError> {
+    let (block_args, profile, vars) = (&args.blocks, args.profile.as_deref(), &args.define);
     let unique_files: HashSet<String> = block_args.iter().map(|b| b.file.clone()).collect();
 
     let layouts: Result<HashMap<String, Config>, LayoutError> = unique_files
         .par_iter()
-        .map(|file| layout::load_layout(file).map(|cfg| (file.clone(), cfg)))
+        .map(|file| {
+            let cfg = layout::load_layout_with_profile(file, vars, profile)?;
+            Ok((file.clone(), cfg))
+        })
         .collect();
 
     let layouts = layouts?;
@@ -52,50 +103,212 @@ fn resolve_blocks(
                 resolved.push(ResolvedBlock {
                     name: block_name.clone(),
                     file: arg.file.clone(),
+                    label: block_name.clone(),
                 });
             }
         } else {
             resolved.push(ResolvedBlock {
                 name: arg.name.clone(),
                 file: arg.file.clone(),
+                label: arg.name.clone(),
             });
         }
     }
 
     let mut seen = HashSet::new();
-    let deduplicated: Vec<ResolvedBlock> = resolved
+    let mut deduplicated: Vec<ResolvedBlock> = resolved
         .into_iter()
         .filter(|b| seen.insert((b.file.clone(), b.name.clone())))
         .collect();
+    if args.filters_blocks() {
+        deduplicated.retain(|b| {
+            let tags = layouts[&b.file]
+                .blocks
+                .get(&b.name)
+                .map_or(&[][..], |block| &block.header.tags);
+            args.selects(&b.name, tags)
+        });
+        if deduplicated.is_empty() {
+            return Err(LayoutError::NoBlocksSelected);
+        }
+    }
+    label_name_collisions(&mut deduplicated);
 
     Ok((deduplicated, layouts))
 }
 
+/// Qualifies blocks whose name is shared across layout files as `file:block`
+/// and warns about each collision.
+fn label_name_collisions(blocks: &mut [ResolvedBlock]) {
+    let mut files_by_name: IndexMap<&str, Vec<&str>> = IndexMap::new();
+    for b in blocks.iter() {
+        files_by_name.entry(&b.name).or_default().push(&b.file);
+    }
+
+    let collisions: HashSet<String> = files_by_name
+        .iter()
+        .filter(|(_, files)| files.len() > 1)
+        .map(|(name, files)| {
+            warnings::warn(format!(
+                "Block '{}' is defined in multiple layout files ({}); reporting as file:block.",
+                name,
+                files.join(", ")
+            ));
+            name.to_string()
+        })
+        .collect();
+
+    for b in blocks.iter_mut() {
+        if collisions.contains(&b.name) {
+            b.label = format!("{}:{}", b.file, b.name);
+        }
+    }
+}
+
+type BlockKey = (String, String);
+
+fn block_key(file: &str, name: &str) -> BlockKey {
+    (file.to_string(), name.to_string())
+}
+
+/// Images of blocks already built from one layout file, in layout byte order:
+/// the block embedding one swaps it again for word addressing.
+struct BuiltRefs<'a> {
+    file: &'a str,
+    built: &'a HashMap<BlockKey, BlockBuildResult>,
+}
+
+impl BlockRefs for BuiltRefs<'_> {
+    fn image(&self, name: &str) -> Option<Vec<u8>> {
+        self.built.get(&block_key(self.file, name)).map(|r| {
+            let mut image = r.data_range.image();
+            if r.word_addressing.enabled() {
+                compare::unswap(&mut image, r.word_addressing.word_size());
+            }
+            image
+        })
+    }
+
+    fn crc(&self, name: &str) -> Option<u32> {
+        self.built
+            .get(&block_key(self.file, name))
+            .and_then(|r| r.stat.crc_value)
+    }
+}
+
+/// Builds the requested blocks and any blocks they reference, one wave of the
+/// [`BuildGraph`] at a time with the blocks of a wave in parallel.
+/// Returns results for the requested blocks only, in request order.
 fn build_bytestreams(
     blocks: &[ResolvedBlock],
     layouts: &HashMap<String, Config>,
     data_source: Option<&dyn DataSource>,
-    strict: bool,
+    policy: &ConversionPolicy,
     capture_values: bool,
+    progress: &ProgressBar,
+    mut skipped: Option<&mut Vec<SkippedBlock>>,
 ) -> Result<Vec<BlockBuildResult>, MintError> {
-    blocks
-        .par_iter()
-        .map(|resolved| {
-            build_single_bytestream(resolved, layouts, data_source, strict, capture_values)
-        })
-        .collect()
+    let graph = BuildGraph::new(blocks, layouts)?;
+    let mut built: HashMap<BlockKey, BlockBuildResult> = HashMap::new();
+    let mut failed: HashSet<BlockKey> = HashSet::new();
+
+    for wave in graph.waves() {
+        let mut ready: Vec<&ResolvedBlock> = wave.iter().collect();
+        if let Some(skipped) = skipped.as_deref_mut() {
+            let (blocked, rest): (Vec<_>, Vec<_>) = ready
+                .into_iter()
+                .partition(|b| graph.dependencies(b).iter().any(|d| failed.contains(d)));
+            for resolved in blocked {
+                let error = "depends on a block that failed".to_string();
+                failed.insert(skip_block(resolved, error, skipped));
+            }
+            ready = rest;
+        }
+
+        let results: Vec<Result<BlockBuildResult, MintError>> = ready
+            .par_iter()
+            .map(|resolved| {
+                let refs = BuiltRefs {
+                    file: &resolved.file,
+                    built: &built,
+                };
+                let result = build_single_bytestream(
+                    resolved,
+                    layouts,
+                    data_source,
+                    policy,
+                    capture_values,
+                    &refs,
+                );
+                progress.inc(1);
+                result
+            })
+            .collect();
+        for (resolved, result) in ready.into_iter().zip(results) {
+            match (result, skipped.as_deref_mut()) {
+                (Ok(result), _) => {
+                    let key = block_key(&result.block_names.file, &result.block_names.name);
+                    built.insert(key, result);
+                }
+                (Err(e), Some(skipped)) => {
+                    let error = match e {
+                        MintError::InBlock { source, .. } => source.to_string(),
+                        e => e.to_string(),
+                    };
+                    failed.insert(skip_block(resolved, error, skipped));
+                }
+                (Err(e), None) => return Err(e),
+            }
+        }
+    }
+
+    Ok(blocks
+        .iter()
+        .filter_map(|b| built.remove(&block_key(&b.file, &b.name)))
+        .collect())
+}
+
+/// Warns that `resolved` is left out for `error` and records it in `skipped`.
+fn skip_block(
+    resolved: &ResolvedBlock,
+    error: String,
+    skipped: &mut Vec<SkippedBlock>,
+) -> BlockKey {
+    warnings::warn(format!("Skipping block '{}': {}", resolved.label, error));
+    skipped.push(SkippedBlock {
+        name: resolved.label.clone(),
+        error,
+    });
+    block_key(&resolved.file, &resolved.name)
 }
 
 fn build_single_bytestream(
     resolved: &ResolvedBlock,
     layouts: &HashMap<String, Config>,
     data_source: Option<&dyn DataSource>,
-    strict: bool,
+    policy: &ConversionPolicy,
     capture_values: bool,
+    refs: &dyn BlockRefs,
 ) -> Result<BlockBuildResult, MintError> {
+    let start_time = Instant::now();
+    let in_block = |e: MintError| MintError::InBlock {
+        block_name: resolved.name.clone(),
+        layout_file: resolved.file.clone(),
+        source: Box::new(e),
+    };
+    let layout = &layouts[&resolved.file];
+    let block = &layout.blocks[&resolved.name];
+    let selected = match (block.header.data_source, data_source) {
+        (Some(kind), Some(ds)) => Some(ds.select(kind)),
+        (Some(kind), None) => Some(Err(data::not_configured(kind))),
+        (None, _) => None,
+    }
+    .transpose()
+    .map_err(|e| in_block(e.into()))?;
+    let data_source = selected.as_deref().or(data_source);
+    let timed_source = data_source.map(TimedDataSource::new);
+    let data_source = timed_source.as_ref().map(|s| s as &dyn DataSource);
     let result = (|| {
-        let layout = &layouts[&resolved.file];
-        let block = &layout.blocks[&resolved.name];
         let mut collector = ValueCollector::new();
         let mut noop = NoopValueSink;
         let value_sink = if capture_values {
@@ -104,24 +317,46 @@ fn build_single_bytestream(
             &mut noop as &mut dyn crate::layout::used_values::ValueSink
         };
 
-        let (bytestream, padding_bytes) =
-            block.build_bytestream(data_source, &layout.settings, strict, value_sink)?;
+        let built = block.build_bytestream_with_spans(
+            data_source,
+            &layout.settings,
+            policy,
+            value_sink,
+            refs,
+        )?;
 
+        let built_len = built.bytes.len();
+        let payload_len =
+            built_len.next_multiple_of(layout.settings.word_addressing.word_size() as usize);
         let data_range = output::bytestream_to_datarange(
-            bytestream,
+            built.bytes,
             &block.header,
             &layout.settings,
-            padding_bytes,
+            built.padding_count,
         )?;
+        let spans = built.spans;
 
-        let crc_value = extract_crc_value(&data_range.crc_bytestream, &layout.settings.endianness);
+        if let Some(erased) = layout.settings.erased_value {
+            // Gaps padded during the build, then the fill up to the CRC or
+            // the block end, less the CRC bytes written over padding.
+            let padded = (built.padding_count as usize + data_range.image_len())
+                .saturating_sub(built_len + data_range.crc_bytestream.len());
+            check_erased_value(resolved, padded, block.header.padding, erased);
+        }
+
+        let crc_value = extract_crc_value(&data_range.crc_bytestream, &layout.settings);
 
         let stat = BlockStat {
-            name: resolved.name.clone(),
+            name: resolved.label.clone(),
             start_address: data_range.start_address,
+            auto_placed: block.header.expressions.auto_placed,
             allocated_size: data_range.allocated_size,
             used_size: data_range.used_size,
+            prologue_size: built.prologue_len,
+            base_fallbacks: Vec::new(),
             crc_value,
+            fetch_duration: Duration::ZERO,
+            emit_duration: Duration::ZERO,
         };
 
         Ok(BlockBuildResult {
@@ -132,49 +367,298 @@ fn build_single_bytestream(
             data_range,
             stat,
             used_values: capture_values.then(|| collector.into_value()),
+            spans,
+            payload_len,
+            word_addressing: layout.settings.word_addressing,
+            endianness: layout.settings.endianness,
+            format: block.header.format,
+            ecc: layout
+                .settings
+                .ecc
+                .clone()
+                .map(|config| (config, block.header.padding)),
         })
     })();
 
-    result.map_err(|e| MintError::InBlock {
-        block_name: resolved.name.clone(),
-        layout_file: resolved.file.clone(),
-        source: Box::new(e),
-    })
+    let fetch_duration = timed_source
+        .as_ref()
+        .map_or(Duration::ZERO, |s| s.elapsed());
+    result
+        .map(|mut r| {
+            r.stat.fetch_duration = fetch_duration;
+            r.stat.base_fallbacks = timed_source
+                .as_ref()
+                .map(|s| s.base_fallbacks())
+                .unwrap_or_default();
+            r.stat.emit_duration = start_time.elapsed().saturating_sub(fetch_duration);
+            r
+        })
+        .map_err(in_block)
+}
+
+/// Warns if the block programs padding bytes other than the flash erased value.
+fn check_erased_value(resolved: &ResolvedBlock, filled: usize, padding: u8, erased: u8) {
+    if padding == erased {
+        return;
+    }
+    if filled > 0 {
+        warnings::warn(format!(
+            "Block '{}' fills {} padding bytes with 0x{:02X}, but erased flash reads 0x{:02X}",
+            resolved.label, filled, padding, erased
+        ));
+    }
 }
 
-fn extract_crc_value(crc_bytestream: &[u8], endianness: &Endianness) -> Option<u32> {
+/// CRC value stored in `crc_bytestream`, read in target byte order before the
+/// word-addressing byte swap.
+fn extract_crc_value(crc_bytestream: &[u8], settings: &Settings) -> Option<u32> {
     if crc_bytestream.len() < 4 {
         return None;
     }
-    let bytes: [u8; 4] = crc_bytestream[..4].try_into().ok()?;
-    Some(match endianness {
+    let mut bytes: [u8; 4] = crc_bytestream[..4].try_into().ok()?;
+    if settings.word_addressing.enabled() {
+        compare::unswap(&mut bytes, settings.word_addressing.word_size());
+    }
+    Some(match settings.endianness {
         Endianness::Big => u32::from_be_bytes(bytes),
         Endianness::Little => u32::from_le_bytes(bytes),
     })
 }
 
-fn output_results(results: Vec<BlockBuildResult>, args: &Args) -> Result<BuildStats, MintError> {
+/// Writes the combined output and any per-block files, appending each path
+/// written to `written`.
+fn output_results(
+    results: Vec<BlockBuildResult>,
+    args: &Args,
+    emitter: &dyn ImageEmitter,
+    written: &mut Vec<PathBuf>,
+) -> Result<BuildStats, MintError> {
+    let gdb_script = args.output.gdb_script.as_ref().map(|path| {
+        let blocks: Vec<GdbBlock> = results
+            .iter()
+            .map(|r| GdbBlock {
+                label: &r.stat.name,
+                start_address: r.data_range.start_address,
+                spans: &r.spans,
+            })
+            .collect();
+        (path, gdb::render_gdb_script(&blocks))
+    });
+
+    let annotated = args.output.annotate.as_ref().map(|path| {
+        let images: Vec<Vec<u8>> = results
+            .iter()
+            .map(|r| {
+                let mut image = r.data_range.image();
+                if r.word_addressing.enabled() {
+                    compare::unswap(&mut image, r.word_addressing.word_size());
+                }
+                image
+            })
+            .collect();
+        let blocks: Vec<AnnotatedBlock> = results
+            .iter()
+            .zip(&images)
+            .map(|(r, image)| AnnotatedBlock {
+                label: &r.stat.name,
+                start_address: r.data_range.start_address,
+                image,
+                spans: &r.spans,
+                crc_offset: (!r.data_range.crc_bytestream.is_empty())
+                    .then(|| (r.data_range.crc_address - r.data_range.start_address) as usize),
+                endianness: r.endianness,
+            })
+            .collect();
+        (path, annotate::render_annotated(&blocks))
+    });
+
+    let baseline = args
+        .output
+        .baseline
+        .as_ref()
+        .map(|path| Image::read(path))
+        .transpose()?;
+
     let mut stats = BuildStats::new();
-    let named_ranges: Vec<(String, DataRange)> = results
-        .into_iter()
-        .map(|r| {
-            stats.add_block(r.stat);
-            (r.block_names.name, r.data_range)
+    if let Some(image) = &baseline {
+        stats.changes = compare::baseline_changes(&results, image);
+    }
+    let mut named_ranges: Vec<(String, DataRange)> = Vec::with_capacity(results.len());
+    let mut crc_ranges: Vec<DataRange> = Vec::new();
+    // Per-block file format for each of `named_ranges`
+    let mut formats: Vec<OutputFormat> = Vec::with_capacity(results.len());
+    for r in results {
+        let label = r.stat.name.clone();
+        stats.fields.extend(r.spans.iter().map(|span| {
+            FieldStat {
+                block: label.clone(),
+                path: span.path.clone(),
+                type_name: span
+                    .dims
+                    .iter()
+                    .fold(span.scalar_type.layout_name().to_string(), |name, dim| {
+                        format!("{}[{}]", name, dim)
+                    }),
+                size: span.len,
+            }
+        }));
+        stats.gaps.extend(stats::padding_gaps(
+            &label,
+            r.data_range.start_address,
+            &r.spans,
+        ));
+        stats.add_block(r.stat);
+        crc_ranges.extend(r.data_range.crc_range());
+        let format = r.format.unwrap_or(args.output.format);
+        match &r.ecc {
+            None => named_ranges.push((label, r.data_range)),
+            Some((config, padding)) => {
+                let mut ranges = ecc::apply(r.data_range, config, *padding)?.into_iter();
+                named_ranges.extend(ranges.next().map(|range| (label.clone(), range)));
+                named_ranges.extend(ranges.map(|range| (format!("{}.ecc", label), range)));
+            }
+        }
+        formats.resize(named_ranges.len(), format);
+    }
+
+    check_overlaps(&named_ranges)?;
+
+    let opts = args.output.emit_options();
+    let show_progress = visuals::show_progress(named_ranges.len(), args.output.quiet);
+    let progress = visuals::progress_bar(0, "Writing", show_progress);
+    if let Some(dir) = &args.output.block_out_dir {
+        progress.inc_length(named_ranges.len() as u64);
+        for ((label, range), &format) in named_ranges.iter().zip(&formats) {
+            let ext = match format {
+                OutputFormat::Hex => "hex",
+                OutputFormat::Mot => "mot",
+            };
+            let path = dir.join(format!("{}.{}", block_file_stem(label), ext));
+            write_streamed(&path, |w| {
+                output::write_records(std::slice::from_ref(range), format, &opts, w)
+            })?;
+            written.push(path);
+            progress.inc(1);
+        }
+    }
+
+    if let Some(path) = &args.output.crc_out {
+        write_streamed(path, |w| {
+            output::write_records(&crc_ranges, args.output.format, &opts, w)
+        })?;
+        written.push(path.clone());
+    }
+
+    let regions: Vec<BlockRegion> = named_ranges
+        .iter()
+        .map(|(label, range)| BlockRegion {
+            label: label.clone(),
+            start: range.start_address,
+            length: range.allocated_size,
         })
         .collect();
 
-    check_overlaps(&named_ranges)?;
-    let ranges: Vec<DataRange> = named_ranges.into_iter().map(|(_, r)| r).collect();
-    let output_file = OutputFile {
-        ranges,
-        format: args.output.format,
-        record_width: args.output.record_width as usize,
+    let mut ranges: Vec<DataRange> = named_ranges.into_iter().map(|(_, r)| r).collect();
+    if let Some(image) = &baseline {
+        ranges = image::diff_ranges(&ranges, image, args.output.baseline_granularity as u64)?;
+    }
+    let outputs: Vec<(PathBuf, Vec<DataRange>)> = if !args.output.regions.is_empty() {
+        let routed = regions::route(ranges, &args.output.regions)?;
+        args.output
+            .regions
+            .iter()
+            .zip(routed)
+            .map(|(region, ranges)| (regions::region_path(&args.output.out, &region.name), ranges))
+            .collect()
+    } else if let Some(unit) = args.output.split {
+        let bases = [args.output.split_even_base, args.output.split_odd_base];
+        let lanes = split::split_ranges(&ranges, unit, bases);
+        split::LANES
+            .iter()
+            .zip(lanes)
+            .map(|(lane, ranges)| (split::lane_path(&args.output.out, lane), ranges))
+            .collect()
+    } else {
+        vec![(args.output.out.clone(), ranges)]
     };
+    progress.inc_length(outputs.len() as u64);
+    let sidecars = emitter.sidecars();
+    for (out, ranges) in outputs {
+        let files = std::iter::once(None).chain(sidecars.iter().map(|ext| Some(ext.as_str())));
+        for sidecar in files {
+            let path = sidecar.map_or_else(|| out.clone(), |ext| out.with_extension(ext));
+            write_streamed(&path, |w| emitter.emit(&ranges, &opts, sidecar, w))?;
+            written.push(path);
+        }
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
 
-    write_output(&output_file, &args.output)?;
+    if let Some(path) = &args.output.flash_script {
+        let tool = args
+            .output
+            .flash_tool
+            .or_else(|| FlashTool::from_path(path))
+            .ok_or_else(|| {
+                OutputError::FileError(format!(
+                    "cannot infer the flash tool from {}; use a .cmm or .jlink extension or --flash-tool",
+                    path.display()
+                ))
+            })?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        let image = args
+            .output
+            .out
+            .strip_prefix(base)
+            .unwrap_or(&args.output.out);
+        // Delta images from --baseline must not erase the unchanged bytes.
+        let erase = if args.output.baseline.is_some() {
+            &[][..]
+        } else {
+            &regions[..]
+        };
+        let script = flash_script::render_flash_script(
+            tool,
+            erase,
+            &image.to_string_lossy().replace('\\', "/"),
+            args.output.format,
+        );
+        write_bytes(script.as_bytes(), path)?;
+        written.push(path.clone());
+    }
+
+    if let Some(path) = &args.output.linker_script {
+        write_bytes(linker::render_linker_script(&regions).as_bytes(), path)?;
+        written.push(path.clone());
+    }
+
+    if let Some((path, script)) = gdb_script {
+        write_bytes(script.as_bytes(), path)?;
+        written.push(path.clone());
+    }
+
+    if let Some((path, dump)) = annotated {
+        write_bytes(dump.as_bytes(), path)?;
+        written.push(path.clone());
+    }
     Ok(stats)
 }
 
+/// File stem for a block label, with characters other than letters, digits,
+/// `-`, `_`, and `.` replaced by `_`.
+fn block_file_stem(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
 fn check_overlaps(named_ranges: &[(String, DataRange)]) -> Result<(), MintError> {
     for i in 0..named_ranges.len() {
         for j in (i + 1)..named_ranges.len() {
@@ -209,40 +693,268 @@ fn check_overlaps(named_ranges: &[(String, DataRange)]) -> Result<(), MintError>
     Ok(())
 }
 
+/// Opens the data source `args` configure, layering `--image-source` values
+/// read against the layout when one is given.
+pub fn open_data_source(args: &Args) -> Result<Option<Box<dyn DataSource>>, MintError> {
+    match &args.data.image_source {
+        Some(path) => {
+            data::create_image_source(&args.data, |source| image_values(args, path, source))
+        }
+        None => Ok(data::create_data_source(&args.data)?),
+    }
+}
+
 pub fn build(args: &Args, data_source: Option<&dyn DataSource>) -> Result<BuildStats, MintError> {
+    let registry = EmitterRegistry::default();
+    build_with_emitter(args, data_source, registry.for_format(args.output.format)?)
+}
+
+/// Like [`build`], recording `invocation` in the `--manifest` file, which
+/// is written before the post-build hook runs.
+pub fn build_recorded(
+    args: &Args,
+    data_source: Option<&dyn DataSource>,
+    invocation: &Invocation,
+) -> Result<BuildStats, MintError> {
+    let registry = EmitterRegistry::default();
+    let emitter = registry.for_format(args.output.format)?;
+    build_inner(args, data_source, emitter, Some(invocation))
+}
+
+/// Every directory the build may write to: those of the output files, and
+/// `--block-out-dir` and a per-block `--export-json` directory themselves.
+fn output_dirs(args: &Args) -> Vec<PathBuf> {
+    let out = &args.output;
+    let export_file = out.export_json.as_ref().filter(|_| !out.export_per_block);
+    let files = [
+        Some(&out.out),
+        out.crc_out.as_ref(),
+        export_file,
+        out.flash_script.as_ref(),
+        out.linker_script.as_ref(),
+        out.gdb_script.as_ref(),
+        out.annotate.as_ref(),
+        out.checksums.as_ref(),
+        out.manifest.as_ref(),
+    ];
+    let dirs = [
+        out.block_out_dir.as_ref(),
+        out.export_json.as_ref().filter(|_| out.export_per_block),
+    ];
+    files
+        .into_iter()
+        .flatten()
+        .map(|path| output::lock::output_dir(path))
+        .chain(dirs.into_iter().flatten().cloned())
+        .collect()
+}
+
+/// Like [`build`], rendering the combined output with `emitter` instead of
+/// the `--format` emitter. Per-block files stay in `--format`.
+pub fn build_with_emitter(
+    args: &Args,
+    data_source: Option<&dyn DataSource>,
+    emitter: &dyn ImageEmitter,
+) -> Result<BuildStats, MintError> {
+    build_inner(args, data_source, emitter, None)
+}
+
+fn build_inner(
+    args: &Args,
+    data_source: Option<&dyn DataSource>,
+    emitter: &dyn ImageEmitter,
+    invocation: Option<&Invocation>,
+) -> Result<BuildStats, MintError> {
     let start_time = Instant::now();
+    let _locks = output::lock::acquire(output_dirs(args), args.output.lock)?;
 
-    let (resolved_blocks, layouts) = resolve_blocks(&args.layout.blocks)?;
+    let (resolved_blocks, layouts) = resolve_blocks(&args.layout)?;
     let capture_values = args.output.export_json.is_some();
-    let mut results = build_bytestreams(
-        &resolved_blocks,
-        &layouts,
-        data_source,
-        args.layout.strict,
-        capture_values,
-    )?;
+    let show_progress = visuals::show_progress(resolved_blocks.len(), args.output.quiet);
+    let progress = visuals::progress_bar(resolved_blocks.len(), "Building", show_progress);
+    let mut skipped = Vec::new();
+    let mut build_blocks = || {
+        build_bytestreams(
+            &resolved_blocks,
+            &layouts,
+            data_source,
+            &args.layout.conversion_policy(),
+            capture_values,
+            &progress,
+            args.layout.skip_failed_blocks.then_some(&mut skipped),
+        )
+    };
+    // One thread builds blocks in a fixed order, so warnings and anything else
+    // order-sensitive come out the same on every run.
+    let mut results = if args.output.deterministic {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .map_err(|e| OutputError::FileError(format!("failed to start build thread: {}", e)))?
+            .install(build_blocks)?
+    } else {
+        build_blocks()?
+    };
+    progress.finish_and_clear();
 
+    if args.output.deny_warnings && warnings::count() > 0 {
+        return Err(MintError::WarningsDenied(warnings::count()));
+    }
+
+    let mut written = Vec::new();
     if let Some(path) = args.output.export_json.as_ref() {
-        let report = take_used_values_report(&mut results)?;
-        output::report::write_used_values_json(path, &report)?;
+        let format = args.output.export_format;
+        if args.output.export_per_block {
+            for (label, document) in take_block_documents(&mut results)? {
+                let path = path.join(format!(
+                    "{}.{}",
+                    block_file_stem(&label),
+                    format.extension()
+                ));
+                output::report::write_used_values(&path, &document, format)?;
+                written.push(path);
+            }
+        } else {
+            let report = take_used_values_report(&mut results)?;
+            output::report::write_used_values(path, &report, format)?;
+            written.push(path.clone());
+        }
+    }
+
+    let mut stats = output_results(results, args, emitter, &mut written)?;
+    stats.skipped = skipped;
+    stats.base_version = data_source.and_then(|ds| match ds.versions().as_slice() {
+        [_, .., base] => Some(base.clone()),
+        _ => None,
+    });
+
+    if let Some(path) = args.output.checksums.as_ref() {
+        output::checksums::write_checksums(path, &written)?;
+        written.push(path.clone());
     }
+    stats.outputs = written;
 
-    let mut stats = output_results(results, args)?;
+    if let Some(path) = args.output.manifest.as_ref() {
+        let invocation = invocation.ok_or_else(|| {
+            MintError::Manifest("--manifest needs the command line it records".to_string())
+        })?;
+        write_manifest(path, invocation, args, data_source, &stats.outputs)?;
+    }
 
-    stats.total_duration = start_time.elapsed();
+    if let Some(command) = args.output.post_build.as_deref() {
+        let mut env = vec![("MINT_OUTPUT", args.output.out.display().to_string())];
+        if let Some(path) = args.output.checksums.as_ref() {
+            env.push(("MINT_CHECKSUMS", path.display().to_string()));
+        }
+        if let Some(path) = args.output.manifest.as_ref() {
+            env.push(("MINT_BUILD_MANIFEST", path.display().to_string()));
+        }
+        hooks::run_hook(command, &env)?;
+    }
+
+    if args.output.deterministic {
+        stats.clear_durations();
+    } else {
+        stats.total_duration = start_time.elapsed();
+    }
     Ok(stats)
 }
 
+/// Builds once per `--version-matrix` stack from a data source loaded with
+/// every version of every stack. Returns the stats of each stack.
+pub fn build_matrix(
+    args: &Args,
+    data_source: Option<&dyn DataSource>,
+) -> Result<Vec<(String, BuildStats)>, MintError> {
+    let mut results = Vec::new();
+    for stack in args.data.get_version_matrix() {
+        let build_stack = || {
+            let versions: Vec<String> = stack.split('/').map(str::to_string).collect();
+            let source = data_source.map(|ds| ds.restack(&versions)).transpose()?;
+
+            let label = stack.replace('/', "-");
+            let mut stack_args = args.clone();
+            stack_args.data.version = Some(stack.clone());
+            stack_args.data.version_matrix = None;
+            stack_args.output.out = matrix_path(&args.output.out, &label);
+            let output = &mut stack_args.output;
+            for path in [
+                &mut output.crc_out,
+                &mut output.export_json,
+                &mut output.block_out_dir,
+                &mut output.flash_script,
+                &mut output.gdb_script,
+                &mut output.annotate,
+                &mut output.linker_script,
+                &mut output.checksums,
+            ] {
+                *path = path.as_deref().map(|p| matrix_path(p, &label));
+            }
+            build(&stack_args, source.as_deref())
+        };
+        let stats = build_stack().map_err(|e| MintError::InVersionStack {
+            stack: stack.clone(),
+            source: Box::new(e),
+        })?;
+        results.push((stack, stats));
+    }
+    Ok(results)
+}
+
+/// Output path for one matrix stack: `{version}` in `path` is replaced by
+/// `label`; otherwise `_label` is appended to the file stem.
+fn matrix_path(path: &Path, label: &str) -> PathBuf {
+    let text = path.to_string_lossy();
+    if text.contains("{version}") {
+        return PathBuf::from(text.replace("{version}", label));
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!("{}_{}.{}", stem, label, ext.to_string_lossy()),
+        None => format!("{}_{}", stem, label),
+    };
+    path.with_file_name(name)
+}
+
+fn take_used_values(result: &mut BlockBuildResult) -> Result<serde_json::Value, OutputError> {
+    result.used_values.take().ok_or_else(|| {
+        OutputError::FileError("JSON export requested but values were not captured.".to_string())
+    })
+}
+
+/// One document per block, keyed by block label, with the block's name, layout
+/// file, addresses, and CRC alongside its used values.
+fn take_block_documents(
+    results: &mut [BlockBuildResult],
+) -> Result<Vec<(String, serde_json::Value)>, MintError> {
+    let mut documents = Vec::with_capacity(results.len());
+    for result in results {
+        let mut document = serde_json::json!({
+            "block": result.block_names.name,
+            "file": result.block_names.file,
+            "start_address": result.stat.start_address,
+            "allocated_size": result.stat.allocated_size,
+            "used_size": result.stat.used_size,
+        });
+        if result.stat.prologue_size > 0 {
+            document["prologue_size"] = result.stat.prologue_size.into();
+        }
+        if let Some(crc) = result.stat.crc_value {
+            document["crc_address"] = result.data_range.crc_address.into();
+            document["crc"] = crc.into();
+        }
+        document["values"] = take_used_values(result)?;
+        documents.push((result.stat.name.clone(), document));
+    }
+    Ok(documents)
+}
+
 fn take_used_values_report(
     results: &mut [BlockBuildResult],
 ) -> Result<serde_json::Value, MintError> {
     let mut report = serde_json::Map::new();
     for result in results {
-        let value = result.used_values.take().ok_or_else(|| {
-            OutputError::FileError(
-                "JSON export requested but values were not captured.".to_string(),
-            )
-        })?;
+        let value = take_used_values(result)?;
         let file_entry = report
             .entry(result.block_names.file.clone())
             .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
@@ -263,3 +975,55 @@ fn take_used_values_report(
     }
     Ok(serde_json::Value::Object(report))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolved(name: &str, file: &str) -> ResolvedBlock {
+        ResolvedBlock {
+            name: name.to_string(),
+            file: file.to_string(),
+            label: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn matrix_path_inserts_stack_label() {
+        assert_eq!(
+            matrix_path(Path::new("out/fw.hex"), "VarA-Default"),
+            PathBuf::from("out/fw_VarA-Default.hex")
+        );
+        assert_eq!(
+            matrix_path(Path::new("out/{version}/fw.hex"), "VarA"),
+            PathBuf::from("out/VarA/fw.hex")
+        );
+    }
+
+    #[test]
+    fn block_file_stem_replaces_path_characters() {
+        assert_eq!(block_file_stem("a.toml:cal"), "a.toml_cal");
+        assert_eq!(block_file_stem("dir/app"), "dir_app");
+    }
+
+    #[test]
+    fn only_colliding_names_are_qualified() {
+        let mut blocks = vec![
+            resolved("cal", "a.toml"),
+            resolved("cal", "b.toml"),
+            resolved("app", "a.toml"),
+        ];
+        label_name_collisions(&mut blocks);
+        let labels: Vec<&str> = blocks.iter().map(|b| b.label.as_str()).collect();
+        assert_eq!(labels, ["a.toml:cal", "b.toml:cal", "app"]);
+    }
+
+    #[test]
+    fn skip_block_records_label_and_error() {
+        let mut skipped = Vec::new();
+        let key = skip_block(&resolved("cal", "a.toml"), "boom".to_string(), &mut skipped);
+        assert_eq!(key, block_key("a.toml", "cal"));
+        assert_eq!(skipped[0].name, "cal");
+        assert_eq!(skipped[0].error, "boom");
+    }
+}
@@ -0,0 +1,144 @@
+use super::{build_bytestreams, resolve_blocks};
+use crate::args::Args;
+use crate::data::DataSource;
+use crate::error::MintError;
+use crate::layout::block::FieldSpan;
+use indicatif::ProgressBar;
+
+/// Alignment padding in one block, with a reordering that needs less if one exists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackingReport {
+    pub name: String,
+    /// Bytes inserted before leaves to reach their natural alignment.
+    pub padding: usize,
+    pub suggestion: Option<PackingSuggestion>,
+}
+
+/// Top-level entry order that needs `padding` bytes of alignment padding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackingSuggestion {
+    pub order: Vec<String>,
+    pub padding: usize,
+}
+
+/// Builds each block and reports how much padding natural alignment inserted.
+///
+/// The suggested order moves whole top-level entries, most strictly aligned
+/// first; entries inside a branch keep their order. Layout files are not changed.
+pub fn suggest_packing(
+    args: &Args,
+    data_source: Option<&dyn DataSource>,
+) -> Result<Vec<PackingReport>, MintError> {
+    let (resolved_blocks, layouts) = resolve_blocks(&args.layout)?;
+    let results = build_bytestreams(
+        &resolved_blocks,
+        &layouts,
+        data_source,
+        &args.layout.conversion_policy(),
+        false,
+        &ProgressBar::hidden(),
+        None,
+    )?;
+
+    Ok(results
+        .into_iter()
+        .map(|result| packing_report(result.stat.name, &result.spans))
+        .collect())
+}
+
+/// A top-level entry as the (alignment, length) of its leaves in order.
+struct Unit {
+    key: String,
+    leaves: Vec<(usize, usize)>,
+}
+
+fn packing_report(name: String, spans: &[FieldSpan]) -> PackingReport {
+    let mut end = 0;
+    let mut padding = 0;
+    for span in spans {
+        padding += span.offset.saturating_sub(end);
+        end = span.offset + span.len;
+    }
+
+    let mut units: Vec<Unit> = Vec::new();
+    for span in spans {
+        let key = span.path.split('.').next().unwrap_or_default();
+        let leaf = (span.scalar_type.size_bytes(), span.len);
+        match units.iter_mut().find(|u| u.key == key) {
+            Some(unit) => unit.leaves.push(leaf),
+            None => units.push(Unit {
+                key: key.to_string(),
+                leaves: vec![leaf],
+            }),
+        }
+    }
+
+    units.sort_by_key(|u| std::cmp::Reverse(u.leaves.iter().map(|l| l.0).max()));
+    let packed = simulate(&units);
+    let suggestion = (packed < padding).then(|| PackingSuggestion {
+        order: units.into_iter().map(|u| u.key).collect(),
+        padding: packed,
+    });
+    PackingReport {
+        name,
+        padding,
+        suggestion,
+    }
+}
+
+/// Padding needed to place `units` in order, aligning each leaf like the builder.
+fn simulate(units: &[Unit]) -> usize {
+    let mut offset: usize = 0;
+    let mut padding = 0;
+    for &(align, len) in units.iter().flat_map(|u| &u.leaves) {
+        let aligned = offset.next_multiple_of(align);
+        padding += aligned - offset;
+        offset = aligned + len;
+    }
+    padding
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::ScalarType;
+
+    fn span(path: &str, offset: usize, scalar_type: ScalarType, len: usize) -> FieldSpan {
+        FieldSpan {
+            path: path.to_string(),
+            offset,
+            len,
+            scalar_type,
+            dims: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn suggests_strictest_alignment_first() {
+        // u8 @0, u32 @4, u8 @8, u16 @10: 3 + 1 bytes of padding.
+        let spans = [
+            span("a", 0, ScalarType::U8, 1),
+            span("b", 4, ScalarType::U32, 4),
+            span("c.x", 8, ScalarType::U8, 1),
+            span("c.y", 10, ScalarType::U16, 2),
+        ];
+        let report = packing_report("blk".to_string(), &spans);
+        assert_eq!(report.padding, 4);
+        assert_eq!(
+            report.suggestion,
+            Some(PackingSuggestion {
+                order: vec!["b".to_string(), "c".to_string(), "a".to_string()],
+                padding: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn no_suggestion_without_savings() {
+        let spans = [
+            span("b", 0, ScalarType::U32, 4),
+            span("a", 4, ScalarType::U8, 1),
+        ];
+        assert_eq!(packing_report("blk".into(), &spans).suggestion, None);
+    }
+}
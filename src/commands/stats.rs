@@ -1,12 +1,87 @@
+use crate::layout::block::FieldSpan;
+use std::path::PathBuf;
 use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub struct BlockStat {
     pub name: String,
     pub start_address: u32,
+    /// Whether `start_address` was chosen by `[settings.auto_place]`.
+    pub auto_placed: bool,
     pub allocated_size: u32,
     pub used_size: u32,
+    /// Bytes of the block's prologue, counted in `used_size`.
+    pub prologue_size: u32,
+    /// Data source names only the base version of the stack provides.
+    pub base_fallbacks: Vec<String>,
     pub crc_value: Option<u32>,
+    /// Time spent looking up values in the data source.
+    pub fetch_duration: Duration,
+    /// Time spent building the block bytes, excluding lookups.
+    pub emit_duration: Duration,
+}
+
+/// Field whose bytes differ from the `--baseline` image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub block: String,
+    /// Leaf path, or `crc` for the block CRC.
+    pub field: String,
+    pub address: u32,
+    /// Baseline bytes; `None` where the baseline has no data.
+    pub old: Vec<Option<u8>>,
+    pub new: Vec<u8>,
+}
+
+/// Leaf entry of a block, for the `--stats fields` view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldStat {
+    pub block: String,
+    pub path: String,
+    /// Layout type name, with array dimensions such as `u16[3]`.
+    pub type_name: String,
+    pub size: usize,
+}
+
+/// Alignment padding between two leaf entries of a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaddingGap {
+    pub block: String,
+    pub address: u32,
+    pub size: usize,
+    /// Entry before the gap; `None` for padding at the block start.
+    pub after: Option<String>,
+    pub before: String,
+}
+
+/// Gaps between the leaf entries of a block starting at `start_address`.
+pub fn padding_gaps(block: &str, start_address: u32, spans: &[FieldSpan]) -> Vec<PaddingGap> {
+    let mut spans: Vec<&FieldSpan> = spans.iter().collect();
+    spans.sort_by_key(|s| s.offset);
+    let mut gaps = Vec::new();
+    let mut end = 0;
+    let mut after: Option<&FieldSpan> = None;
+    for span in spans {
+        if span.offset > end {
+            gaps.push(PaddingGap {
+                block: block.to_string(),
+                address: start_address + end as u32,
+                size: span.offset - end,
+                after: after.map(|s| s.path.clone()),
+                before: span.path.clone(),
+            });
+        }
+        end = end.max(span.offset + span.len);
+        after = Some(span);
+    }
+    gaps
+}
+
+/// Block left out of the output by `--skip-failed-blocks`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedBlock {
+    pub name: String,
+    pub error: String,
 }
 
 #[derive(Debug)]
@@ -16,6 +91,19 @@ pub struct BuildStats {
     pub total_used: usize,
     pub total_duration: Duration,
     pub block_stats: Vec<BlockStat>,
+    /// Fields that differ from the baseline, in layout order.
+    pub changes: Vec<FieldChange>,
+    /// Leaf entries of every block, in layout order.
+    pub fields: Vec<FieldStat>,
+    /// Alignment padding inside every block, in address order.
+    pub gaps: Vec<PaddingGap>,
+    /// Blocks that failed to build and were left out.
+    pub skipped: Vec<SkippedBlock>,
+    /// Lowest-priority version of a stack of several, which the blocks'
+    /// `base_fallbacks` come from.
+    pub base_version: Option<String>,
+    /// Files the build wrote, in write order.
+    pub outputs: Vec<PathBuf>,
 }
 
 impl Default for BuildStats {
@@ -32,6 +120,12 @@ impl BuildStats {
             total_used: 0,
             total_duration: Duration::from_secs(0),
             block_stats: Vec::new(),
+            changes: Vec::new(),
+            fields: Vec::new(),
+            gaps: Vec::new(),
+            skipped: Vec::new(),
+            base_version: None,
+            outputs: Vec::new(),
         }
     }
 
@@ -42,6 +136,53 @@ impl BuildStats {
         self.block_stats.push(stat);
     }
 
+    /// The `n` largest leaf entries of `block`, largest first; ties keep layout order.
+    pub fn largest_fields(&self, block: &str, n: usize) -> Vec<&FieldStat> {
+        let mut fields: Vec<&FieldStat> = self.fields.iter().filter(|f| f.block == block).collect();
+        fields.sort_by_key(|f| std::cmp::Reverse(f.size));
+        fields.truncate(n);
+        fields
+    }
+
+    /// Number of values all blocks take from the base version.
+    pub fn base_fallback_count(&self) -> usize {
+        self.block_stats
+            .iter()
+            .map(|b| b.base_fallbacks.len())
+            .sum()
+    }
+
+    /// Total padding bytes between the entries of `block`.
+    pub fn padding_of(&self, block: &str) -> usize {
+        self.gaps
+            .iter()
+            .filter(|g| g.block == block)
+            .map(|g| g.size)
+            .sum()
+    }
+
+    /// Drops all timings, so stats of identical builds are identical.
+    pub fn clear_durations(&mut self) {
+        self.total_duration = Duration::ZERO;
+        for block in &mut self.block_stats {
+            block.fetch_duration = Duration::ZERO;
+            block.emit_duration = Duration::ZERO;
+        }
+    }
+
+    /// Whether durations were recorded; see [`BuildStats::clear_durations`].
+    pub fn has_timings(&self) -> bool {
+        !self.total_duration.is_zero()
+    }
+
+    /// The `n` blocks that took longest to build, slowest first.
+    pub fn slowest_blocks(&self, n: usize) -> Vec<&BlockStat> {
+        let mut blocks: Vec<&BlockStat> = self.block_stats.iter().collect();
+        blocks.sort_by_key(|b| std::cmp::Reverse(b.fetch_duration + b.emit_duration));
+        blocks.truncate(n);
+        blocks
+    }
+
     pub fn space_efficiency(&self) -> f64 {
         if self.total_allocated == 0 {
             0.0
@@ -50,3 +191,94 @@ impl BuildStats {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::ScalarType;
+
+    fn field(block: &str, path: &str, size: usize) -> FieldStat {
+        FieldStat {
+            block: block.to_string(),
+            path: path.to_string(),
+            type_name: "u8".to_string(),
+            size,
+        }
+    }
+
+    fn span(path: &str, offset: usize, len: usize) -> FieldSpan {
+        FieldSpan {
+            path: path.to_string(),
+            offset,
+            len,
+            scalar_type: ScalarType::U8,
+            dims: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn padding_gaps_name_surrounding_entries() {
+        let spans = [span("id", 2, 2), span("gain", 8, 4), span("flag", 4, 1)];
+        let gaps = padding_gaps("cal", 0x1000, &spans);
+        assert_eq!(
+            gaps.iter()
+                .map(|g| (g.address, g.size, g.after.as_deref(), g.before.as_str()))
+                .collect::<Vec<_>>(),
+            [(0x1000, 2, None, "id"), (0x1005, 3, Some("flag"), "gain"),]
+        );
+    }
+
+    #[test]
+    fn slowest_blocks_rank_by_total_duration() {
+        let mut stats = BuildStats::new();
+        for (name, fetch, emit) in [("a", 5, 1), ("b", 0, 9), ("c", 1, 1)] {
+            stats.add_block(BlockStat {
+                name: name.to_string(),
+                start_address: 0,
+                auto_placed: false,
+                allocated_size: 0,
+                used_size: 0,
+                prologue_size: 0,
+                base_fallbacks: Vec::new(),
+                crc_value: None,
+                fetch_duration: Duration::from_millis(fetch),
+                emit_duration: Duration::from_millis(emit),
+            });
+        }
+        let names: Vec<&str> = stats
+            .slowest_blocks(2)
+            .iter()
+            .map(|b| b.name.as_str())
+            .collect();
+        assert_eq!(names, ["b", "a"]);
+
+        stats.total_duration = Duration::from_millis(12);
+        assert!(stats.has_timings());
+        stats.clear_durations();
+        assert!(!stats.has_timings());
+        assert!(
+            stats
+                .slowest_blocks(3)
+                .iter()
+                .all(|b| b.emit_duration.is_zero())
+        );
+    }
+
+    #[test]
+    fn largest_fields_sorts_within_block() {
+        let mut stats = BuildStats::new();
+        stats.fields = vec![
+            field("cal", "a", 2),
+            field("cal", "b", 8),
+            field("other", "c", 64),
+            field("cal", "d", 2),
+            field("cal", "e", 1),
+        ];
+        let paths: Vec<&str> = stats
+            .largest_fields("cal", 3)
+            .iter()
+            .map(|f| f.path.as_str())
+            .collect();
+        assert_eq!(paths, ["b", "a", "d"]);
+    }
+}
@@ -0,0 +1,50 @@
+use super::{build_bytestreams, resolve_blocks};
+use crate::args::Args;
+use crate::data::DataSource;
+use crate::error::MintError;
+use indicatif::ProgressBar;
+
+/// Leaf located in the built image, for the `--view` inspector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViewField {
+    pub block: String,
+    pub path: String,
+    pub address: u64,
+    pub len: usize,
+}
+
+/// Builds each block and returns the address range of every leaf, in layout order.
+///
+/// The image itself is not rebuilt; only the layout and the data source are
+/// needed to place fields whose size comes from the data.
+pub fn view_fields(
+    args: &Args,
+    data_source: Option<&dyn DataSource>,
+) -> Result<Vec<ViewField>, MintError> {
+    let (resolved_blocks, layouts) = resolve_blocks(&args.layout)?;
+    let results = build_bytestreams(
+        &resolved_blocks,
+        &layouts,
+        data_source,
+        &args.layout.conversion_policy(),
+        false,
+        &ProgressBar::hidden(),
+        None,
+    )?;
+
+    let mut fields = Vec::new();
+    for result in results {
+        let start = result.data_range.start_address as u64;
+        for span in &result.spans {
+            // Word addressing reverses bytes within each word, but fields span
+            // whole words, so their byte ranges are unchanged.
+            fields.push(ViewField {
+                block: result.stat.name.clone(),
+                path: span.path.clone(),
+                address: start + span.offset as u64,
+                len: span.len,
+            });
+        }
+    }
+    Ok(fields)
+}
@@ -1,13 +1,37 @@
-use crate::output::OutputFile;
-use crate::output::args::OutputArgs;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
 use crate::output::error::OutputError;
 
-/// Write a single output file to the path specified in args.
-pub fn write_output(file: &OutputFile, args: &OutputArgs) -> Result<(), OutputError> {
-    let contents = file.render()?;
+/// Stream the contents `write` produces to `path`.
+pub fn write_streamed(
+    path: &Path,
+    write: impl FnOnce(&mut dyn Write) -> Result<(), OutputError>,
+) -> Result<(), OutputError> {
+    create_parent_dir(path)?;
+
+    let handle = File::create(path).map_err(|e| {
+        OutputError::FileError(format!("failed to write {}: {}", path.display(), e))
+    })?;
+    let mut out = BufWriter::new(handle);
+    write(&mut out)?;
+    out.flush().map_err(|e| {
+        OutputError::FileError(format!("failed to write {}: {}", path.display(), e))
+    })?;
+    Ok(())
+}
+
+/// Write already rendered contents to `path`.
+pub fn write_bytes(contents: &[u8], path: &Path) -> Result<(), OutputError> {
+    create_parent_dir(path)?;
+    std::fs::write(path, contents)
+        .map_err(|e| OutputError::FileError(format!("failed to write {}: {}", path.display(), e)))
+}
 
-    // Create parent directory if it doesn't exist
-    if let Some(parent) = args.out.parent()
+/// Create the parent directory of `path` if it doesn't exist.
+fn create_parent_dir(path: &Path) -> Result<(), OutputError> {
+    if let Some(parent) = path.parent()
         && !parent.as_os_str().is_empty()
     {
         std::fs::create_dir_all(parent).map_err(|e| {
@@ -18,9 +42,5 @@ pub fn write_output(file: &OutputFile, args: &OutputArgs) -> Result<(), OutputEr
             ))
         })?;
     }
-
-    std::fs::write(&args.out, contents).map_err(|e| {
-        OutputError::FileError(format!("failed to write {}: {}", args.out.display(), e))
-    })?;
     Ok(())
 }
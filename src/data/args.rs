@@ -1,6 +1,9 @@
-use clap::Args;
+use clap::{ArgGroup, Args, ValueEnum};
+use serde::Deserialize;
+use std::path::PathBuf;
 
 #[derive(Args, Debug, Clone, Default)]
+#[command(group(ArgGroup::new("datasource").multiple(true)))]
 pub struct DataArgs {
     #[arg(
         long,
@@ -19,7 +22,7 @@ pub struct DataArgs {
         value_name = "PATH or json string",
         group = "datasource",
         requires = "versions",
-        help = "Path to the JSON file or a JSON string containing the postgres configuration (url, query_template, optional data_path for nested extraction)"
+        help = "Path to the JSON file or a JSON string containing the postgres configuration (url, query_template, optional data_path for nested extraction, optional map for record arrays)"
     )]
     pub postgres: Option<String>,
 
@@ -28,7 +31,7 @@ pub struct DataArgs {
         value_name = "PATH or json string",
         group = "datasource",
         requires = "versions",
-        help = "HTTP API configuration (url with $VERSION placeholder, optional method [GET/POST], optional body with $VERSION, optional headers, optional data_path)"
+        help = "HTTP API configuration (url with $VERSION placeholder, optional method [GET/POST], optional body with $VERSION, optional headers, optional data_path, optional map)"
     )]
     pub http: Option<String>,
 
@@ -37,10 +40,17 @@ pub struct DataArgs {
         value_name = "PATH or json string",
         group = "datasource",
         requires = "versions",
-        help = "Path to JSON file or JSON string. Format: object with version names as keys, each containing an object with name:value pairs (e.g., {\"VersionName\": {\"key1\": value1, \"key2\": value2}})"
+        help = "Path to JSON file or JSON string. Format: object with version names as keys, each containing an object with name:value pairs (e.g., {\"VersionName\": {\"key1\": value1, \"key2\": value2}}). A version's \"$include\" names JSON files to merge beneath its values"
     )]
     pub json: Option<String>,
 
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "JSON file of name:value secrets for ${secret:NAME} references in Postgres and HTTP configs"
+    )]
+    pub secrets: Option<String>,
+
     #[arg(
         short = 'v',
         long,
@@ -59,20 +69,158 @@ pub struct DataArgs {
         help = "[DEPRECATED] Use --version instead. Version columns to use in priority order (separate with '/')"
     )]
     pub variant: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "STACK[;STACK...]",
+        requires = "datasource",
+        group = "versions",
+        help = "Build once per version stack (separate stacks with ';'), loading the data source once"
+    )]
+    pub version_matrix: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "datasource",
+        help = "Write the default data source's per-version values to FILE as --json data and exit without building"
+    )]
+    pub snapshot: Option<PathBuf>,
+
+    #[arg(
+        long = "override",
+        value_name = "PATH or json string",
+        help = "JSON object of name:value pairs that take priority over the data source"
+    )]
+    pub overrides: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Hex or S-record image whose field values are used under --override and over the data source"
+    )]
+    pub image_source: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "VERSION",
+        requires = "image_source",
+        help = "Schema version of the --image-source image; fields are read through each block's migration from it"
+    )]
+    pub image_schema: Option<u32>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "SEPARATOR",
+        default_value_t = DecimalSeparator::Point,
+        help = "Decimal separator for numbers written as text in the Excel workbook; comma also accepts '.' thousands separators and disables ',' as a list delimiter"
+    )]
+    pub xlsx_decimal_separator: DecimalSeparator,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        default_value_t = DateFormat::Epoch,
+        help = "Representation of Excel date cells: Unix epoch seconds, BCD date (0xYYYYMMDD), or ISO 8601 string"
+    )]
+    pub date_format: DateFormat,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "POLICY",
+        default_value_t = DuplicatePolicy::Warn,
+        help = "Handling of names defined more than once in a data source version"
+    )]
+    pub on_duplicate_name: DuplicatePolicy,
+
+    #[arg(
+        long,
+        help = "Fall back to case- and whitespace-insensitive name lookup, warning when it is used"
+    )]
+    pub normalize_names: bool,
+}
+
+/// Handling of duplicated names within one version of a data source.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Fail to load the data source.
+    Error,
+    /// Print a warning and use the first Excel row or the last JSON key.
+    #[default]
+    Warn,
+    /// Silently use the last definition.
+    LastWins,
+    /// Silently use the first definition.
+    FirstWins,
+}
+
+/// Representation of Excel date/time cells.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DateFormat {
+    /// Seconds since 1970-01-01T00:00:00 (time zone ignored).
+    #[default]
+    Epoch,
+    /// Date as binary-coded decimal digits, e.g. `0x20240315`.
+    Bcd,
+    /// ISO 8601 text, e.g. `2024-03-15` or `2024-03-15T08:30:00`.
+    Iso,
+}
+
+/// Decimal separator used when parsing numbers from text.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DecimalSeparator {
+    /// `1234.5`; lists may be comma-delimited.
+    #[default]
+    Point,
+    /// `1.234,5`; lists are space- or semicolon-delimited.
+    Comma,
 }
 
 impl DataArgs {
     /// Parses the version stack from the raw slash-separated string.
-    /// Handles fallback from deprecated --variant flag.
+    /// Handles fallback from deprecated --variant flag. With `--version-matrix`,
+    /// returns every version used by any stack, in first-seen order.
     pub fn get_version_list(&self) -> Vec<String> {
-        let raw = self.version.as_deref().or(self.variant.as_deref());
-        raw.map(|r| {
-            r.split('/')
-                .map(|name| name.trim())
-                .filter(|name| !name.is_empty())
-                .map(|name| name.to_string())
-                .collect()
-        })
-        .unwrap_or_default()
+        match self.version.as_deref().or(self.variant.as_deref()) {
+            Some(raw) => split_stack(raw),
+            None => {
+                let mut versions: Vec<String> = Vec::new();
+                for version in self
+                    .get_version_matrix()
+                    .iter()
+                    .flat_map(|s| split_stack(s))
+                {
+                    if !versions.contains(&version) {
+                        versions.push(version);
+                    }
+                }
+                versions
+            }
+        }
     }
+
+    /// Version stacks from `--version-matrix`, each as a slash-separated string.
+    pub fn get_version_matrix(&self) -> Vec<String> {
+        self.version_matrix
+            .as_deref()
+            .map(|raw| {
+                raw.split(';')
+                    .map(|stack| split_stack(stack).join("/"))
+                    .filter(|stack| !stack.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn split_stack(raw: &str) -> Vec<String> {
+    raw.split('/')
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+        .collect()
 }
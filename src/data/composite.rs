@@ -0,0 +1,82 @@
+use super::error::DataError;
+use super::{DataSource, Snapshot, SourceKind};
+use crate::layout::value::{DataValue, ValueSource};
+use std::collections::HashMap;
+
+/// Every configured data source; blocks read the first unless their header
+/// names another with `data_source`.
+pub struct CompositeDataSource {
+    sources: Vec<(SourceKind, Box<dyn DataSource>)>,
+}
+
+impl CompositeDataSource {
+    /// `sources` must not be empty; the first is the default.
+    pub(crate) fn new(sources: Vec<(SourceKind, Box<dyn DataSource>)>) -> Self {
+        assert!(!sources.is_empty(), "composite data source needs a source");
+        Self { sources }
+    }
+
+    fn default_source(&self) -> &dyn DataSource {
+        self.sources[0].1.as_ref()
+    }
+}
+
+impl DataSource for CompositeDataSource {
+    fn retrieve_single_value(&self, name: &str) -> Result<DataValue, DataError> {
+        self.default_source().retrieve_single_value(name)
+    }
+
+    fn retrieve_1d_array_or_string(&self, name: &str) -> Result<ValueSource, DataError> {
+        self.default_source().retrieve_1d_array_or_string(name)
+    }
+
+    fn retrieve_1d_array(&self, name: &str) -> Result<ValueSource, DataError> {
+        self.default_source().retrieve_1d_array(name)
+    }
+
+    fn retrieve_2d_array(&self, name: &str) -> Result<Vec<Vec<DataValue>>, DataError> {
+        self.default_source().retrieve_2d_array(name)
+    }
+
+    fn retrieve_map(&self, table: &str) -> Result<HashMap<String, DataValue>, DataError> {
+        self.default_source().retrieve_map(table)
+    }
+
+    fn versions(&self) -> Vec<String> {
+        self.default_source().versions()
+    }
+
+    fn version_of(&self, name: &str) -> Option<String> {
+        self.default_source().version_of(name)
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.default_source().names()
+    }
+
+    fn restack(&self, versions: &[String]) -> Result<Box<dyn DataSource>, DataError> {
+        let sources = self
+            .sources
+            .iter()
+            .map(|(kind, source)| Ok((*kind, source.restack(versions)?)))
+            .collect::<Result<_, DataError>>()?;
+        Ok(Box::new(Self::new(sources)))
+    }
+
+    fn select(&self, source: SourceKind) -> Result<Box<dyn DataSource + '_>, DataError> {
+        self.sources
+            .iter()
+            .find(|(kind, _)| *kind == source)
+            .map(|(_, s)| Box::new(s.as_ref()) as Box<dyn DataSource>)
+            .ok_or_else(|| not_configured(source))
+    }
+
+    fn snapshot(&self) -> Result<Snapshot, DataError> {
+        self.default_source().snapshot()
+    }
+}
+
+/// Error for a block selecting a source that was not given.
+pub(crate) fn not_configured(source: SourceKind) -> DataError {
+    DataError::MiscError(format!("data source --{} is not configured", source))
+}
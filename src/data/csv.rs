@@ -0,0 +1,123 @@
+use super::args::DecimalSeparator;
+use super::error::DataError;
+use super::helpers;
+use crate::layout::value::DataValue;
+use std::path::Path;
+
+/// Reads a table of numbers, one row per record.
+///
+/// Cells use the same numeric rules as sheet references with `separator`.
+/// Cells are separated by commas, or by semicolons with a comma decimal
+/// separator, and may be quoted. A non-numeric first record is taken as a
+/// header and skipped. Blank lines and trailing empty cells are ignored;
+/// an empty cell inside a row and rows of different lengths are errors.
+pub fn read_csv(
+    path: &Path,
+    separator: DecimalSeparator,
+) -> Result<Vec<Vec<DataValue>>, DataError> {
+    let text = std::fs::read_to_string(path).map_err(|e| {
+        DataError::FileError(format!("failed to read CSV {}: {}", path.display(), e))
+    })?;
+    parse_csv(&text, separator).map_err(|e| DataError::WhileRetrieving {
+        name: path.display().to_string(),
+        source: Box::new(e),
+    })
+}
+
+fn parse_csv(text: &str, separator: DecimalSeparator) -> Result<Vec<Vec<DataValue>>, DataError> {
+    let delimiter = match separator {
+        DecimalSeparator::Point => b',',
+        DecimalSeparator::Comma => b';',
+    };
+    let mut reader = ::csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(::csv::Trim::All)
+        .delimiter(delimiter)
+        .from_reader(text.as_bytes());
+
+    let mut rows: Vec<Vec<DataValue>> = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| DataError::RetrievalError(e.to_string()))?;
+        let line = record.position().map_or(index as u64 + 1, |p| p.line());
+        let mut cells: Vec<&str> = record.iter().collect();
+        while cells.last().is_some_and(|cell| cell.is_empty()) {
+            cells.pop();
+        }
+        if cells.is_empty() {
+            continue;
+        }
+        if let Some(column) = cells.iter().position(|cell| cell.is_empty()) {
+            return Err(DataError::RetrievalError(format!(
+                "empty cell in column {} on line {}",
+                column + 1,
+                line
+            )));
+        }
+        let parsed: Option<Vec<DataValue>> = cells
+            .iter()
+            .map(|cell| helpers::parse_number(cell, separator))
+            .collect();
+        let values = match parsed {
+            Some(values) => values,
+            None if index == 0 => continue,
+            None => {
+                return Err(DataError::RetrievalError(format!(
+                    "non-numeric cell on line {}",
+                    line
+                )));
+            }
+        };
+        if let Some(first) = rows.first()
+            && first.len() != values.len()
+        {
+            return Err(DataError::RetrievalError(format!(
+                "line {} has {} cells where earlier rows have {}",
+                line,
+                values.len(),
+                first.len()
+            )));
+        }
+        rows.push(values);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debug(rows: &[Vec<DataValue>]) -> Vec<String> {
+        rows.iter().map(|r| format!("{:?}", r)).collect()
+    }
+
+    #[test]
+    fn parse_csv_skips_header_and_trailing_blanks() {
+        let rows = parse_csv(
+            "x,y,z\n1,0x10,2.5,\n\n4,\"5\",\"6\"\n",
+            DecimalSeparator::Point,
+        );
+        assert_eq!(
+            debug(&rows.unwrap()),
+            ["[U64(1), U64(16), F64(2.5)]", "[U64(4), U64(5), U64(6)]"]
+        );
+
+        let rows = parse_csv("1,5;\"2,25\"\n", DecimalSeparator::Comma).unwrap();
+        assert_eq!(debug(&rows), ["[F64(1.5), F64(2.25)]"]);
+    }
+
+    #[test]
+    fn parse_csv_rejects_gaps_and_jagged_rows() {
+        let err = parse_csv("1,2\n3,abc\n", DecimalSeparator::Point).unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{err}");
+
+        let err = parse_csv("1,,3\n", DecimalSeparator::Point).unwrap_err();
+        assert!(err.to_string().contains("column 2 on line 1"), "{err}");
+
+        let err = parse_csv("1,2,3\n4,5\n", DecimalSeparator::Point).unwrap_err();
+        assert!(err.to_string().contains("line 2 has 2 cells"), "{err}");
+
+        let err = parse_csv("1\n\"1,5\"\n", DecimalSeparator::Point).unwrap_err();
+        assert!(err.to_string().contains("non-numeric"), "{err}");
+    }
+}
@@ -11,9 +11,18 @@ pub enum DataError {
     #[error("Excel retrieval error: {0}.")]
     RetrievalError(String),
 
+    #[error("Duplicate names in {0}.")]
+    DuplicateNames(String),
+
     #[error("Misc error: {0}.")]
     MiscError(String),
 
+    #[error("Data source unavailable: {0}.")]
+    Unavailable(String),
+
+    #[error("Data source does not match its schema: {0}.")]
+    SchemaMismatch(String),
+
     #[error("While retrieving '{name}': {source}")]
     WhileRetrieving {
         name: String,
@@ -21,3 +30,29 @@ pub enum DataError {
         source: Box<DataError>,
     },
 }
+
+impl DataError {
+    /// Stable code of the underlying error; see `mint --explain`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            DataError::FileError(_) => "MINT-D001",
+            DataError::ColumnNotFound(_) => "MINT-D002",
+            DataError::RetrievalError(_) => "MINT-D003",
+            DataError::DuplicateNames(_) => "MINT-D004",
+            DataError::MiscError(_) => "MINT-D005",
+            DataError::Unavailable(_) => "MINT-D006",
+            DataError::SchemaMismatch(_) => "MINT-D007",
+            DataError::WhileRetrieving { source, .. } => source.code(),
+        }
+    }
+
+    /// Whether the data source itself could not be read or reached, as opposed
+    /// to a value in it being missing or invalid.
+    pub fn is_source_unavailable(&self) -> bool {
+        match self {
+            DataError::FileError(_) | DataError::Unavailable(_) => true,
+            DataError::WhileRetrieving { source, .. } => source.is_source_unavailable(),
+            _ => false,
+        }
+    }
+}
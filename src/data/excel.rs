@@ -1,17 +1,25 @@
-use calamine::{Data, Range, Reader, Xlsx, open_workbook};
+use calamine::{Data, ExcelDateTime, Range, Reader, Xlsx, open_workbook};
+use chrono::{Datelike, Timelike};
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use super::DataSource;
-use super::args::DataArgs;
+use super::args::{DataArgs, DateFormat, DecimalSeparator, DuplicatePolicy};
 use super::error::DataError;
 use super::helpers;
 use crate::layout::value::{DataValue, ValueSource};
 
 /// Excel-backed data source for versions.
 pub struct ExcelDataSource {
-    names: Vec<String>,
-    version_columns: Vec<Vec<Data>>,
-    sheets: HashMap<String, Range<Data>>,
+    names: Arc<Vec<String>>,
+    /// Loaded version names, parallel to `version_columns`.
+    versions: Vec<String>,
+    version_columns: Vec<Arc<Vec<Data>>>,
+    sheets: Arc<HashMap<String, Range<Data>>>,
+    decimal_separator: DecimalSeparator,
+    date_format: DateFormat,
+    last_wins: bool,
+    normalize_names: bool,
 }
 
 impl ExcelDataSource {
@@ -47,9 +55,13 @@ impl ExcelDataSource {
                 .map(|c| c.to_string().trim().to_string())
                 .unwrap_or_default()
         }));
-        helpers::warn_duplicate_names(&names);
+        helpers::check_duplicate_names(&names, args.on_duplicate_name)?;
 
-        let version_columns = Self::collect_version_columns(headers, &rows, data_rows, args)?;
+        let (versions, version_columns) =
+            Self::collect_version_columns(headers, &rows, data_rows, args)?
+                .into_iter()
+                .map(|(version, column)| (version, Arc::new(column)))
+                .unzip();
 
         let mut sheets: HashMap<String, Range<Data>> =
             HashMap::with_capacity(workbook.worksheets().len().saturating_sub(1));
@@ -60,20 +72,47 @@ impl ExcelDataSource {
         }
 
         Ok(Self {
-            names,
+            names: Arc::new(names),
+            versions,
             version_columns,
-            sheets,
+            sheets: Arc::new(sheets),
+            decimal_separator: args.xlsx_decimal_separator,
+            date_format: args.date_format,
+            last_wins: args.on_duplicate_name == DuplicatePolicy::LastWins,
+            normalize_names: args.normalize_names,
         })
     }
 
+    /// Row index of the first (or, with `last-wins`, last) matching name.
+    fn find_name(&self, matches: impl Fn(&str) -> bool) -> Option<usize> {
+        let mut names = self.names.iter();
+        if self.last_wins {
+            names.rposition(|n| matches(n))
+        } else {
+            names.position(|n| matches(n))
+        }
+    }
+
+    /// Row of `name`, and whether it matched only after normalization.
+    fn row_of(&self, name: &str) -> Option<(usize, bool)> {
+        if let Some(index) = self.find_name(|n| n == name) {
+            return Some((index, false));
+        }
+        if !self.normalize_names {
+            return None;
+        }
+        let wanted = helpers::normalize_name(name);
+        let index = self.find_name(|n| helpers::normalize_name(n) == wanted)?;
+        Some((index, true))
+    }
+
     fn retrieve_cell(&self, name: &str) -> Result<&Data, DataError> {
-        let index = self
-            .names
-            .iter()
-            .position(|n| n == name)
-            .ok_or(DataError::RetrievalError(
-                "index not found in data sheet".to_string(),
-            ))?;
+        let (index, fuzzy) = self.row_of(name).ok_or(DataError::RetrievalError(
+            "index not found in data sheet".to_string(),
+        ))?;
+        if fuzzy {
+            helpers::warn_fuzzy_match(name, &self.names[index]);
+        }
 
         for column in &self.version_columns {
             if let Some(value) = column.get(index).filter(|v| !Self::cell_is_empty(v)) {
@@ -86,6 +125,30 @@ impl ExcelDataSource {
         ))
     }
 
+    /// Converts a date/time cell to the configured representation. Durations
+    /// are always whole seconds.
+    fn date_value(&self, dt: &ExcelDateTime) -> Result<DataValue, DataError> {
+        if dt.is_duration() {
+            return Ok(DataValue::I64((dt.as_f64() * 86_400.0).round() as i64));
+        }
+        let value = dt
+            .as_datetime()
+            .ok_or_else(|| DataError::RetrievalError("invalid date cell".to_string()))?;
+        Ok(match self.date_format {
+            DateFormat::Epoch => DataValue::I64(value.and_utc().timestamp()),
+            DateFormat::Bcd => {
+                let digits = format!("{:04}{:02}{:02}", value.year(), value.month(), value.day());
+                DataValue::U64(u64::from_str_radix(&digits, 16).map_err(|_| {
+                    DataError::RetrievalError(format!("date {} cannot be BCD encoded", value))
+                })?)
+            }
+            DateFormat::Iso if value.num_seconds_from_midnight() == 0 => {
+                DataValue::Str(value.format("%Y-%m-%d").to_string())
+            }
+            DateFormat::Iso => DataValue::Str(value.format("%Y-%m-%dT%H:%M:%S").to_string()),
+        })
+    }
+
     fn cell_eq_ascii(cell: &Data, target: &str) -> bool {
         match cell {
             Data::String(s) => s.trim().eq_ignore_ascii_case(target),
@@ -93,6 +156,17 @@ impl ExcelDataSource {
         }
     }
 
+    fn sheet(&self, name: &str) -> Result<&Range<Data>, DataError> {
+        self.sheets.get(name).ok_or_else(|| {
+            let available: Vec<_> = self.sheets.keys().map(|s| s.as_str()).collect();
+            DataError::RetrievalError(format!(
+                "Sheet not found: '{}'. Available sheets: {}",
+                name,
+                available.join(", ")
+            ))
+        })
+    }
+
     fn cell_is_empty(cell: &Data) -> bool {
         match cell {
             Data::Empty => true,
@@ -116,7 +190,7 @@ impl ExcelDataSource {
         rows: &[&[Data]],
         data_rows: usize,
         args: &DataArgs,
-    ) -> Result<Vec<Vec<Data>>, DataError> {
+    ) -> Result<Vec<(String, Vec<Data>)>, DataError> {
         let versions = args.get_version_list();
 
         let mut seen = HashSet::new();
@@ -129,49 +203,30 @@ impl ExcelDataSource {
                     .position(|cell| Self::cell_eq_ascii(cell, &v))
                     .ok_or_else(|| DataError::ColumnNotFound(v.clone()))?;
 
-                columns.push(Self::collect_column(rows, index, data_rows));
+                columns.push((v, Self::collect_column(rows, index, data_rows)));
             }
         }
 
         Ok(columns)
     }
-}
 
-impl DataSource for ExcelDataSource {
-    fn retrieve_single_value(&self, name: &str) -> Result<DataValue, DataError> {
-        let result = (|| match self.retrieve_cell(name)? {
-            Data::Int(i) => Ok(DataValue::I64(*i)),
-            Data::Float(f) => Ok(DataValue::F64(*f)),
-            Data::Bool(b) => Ok(DataValue::Bool(*b)),
-            _ => Err(DataError::RetrievalError(
-                "Found non-numeric single value".to_string(),
-            )),
-        })();
-
-        result.map_err(|e| DataError::WhileRetrieving {
-            name: name.to_string(),
-            source: Box::new(e),
-        })
-    }
-
-    fn retrieve_1d_array_or_string(&self, name: &str) -> Result<ValueSource, DataError> {
+    /// Reads a 1D array from a sheet reference, or a text cell as a literal
+    /// string unless `split_text` asks for its delimited numbers.
+    fn retrieve_1d(&self, name: &str, split_text: bool) -> Result<ValueSource, DataError> {
         let result = (|| {
-            let Data::String(cell_string) = self.retrieve_cell(name)? else {
-                return Err(DataError::RetrievalError(
-                    "Expected string value for 1D array or string".to_string(),
-                ));
+            let cell_string = match self.retrieve_cell(name)? {
+                Data::String(s) => s,
+                Data::DateTime(dt) => return Ok(ValueSource::Single(self.date_value(dt)?)),
+                _ => {
+                    return Err(DataError::RetrievalError(
+                        "Expected string value for 1D array or string".to_string(),
+                    ));
+                }
             };
 
             // Check if the value starts with '#' to indicate a sheet reference
             if let Some(sheet_name) = cell_string.strip_prefix('#') {
-                let sheet = self.sheets.get(sheet_name).ok_or_else(|| {
-                    let available: Vec<_> = self.sheets.keys().map(|s| s.as_str()).collect();
-                    DataError::RetrievalError(format!(
-                        "Sheet not found: '{}'. Available sheets: {}",
-                        sheet_name,
-                        available.join(", ")
-                    ))
-                })?;
+                let sheet = self.sheet(sheet_name)?;
 
                 let mut out = Vec::new();
 
@@ -182,7 +237,11 @@ impl DataSource for ExcelDataSource {
                                 Data::Int(i) => DataValue::I64(*i),
                                 Data::Float(f) => DataValue::F64(*f),
                                 Data::Bool(b) => DataValue::Bool(*b),
-                                Data::String(s) => DataValue::Str(s.to_owned()),
+                                Data::DateTime(dt) => self.date_value(dt)?,
+                                Data::String(s) => {
+                                    helpers::parse_number(s.trim(), self.decimal_separator)
+                                        .unwrap_or_else(|| DataValue::Str(s.to_owned()))
+                                }
                                 _ => {
                                     return Err(DataError::RetrievalError(
                                         "Unsupported data type in 1D array".to_string(),
@@ -197,8 +256,36 @@ impl DataSource for ExcelDataSource {
                 return Ok(ValueSource::Array(out));
             }
 
-            // No '#' prefix, treat as a literal string
-            Ok(ValueSource::Single(DataValue::Str(cell_string.to_owned())))
+            // No '#' prefix: a delimited list of numbers where an array is
+            // wanted, otherwise a literal string
+            match helpers::parse_delimited_numbers(cell_string, self.decimal_separator) {
+                Some(arr) if split_text && !arr.is_empty() => Ok(ValueSource::Array(arr)),
+                _ => Ok(ValueSource::Single(DataValue::Str(cell_string.to_owned()))),
+            }
+        })();
+
+        result.map_err(|e| DataError::WhileRetrieving {
+            name: name.to_string(),
+            source: Box::new(e),
+        })
+    }
+}
+
+impl DataSource for ExcelDataSource {
+    fn retrieve_single_value(&self, name: &str) -> Result<DataValue, DataError> {
+        let result = (|| match self.retrieve_cell(name)? {
+            Data::Int(i) => Ok(DataValue::I64(*i)),
+            Data::Float(f) => Ok(DataValue::F64(*f)),
+            Data::Bool(b) => Ok(DataValue::Bool(*b)),
+            Data::DateTime(dt) => self.date_value(dt),
+            Data::String(s) => {
+                helpers::parse_number(s.trim(), self.decimal_separator).ok_or_else(|| {
+                    DataError::RetrievalError("Found non-numeric single value".to_string())
+                })
+            }
+            _ => Err(DataError::RetrievalError(
+                "Found non-numeric single value".to_string(),
+            )),
         })();
 
         result.map_err(|e| DataError::WhileRetrieving {
@@ -207,6 +294,14 @@ impl DataSource for ExcelDataSource {
         })
     }
 
+    fn retrieve_1d_array_or_string(&self, name: &str) -> Result<ValueSource, DataError> {
+        self.retrieve_1d(name, false)
+    }
+
+    fn retrieve_1d_array(&self, name: &str) -> Result<ValueSource, DataError> {
+        self.retrieve_1d(name, true)
+    }
+
     fn retrieve_2d_array(&self, name: &str) -> Result<Vec<Vec<DataValue>>, DataError> {
         let result = (|| {
             let Data::String(cell_string) = self.retrieve_cell(name)? else {
@@ -222,26 +317,27 @@ impl DataSource for ExcelDataSource {
                 ))
             })?;
 
-            let sheet = self.sheets.get(sheet_name).ok_or_else(|| {
-                let available: Vec<_> = self.sheets.keys().map(|s| s.as_str()).collect();
-                DataError::RetrievalError(format!(
-                    "Sheet not found: '{}'. Available sheets: {}",
-                    sheet_name,
-                    available.join(", ")
-                ))
-            })?;
+            let sheet = self.sheet(sheet_name)?;
 
             let convert = |cell: &Data| -> Result<DataValue, DataError> {
                 match cell {
                     Data::Int(i) => Ok(DataValue::I64(*i)),
                     Data::Float(f) => Ok(DataValue::F64(*f)),
                     Data::Bool(b) => Ok(DataValue::Bool(*b)),
+                    Data::DateTime(dt) => self.date_value(dt),
+                    Data::String(s) => helpers::parse_number(s.trim(), self.decimal_separator)
+                        .ok_or_else(|| {
+                            DataError::RetrievalError(
+                                "Unsupported data type in 2D array".to_string(),
+                            )
+                        }),
                     _ => Err(DataError::RetrievalError(
                         "Unsupported data type in 2D array".to_string(),
                     )),
                 }
             };
 
+            let (first_row, first_col) = sheet.start().unwrap_or((0, 0));
             let mut rows = sheet.rows();
             let hdrs = rows.next().ok_or_else(|| {
                 DataError::RetrievalError("No headers found in 2D array".to_string())
@@ -255,21 +351,31 @@ impl DataSource for ExcelDataSource {
 
             let mut out = Vec::new();
 
-            'outer: for row in rows {
+            for (index, row) in rows.enumerate() {
                 if row.first().is_none_or(Self::cell_is_empty) {
                     break;
                 }
 
-                let mut vals = Vec::with_capacity(width);
-                for col in 0..width {
-                    let Some(cell) = row.get(col) else {
-                        break 'outer;
-                    };
-                    if Self::cell_is_empty(cell) {
-                        break 'outer;
-                    };
-                    vals.push(convert(cell)?);
+                // Trailing empty cells shorten a row; an empty cell before a
+                // filled one is an error.
+                let cells = &row[..width.min(row.len())];
+                let len = cells.len()
+                    - cells
+                        .iter()
+                        .rev()
+                        .take_while(|c| Self::cell_is_empty(c))
+                        .count();
+                if let Some(col) = cells[..len].iter().position(Self::cell_is_empty) {
+                    return Err(DataError::RetrievalError(format!(
+                        "empty cell {}!{} inside a 2D array row",
+                        sheet_name,
+                        cell_reference(first_row + 1 + index as u32, first_col + col as u32)
+                    )));
                 }
+                let vals = cells[..len]
+                    .iter()
+                    .map(convert)
+                    .collect::<Result<Vec<_>, _>>()?;
                 out.push(vals);
             }
 
@@ -281,6 +387,97 @@ impl DataSource for ExcelDataSource {
             source: Box::new(e),
         })
     }
+
+    /// Reads a sheet whose first column holds the strings and second column the
+    /// values, below a header row, up to the first empty string.
+    fn retrieve_map(&self, table: &str) -> Result<HashMap<String, DataValue>, DataError> {
+        let result = (|| {
+            let mut map = HashMap::new();
+            for row in self.sheet(table)?.rows().skip(1) {
+                let key = match row.first() {
+                    Some(cell) if !Self::cell_is_empty(cell) => cell.to_string().trim().to_string(),
+                    _ => break,
+                };
+                let value = match row.get(1) {
+                    Some(Data::Int(i)) => DataValue::I64(*i),
+                    Some(Data::Float(f)) => DataValue::F64(*f),
+                    Some(Data::Bool(b)) => DataValue::Bool(*b),
+                    Some(Data::String(s)) => helpers::parse_number(
+                        s.trim(),
+                        self.decimal_separator,
+                    )
+                    .ok_or_else(|| {
+                        DataError::RetrievalError(format!("non-numeric value for '{}'", key))
+                    })?,
+                    _ => {
+                        return Err(DataError::RetrievalError(format!(
+                            "missing value for '{}'",
+                            key
+                        )));
+                    }
+                };
+                if map.insert(key.clone(), value).is_some() {
+                    return Err(DataError::DuplicateNames(format!(
+                        "mapping table '{}': '{}'",
+                        table, key
+                    )));
+                }
+            }
+            Ok(map)
+        })();
+
+        result.map_err(|e| DataError::WhileRetrieving {
+            name: format!("#{}", table),
+            source: Box::new(e),
+        })
+    }
+
+    fn names(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        self.names
+            .iter()
+            .filter(|n| !n.is_empty() && seen.insert(n.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    fn versions(&self) -> Vec<String> {
+        self.versions.clone()
+    }
+
+    fn version_of(&self, name: &str) -> Option<String> {
+        let (index, _) = self.row_of(name)?;
+        self.version_columns
+            .iter()
+            .position(|column| column.get(index).is_some_and(|v| !Self::cell_is_empty(v)))
+            .map(|i| self.versions[i].clone())
+    }
+
+    fn restack(&self, versions: &[String]) -> Result<Box<dyn DataSource>, DataError> {
+        let indices = helpers::stack_indices(&self.versions, versions)?;
+        Ok(Box::new(Self {
+            names: Arc::clone(&self.names),
+            versions: indices.iter().map(|&i| self.versions[i].clone()).collect(),
+            version_columns: indices
+                .iter()
+                .map(|&i| Arc::clone(&self.version_columns[i]))
+                .collect(),
+            sheets: Arc::clone(&self.sheets),
+            ..*self
+        }))
+    }
+}
+
+/// A1-style reference of the zero-based cell at `row`, `col`.
+fn cell_reference(row: u32, col: u32) -> String {
+    let mut letters = Vec::new();
+    let mut n = col + 1;
+    while n > 0 {
+        letters.push(b'A' + ((n - 1) % 26) as u8);
+        n = (n - 1) / 26;
+    }
+    letters.reverse();
+    format!("{}{}", String::from_utf8_lossy(&letters), row + 1)
 }
 
 #[cfg(test)]
@@ -291,10 +488,85 @@ mod tests {
 
     fn datasource_with_version(value: Data) -> ExcelDataSource {
         ExcelDataSource {
-            names: vec!["Flag".to_string()],
-            version_columns: vec![vec![value]],
-            sheets: HashMap::new(),
+            names: Arc::new(vec!["Flag".to_string()]),
+            versions: vec!["Default".to_string()],
+            version_columns: vec![Arc::new(vec![value])],
+            sheets: Arc::new(HashMap::new()),
+            decimal_separator: DecimalSeparator::Point,
+            date_format: DateFormat::Epoch,
+            last_wins: false,
+            normalize_names: false,
+        }
+    }
+
+    #[test]
+    fn date_cells_follow_date_format() {
+        use calamine::ExcelDateTimeType;
+
+        // 2024-03-15 08:30:00 as an Excel serial date.
+        let cell = Data::DateTime(ExcelDateTime::new(
+            45366.354166666664,
+            ExcelDateTimeType::DateTime,
+            false,
+        ));
+        let mut ds = datasource_with_version(cell);
+
+        let value =
+            |ds: &ExcelDataSource| format!("{:?}", ds.retrieve_single_value("Flag").unwrap());
+        assert_eq!(value(&ds), "I64(1710491400)");
+        ds.date_format = DateFormat::Bcd;
+        assert_eq!(value(&ds), format!("U64({})", 0x2024_0315u64));
+        ds.date_format = DateFormat::Iso;
+        assert_eq!(value(&ds), "Str(\"2024-03-15T08:30:00\")");
+    }
+
+    #[test]
+    fn digit_text_is_split_only_for_arrays() {
+        let ds = datasource_with_version(Data::String("0012345".to_string()));
+        let value = ds.retrieve_1d_array_or_string("Flag").unwrap();
+        assert_eq!(format!("{:?}", value), "Single(Str(\"0012345\"))");
+
+        let ds = datasource_with_version(Data::String("0x10 0x20".to_string()));
+        let value = ds.retrieve_1d_array("Flag").unwrap();
+        assert_eq!(format!("{:?}", value), "Array([U64(16), U64(32)])");
+    }
+
+    #[test]
+    fn retrieve_2d_array_keeps_short_rows() {
+        let mut sheet = Range::new((0, 0), (3, 2));
+        for (col, header) in ["A", "B", "C"].into_iter().enumerate() {
+            sheet.set_value((0, col as u32), Data::String(header.to_string()));
         }
+        sheet.set_value((1, 0), Data::Int(1));
+        sheet.set_value((1, 1), Data::Int(2));
+        sheet.set_value((1, 2), Data::Int(3));
+        sheet.set_value((2, 0), Data::Int(4));
+        sheet.set_value((3, 0), Data::Int(5));
+        sheet.set_value((3, 1), Data::Int(6));
+
+        let mut ds = datasource_with_version(Data::String("#Map".to_string()));
+        Arc::make_mut(&mut ds.sheets).insert("Map".to_string(), sheet);
+
+        let rows = ds.retrieve_2d_array("Flag").unwrap();
+        let lens: Vec<usize> = rows.iter().map(Vec::len).collect();
+        assert_eq!(lens, [3, 1, 2]);
+    }
+
+    #[test]
+    fn retrieve_2d_array_rejects_interior_blanks() {
+        let mut sheet = Range::new((0, 0), (1, 2));
+        for (col, header) in ["A", "B", "C"].into_iter().enumerate() {
+            sheet.set_value((0, col as u32), Data::String(header.to_string()));
+        }
+        sheet.set_value((1, 0), Data::Int(1));
+        sheet.set_value((1, 2), Data::Int(3));
+
+        let mut ds = datasource_with_version(Data::String("#Map".to_string()));
+        Arc::make_mut(&mut ds.sheets).insert("Map".to_string(), sheet);
+
+        let err = ds.retrieve_2d_array("Flag").unwrap_err().to_string();
+        assert!(err.contains("empty cell Map!B2"), "{err}");
+        assert_eq!(cell_reference(0, 27), "AB1");
     }
 
     #[test]
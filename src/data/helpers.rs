@@ -1,9 +1,34 @@
+use serde::de::{DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{Map, Value};
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fmt;
 
-/// Warn about duplicate names and their 1-based row indices (including header offset of 1).
+use super::args::{DecimalSeparator, DuplicatePolicy};
+use super::error::DataError;
+use crate::layout::value::DataValue;
+
+/// Trims, lowercases, and collapses internal whitespace for fuzzy name matching.
+pub fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Warns that `name` was resolved to the differently written `matched`.
+pub fn warn_fuzzy_match(name: &str, matched: &str) {
+    crate::warnings::warn(format!(
+        "Name '{}' matched '{}' only after normalization.",
+        name, matched
+    ));
+}
+
+/// Applies the duplicate-name policy to the main sheet's names, reporting
+/// 1-based row indices (including header offset of 1).
 ///
 /// - `names` should be the list of names as read from the main sheet (excluding the header row).
-pub fn warn_duplicate_names(names: &[String]) {
+pub fn check_duplicate_names(names: &[String], policy: DuplicatePolicy) -> Result<(), DataError> {
     let mut index_map: HashMap<String, Vec<usize>> = HashMap::new();
 
     for (idx, name) in names.iter().enumerate() {
@@ -22,15 +47,389 @@ pub fn warn_duplicate_names(names: &[String]) {
 
     duplicates.sort_by(|a, b| a.0.cmp(&b.0));
 
-    if !duplicates.is_empty() {
-        eprintln!("[WARN] Duplicate names detected (column 'Name'):");
-        for (dup_name, rows) in duplicates {
+    let described: Vec<String> = duplicates
+        .into_iter()
+        .map(|(dup_name, rows)| {
             let rows_str = rows
                 .iter()
                 .map(|r| r.to_string())
                 .collect::<Vec<_>>()
                 .join(", ");
-            eprintln!("  - '{}' at rows {}", dup_name, rows_str);
+            format!("'{}' at rows {}", dup_name, rows_str)
+        })
+        .collect();
+    report_duplicates(&described, policy, "column 'Name'")
+}
+
+/// Errors or warns about duplicated names according to `policy`.
+pub fn report_duplicates(
+    duplicates: &[String],
+    policy: DuplicatePolicy,
+    origin: &str,
+) -> Result<(), DataError> {
+    if duplicates.is_empty() {
+        return Ok(());
+    }
+    match policy {
+        DuplicatePolicy::Error => Err(DataError::DuplicateNames(format!(
+            "{}: {}",
+            origin,
+            duplicates.join("; ")
+        ))),
+        DuplicatePolicy::Warn => {
+            crate::warnings::warn(format!(
+                "Duplicate names detected ({}):\n  - {}",
+                origin,
+                duplicates.join("\n  - ")
+            ));
+            Ok(())
+        }
+        DuplicatePolicy::LastWins | DuplicatePolicy::FirstWins => Ok(()),
+    }
+}
+
+/// Parses JSON, keeping the first definition of a repeated object key under
+/// `first-wins` and the last otherwise, as `serde_json` does; `warn` keeps
+/// the last so warning about a file does not change what it builds. Also
+/// returns the repeated keys as dotted paths.
+pub fn parse_json(
+    text: &str,
+    policy: DuplicatePolicy,
+) -> Result<(Value, Vec<String>), serde_json::Error> {
+    let duplicates = RefCell::new(Vec::new());
+    let mut deserializer = serde_json::Deserializer::from_str(text);
+    let value = JsonSeed {
+        last_wins: policy != DuplicatePolicy::FirstWins,
+        path: String::new(),
+        duplicates: &duplicates,
+    }
+    .deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok((value, duplicates.into_inner()))
+}
+
+struct JsonSeed<'a> {
+    last_wins: bool,
+    path: String,
+    duplicates: &'a RefCell<Vec<String>>,
+}
+
+impl JsonSeed<'_> {
+    fn child(&self, path: String) -> Self {
+        JsonSeed {
+            last_wins: self.last_wins,
+            path,
+            duplicates: self.duplicates,
+        }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for JsonSeed<'_> {
+    type Value = Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for JsonSeed<'_> {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element_seed(self.child(self.path.clone()))? {
+            items.push(item);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut out = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let path = if self.path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", self.path, key)
+            };
+            let value = map.next_value_seed(self.child(path.clone()))?;
+            if out.contains_key(&key) {
+                self.duplicates.borrow_mut().push(path);
+                if !self.last_wins {
+                    continue;
+                }
+            }
+            out.insert(key, value);
         }
+        Ok(Value::Object(out))
+    }
+}
+
+/// Indices into `loaded` for each version of `stack`, skipping repeats.
+/// Fails if a version of the stack was not loaded.
+pub fn stack_indices(loaded: &[String], stack: &[String]) -> Result<Vec<usize>, DataError> {
+    let mut indices: Vec<usize> = Vec::with_capacity(stack.len());
+    for version in stack {
+        let index = loaded.iter().position(|v| v == version).ok_or_else(|| {
+            DataError::RetrievalError(format!("version '{}' was not loaded", version))
+        })?;
+        if !indices.contains(&index) {
+            indices.push(index);
+        }
+    }
+    Ok(indices)
+}
+
+/// Parses a numeric literal: decimal, `0x` hex, `0b` binary, or a decimal with
+/// an engineering suffix (`k` = 10^3, `M` = 10^6). A leading `-` is allowed.
+pub fn parse_number(text: &str, separator: DecimalSeparator) -> Option<DataValue> {
+    let unsigned = text.strip_prefix('-').unwrap_or(text);
+    let has_radix = ["0x", "0X", "0b", "0B"]
+        .iter()
+        .any(|p| unsigned.starts_with(p));
+    match separator {
+        DecimalSeparator::Comma if !has_radix => {
+            parse_point_number(&normalize_decimal_comma(text)?)
+        }
+        _ => parse_point_number(text),
+    }
+}
+
+/// Rewrites `1.234,5` as `1234.5`. `None` if the `.` thousands groups are malformed.
+fn normalize_decimal_comma(text: &str) -> Option<String> {
+    let (whole, fraction) = match text.split_once(',') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (text, None),
+    };
+    if fraction.is_some_and(|f| f.contains([',', '.'])) {
+        return None;
+    }
+    let groups: Vec<&str> = whole.split('.').collect();
+    if let [first, rest @ ..] = groups.as_slice()
+        && !rest.is_empty()
+    {
+        let first = first.trim_start_matches('-');
+        if first.is_empty() || first.len() > 3 || rest.iter().any(|g| g.len() != 3) {
+            return None;
+        }
+    }
+    let mut out = groups.concat();
+    if let Some(fraction) = fraction {
+        out.push('.');
+        out.push_str(fraction);
+    }
+    Some(out)
+}
+
+fn parse_point_number(text: &str) -> Option<DataValue> {
+    let (negative, body) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let radix = [("0x", 16), ("0X", 16), ("0b", 2), ("0B", 2)]
+        .iter()
+        .find_map(|(prefix, radix)| body.strip_prefix(prefix).map(|digits| (digits, *radix)));
+    if let Some((digits, radix)) = radix {
+        let magnitude = u64::from_str_radix(digits, radix).ok()?;
+        return if negative {
+            0i64.checked_sub_unsigned(magnitude).map(DataValue::I64)
+        } else {
+            Some(DataValue::U64(magnitude))
+        };
+    }
+
+    let exponent = match body.chars().last()? {
+        'k' => Some(3),
+        'M' => Some(6),
+        _ => None,
+    };
+    if let Some(exponent) = exponent {
+        let mantissa = &body[..body.len() - 1];
+        if mantissa.is_empty() || !mantissa.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            return None;
+        }
+        let value: f64 = format!("{}e{}", mantissa, exponent).parse().ok()?;
+        let value = if negative { -value } else { value };
+        return Some(integral_value(value));
+    }
+
+    text.parse::<u64>()
+        .map(DataValue::U64)
+        .ok()
+        .or_else(|| text.parse::<i64>().map(DataValue::I64).ok())
+        .or_else(|| text.parse::<f64>().map(DataValue::F64).ok())
+}
+
+/// Stores whole floats as integers so suffixed literals such as `4k` stay exact.
+fn integral_value(value: f64) -> DataValue {
+    if value.fract() == 0.0 && value >= 0.0 && value <= u64::MAX as f64 {
+        DataValue::U64(value as u64)
+    } else if value.fract() == 0.0 && value >= i64::MIN as f64 && value < 0.0 {
+        DataValue::I64(value as i64)
+    } else {
+        DataValue::F64(value)
+    }
+}
+
+/// Parses a space/comma/semicolon-delimited list of numeric literals. With a
+/// comma decimal separator only spaces and semicolons delimit.
+///
+/// A lone `0x` token is left alone so it can be read as a hex byte string.
+pub fn parse_delimited_numbers(s: &str, separator: DecimalSeparator) -> Option<Vec<DataValue>> {
+    let comma_delimits = separator == DecimalSeparator::Point;
+    let tokens: Vec<&str> = s
+        .split(|c: char| c.is_whitespace() || c == ';' || (c == ',' && comma_delimits))
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if let [only] = tokens.as_slice()
+        && (only.starts_with("0x") || only.starts_with("0X"))
+    {
+        return None;
+    }
+    tokens
+        .into_iter()
+        .map(|t| parse_number(t, separator))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(s: &str) -> Option<Vec<String>> {
+        parse_delimited_numbers(s, DecimalSeparator::Point)
+            .map(|v| v.iter().map(|d| format!("{:?}", d)).collect())
+    }
+
+    #[test]
+    fn parse_number_accepts_radix_and_suffix_literals() {
+        assert!(matches!(
+            parse_number("0x1F", DecimalSeparator::Point),
+            Some(DataValue::U64(31))
+        ));
+        assert!(matches!(
+            parse_number("0b1010", DecimalSeparator::Point),
+            Some(DataValue::U64(10))
+        ));
+        assert!(matches!(
+            parse_number("-0x10", DecimalSeparator::Point),
+            Some(DataValue::I64(-16))
+        ));
+        assert!(matches!(
+            parse_number("4k", DecimalSeparator::Point),
+            Some(DataValue::U64(4000))
+        ));
+        assert!(matches!(
+            parse_number("2.2k", DecimalSeparator::Point),
+            Some(DataValue::U64(2200))
+        ));
+        assert!(matches!(
+            parse_number("1.5M", DecimalSeparator::Point),
+            Some(DataValue::U64(1_500_000))
+        ));
+        assert!(matches!(
+            parse_number("-3k", DecimalSeparator::Point),
+            Some(DataValue::I64(-3000))
+        ));
+        assert!(matches!(
+            parse_number("0.0005k", DecimalSeparator::Point),
+            Some(DataValue::F64(_))
+        ));
+        assert!(parse_number("k", DecimalSeparator::Point).is_none());
+        assert!(parse_number("0xZZ", DecimalSeparator::Point).is_none());
+        assert!(parse_number("desk", DecimalSeparator::Point).is_none());
+    }
+
+    #[test]
+    fn parse_delimited_numbers_mixes_literals() {
+        assert_eq!(
+            numbers("0x10 0x20, 0b11; 1k"),
+            Some(
+                vec!["U64(16)", "U64(32)", "U64(3)", "U64(1000)"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect()
+            )
+        );
+        assert_eq!(numbers("0xDEADBEEF"), None);
+        assert_eq!(numbers("serial 42"), None);
+    }
+
+    #[test]
+    fn normalize_name_folds_case_and_whitespace() {
+        assert_eq!(normalize_name("  Engine   Gain\tMax "), "engine gain max");
+    }
+
+    #[test]
+    fn parse_json_resolves_duplicate_keys() {
+        let text = r#"{"A": 1, "B": {"C": 2, "C": 3}, "A": 4}"#;
+
+        let (value, duplicates) = parse_json(text, DuplicatePolicy::Warn).unwrap();
+        assert_eq!(value, serde_json::json!({"A": 4, "B": {"C": 3}}));
+        assert_eq!(duplicates, ["B.C", "A"]);
+
+        let (value, _) = parse_json(text, DuplicatePolicy::FirstWins).unwrap();
+        assert_eq!(value, serde_json::json!({"A": 1, "B": {"C": 2}}));
+
+        let err = report_duplicates(&duplicates, DuplicatePolicy::Error, "version 'X'");
+        assert!(err.unwrap_err().to_string().contains("B.C; A"));
+    }
+
+    #[test]
+    fn comma_separator_accepts_thousands_groups() {
+        let comma = DecimalSeparator::Comma;
+        assert!(matches!(parse_number("1.234,5", comma), Some(DataValue::F64(v)) if v == 1234.5));
+        assert!(matches!(
+            parse_number("-1.000.000", comma),
+            Some(DataValue::I64(-1_000_000))
+        ));
+        assert!(matches!(
+            parse_number("2,5k", comma),
+            Some(DataValue::U64(2500))
+        ));
+        assert!(matches!(
+            parse_number("0x1F", comma),
+            Some(DataValue::U64(31))
+        ));
+        assert!(parse_number("1.5", comma).is_none());
+        assert!(parse_number("1,2,3", comma).is_none());
+
+        let list = parse_delimited_numbers("0,5; 1,25 2", comma).expect("list");
+        assert!(matches!(
+            list.as_slice(),
+            [DataValue::F64(a), DataValue::F64(b), DataValue::U64(2)] if *a == 0.5 && *b == 1.25
+        ));
     }
 }
@@ -1,11 +1,19 @@
 use postgres::{Client, NoTls};
 use serde::Deserialize;
+use serde::de::DeserializeOwned;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use ureq::Agent;
 
-use super::DataSource;
-use super::args::DataArgs;
+use super::args::{DataArgs, DecimalSeparator};
 use super::error::DataError;
+use super::helpers;
+use super::response_map::ResponseMap;
+use super::schema::{self, Schema};
+use super::{DataSource, Snapshot};
 use crate::layout::value::{DataValue, ValueSource};
 
 fn load_json_string_or_file(input: &str) -> Result<String, DataError> {
@@ -17,19 +25,194 @@ fn load_json_string_or_file(input: &str) -> Result<String, DataError> {
     }
 }
 
-/// Navigates into nested JSON objects using a path of keys.
-/// Returns an error if any key in the path is not found.
+/// Loads a source config, expanding `${VAR}` and secret references in its
+/// string values. The config is checked against `T` before expansion, so
+/// parse errors never quote a secret.
+fn load_config_json<T: DeserializeOwned>(input: &str, args: &DataArgs) -> Result<T, DataError> {
+    let invalid =
+        |e: serde_json::Error| DataError::FileError(format!("failed to parse JSON: {}", e));
+    let text = load_json_string_or_file(input)?;
+    let mut config: Value = serde_json::from_str(&text).map_err(invalid)?;
+    T::deserialize(&config).map_err(invalid)?;
+    super::secrets::expand_config(&mut config, args.secrets.as_deref())?;
+    serde_json::from_value(config).map_err(invalid)
+}
+
+/// Navigates into nested JSON values using a path of keys.
+/// Each key may be followed by array selectors: `items[0]` takes an element
+/// by index, and `results[?name=='cal']` the first element whose `name`
+/// field equals the given value. A key the object holds verbatim is taken
+/// as-is. Returns an error if any step of the path is not found.
 /// If path is empty, returns the original value unchanged.
-fn extract_nested_value<'a>(value: &'a Value, path: &[String]) -> Result<&'a Value, DataError> {
+pub(super) fn extract_nested_value<'a>(
+    value: &'a Value,
+    path: &[String],
+) -> Result<&'a Value, String> {
     let mut current = value;
     for key in path {
-        current = current.get(key).ok_or_else(|| {
-            DataError::RetrievalError(format!("nested key '{}' not found in response", key))
-        })?;
+        current = match current.get(key) {
+            Some(v) => v,
+            None => select(current, key)?,
+        };
+    }
+    Ok(current)
+}
+
+/// Applies one `data_path` segment: an optional key, then any `[...]` selectors.
+fn select<'a>(value: &'a Value, segment: &str) -> Result<&'a Value, String> {
+    let invalid = || format!("invalid data_path segment '{}'", segment);
+    let (key, mut rest) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+    let mut current = match key {
+        "" => value,
+        key => value
+            .get(key)
+            .ok_or_else(|| format!("nested key '{}' not found in response", key))?,
+    };
+    while !rest.is_empty() {
+        let close = rest.find(']').ok_or_else(invalid)?;
+        let selector = rest.get(1..close).filter(|_| rest.starts_with('['));
+        let selector = selector.ok_or_else(invalid)?;
+        rest = &rest[close + 1..];
+
+        let elements = current
+            .as_array()
+            .ok_or_else(|| format!("'{}' selects from a value that is not an array", segment))?;
+        current = match selector.strip_prefix('?') {
+            Some(filter) => {
+                let (field, expected) = filter.split_once("==").ok_or_else(invalid)?;
+                let expected = filter_value(expected.trim());
+                elements
+                    .iter()
+                    .find(|e| e.get(field.trim()) == Some(&expected))
+                    .ok_or_else(|| {
+                        format!("no element matches '[{}]' in '{}'", selector, segment)
+                    })?
+            }
+            None => {
+                let index: usize = selector.trim().parse().map_err(|_| invalid())?;
+                elements.get(index).ok_or_else(|| {
+                    format!(
+                        "index {} is out of range for {} elements in '{}'",
+                        index,
+                        elements.len(),
+                        segment
+                    )
+                })?
+            }
+        };
     }
     Ok(current)
 }
 
+/// Right-hand side of a `[?field==value]` filter: a quoted string, or any
+/// JSON literal, with anything else compared as a bare string.
+fn filter_value(text: &str) -> Value {
+    let quoted = ['\'', '"']
+        .iter()
+        .find_map(|q| text.strip_prefix(*q)?.strip_suffix(*q));
+    match quoted {
+        Some(s) => Value::String(s.to_string()),
+        None => serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string())),
+    }
+}
+
+/// Fetches every version over at most `pool_size` connections at once and
+/// returns the results in version order. Each worker opens its connection with
+/// `connect` when it first needs one and reuses it for every version it picks up,
+/// so a version stack pays for `pool_size` handshakes rather than one per version.
+fn fetch_pooled<C, T: Send>(
+    versions: &[String],
+    pool_size: usize,
+    connect: impl Fn() -> Result<C, DataError> + Sync,
+    fetch: impl Fn(&mut C, &str) -> Result<T, DataError> + Sync,
+) -> Result<Vec<T>, DataError> {
+    let next = AtomicUsize::new(0);
+    let worker = || {
+        let mut connection = None;
+        let mut results = Vec::new();
+        while let Some(version) = versions.get(next.fetch_add(1, Ordering::Relaxed)) {
+            let result = match &mut connection {
+                Some(c) => fetch(c, version),
+                None => connect().and_then(|c| fetch(connection.insert(c), version)),
+            };
+            results.push((version, result));
+        }
+        results
+    };
+
+    let workers = pool_size.clamp(1, versions.len().max(1));
+    let mut results: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers).map(|_| scope.spawn(worker)).collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("fetch thread panicked"))
+            .collect()
+    });
+    results.sort_by_key(|(version, _)| versions.iter().position(|v| v == *version));
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Parses one version's JSON payload into its name map, reading the object at
+/// `data_path`, or the records `map` reshapes into one. Parsing runs in
+/// version order so duplicate-name warnings do too.
+fn parse_payload(
+    json_str: &str,
+    version: &str,
+    data_path: &[String],
+    map: Option<&ResponseMap>,
+    args: &DataArgs,
+) -> Result<HashMap<String, Value>, DataError> {
+    let (response_value, duplicates) = helpers::parse_json(json_str, args.on_duplicate_name)
+        .map_err(|e| {
+            DataError::RetrievalError(format!(
+                "failed to parse JSON for version '{}': {}",
+                version, e
+            ))
+        })?;
+    helpers::report_duplicates(
+        &duplicates,
+        args.on_duplicate_name,
+        &format!("version '{}'", version),
+    )?;
+
+    // Navigate into nested objects if data_path is specified
+    let target_value = extract_nested_value(&response_value, data_path).map_err(|e| {
+        DataError::RetrievalError(format!(
+            "failed to extract nested data for version '{}': {}",
+            version, e
+        ))
+    })?;
+
+    if let Some(map) = map {
+        let (map, duplicates) = map
+            .apply(target_value, args.on_duplicate_name)
+            .map_err(|e| {
+                DataError::RetrievalError(format!(
+                    "failed to map response for version '{}': {}",
+                    version, e
+                ))
+            })?;
+        helpers::report_duplicates(
+            &duplicates,
+            args.on_duplicate_name,
+            &format!("version '{}'", version),
+        )?;
+        return Ok(map);
+    }
+
+    Ok(target_value
+        .as_object()
+        .ok_or_else(|| {
+            DataError::RetrievalError(format!(
+                "expected object at data_path for version '{}'",
+                version
+            ))
+        })?
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect())
+}
+
 #[derive(Debug, Deserialize)]
 struct PostgresConfig {
     url: String,
@@ -37,6 +220,18 @@ struct PostgresConfig {
     /// Path of keys to navigate into nested response objects.
     #[serde(default)]
     data_path: Vec<String>,
+    /// Reshapes record arrays at `data_path` into the name map.
+    #[serde(default)]
+    map: Option<ResponseMap>,
+    /// Expected types of names, checked once every version is fetched.
+    #[serde(default)]
+    schema: Schema,
+    /// Most connections open at once while fetching versions.
+    #[serde(default = "default_pool_size")]
+    pool_size: usize,
+    /// Decimal separator for numbers the source returns as text.
+    #[serde(default)]
+    decimal_separator: DecimalSeparator,
 }
 
 /// Unified HTTP data source configuration for REST and GraphQL-style APIs.
@@ -51,24 +246,200 @@ struct HttpConfig {
     body: Option<String>,
     #[serde(default)]
     headers: HashMap<String, String>,
+    /// GraphQL request posted in place of `body`.
+    #[serde(default)]
+    graphql: Option<GraphqlConfig>,
     /// Path of keys to navigate into nested response objects.
     #[serde(default)]
     data_path: Vec<String>,
+    /// Reshapes record arrays at `data_path` into the name map.
+    #[serde(default)]
+    map: Option<ResponseMap>,
+    /// Expected types of names, checked once every version is fetched.
+    #[serde(default)]
+    schema: Schema,
+    /// Most connections open at once while fetching versions.
+    #[serde(default = "default_pool_size")]
+    pool_size: usize,
+    /// Decimal separator for numbers the source returns as text.
+    #[serde(default)]
+    decimal_separator: DecimalSeparator,
+}
+
+/// GraphQL request: the query text, or the hash of a query the server has
+/// persisted, with variables in which `$VERSION` is substituted.
+#[derive(Debug, Deserialize)]
+struct GraphqlConfig {
+    #[serde(default)]
+    query: Option<String>,
+    /// SHA-256 hash of a persisted query, sent as the `persistedQuery` extension.
+    #[serde(default)]
+    persisted_query: Option<String>,
+    #[serde(default)]
+    operation_name: Option<String>,
+    #[serde(default)]
+    variables: Value,
+}
+
+impl GraphqlConfig {
+    fn validate(&self) -> Result<(), DataError> {
+        match (&self.query, &self.persisted_query) {
+            (Some(_), None) | (None, Some(_)) => Ok(()),
+            _ => Err(DataError::FileError(
+                "graphql config needs exactly one of query or persisted_query".to_string(),
+            )),
+        }
+    }
+
+    /// Request body for `version`.
+    fn body(&self, version: &str) -> String {
+        let mut body = serde_json::Map::new();
+        if let Some(query) = &self.query {
+            body.insert("query".into(), query.clone().into());
+        }
+        if let Some(name) = &self.operation_name {
+            body.insert("operationName".into(), name.clone().into());
+        }
+        if !self.variables.is_null() {
+            body.insert(
+                "variables".into(),
+                substitute_version(&self.variables, version),
+            );
+        }
+        if let Some(hash) = &self.persisted_query {
+            body.insert(
+                "extensions".into(),
+                serde_json::json!({"persistedQuery": {"version": 1, "sha256Hash": hash}}),
+            );
+        }
+        Value::Object(body).to_string()
+    }
+}
+
+/// Replaces `$VERSION` in every string within `value`.
+fn substitute_version(value: &Value, version: &str) -> Value {
+    match value {
+        Value::String(s) => Value::String(s.replace("$VERSION", version)),
+        Value::Array(items) => items
+            .iter()
+            .map(|v| substitute_version(v, version))
+            .collect(),
+        Value::Object(fields) => fields
+            .iter()
+            .map(|(k, v)| (k.clone(), substitute_version(v, version)))
+            .collect(),
+        other => other.clone(),
+    }
+}
+
+/// Key naming the JSON files whose values a `--json` version starts from.
+const INCLUDE_KEY: &str = "$include";
+
+/// Top-level `--json` key giving the decimal separator of text numbers.
+const SEPARATOR_KEY: &str = "$decimal_separator";
+
+/// Merges the flat JSON files named by `"$include"` (a path or list of paths,
+/// relative to `dir`) beneath `values`: later files override earlier ones and
+/// the version's own values override both. Included files may include others;
+/// `chain` holds the files being expanded, to catch cycles.
+fn resolve_includes(
+    mut values: HashMap<String, Value>,
+    dir: &Path,
+    args: &DataArgs,
+    chain: &mut Vec<PathBuf>,
+) -> Result<HashMap<String, Value>, DataError> {
+    let Some(include) = values.remove(INCLUDE_KEY) else {
+        return Ok(values);
+    };
+    let invalid = || {
+        DataError::FileError(format!(
+            "{} must be a path or a list of paths, got {}",
+            INCLUDE_KEY, include
+        ))
+    };
+    let paths: Vec<&str> = match &include {
+        Value::String(path) => vec![path],
+        Value::Array(items) => items
+            .iter()
+            .map(|item| item.as_str().ok_or_else(invalid))
+            .collect::<Result<_, _>>()?,
+        _ => return Err(invalid()),
+    };
+
+    let mut merged = HashMap::new();
+    for path in paths {
+        let path = dir.join(path);
+        let text = std::fs::read_to_string(&path).map_err(|e| {
+            DataError::FileError(format!(
+                "failed to open included file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let canonical = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        if chain.contains(&canonical) {
+            return Err(DataError::FileError(format!(
+                "{} cycle through {}",
+                INCLUDE_KEY,
+                path.display()
+            )));
+        }
+        let origin = path.display().to_string();
+        let (value, duplicates) = helpers::parse_json(&text, args.on_duplicate_name)
+            .map_err(|e| DataError::FileError(format!("failed to parse {}: {}", origin, e)))?;
+        helpers::report_duplicates(&duplicates, args.on_duplicate_name, &origin)?;
+        let included: HashMap<String, Value> = serde_json::from_value(value)
+            .map_err(|e| DataError::FileError(format!("failed to parse {}: {}", origin, e)))?;
+
+        chain.push(canonical);
+        let included = resolve_includes(
+            included,
+            path.parent().unwrap_or(Path::new("")),
+            args,
+            chain,
+        )?;
+        chain.pop();
+        merged.extend(included);
+    }
+    merged.extend(values);
+    Ok(merged)
 }
 
 fn default_method() -> String {
     "GET".to_string()
 }
 
+fn default_pool_size() -> usize {
+    4
+}
+
 /// Shared JSON-based data source that reads version data from JSON objects.
 /// Result: `Vec<HashMap<String, Value>>` in version priority order.
+#[derive(Clone)]
 pub struct JsonDataSource {
-    version_columns: Vec<HashMap<String, Value>>,
+    /// Loaded version names, parallel to `version_columns`.
+    versions: Vec<String>,
+    version_columns: Vec<Arc<HashMap<String, Value>>>,
+    decimal_separator: DecimalSeparator,
+    normalize_names: bool,
 }
 
 impl JsonDataSource {
-    fn new(version_columns: Vec<HashMap<String, Value>>) -> Self {
-        JsonDataSource { version_columns }
+    fn new(
+        columns: Vec<(String, HashMap<String, Value>)>,
+        decimal_separator: DecimalSeparator,
+        args: &DataArgs,
+    ) -> Self {
+        let (versions, version_columns) = columns
+            .into_iter()
+            .map(|(version, map)| (version, Arc::new(map)))
+            .unzip();
+        JsonDataSource {
+            versions,
+            version_columns,
+            decimal_separator,
+            normalize_names: args.normalize_names,
+        }
     }
 
     /// Creates a JSON data source from Postgres queries.
@@ -78,65 +449,48 @@ impl JsonDataSource {
             .as_ref()
             .ok_or_else(|| DataError::MiscError("missing postgres config".to_string()))?;
 
-        let json_str = load_json_string_or_file(pg_config_str)?;
-        let config: PostgresConfig = serde_json::from_str(&json_str)
-            .map_err(|e| DataError::FileError(format!("failed to parse JSON: {}", e)))?;
-
-        let mut client = Client::connect(&config.url, NoTls)
-            .map_err(|e| DataError::MiscError(format!("failed to connect to Postgres: {}", e)))?;
+        let config: PostgresConfig = load_config_json(pg_config_str, args)?;
 
         let versions = args.get_version_list();
-        let mut version_columns = Vec::with_capacity(versions.len());
-
-        for version in &versions {
+        let connect = || {
+            Client::connect(&config.url, NoTls).map_err(|e| {
+                DataError::Unavailable(format!("failed to connect to Postgres: {}", e))
+            })
+        };
+        let payloads = fetch_pooled(&versions, config.pool_size, connect, |client, version| {
             let row = client
-                .query_one(&config.query_template, &[version])
+                .query_one(&config.query_template, &[&version])
                 .map_err(|e| {
                     DataError::RetrievalError(format!(
                         "query failed for version '{}': {}",
                         version, e
                     ))
                 })?;
-
-            let json_str: String = row.try_get(0).map_err(|e| {
+            row.try_get::<_, String>(0).map_err(|e| {
                 DataError::RetrievalError(format!(
                     "failed to get JSON column for version '{}': {}",
                     version, e
                 ))
-            })?;
-
-            let response_value: Value = serde_json::from_str(&json_str).map_err(|e| {
-                DataError::RetrievalError(format!(
-                    "failed to parse JSON for version '{}': {}",
-                    version, e
-                ))
-            })?;
-
-            // Navigate into nested objects if data_path is specified
-            let target_value =
-                extract_nested_value(&response_value, &config.data_path).map_err(|e| {
-                    DataError::RetrievalError(format!(
-                        "failed to extract nested data for version '{}': {}",
-                        version, e
-                    ))
-                })?;
-
-            let map: HashMap<String, Value> = target_value
-                .as_object()
-                .ok_or_else(|| {
-                    DataError::RetrievalError(format!(
-                        "expected object at data_path for version '{}'",
-                        version
-                    ))
-                })?
-                .iter()
-                .map(|(k, v)| (k.clone(), v.clone()))
-                .collect();
+            })
+        })?;
 
-            version_columns.push(map);
-        }
+        let version_columns = versions
+            .into_iter()
+            .zip(payloads)
+            .map(|(version, json_str)| {
+                let map = parse_payload(
+                    &json_str,
+                    &version,
+                    &config.data_path,
+                    config.map.as_ref(),
+                    args,
+                )?;
+                Ok((version, map))
+            })
+            .collect::<Result<Vec<_>, DataError>>()?;
+        schema::validate(&config.schema, &version_columns, config.decimal_separator)?;
 
-        Ok(Self::new(version_columns))
+        Ok(Self::new(version_columns, config.decimal_separator, args))
     }
 
     /// Creates a JSON data source from HTTP API calls (unified REST/GraphQL).
@@ -147,33 +501,50 @@ impl JsonDataSource {
             .as_ref()
             .ok_or_else(|| DataError::MiscError("missing http config".to_string()))?;
 
-        let json_str = load_json_string_or_file(http_config_str)?;
-        let config: HttpConfig = serde_json::from_str(&json_str)
-            .map_err(|e| DataError::FileError(format!("failed to parse JSON: {}", e)))?;
+        let config: HttpConfig = load_config_json(http_config_str, args)?;
+        if let Some(graphql) = &config.graphql {
+            graphql.validate()?;
+            if config.body.is_some() {
+                return Err(DataError::FileError(
+                    "http config takes either body or graphql, not both".to_string(),
+                ));
+            }
+        }
 
         let versions = args.get_version_list();
-        let mut version_columns = Vec::with_capacity(versions.len());
-
-        for version in &versions {
+        // One agent keeps finished connections open for the next version.
+        let agent: Agent = Agent::config_builder()
+            .max_idle_connections_per_host(config.pool_size)
+            .build()
+            .into();
+        let connect = || Ok(agent.clone());
+        let payloads = fetch_pooled(&versions, config.pool_size, connect, |agent, version| {
             let encoded_version =
                 percent_encoding::utf8_percent_encode(version, percent_encoding::NON_ALPHANUMERIC);
             let url = config.url.replace("$VERSION", &encoded_version.to_string());
 
-            let response = match config.method.to_uppercase().as_str() {
+            let method = match config.graphql {
+                Some(_) => "POST".to_string(),
+                None => config.method.to_uppercase(),
+            };
+            let response = match method.as_str() {
                 "POST" => {
-                    let body = config
-                        .body
-                        .as_ref()
-                        .map(|b| b.replace("$VERSION", version))
-                        .unwrap_or_default();
+                    let body = match &config.graphql {
+                        Some(graphql) => graphql.body(version),
+                        None => config
+                            .body
+                            .as_ref()
+                            .map(|b| b.replace("$VERSION", version))
+                            .unwrap_or_default(),
+                    };
 
-                    let mut request = ureq::post(&url).header("Content-Type", "application/json");
+                    let mut request = agent.post(&url).header("Content-Type", "application/json");
                     for (key, value) in &config.headers {
                         request = request.header(key, value);
                     }
 
                     request.send(body.as_bytes()).map_err(|e| {
-                        DataError::RetrievalError(format!(
+                        DataError::Unavailable(format!(
                             "HTTP POST request failed for version '{}': {}",
                             version, e
                         ))
@@ -181,13 +552,13 @@ impl JsonDataSource {
                 }
                 _ => {
                     // Default to GET
-                    let mut request = ureq::get(&url);
+                    let mut request = agent.get(&url);
                     for (key, value) in &config.headers {
                         request = request.header(key, value);
                     }
 
                     request.call().map_err(|e| {
-                        DataError::RetrievalError(format!(
+                        DataError::Unavailable(format!(
                             "HTTP GET request failed for version '{}': {}",
                             version, e
                         ))
@@ -195,45 +566,31 @@ impl JsonDataSource {
                 }
             };
 
-            let json_str = response.into_body().read_to_string().map_err(|e| {
+            response.into_body().read_to_string().map_err(|e| {
                 DataError::RetrievalError(format!(
                     "failed to read response body for version '{}': {}",
                     version, e
                 ))
-            })?;
-
-            let response_value: Value = serde_json::from_str(&json_str).map_err(|e| {
-                DataError::RetrievalError(format!(
-                    "failed to parse JSON for version '{}': {}",
-                    version, e
-                ))
-            })?;
-
-            // Navigate into nested objects if data_path is specified
-            let target_value =
-                extract_nested_value(&response_value, &config.data_path).map_err(|e| {
-                    DataError::RetrievalError(format!(
-                        "failed to extract nested data for version '{}': {}",
-                        version, e
-                    ))
-                })?;
-
-            let map: HashMap<String, Value> = target_value
-                .as_object()
-                .ok_or_else(|| {
-                    DataError::RetrievalError(format!(
-                        "expected object at data_path for version '{}'",
-                        version
-                    ))
-                })?
-                .iter()
-                .map(|(k, v)| (k.clone(), v.clone()))
-                .collect();
+            })
+        })?;
 
-            version_columns.push(map);
-        }
+        let version_columns = versions
+            .into_iter()
+            .zip(payloads)
+            .map(|(version, json_str)| {
+                let map = parse_payload(
+                    &json_str,
+                    &version,
+                    &config.data_path,
+                    config.map.as_ref(),
+                    args,
+                )?;
+                Ok((version, map))
+            })
+            .collect::<Result<Vec<_>, DataError>>()?;
+        schema::validate(&config.schema, &version_columns, config.decimal_separator)?;
 
-        Ok(Self::new(version_columns))
+        Ok(Self::new(version_columns, config.decimal_separator, args))
     }
 
     /// Creates a JSON data source from a JSON object.
@@ -245,9 +602,24 @@ impl JsonDataSource {
             .ok_or_else(|| DataError::MiscError("missing json config".to_string()))?;
 
         let json_content = load_json_string_or_file(json_str)?;
-        let data: HashMap<String, HashMap<String, Value>> = serde_json::from_str(&json_content)
+        let (mut value, duplicates) = helpers::parse_json(&json_content, args.on_duplicate_name)
+            .map_err(|e| DataError::FileError(format!("failed to parse JSON: {}", e)))?;
+        helpers::report_duplicates(&duplicates, args.on_duplicate_name, "JSON data")?;
+        let decimal_separator = match value.as_object_mut().and_then(|o| o.remove(SEPARATOR_KEY)) {
+            Some(separator) => serde_json::from_value(separator)
+                .map_err(|e| DataError::FileError(format!("invalid {}: {}", SEPARATOR_KEY, e)))?,
+            None => DecimalSeparator::default(),
+        };
+        let data: HashMap<String, HashMap<String, Value>> = serde_json::from_value(value)
             .map_err(|e| DataError::FileError(format!("failed to parse JSON: {}", e)))?;
 
+        let (dir, root) = match json_str.ends_with(".json") {
+            true => (
+                Path::new(json_str).parent().unwrap_or(Path::new("")),
+                std::fs::canonicalize(json_str).ok(),
+            ),
+            false => (Path::new(""), None),
+        };
         let versions = args.get_version_list();
         let mut version_columns = Vec::with_capacity(versions.len());
 
@@ -261,16 +633,67 @@ impl JsonDataSource {
                     ))
                 })?
                 .clone();
-            version_columns.push(map);
+            let mut chain: Vec<PathBuf> = root.iter().cloned().collect();
+            let map = resolve_includes(map, dir, args, &mut chain)?;
+            version_columns.push((version.clone(), map));
         }
 
-        Ok(Self::new(version_columns))
+        Ok(Self::new(version_columns, decimal_separator, args))
+    }
+
+    /// Creates a single-layer source from a flat `{ name: value }` JSON object.
+    pub(crate) fn from_overrides(input: &str, args: &DataArgs) -> Result<Self, DataError> {
+        let json_content = load_json_string_or_file(input)?;
+        let (value, duplicates) = helpers::parse_json(&json_content, args.on_duplicate_name)
+            .map_err(|e| DataError::FileError(format!("failed to parse overrides: {}", e)))?;
+        helpers::report_duplicates(&duplicates, args.on_duplicate_name, "overrides")?;
+        let map: HashMap<String, Value> = serde_json::from_value(value)
+            .map_err(|e| DataError::FileError(format!("failed to parse overrides: {}", e)))?;
+        Ok(Self::from_values("overrides", map, args))
+    }
+
+    /// Creates a single-layer source labelled `label` from name:value pairs.
+    pub(crate) fn from_values(
+        label: &str,
+        values: HashMap<String, Value>,
+        args: &DataArgs,
+    ) -> Self {
+        Self::new(
+            vec![(label.to_string(), values)],
+            DecimalSeparator::default(),
+            args,
+        )
+    }
+
+    /// Returns true if any version defines a non-null value for `name`.
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.find(name).is_some()
     }
 
     fn lookup(&self, name: &str) -> Option<&Value> {
+        let (_, key, value) = self.find(name)?;
+        if key != name {
+            helpers::warn_fuzzy_match(name, key);
+        }
+        Some(value)
+    }
+
+    /// First version defining `name` (exactly, or normalized when enabled),
+    /// as its index, the key, and the value.
+    fn find(&self, name: &str) -> Option<(usize, &str, &Value)> {
+        let wanted = self.normalize_names.then(|| helpers::normalize_name(name));
         self.version_columns
             .iter()
-            .find_map(|map| map.get(name).filter(|v| !v.is_null()))
+            .enumerate()
+            .find_map(|(index, map)| {
+                if let Some((key, value)) = map.get_key_value(name).filter(|(_, v)| !v.is_null()) {
+                    return Some((index, key.as_str(), value));
+                }
+                let wanted = wanted.as_ref()?;
+                map.iter()
+                    .find(|(k, v)| !v.is_null() && helpers::normalize_name(k) == *wanted)
+                    .map(|(k, v)| (index, k.as_str(), v))
+            })
     }
 
     fn value_to_data_value(value: &Value) -> Result<DataValue, DataError> {
@@ -295,20 +718,6 @@ impl JsonDataSource {
             )),
         }
     }
-
-    fn parse_delimited_numbers(s: &str) -> Option<Vec<DataValue>> {
-        s.split(|c: char| c.is_whitespace() || c == ',' || c == ';')
-            .map(|p| p.trim())
-            .filter(|p| !p.is_empty())
-            .map(|p| {
-                p.parse::<u64>()
-                    .map(DataValue::U64)
-                    .ok()
-                    .or_else(|| p.parse::<i64>().map(DataValue::I64).ok())
-                    .or_else(|| p.parse::<f64>().map(DataValue::F64).ok())
-            })
-            .collect()
-    }
 }
 
 impl DataSource for JsonDataSource {
@@ -318,12 +727,12 @@ impl DataSource for JsonDataSource {
                 .lookup(name)
                 .ok_or_else(|| DataError::RetrievalError("key not found in any version".into()))?;
 
-            let dv = Self::value_to_data_value(value)?;
-            match dv {
-                DataValue::Str(_) => Err(DataError::RetrievalError(
-                    "Found non-numeric single value".to_string(),
-                )),
-                _ => Ok(dv),
+            match Self::value_to_data_value(value)? {
+                DataValue::Str(s) => helpers::parse_number(s.trim(), self.decimal_separator)
+                    .ok_or_else(|| {
+                        DataError::RetrievalError("Found non-numeric single value".to_string())
+                    }),
+                dv => Ok(dv),
             }
         })();
 
@@ -345,10 +754,12 @@ impl DataSource for JsonDataSource {
                         arr.iter().map(Self::value_to_data_value).collect();
                     Ok(ValueSource::Array(items?))
                 }
-                Value::String(s) => match Self::parse_delimited_numbers(s) {
-                    Some(arr) if !arr.is_empty() => Ok(ValueSource::Array(arr)),
-                    _ => Ok(ValueSource::Single(DataValue::Str(s.clone()))),
-                },
+                Value::String(s) => {
+                    match helpers::parse_delimited_numbers(s, self.decimal_separator) {
+                        Some(arr) if !arr.is_empty() => Ok(ValueSource::Array(arr)),
+                        _ => Ok(ValueSource::Single(DataValue::Str(s.clone()))),
+                    }
+                }
                 _ => Err(DataError::RetrievalError(
                     "expected array or string for 1D array".to_string(),
                 )),
@@ -391,4 +802,176 @@ impl DataSource for JsonDataSource {
             source: Box::new(e),
         })
     }
+
+    /// Reads a name whose value is an object of string:number pairs, following
+    /// the version stack like any other name.
+    fn retrieve_map(&self, table: &str) -> Result<HashMap<String, DataValue>, DataError> {
+        let result = (|| {
+            let value = self
+                .lookup(table)
+                .ok_or_else(|| DataError::RetrievalError("key not found in any version".into()))?;
+            let Value::Object(entries) = value else {
+                return Err(DataError::RetrievalError(
+                    "expected an object for a mapping table".to_string(),
+                ));
+            };
+            entries
+                .iter()
+                .map(|(key, value)| match Self::value_to_data_value(value)? {
+                    DataValue::Str(_) => Err(DataError::RetrievalError(format!(
+                        "non-numeric value for '{}'",
+                        key
+                    ))),
+                    dv => Ok((key.clone(), dv)),
+                })
+                .collect()
+        })();
+
+        result.map_err(|e| DataError::WhileRetrieving {
+            name: format!("#{}", table),
+            source: Box::new(e),
+        })
+    }
+
+    fn names(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        self.version_columns
+            .iter()
+            .flat_map(|map| map.iter())
+            .filter(|(key, value)| !value.is_null() && seen.insert(key.as_str()))
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    fn versions(&self) -> Vec<String> {
+        self.versions.clone()
+    }
+
+    fn version_of(&self, name: &str) -> Option<String> {
+        self.find(name)
+            .map(|(index, _, _)| self.versions[index].clone())
+    }
+
+    fn snapshot(&self) -> Result<Snapshot, DataError> {
+        Ok(self
+            .versions
+            .iter()
+            .cloned()
+            .zip(self.version_columns.iter().map(|column| (**column).clone()))
+            .collect())
+    }
+
+    fn restack(&self, versions: &[String]) -> Result<Box<dyn DataSource>, DataError> {
+        let indices = helpers::stack_indices(&self.versions, versions)?;
+        Ok(Box::new(Self {
+            versions: indices.iter().map(|&i| self.versions[i].clone()).collect(),
+            version_columns: indices
+                .iter()
+                .map(|&i| Arc::clone(&self.version_columns[i]))
+                .collect(),
+            ..*self
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pooled_fetch_reuses_connections_and_keeps_version_order() {
+        let versions: Vec<String> = (0..6).map(|i| format!("V{}", i)).collect();
+        let connections = AtomicUsize::new(0);
+        let connect = || Ok(connections.fetch_add(1, Ordering::Relaxed));
+        let results = fetch_pooled(&versions, 2, connect, |_, version| {
+            Ok(version.to_lowercase())
+        })
+        .unwrap();
+        assert_eq!(results, ["v0", "v1", "v2", "v3", "v4", "v5"]);
+        assert!(connections.load(Ordering::Relaxed) <= 2);
+
+        let err = fetch_pooled(&versions, 2, connect, |_, version| match version {
+            "V3" | "V1" => Err(DataError::MiscError(version.to_string())),
+            _ => Ok(()),
+        })
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            DataError::MiscError("V1".into()).to_string()
+        );
+    }
+
+    #[test]
+    fn data_path_indexes_and_filters_arrays() {
+        let response = serde_json::json!({
+            "items": [{"values": {"Gain": 1}}],
+            "results": [
+                {"name": "boot", "id": 1, "values": {"Gain": 2}},
+                {"name": "cal", "id": 2, "values": {"Gain": 3}}
+            ],
+            "a[0]": {"Gain": 4},
+            "grid": [[{"Gain": 5}]]
+        });
+        let gain = |path: &[&str]| {
+            let path: Vec<String> = path.iter().map(|s| s.to_string()).collect();
+            extract_nested_value(&response, &path).map(|v| v["Gain"].clone())
+        };
+        assert_eq!(gain(&["items[0]", "values"]).unwrap(), 1);
+        assert_eq!(gain(&["results[?name=='cal']", "values"]).unwrap(), 3);
+        assert_eq!(gain(&["results[?id == 1]", "values"]).unwrap(), 2);
+        assert_eq!(gain(&["a[0]"]).unwrap(), 4);
+        assert_eq!(gain(&["grid[0][0]"]).unwrap(), 5);
+        assert_eq!(gain(&["grid", "[0]", "[0]"]).unwrap(), 5);
+
+        let err = |path: &[&str]| gain(path).unwrap_err().to_string();
+        assert!(err(&["items[1]"]).contains("index 1 is out of range for 1 elements"));
+        assert!(err(&["results[?name=='app']"]).contains("no element matches"));
+        assert!(err(&["items[x]"]).contains("invalid data_path segment 'items[x]'"));
+        assert!(err(&["items[0"]).contains("invalid data_path segment"));
+        assert!(err(&["missing[0]"]).contains("nested key 'missing' not found"));
+        assert!(err(&["a[0]", "[0]"]).contains("not an array"));
+    }
+
+    #[test]
+    fn graphql_body_carries_persisted_query_and_variables() {
+        let config: GraphqlConfig = serde_json::from_str(
+            r#"{"persisted_query": "abc123", "operation_name": "Calibration",
+                "variables": {"variant": "$VERSION", "tags": ["$VERSION-rc"], "limit": 5}}"#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        let body: Value = serde_json::from_str(&config.body("Debug")).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "operationName": "Calibration",
+                "variables": {"variant": "Debug", "tags": ["Debug-rc"], "limit": 5},
+                "extensions": {"persistedQuery": {"version": 1, "sha256Hash": "abc123"}}
+            })
+        );
+
+        let both: GraphqlConfig =
+            serde_json::from_str(r#"{"query": "{ a }", "persisted_query": "abc"}"#).unwrap();
+        assert!(both.validate().is_err());
+        let neither: GraphqlConfig = serde_json::from_str("{}").unwrap();
+        assert!(neither.validate().is_err());
+    }
+
+    #[test]
+    fn mapping_tables_follow_the_version_stack() {
+        let args = DataArgs {
+            json: Some(
+                r#"{"Debug": {"States": {"Off": 0, "On": 1, "Fault": 7}},
+                    "Default": {"States": {"Off": 0}, "Mode": {"On": "x"}}}"#
+                    .to_string(),
+            ),
+            version: Some("Debug/Default".to_string()),
+            ..Default::default()
+        };
+        let ds = JsonDataSource::from_json(&args).unwrap();
+        let states = ds.retrieve_map("States").unwrap();
+        assert!(matches!(states["Fault"], DataValue::U64(7)));
+        assert!(ds.retrieve_map("Mode").is_err());
+        assert!(ds.retrieve_map("Missing").is_err());
+    }
 }
@@ -1,13 +1,32 @@
 pub mod args;
+mod composite;
+mod csv;
 pub mod error;
 mod excel;
 mod helpers;
 mod json;
+mod overlay;
+mod response_map;
+mod schema;
+mod secrets;
+mod timed;
 
 use crate::layout::value::{DataValue, ValueSource};
+use composite::CompositeDataSource;
+pub(crate) use composite::not_configured;
+pub use csv::read_csv;
 use error::DataError;
 use excel::ExcelDataSource;
 use json::JsonDataSource;
+use overlay::OverrideDataSource;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+pub use timed::TimedDataSource;
+
+/// Loaded versions' name:value maps, highest priority first.
+pub type Snapshot = Vec<(String, HashMap<String, serde_json::Value>)>;
 
 /// Trait for data sources that provide values by name.
 pub trait DataSource: Sync {
@@ -17,24 +36,237 @@ pub trait DataSource: Sync {
     /// Retrieves a 1D array (from sheet reference) or a literal string.
     fn retrieve_1d_array_or_string(&self, name: &str) -> Result<ValueSource, DataError>;
 
+    /// Retrieves a 1D array for an entry that cannot hold a string, so text
+    /// may be read as a delimited list of numbers.
+    fn retrieve_1d_array(&self, name: &str) -> Result<ValueSource, DataError> {
+        self.retrieve_1d_array_or_string(name)
+    }
+
     /// Retrieves a 2D array from a sheet reference.
     fn retrieve_2d_array(&self, name: &str) -> Result<Vec<Vec<DataValue>>, DataError>;
+
+    /// Table mapping strings to values, referenced by `map = "#Table"`.
+    fn retrieve_map(&self, table: &str) -> Result<HashMap<String, DataValue>, DataError> {
+        Err(DataError::MiscError(format!(
+            "data source does not provide mapping tables ('{}')",
+            table
+        )))
+    }
+
+    /// Version stack the source resolves names through, highest priority first.
+    fn versions(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Version that provides `name`: the first in the stack defining it, or
+    /// `None` if none does or the source has no versions.
+    fn version_of(&self, _name: &str) -> Option<String> {
+        None
+    }
+
+    /// Names defined by the source, used to expand `names` patterns.
+    fn names(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// The same source with `versions` as its priority stack, sharing the
+    /// loaded data. Every version must have been loaded.
+    fn restack(&self, _versions: &[String]) -> Result<Box<dyn DataSource>, DataError> {
+        Err(DataError::MiscError(
+            "data source does not support version stacks".to_string(),
+        ))
+    }
+
+    /// The configured source of the given kind, for a block whose header
+    /// names it, with any overrides still layered on top.
+    fn select(&self, source: SourceKind) -> Result<Box<dyn DataSource + '_>, DataError> {
+        Err(composite::not_configured(source))
+    }
+
+    /// Each loaded version's name:value map, highest priority first, for
+    /// `--snapshot`.
+    fn snapshot(&self) -> Result<Snapshot, DataError> {
+        Err(DataError::MiscError(
+            "--snapshot needs a --json, --http, or --postgres default source".to_string(),
+        ))
+    }
+}
+
+impl<T: DataSource + ?Sized> DataSource for &T {
+    fn retrieve_single_value(&self, name: &str) -> Result<DataValue, DataError> {
+        (**self).retrieve_single_value(name)
+    }
+
+    fn retrieve_1d_array_or_string(&self, name: &str) -> Result<ValueSource, DataError> {
+        (**self).retrieve_1d_array_or_string(name)
+    }
+
+    fn retrieve_1d_array(&self, name: &str) -> Result<ValueSource, DataError> {
+        (**self).retrieve_1d_array(name)
+    }
+
+    fn retrieve_2d_array(&self, name: &str) -> Result<Vec<Vec<DataValue>>, DataError> {
+        (**self).retrieve_2d_array(name)
+    }
+
+    fn retrieve_map(&self, table: &str) -> Result<HashMap<String, DataValue>, DataError> {
+        (**self).retrieve_map(table)
+    }
+
+    fn versions(&self) -> Vec<String> {
+        (**self).versions()
+    }
+
+    fn version_of(&self, name: &str) -> Option<String> {
+        (**self).version_of(name)
+    }
+
+    fn names(&self) -> Vec<String> {
+        (**self).names()
+    }
+
+    fn restack(&self, versions: &[String]) -> Result<Box<dyn DataSource>, DataError> {
+        (**self).restack(versions)
+    }
+
+    fn select(&self, source: SourceKind) -> Result<Box<dyn DataSource + '_>, DataError> {
+        (**self).select(source)
+    }
+
+    fn snapshot(&self) -> Result<Snapshot, DataError> {
+        (**self).snapshot()
+    }
+}
+
+/// Data source flags, in the order the default source is chosen from when
+/// several are given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceKind {
+    Xlsx,
+    Postgres,
+    Http,
+    Json,
+}
+
+impl fmt::Display for SourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            SourceKind::Xlsx => "xlsx",
+            SourceKind::Postgres => "postgres",
+            SourceKind::Http => "http",
+            SourceKind::Json => "json",
+        })
+    }
 }
 
 /// Creates a data source from CLI arguments.
 ///
 /// Returns `None` if no data source is configured (e.g., no `--xlsx` provided).
 pub fn create_data_source(args: &args::DataArgs) -> Result<Option<Box<dyn DataSource>>, DataError> {
+    let base = create_base_source(args)?;
+    match &args.overrides {
+        Some(input) => Ok(Some(Box::new(OverrideDataSource::new(
+            JsonDataSource::from_overrides(input, args)?,
+            base,
+        )))),
+        None => Ok(base),
+    }
+}
+
+/// Creates the data sources named by `--xlsx`, `--postgres`, `--http` and
+/// `--json`, without `--override` or `--image-source` values. The first given,
+/// in that order, is the default for blocks that do not select one.
+pub fn create_base_source(args: &args::DataArgs) -> Result<Option<Box<dyn DataSource>>, DataError> {
     // Handle fallback from deprecated --variant flag
     if args.variant.is_some() && args.version.is_none() {
-        eprintln!("Warning: --variant is deprecated, use --version instead");
+        crate::warnings::warn("--variant is deprecated, use --version instead");
+    }
+
+    let configured = [&args.xlsx, &args.postgres, &args.http, &args.json];
+    if configured.iter().any(|s| s.is_some()) && args.get_version_list().is_empty() {
+        // clap requires -v with a source flag; a mint.toml source may omit it
+        return Err(DataError::MiscError(
+            "a data source is configured but no version was given".to_string(),
+        ));
     }
 
-    match (&args.xlsx, &args.postgres, &args.http, &args.json) {
-        (Some(_), _, _, _) => Ok(Some(Box::new(ExcelDataSource::new(args)?))),
-        (_, Some(_), _, _) => Ok(Some(Box::new(JsonDataSource::from_postgres(args)?))),
-        (_, _, Some(_), _) => Ok(Some(Box::new(JsonDataSource::from_http(args)?))),
-        (_, _, _, Some(_)) => Ok(Some(Box::new(JsonDataSource::from_json(args)?))),
-        _ => Ok(None),
+    let mut sources: Vec<(SourceKind, Box<dyn DataSource>)> = Vec::new();
+    if args.xlsx.is_some() {
+        sources.push((SourceKind::Xlsx, Box::new(ExcelDataSource::new(args)?)));
+    }
+    if args.postgres.is_some() {
+        let source = JsonDataSource::from_postgres(args)?;
+        sources.push((SourceKind::Postgres, Box::new(source)));
+    }
+    if args.http.is_some() {
+        sources.push((SourceKind::Http, Box::new(JsonDataSource::from_http(args)?)));
+    }
+    if args.json.is_some() {
+        sources.push((SourceKind::Json, Box::new(JsonDataSource::from_json(args)?)));
     }
+    Ok(match sources.is_empty() {
+        true => None,
+        false => Some(Box::new(CompositeDataSource::new(sources))),
+    })
+}
+
+/// The default source's per-version maps as a `--json` document, names sorted.
+pub fn snapshot_document(
+    source: &dyn DataSource,
+) -> Result<serde_json::Map<String, serde_json::Value>, DataError> {
+    Ok(source
+        .snapshot()?
+        .iter()
+        .map(|(version, values)| {
+            let values: BTreeMap<&String, &serde_json::Value> = values.iter().collect();
+            (version.clone(), serde_json::json!(values))
+        })
+        .collect())
+}
+
+/// Writes the default source's per-version maps to `path` as a `--json`
+/// document, names sorted, and returns the number of versions written.
+pub fn write_snapshot(args: &args::DataArgs, path: &Path) -> Result<usize, DataError> {
+    let source = create_base_source(args)?
+        .ok_or_else(|| DataError::MiscError("--snapshot needs a data source".to_string()))?;
+    let document = snapshot_document(source.as_ref())?;
+    let text = serde_json::to_string_pretty(&document)
+        .map_err(|e| DataError::MiscError(format!("failed to serialize snapshot: {}", e)))?;
+    std::fs::write(path, text + "\n").map_err(|e| {
+        DataError::FileError(format!(
+            "failed to write snapshot {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    Ok(document.len())
+}
+
+/// Creates the data source with values read from an `--image-source` image
+/// layered between the base source and `--override`. `read_image` gets the
+/// source without the image values, to place fields the image does not hold.
+pub fn create_image_source<E: From<DataError>>(
+    args: &args::DataArgs,
+    read_image: impl FnOnce(Option<&dyn DataSource>) -> Result<HashMap<String, serde_json::Value>, E>,
+) -> Result<Option<Box<dyn DataSource>>, E> {
+    let base = create_base_source(args)?;
+    let overrides = match &args.overrides {
+        Some(input) => Some(JsonDataSource::from_overrides(input, args)?),
+        None => None,
+    };
+    let (values, base) = match &overrides {
+        Some(overrides) => {
+            let source = OverrideDataSource::new(overrides.clone(), base);
+            (read_image(Some(&source))?, source.into_base())
+        }
+        None => (read_image(base.as_deref())?, base),
+    };
+
+    let image = JsonDataSource::from_values("image", values, args);
+    let layered = OverrideDataSource::new(image, base);
+    Ok(Some(match overrides {
+        Some(overrides) => Box::new(OverrideDataSource::new(overrides, Some(Box::new(layered)))),
+        None => Box::new(layered),
+    }))
 }
@@ -0,0 +1,127 @@
+use super::error::DataError;
+use super::json::JsonDataSource;
+use super::{DataSource, Snapshot, SourceKind};
+use crate::layout::value::{DataValue, ValueSource};
+use std::collections::HashMap;
+
+/// Serves names defined in the overrides first, then falls back to the base source.
+pub struct OverrideDataSource<'a> {
+    overrides: JsonDataSource,
+    base: Option<Box<dyn DataSource + 'a>>,
+}
+
+impl<'a> OverrideDataSource<'a> {
+    pub(crate) fn new(overrides: JsonDataSource, base: Option<Box<dyn DataSource + 'a>>) -> Self {
+        Self { overrides, base }
+    }
+
+    /// The source the overrides fall back to.
+    pub(crate) fn into_base(self) -> Option<Box<dyn DataSource + 'a>> {
+        self.base
+    }
+
+    /// Source responsible for `name`: the overrides if they define it, else the base.
+    fn source_for(&self, name: &str) -> &dyn DataSource {
+        match &self.base {
+            Some(base) if !self.overrides.contains(name) => base.as_ref(),
+            _ => &self.overrides,
+        }
+    }
+}
+
+impl DataSource for OverrideDataSource<'_> {
+    fn retrieve_single_value(&self, name: &str) -> Result<DataValue, DataError> {
+        self.source_for(name).retrieve_single_value(name)
+    }
+
+    fn retrieve_1d_array_or_string(&self, name: &str) -> Result<ValueSource, DataError> {
+        self.source_for(name).retrieve_1d_array_or_string(name)
+    }
+
+    fn retrieve_1d_array(&self, name: &str) -> Result<ValueSource, DataError> {
+        self.source_for(name).retrieve_1d_array(name)
+    }
+
+    fn retrieve_2d_array(&self, name: &str) -> Result<Vec<Vec<DataValue>>, DataError> {
+        self.source_for(name).retrieve_2d_array(name)
+    }
+
+    fn retrieve_map(&self, table: &str) -> Result<HashMap<String, DataValue>, DataError> {
+        self.source_for(table).retrieve_map(table)
+    }
+
+    fn versions(&self) -> Vec<String> {
+        self.base.as_ref().map(|b| b.versions()).unwrap_or_default()
+    }
+
+    fn version_of(&self, name: &str) -> Option<String> {
+        self.source_for(name).version_of(name)
+    }
+
+    fn names(&self) -> Vec<String> {
+        let mut names = self.overrides.names();
+        if let Some(base) = &self.base {
+            for name in base.names() {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+
+    fn restack(&self, versions: &[String]) -> Result<Box<dyn DataSource>, DataError> {
+        let base = match &self.base {
+            Some(base) => Some(base.restack(versions)?),
+            None => None,
+        };
+        Ok(Box::new(OverrideDataSource::new(
+            self.overrides.clone(),
+            base,
+        )))
+    }
+
+    fn select(&self, source: SourceKind) -> Result<Box<dyn DataSource + '_>, DataError> {
+        let base = match &self.base {
+            Some(base) => base.select(source)?,
+            None => return Err(super::composite::not_configured(source)),
+        };
+        Ok(Box::new(OverrideDataSource::new(
+            self.overrides.clone(),
+            Some(base),
+        )))
+    }
+
+    /// The base source's versions; the layered values are not part of them.
+    fn snapshot(&self) -> Result<Snapshot, DataError> {
+        match &self.base {
+            Some(base) => base.snapshot(),
+            None => Err(DataError::MiscError(
+                "--snapshot needs a data source".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::args::DataArgs;
+
+    #[test]
+    fn overrides_take_priority_over_base() {
+        let base =
+            JsonDataSource::from_overrides(r#"{"A": 1, "B": 2}"#, &DataArgs::default()).unwrap();
+        let overrides =
+            JsonDataSource::from_overrides(r#"{"B": 20}"#, &DataArgs::default()).unwrap();
+        let ds = OverrideDataSource::new(overrides, Some(Box::new(base)));
+
+        let value = |name| match ds.retrieve_single_value(name).unwrap() {
+            DataValue::U64(v) => v,
+            other => panic!("unexpected value {other:?}"),
+        };
+        assert_eq!(value("A"), 1);
+        assert_eq!(value("B"), 20);
+        assert!(ds.retrieve_single_value("C").is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use super::args::DuplicatePolicy;
+use super::json::extract_nested_value;
+
+/// jq-style `map` expression that reshapes an array of records into the flat
+/// name to value object a source config must yield, e.g.
+/// `.rows[] | {(.name): .value}`.
+///
+/// Each path is `.` or a run of `.key` steps, where a key may carry the
+/// `[N]` and `[?field=='value']` selectors `data_path` accepts.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub(crate) struct ResponseMap {
+    records: Vec<String>,
+    name: Vec<String>,
+    value: Vec<String>,
+}
+
+impl ResponseMap {
+    /// Builds the name map from `response`, returning it with the names that
+    /// more than one record gave. `policy` picks which of those records wins.
+    pub(crate) fn apply(
+        &self,
+        response: &Value,
+        policy: DuplicatePolicy,
+    ) -> Result<(HashMap<String, Value>, Vec<String>), String> {
+        let records = extract_nested_value(response, &self.records)?
+            .as_array()
+            .ok_or("map records are not an array")?;
+
+        let mut map = HashMap::new();
+        let mut duplicates = Vec::new();
+        for (i, record) in records.iter().enumerate() {
+            let field = |path: &[String]| {
+                extract_nested_value(record, path).map_err(|e| format!("map record {}: {}", i, e))
+            };
+            let name = field(&self.name)?
+                .as_str()
+                .ok_or_else(|| format!("map record {}: name is not a string", i))?;
+            let value = field(&self.value)?.clone();
+            if map.contains_key(name) {
+                duplicates.push(name.to_string());
+                if policy != DuplicatePolicy::LastWins {
+                    continue;
+                }
+            }
+            map.insert(name.to_string(), value);
+        }
+        Ok((map, duplicates))
+    }
+}
+
+impl TryFrom<String> for ResponseMap {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        text.parse()
+    }
+}
+
+impl FromStr for ResponseMap {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let invalid = |reason: &str| format!("invalid map '{}': {}", text, reason);
+        let (records, object) = text
+            .split_once('|')
+            .ok_or_else(|| invalid("expected '<records>[] | {(<name>): <value>}'"))?;
+        let records = records
+            .trim()
+            .strip_suffix("[]")
+            .ok_or_else(|| invalid("records must end in '[]'"))?;
+        let (name, value) = object
+            .trim()
+            .strip_prefix("{(")
+            .and_then(|rest| rest.strip_suffix('}'))
+            .and_then(|rest| rest.split_once("):"))
+            .ok_or_else(|| invalid("expected '{(<name>): <value>}' after '|'"))?;
+
+        Ok(ResponseMap {
+            records: parse_path(records).map_err(|e| invalid(&e))?,
+            name: parse_path(name).map_err(|e| invalid(&e))?,
+            value: parse_path(value).map_err(|e| invalid(&e))?,
+        })
+    }
+}
+
+/// Splits `.a.b[0]` into `data_path` steps; `.` alone is the value itself.
+fn parse_path(text: &str) -> Result<Vec<String>, String> {
+    let text = text.trim();
+    let rest = text
+        .strip_prefix('.')
+        .ok_or_else(|| format!("path '{}' must start with '.'", text))?;
+    if rest.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut steps = vec![String::new()];
+    let mut depth = 0usize;
+    for c in rest.chars() {
+        match c {
+            '.' if depth == 0 => steps.push(String::new()),
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        if c != '.' || depth > 0 {
+            steps.last_mut().unwrap().push(c);
+        }
+    }
+    match steps.iter().any(String::is_empty) {
+        true => Err(format!("path '{}' has an empty step", text)),
+        false => Ok(steps),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reshapes_record_arrays_into_a_name_map() {
+        let map: ResponseMap = ".data.rows[] | {(.name): .value}".parse().unwrap();
+        let response = json!({"data": {"rows": [
+            {"name": "Gain", "value": 1.5},
+            {"name": "Table", "value": [1, 2]},
+            {"name": "Gain", "value": 2.5}
+        ]}});
+        let (values, duplicates) = map.apply(&response, DuplicatePolicy::Warn).unwrap();
+        assert_eq!(values["Gain"], 1.5);
+        assert_eq!(values["Table"], json!([1, 2]));
+        assert_eq!(duplicates, ["Gain"]);
+
+        let (values, _) = map.apply(&response, DuplicatePolicy::LastWins).unwrap();
+        assert_eq!(values["Gain"], 2.5);
+    }
+
+    #[test]
+    fn paths_accept_selectors_and_the_root() {
+        let map: ResponseMap = ".[] | {(.meta.key): .values[?unit=='raw'].v}"
+            .parse()
+            .unwrap();
+        let response = json!([
+            {"meta": {"key": "Id"}, "values": [{"unit": "si", "v": 1}, {"unit": "raw", "v": 7}]}
+        ]);
+        let (values, _) = map.apply(&response, DuplicatePolicy::Error).unwrap();
+        assert_eq!(values["Id"], 7);
+        assert_eq!(
+            parse_path(".a[?n=='x.y'].b").unwrap(),
+            ["a[?n=='x.y']", "b"]
+        );
+    }
+
+    #[test]
+    fn reports_malformed_maps_and_records() {
+        let err = |text: &str| text.parse::<ResponseMap>().unwrap_err();
+        assert!(err(".rows | {(.name): .value}").contains("records must end in '[]'"));
+        assert!(err(".rows[]").contains("expected '<records>[] |"));
+        assert!(err(".rows[] | {.name: .value}").contains("expected '{(<name>): <value>}'"));
+        assert!(err(".rows[] | {(name): .value}").contains("must start with '.'"));
+        assert!(err(".rows..x[] | {(.name): .value}").contains("empty step"));
+
+        let map: ResponseMap = ".rows[] | {(.name): .value}".parse().unwrap();
+        let apply = |response| map.apply(&response, DuplicatePolicy::Warn).unwrap_err();
+        assert!(apply(json!({"rows": {}})).contains("not an array"));
+        assert!(
+            apply(json!({"rows": [{"name": 1, "value": 2}]}))
+                .contains("record 0: name is not a string")
+        );
+        assert!(
+            apply(json!({"rows": [{"name": "A"}]}))
+                .contains("record 0: nested key 'value' not found")
+        );
+    }
+}
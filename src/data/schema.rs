@@ -0,0 +1,226 @@
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use super::args::DecimalSeparator;
+use super::error::DataError;
+use super::helpers;
+use crate::layout::decode_byte_string;
+use crate::layout::value::DataValue;
+
+/// Expected type of a name in a source config's `schema`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub(crate) enum ValueType {
+    Int,
+    Float,
+    Bool,
+    String,
+    /// A string of raw bytes for a `u8` array: `0x` or `\\x` hex, or `base64:`.
+    Bytes,
+    Array(Box<ValueType>),
+}
+
+/// Names a source config expects, with their types.
+pub(crate) type Schema = IndexMap<String, ValueType>;
+
+impl ValueType {
+    /// Whether `value` holds this type, reading numeric strings and delimited
+    /// number lists the way the data source does.
+    fn matches(&self, value: &Value, separator: DecimalSeparator) -> bool {
+        match (self, value) {
+            (ValueType::Bool, Value::Bool(_)) | (ValueType::String, Value::String(_)) => true,
+            (ValueType::Bytes, Value::String(s)) => {
+                matches!(decode_byte_string(s, true), Ok(Some(_)))
+            }
+            (ValueType::Int, Value::Number(n)) => n.is_i64() || n.is_u64(),
+            (ValueType::Float, Value::Number(_)) => true,
+            (ValueType::Int | ValueType::Float, Value::String(s)) => {
+                helpers::parse_number(s.trim(), separator).is_some_and(|n| self.accepts(&n))
+            }
+            (ValueType::Array(item), Value::Array(items)) => {
+                items.iter().all(|v| item.matches(v, separator))
+            }
+            (ValueType::Array(item), Value::String(s)) => {
+                helpers::parse_delimited_numbers(s, separator)
+                    .is_some_and(|items| !items.is_empty() && items.iter().all(|n| item.accepts(n)))
+            }
+            _ => false,
+        }
+    }
+
+    fn accepts(&self, number: &DataValue) -> bool {
+        match self {
+            ValueType::Int => matches!(number, DataValue::U64(_) | DataValue::I64(_)),
+            ValueType::Float => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValueType::Int => f.write_str("int"),
+            ValueType::Float => f.write_str("float"),
+            ValueType::Bool => f.write_str("bool"),
+            ValueType::String => f.write_str("string"),
+            ValueType::Bytes => f.write_str("bytes"),
+            ValueType::Array(item) => write!(f, "array<{}>", item),
+        }
+    }
+}
+
+impl TryFrom<String> for ValueType {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        text.parse()
+    }
+}
+
+impl FromStr for ValueType {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        if let Some(item) = text
+            .strip_prefix("array<")
+            .and_then(|rest| rest.strip_suffix('>'))
+        {
+            return Ok(ValueType::Array(Box::new(item.parse()?)));
+        }
+        match text {
+            "int" => Ok(ValueType::Int),
+            "float" => Ok(ValueType::Float),
+            "bool" => Ok(ValueType::Bool),
+            "string" => Ok(ValueType::String),
+            "bytes" => Ok(ValueType::Bytes),
+            _ => Err(format!(
+                "unknown schema type '{}'; expected int, float, bool, string, bytes, or array<...>",
+                text
+            )),
+        }
+    }
+}
+
+/// JSON type name of `value`, for mismatch reports.
+fn found(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(n) if n.is_f64() => "float",
+        Value::Number(_) => "int",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Checks every name of `schema` each version provides, reporting all
+/// mismatches together. Names a version leaves out are not checked.
+pub(crate) fn validate(
+    schema: &Schema,
+    columns: &[(String, HashMap<String, Value>)],
+    separator: DecimalSeparator,
+) -> Result<(), DataError> {
+    let mismatches: Vec<String> = columns
+        .iter()
+        .flat_map(|(version, map)| {
+            schema.iter().filter_map(move |(name, expected)| {
+                let value = map.get(name)?;
+                (!expected.matches(value, separator)).then(|| {
+                    format!(
+                        "'{}' in version '{}' should be {} but is {} {}",
+                        name,
+                        version,
+                        expected,
+                        found(value),
+                        value
+                    )
+                })
+            })
+        })
+        .collect();
+    match mismatches.is_empty() {
+        true => Ok(()),
+        false => Err(DataError::SchemaMismatch(mismatches.join("; "))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn columns(values: Value) -> Vec<(String, HashMap<String, Value>)> {
+        vec![(
+            "Default".to_string(),
+            serde_json::from_value(values).unwrap(),
+        )]
+    }
+
+    #[test]
+    fn parses_nested_types() {
+        assert_eq!(
+            "array<array<float>>"
+                .parse::<ValueType>()
+                .unwrap()
+                .to_string(),
+            "array<array<float>>"
+        );
+        assert!(
+            "array<long>"
+                .parse::<ValueType>()
+                .unwrap_err()
+                .contains("'long'")
+        );
+        assert!("array<int".parse::<ValueType>().is_err());
+    }
+
+    #[test]
+    fn accepts_values_the_source_can_read() {
+        let schema: Schema = serde_json::from_value(json!({
+            "Max": "int", "Gain": "float", "On": "bool", "Label": "string",
+            "Curve": "array<float>", "Steps": "array<int>", "Map": "array<array<int>>",
+            "Blob": "bytes"
+        }))
+        .unwrap();
+        let values = columns(json!({
+            "Max": 55, "Gain": "1.5", "On": true, "Label": "v1",
+            "Curve": [0.5, 1], "Steps": "0 100 200", "Map": [[1, 2], [3, 4]],
+            "Blob": "\\x0102"
+        }));
+        validate(&schema, &values, DecimalSeparator::Point).unwrap();
+    }
+
+    #[test]
+    fn reports_every_mismatch_together() {
+        let schema: Schema = serde_json::from_value(json!({
+            "Max": "int", "Curve": "array<float>", "Absent": "bool", "Blob": "bytes"
+        }))
+        .unwrap();
+        let values = columns(json!({"Max": 5.5, "Curve": "fast", "Blob": "base64:!"}));
+        let err = validate(&schema, &values, DecimalSeparator::Point)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("'Max' in version 'Default' should be int but is float 5.5"),
+            "{err}"
+        );
+        assert!(
+            err.contains(
+                "'Curve' in version 'Default' should be array<float> but is string \"fast\""
+            ),
+            "{err}"
+        );
+        assert!(
+            err.contains("'Blob' in version 'Default' should be bytes"),
+            "{err}"
+        );
+        assert!(!err.contains("Absent"), "{err}");
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use serde_json::Value;
+
+use super::error::DataError;
+
+/// Expands `${VAR}` and secret references in the string values of a parsed
+/// Postgres or HTTP config. Keys and other values are left alone, so an
+/// expanded value cannot change the config's structure.
+///
+/// `${secret:NAME}` reads `NAME` from `secrets_file`, a JSON object of strings;
+/// `${keyring:SERVICE/USER}` reads the password stored in the OS keyring.
+/// Secrets in the top-level `url` are percent-encoded so they stay inside
+/// their URL part.
+pub(super) fn expand_config(
+    config: &mut Value,
+    secrets_file: Option<&str>,
+) -> Result<(), DataError> {
+    let file = secrets_file.map(load_file).transpose()?;
+    let secret = |scheme: &str, key: &str| match scheme {
+        "secret" => match &file {
+            Some(secrets) => secrets
+                .get(key)
+                .cloned()
+                .ok_or_else(|| format!("secret '{}' is not in the secrets file", key)),
+            None => Err(format!(
+                "secret '{}' is referenced but no --secrets file was given",
+                key
+            )),
+        },
+        "keyring" => from_keyring(key),
+        _ => Err(format!("unknown secret scheme '{}'", scheme)),
+    };
+    let in_url = |scheme: &str, key: &str| {
+        secret(scheme, key).map(|value| utf8_percent_encode(&value, NON_ALPHANUMERIC).to_string())
+    };
+    let result = match config {
+        Value::Object(map) => map
+            .iter_mut()
+            .try_for_each(|(name, value)| match name.as_str() {
+                "url" => expand_strings(value, &in_url),
+                _ => expand_strings(value, &secret),
+            }),
+        other => expand_strings(other, &secret),
+    };
+    result.map_err(|e| DataError::FileError(format!("failed to expand config: {}", e)))
+}
+
+/// Expands the references in every string nested in `value`.
+fn expand_strings(
+    value: &mut Value,
+    secret: &dyn Fn(&str, &str) -> Result<String, String>,
+) -> Result<(), String> {
+    match value {
+        Value::String(text) => {
+            *text = crate::interpolate::expand_env_and_secrets(text, secret)?;
+            Ok(())
+        }
+        Value::Array(items) => items.iter_mut().try_for_each(|v| expand_strings(v, secret)),
+        Value::Object(map) => map.values_mut().try_for_each(|v| expand_strings(v, secret)),
+        _ => Ok(()),
+    }
+}
+
+fn load_file(path: &str) -> Result<HashMap<String, String>, DataError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|_| DataError::FileError(format!("failed to open secrets file: {}", path)))?;
+    serde_json::from_str(&text).map_err(|e| {
+        DataError::FileError(format!(
+            "secrets file {} must be a JSON object of strings: {}",
+            path, e
+        ))
+    })
+}
+
+/// Reads the password of `SERVICE/USER` from the platform credential store.
+fn from_keyring(key: &str) -> Result<String, String> {
+    let (service, user) = key
+        .rsplit_once('/')
+        .ok_or_else(|| format!("keyring secret '{}' must be SERVICE/USER", key))?;
+    keyring::Entry::new(service, user)
+        .and_then(|entry| entry.get_password())
+        .map_err(|e| format!("keyring secret '{}': {}", key, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_secrets_from_the_file() {
+        let path = std::env::temp_dir().join("mint_secrets_unit.json");
+        std::fs::write(&path, r#"{"db": "p@ss/w:rd", "token": "a\"b"}"#).unwrap();
+        let path = path.to_str();
+
+        let mut config = serde_json::json!({
+            "url": "postgres://u:${secret:db}@h",
+            "headers": {"Authorization": "${secret:token} # ${secret:db}"},
+        });
+        expand_config(&mut config, path).unwrap();
+        assert_eq!(config["url"], "postgres://u:p%40ss%2Fw%3Ard@h");
+        assert_eq!(config["headers"]["Authorization"], "a\"b # p@ss/w:rd");
+
+        let expand = |text: &str, path| expand_config(&mut Value::from(text), path);
+        let err = expand("${secret:api}", path).unwrap_err().to_string();
+        assert!(err.contains("'api' is not in the secrets file"), "{err}");
+        let err = expand("${secret:db}", None).unwrap_err().to_string();
+        assert!(err.contains("no --secrets file"), "{err}");
+        let err = expand("${keyring:mint}", None).unwrap_err().to_string();
+        assert!(err.contains("SERVICE/USER"), "{err}");
+    }
+}
@@ -0,0 +1,98 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use super::DataSource;
+use super::error::DataError;
+use crate::layout::value::{DataValue, ValueSource};
+use indexmap::IndexSet;
+use std::collections::HashMap;
+
+/// Forwards to `inner`, adding up the time spent in each lookup and noting
+/// the names looked up.
+pub struct TimedDataSource<'a> {
+    inner: &'a dyn DataSource,
+    nanos: AtomicU64,
+    looked_up: Mutex<IndexSet<String>>,
+}
+
+impl<'a> TimedDataSource<'a> {
+    pub fn new(inner: &'a dyn DataSource) -> Self {
+        Self {
+            inner,
+            nanos: AtomicU64::new(0),
+            looked_up: Mutex::new(IndexSet::new()),
+        }
+    }
+
+    /// Total time spent in lookups so far.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Relaxed))
+    }
+
+    /// Names looked up so far that only the lowest-priority version of a
+    /// stack of several provides, in lookup order.
+    pub fn base_fallbacks(&self) -> Vec<String> {
+        let versions = self.inner.versions();
+        let [_, .., base] = versions.as_slice() else {
+            return Vec::new();
+        };
+        let looked_up = self.looked_up.lock().unwrap();
+        looked_up
+            .iter()
+            .filter(|name| self.inner.version_of(name).as_ref() == Some(base))
+            .cloned()
+            .collect()
+    }
+
+    fn timed_value<T>(&self, name: &str, lookup: impl FnOnce() -> T) -> T {
+        let mut looked_up = self.looked_up.lock().unwrap();
+        if !looked_up.contains(name) {
+            looked_up.insert(name.to_string());
+        }
+        drop(looked_up);
+        self.timed(lookup)
+    }
+
+    fn timed<T>(&self, lookup: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = lookup();
+        self.nanos
+            .fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        result
+    }
+}
+
+impl DataSource for TimedDataSource<'_> {
+    fn retrieve_single_value(&self, name: &str) -> Result<DataValue, DataError> {
+        self.timed_value(name, || self.inner.retrieve_single_value(name))
+    }
+
+    fn retrieve_1d_array_or_string(&self, name: &str) -> Result<ValueSource, DataError> {
+        self.timed_value(name, || self.inner.retrieve_1d_array_or_string(name))
+    }
+
+    fn retrieve_1d_array(&self, name: &str) -> Result<ValueSource, DataError> {
+        self.timed_value(name, || self.inner.retrieve_1d_array(name))
+    }
+
+    fn retrieve_2d_array(&self, name: &str) -> Result<Vec<Vec<DataValue>>, DataError> {
+        self.timed_value(name, || self.inner.retrieve_2d_array(name))
+    }
+
+    fn retrieve_map(&self, table: &str) -> Result<HashMap<String, DataValue>, DataError> {
+        self.timed(|| self.inner.retrieve_map(table))
+    }
+
+    fn versions(&self) -> Vec<String> {
+        self.inner.versions()
+    }
+
+    fn version_of(&self, name: &str) -> Option<String> {
+        self.timed(|| self.inner.version_of(name))
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.timed(|| self.inner.names())
+    }
+}
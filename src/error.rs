@@ -1,6 +1,7 @@
 use thiserror::Error;
 
 use crate::data::error::DataError;
+use crate::import::error::ImportError;
 use crate::layout::error::LayoutError;
 use crate::output::error::OutputError;
 
@@ -15,6 +16,34 @@ pub enum MintError {
     #[error(transparent)]
     Output(#[from] OutputError),
 
+    #[error(transparent)]
+    Import(#[from] ImportError),
+
+    #[error("Read-back image does not match the reference build in {0} block(s).")]
+    CompareMismatch(usize),
+
+    #[error("Hook '{command}' failed: {message}.")]
+    Hook { command: String, message: String },
+
+    #[error("Project config error: {0}.")]
+    ProjectConfig(String),
+
+    #[error("{0} warning(s) emitted with --deny-warnings.")]
+    WarningsDenied(usize),
+
+    #[error("Manifest error: {0}.")]
+    Manifest(String),
+
+    #[error("Rebuilt outputs differ from the manifest: {}.", .0.join(", "))]
+    RebuildMismatch(Vec<String>),
+
+    #[error("While building version stack '{stack}': {source}")]
+    InVersionStack {
+        stack: String,
+        #[source]
+        source: Box<MintError>,
+    },
+
     #[error("While building block '{block_name}' from '{layout_file}': {source}")]
     InBlock {
         block_name: String,
@@ -23,3 +52,296 @@ pub enum MintError {
         source: Box<MintError>,
     },
 }
+
+/// Process exit codes by error category. These values are stable.
+pub mod exit_code {
+    /// Any error without a more specific code below.
+    pub const OTHER: u8 = 1;
+    /// Invalid command-line arguments (reported by the argument parser).
+    pub const USAGE: u8 = 2;
+    /// Layout file missing, malformed, or inconsistent.
+    pub const LAYOUT: u8 = 3;
+    /// A data value is missing or cannot be converted.
+    pub const DATA: u8 = 4;
+    /// The data source could not be read or reached.
+    pub const DATA_SOURCE: u8 = 5;
+    /// Output could not be generated or written.
+    pub const OUTPUT: u8 = 6;
+    /// Block contents do not fit the block length.
+    pub const BUDGET: u8 = 7;
+    /// Warnings were emitted with `--deny-warnings`.
+    pub const WARNINGS: u8 = 8;
+    /// `--compare` or `--rebuild` found a mismatch.
+    pub const COMPARE: u8 = 9;
+}
+
+/// Every error code with what it means and what usually fixes it, for
+/// `mint --explain`. Codes are never reused or renumbered.
+pub const CODES: &[(&str, &str)] = &[
+    (
+        "MINT-L001",
+        "A layout file could not be read. Check the path and permissions.",
+    ),
+    (
+        "MINT-L002",
+        "A layout file is not valid TOML, YAML, or JSON, or does not match the layout schema. The message points at the line and column.",
+    ),
+    (
+        "MINT-L003",
+        "Two entries in one block resolve to the same field path. Rename one of them.",
+    ),
+    (
+        "MINT-L004",
+        "The --profile given is not defined in the layout file. The message lists the available profiles.",
+    ),
+    (
+        "MINT-L005",
+        "--fmt --check found layout files that are not canonically formatted. Run mint --fmt on them.",
+    ),
+    (
+        "MINT-L006",
+        "A block named on the command line or referenced by another block does not exist in its layout file.",
+    ),
+    (
+        "MINT-L007",
+        "Blocks reference each other in a cycle, so no build order exists.",
+    ),
+    (
+        "MINT-L008",
+        "A field value could not be turned into bytes: wrong shape, size, or type for the entry.",
+    ),
+    (
+        "MINT-L009",
+        "A value needed a lossy conversion (rounding, saturation, or a lenient cast) that the conversion policy treats as an error.",
+    ),
+    (
+        "MINT-L010",
+        "A block argument is malformed. Use name@layout_file or a layout file path.",
+    ),
+    ("MINT-L011", "No blocks were given to build."),
+    (
+        "MINT-L012",
+        "A field takes its value from a data source, but no data source was given.",
+    ),
+    (
+        "MINT-L013",
+        "A bitmap field value does not fit in its number of bits.",
+    ),
+    (
+        "MINT-L014",
+        "The --only, --exclude, and --tag filters left no blocks to build.",
+    ),
+    (
+        "MINT-L015",
+        "A block's migrations name unknown fields or versions not older than its schema_version, or an image's schema version has no migration.",
+    ),
+    (
+        "MINT-L016",
+        "A header start_address or length expression refers to a missing block or non-integer constant, forms a cycle, or evaluates outside 0..=0xFFFFFFFF.",
+    ),
+    (
+        "MINT-L017",
+        "A field with require_version took its value from another version of the stack, the overrides, or no version at all.",
+    ),
+    (
+        "MINT-L018",
+        "A value refers to a name missing from the layout's [constants], or uses an array constant where a single value is expected.",
+    ),
+    (
+        "MINT-D001",
+        "A data source file (workbook, JSON, CSV, or config) could not be read or parsed.",
+    ),
+    (
+        "MINT-D002",
+        "A version column named by -v is missing from the Excel workbook.",
+    ),
+    (
+        "MINT-D003",
+        "A value is missing from the data source or could not be read as the requested type.",
+    ),
+    (
+        "MINT-D004",
+        "The data source defines the same name more than once and the duplicate-name policy is error.",
+    ),
+    (
+        "MINT-D005",
+        "The data source configuration is incomplete or inconsistent, such as a missing sheet or an unloaded version.",
+    ),
+    (
+        "MINT-D006",
+        "The data source could not be reached: the Postgres connection or HTTP request failed.",
+    ),
+    (
+        "MINT-D007",
+        "Values fetched from a Postgres or HTTP source do not have the types its config's schema expects.",
+    ),
+    (
+        "MINT-O001",
+        "An output file or directory could not be written, or an input to the output stage could not be read.",
+    ),
+    (
+        "MINT-O002",
+        "The output could not be generated: invalid CRC, record, region, split, or ECC settings, or addresses out of range.",
+    ),
+    (
+        "MINT-O003",
+        "Block contents or the CRC do not fit within the block length.",
+    ),
+    ("MINT-O004", "Two blocks occupy overlapping memory ranges."),
+    ("MINT-I001", "A file to import could not be read."),
+    (
+        "MINT-I002",
+        "A C header could not be parsed at the reported position.",
+    ),
+    (
+        "MINT-I003",
+        "The struct named by --import-struct was not found.",
+    ),
+    (
+        "MINT-I004",
+        "The symbol named by --import-symbol was not found in the ELF file.",
+    ),
+    (
+        "MINT-I005",
+        "The ELF file's debug information could not be read.",
+    ),
+    (
+        "MINT-I006",
+        "A struct member has a type that cannot be expressed in a layout.",
+    ),
+    (
+        "MINT-I007",
+        "A struct member is not naturally aligned; packed structs are not supported.",
+    ),
+    (
+        "MINT-M001",
+        "--compare found blocks in the read-back image that differ from the reference build.",
+    ),
+    (
+        "MINT-M002",
+        "A --pre-build or --post-build hook failed or exited with a non-zero status.",
+    ),
+    (
+        "MINT-M003",
+        "mint.toml or the combination of command-line flags is invalid.",
+    ),
+    (
+        "MINT-M004",
+        "Warnings were emitted while --deny-warnings was set.",
+    ),
+    (
+        "MINT-M005",
+        "A --rebuild manifest could not be read, its arguments no longer parse, or an input file changed since it was written.",
+    ),
+    (
+        "MINT-M006",
+        "--rebuild produced outputs that differ from the hashes in its manifest.",
+    ),
+];
+
+/// Canonical code and explanation of `code`, matched case-insensitively and with or without the
+/// `MINT-` prefix.
+pub fn explain(code: &str) -> Option<(&'static str, &'static str)> {
+    let code = code.to_ascii_uppercase();
+    let code = code.strip_prefix("MINT-").unwrap_or(&code);
+    CODES
+        .iter()
+        .find(|(c, _)| c.strip_prefix("MINT-") == Some(code))
+        .copied()
+}
+
+impl MintError {
+    /// Stable code of the underlying error, such as `MINT-L002`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            MintError::Layout(e) => e.code(),
+            MintError::Data(e) => e.code(),
+            MintError::Output(e) => e.code(),
+            MintError::Import(e) => e.code(),
+            MintError::CompareMismatch(_) => "MINT-M001",
+            MintError::Hook { .. } => "MINT-M002",
+            MintError::ProjectConfig(_) => "MINT-M003",
+            MintError::WarningsDenied(_) => "MINT-M004",
+            MintError::Manifest(_) => "MINT-M005",
+            MintError::RebuildMismatch(_) => "MINT-M006",
+            MintError::InVersionStack { source, .. } | MintError::InBlock { source, .. } => {
+                source.code()
+            }
+        }
+    }
+
+    /// Exit code for this error's category; see [`exit_code`].
+    pub fn exit_code(&self) -> u8 {
+        fn data(e: &DataError) -> u8 {
+            match e.is_source_unavailable() {
+                true => exit_code::DATA_SOURCE,
+                false => exit_code::DATA,
+            }
+        }
+        fn layout(e: &LayoutError) -> u8 {
+            match e {
+                LayoutError::InField { source, .. } => layout(source),
+                LayoutError::Data(e) => data(e),
+                LayoutError::MissingDataSheet(_) => exit_code::DATA,
+                _ => exit_code::LAYOUT,
+            }
+        }
+        match self {
+            MintError::Layout(e) => layout(e),
+            MintError::Data(e) => data(e),
+            MintError::Output(OutputError::BlockOverflow(_)) => exit_code::BUDGET,
+            MintError::Output(_) => exit_code::OUTPUT,
+            MintError::WarningsDenied(_) => exit_code::WARNINGS,
+            MintError::CompareMismatch(_) | MintError::RebuildMismatch(_) => exit_code::COMPARE,
+            MintError::InVersionStack { source, .. } | MintError::InBlock { source, .. } => {
+                source.exit_code()
+            }
+            MintError::Import(_)
+            | MintError::Hook { .. }
+            | MintError::ProjectConfig(_)
+            | MintError::Manifest(_) => exit_code::OTHER,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_code_follows_wrapped_error() {
+        let missing = MintError::InBlock {
+            block_name: "cal".to_string(),
+            layout_file: "layout.toml".to_string(),
+            source: Box::new(
+                LayoutError::InField {
+                    field: "gain".to_string(),
+                    source: Box::new(LayoutError::Data(DataError::RetrievalError(
+                        "Gain not found".to_string(),
+                    ))),
+                }
+                .into(),
+            ),
+        };
+        assert_eq!(missing.exit_code(), exit_code::DATA);
+
+        let unreachable = MintError::Data(DataError::Unavailable("connection refused".into()));
+        assert_eq!(unreachable.exit_code(), exit_code::DATA_SOURCE);
+
+        let overflow = MintError::Output(OutputError::BlockOverflow("too long".into()));
+        assert_eq!(overflow.exit_code(), exit_code::BUDGET);
+        assert_eq!(missing.code(), "MINT-D003");
+        assert_eq!(overflow.code(), "MINT-O003");
+    }
+
+    #[test]
+    fn codes_are_unique_and_explained() {
+        let mut codes: Vec<&str> = CODES.iter().map(|(c, _)| *c).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), CODES.len());
+        assert_eq!(explain("mint-l002").unwrap().0, "MINT-L002");
+        assert_eq!(explain("O003"), explain("MINT-O003"));
+        assert!(explain("MINT-X999").is_none());
+    }
+}
@@ -0,0 +1,527 @@
+use super::error::ImportError;
+use super::{CType, ImportedStruct, Member, MemberKind};
+use crate::layout::ScalarType;
+use lang_c::ast::{
+    ArraySize, BinaryOperator, Constant, DeclarationSpecifier, DeclaratorKind, DerivedDeclarator,
+    Expression, ExternalDeclaration, IntegerBase, SpecifierQualifier, StorageClassSpecifier,
+    StructDeclaration, StructKind, StructType, TypeSpecifier,
+};
+use lang_c::driver::{Config, parse_preprocessed};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Fixed-width typedefs normally pulled in from `<stdint.h>` and `<stdbool.h>`.
+const PRELUDE: &str = "typedef unsigned char uint8_t; typedef signed char int8_t; \
+    typedef unsigned short uint16_t; typedef short int16_t; \
+    typedef unsigned int uint32_t; typedef int int32_t; \
+    typedef unsigned long long uint64_t; typedef long long int64_t; \
+    typedef _Bool bool;\n";
+
+/// Reads a C header and returns the layout of struct `name` (a tag or typedef).
+pub fn import_header(path: &Path, name: &str) -> Result<ImportedStruct, ImportError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| ImportError::FileError(format!("failed to read {}: {}", path.display(), e)))?;
+    import_source(&text, &path.display().to_string(), name)
+}
+
+/// Parses C source text and returns the layout of struct `name`.
+///
+/// Comments and preprocessor directives are stripped; object-like `#define`s
+/// are substituted so they can size arrays. Types follow a 32-bit target:
+/// `int` and `long` are 4 bytes, `long long` is 8, enums are `int`.
+pub fn import_source(source: &str, file: &str, name: &str) -> Result<ImportedStruct, ImportError> {
+    let text = format!("{}{}", PRELUDE, preprocess(source));
+    let parse = parse_preprocessed(&Config::default(), text).map_err(|e| {
+        let mut expected: Vec<&str> = e.expected.iter().copied().collect();
+        expected.sort_unstable();
+        ImportError::Parse {
+            file: file.to_string(),
+            line: e.line.saturating_sub(1),
+            column: e.column,
+            expected: expected.join(", "),
+        }
+    })?;
+
+    let mut scope = Scope::default();
+    for decl in &parse.unit.0 {
+        let ExternalDeclaration::Declaration(decl) = &decl.node else {
+            continue;
+        };
+        let decl = &decl.node;
+        let specs = type_specifiers_of_decl(&decl.specifiers);
+        for spec in &specs {
+            if let TypeSpecifier::Struct(st) = spec {
+                scope.record_tag(&st.node);
+            }
+        }
+        let is_typedef = decl.specifiers.iter().any(|s| {
+            matches!(&s.node, DeclarationSpecifier::StorageClass(c)
+                if c.node == StorageClassSpecifier::Typedef)
+        });
+        if !is_typedef {
+            continue;
+        }
+        for init in &decl.declarators {
+            let declarator = &init.node.declarator.node;
+            let DeclaratorKind::Identifier(id) = &declarator.kind.node else {
+                continue;
+            };
+            let typedef_name = id.node.name.clone();
+            let ty = scope
+                .resolve(&specs, &typedef_name)
+                .and_then(|base| scope.apply_derived(base, &declarator.derived, &typedef_name));
+            // Typedefs unrelated to the requested struct may use unsupported types.
+            scope.typedefs.insert(typedef_name, ty);
+        }
+    }
+
+    let ty = match scope.typedefs.get(name) {
+        Some(Ok(ty)) => ty.clone(),
+        Some(Err(e)) => return Err(e.clone()),
+        None => scope
+            .tags
+            .get(name)
+            .cloned()
+            .ok_or_else(|| ImportError::StructNotFound(name.to_string()))
+            .and_then(|st| scope.layout_struct(&st, name))
+            .map(CType::Struct)?,
+    };
+    match ty {
+        CType::Struct(mut layout) => {
+            layout.name = name.to_string();
+            Ok(layout)
+        }
+        _ => Err(ImportError::StructNotFound(name.to_string())),
+    }
+}
+
+#[derive(Default)]
+struct Scope {
+    tags: HashMap<String, StructType>,
+    typedefs: HashMap<String, Result<CType, ImportError>>,
+}
+
+impl Scope {
+    fn record_tag(&mut self, st: &StructType) {
+        if let (Some(id), Some(_)) = (&st.identifier, &st.declarations) {
+            self.tags.insert(id.node.name.clone(), st.clone());
+        }
+    }
+
+    fn resolve(&self, specs: &[&TypeSpecifier], field: &str) -> Result<CType, ImportError> {
+        let unsupported = |reason: &str| ImportError::Unsupported {
+            field: field.to_string(),
+            reason: reason.to_string(),
+        };
+        let mut unsigned = false;
+        let mut signed = false;
+        let mut longs = 0;
+        let mut base = None;
+        for spec in specs {
+            match spec {
+                TypeSpecifier::Unsigned => unsigned = true,
+                TypeSpecifier::Signed => signed = true,
+                TypeSpecifier::Long => longs += 1,
+                TypeSpecifier::Int => {}
+                TypeSpecifier::Char | TypeSpecifier::Short | TypeSpecifier::Float => {
+                    base = Some(*spec)
+                }
+                TypeSpecifier::Double | TypeSpecifier::Bool => base = Some(*spec),
+                TypeSpecifier::Enum(_) => return Ok(CType::Scalar(ScalarType::I32)),
+                TypeSpecifier::Struct(st) => return self.struct_type(&st.node, field),
+                TypeSpecifier::TypedefName(id) => {
+                    return match self.typedefs.get(&id.node.name) {
+                        Some(Ok(ty)) => Ok(ty.clone()),
+                        Some(Err(e)) => Err(e.clone()),
+                        None => Err(unsupported(&format!("unknown type '{}'", id.node.name))),
+                    };
+                }
+                TypeSpecifier::Void => return Err(unsupported("void")),
+                _ => return Err(unsupported("type specifier not supported")),
+            }
+        }
+        let ty = match base {
+            Some(TypeSpecifier::Char) if signed => ScalarType::I8,
+            Some(TypeSpecifier::Char) => ScalarType::U8,
+            Some(TypeSpecifier::Bool) => ScalarType::U8,
+            Some(TypeSpecifier::Short) if unsigned => ScalarType::U16,
+            Some(TypeSpecifier::Short) => ScalarType::I16,
+            Some(TypeSpecifier::Float) => ScalarType::F32,
+            Some(TypeSpecifier::Double) if longs > 0 => {
+                return Err(unsupported("long double"));
+            }
+            Some(TypeSpecifier::Double) => ScalarType::F64,
+            _ if longs >= 2 && unsigned => ScalarType::U64,
+            _ if longs >= 2 => ScalarType::I64,
+            _ if unsigned => ScalarType::U32,
+            _ => ScalarType::I32,
+        };
+        Ok(CType::Scalar(ty))
+    }
+
+    fn struct_type(&self, st: &StructType, field: &str) -> Result<CType, ImportError> {
+        let resolved = match (&st.declarations, &st.identifier) {
+            (Some(_), _) => st,
+            (None, Some(id)) => {
+                self.tags
+                    .get(&id.node.name)
+                    .ok_or_else(|| ImportError::Unsupported {
+                        field: field.to_string(),
+                        reason: format!("incomplete struct '{}'", id.node.name),
+                    })?
+            }
+            (None, None) => {
+                return Err(ImportError::Unsupported {
+                    field: field.to_string(),
+                    reason: "anonymous struct without members".to_string(),
+                });
+            }
+        };
+        self.layout_struct(resolved, field).map(CType::Struct)
+    }
+
+    fn apply_derived(
+        &self,
+        base: CType,
+        derived: &[lang_c::span::Node<DerivedDeclarator>],
+        field: &str,
+    ) -> Result<CType, ImportError> {
+        let mut dims = Vec::new();
+        for d in derived {
+            match &d.node {
+                DerivedDeclarator::Array(array) => match &array.node.size {
+                    ArraySize::VariableExpression(expr) | ArraySize::StaticExpression(expr) => {
+                        let n = eval(&expr.node).ok_or_else(|| ImportError::Unsupported {
+                            field: field.to_string(),
+                            reason: "array size is not an integer constant".to_string(),
+                        })?;
+                        dims.push(n as usize);
+                    }
+                    _ => {
+                        return Err(ImportError::Unsupported {
+                            field: field.to_string(),
+                            reason: "array without a size".to_string(),
+                        });
+                    }
+                },
+                DerivedDeclarator::Pointer(_) => {
+                    return Err(ImportError::Unsupported {
+                        field: field.to_string(),
+                        reason: "pointer".to_string(),
+                    });
+                }
+                _ => {
+                    return Err(ImportError::Unsupported {
+                        field: field.to_string(),
+                        reason: "function".to_string(),
+                    });
+                }
+            }
+        }
+        Ok(dims
+            .into_iter()
+            .rev()
+            .fold(base, |ty, n| CType::Array(Box::new(ty), n)))
+    }
+
+    fn layout_struct(&self, st: &StructType, name: &str) -> Result<ImportedStruct, ImportError> {
+        if st.kind.node == StructKind::Union {
+            return Err(ImportError::Unsupported {
+                field: name.to_string(),
+                reason: "unions are not supported".to_string(),
+            });
+        }
+        let mut members = Vec::new();
+        let mut offset: usize = 0;
+        let mut align = 1;
+        // Open bitfield unit: (member index, storage type, bits used).
+        let mut unit: Option<(usize, ScalarType, u32)> = None;
+
+        for decl in st.declarations.iter().flatten() {
+            let StructDeclaration::Field(field) = &decl.node else {
+                continue;
+            };
+            let specs: Vec<&TypeSpecifier> = field
+                .node
+                .specifiers
+                .iter()
+                .filter_map(|s| match &s.node {
+                    SpecifierQualifier::TypeSpecifier(t) => Some(&t.node),
+                    _ => None,
+                })
+                .collect();
+            for sd in &field.node.declarators {
+                let field_name =
+                    sd.node
+                        .declarator
+                        .as_ref()
+                        .and_then(|d| match &d.node.kind.node {
+                            DeclaratorKind::Identifier(id) => Some(id.node.name.clone()),
+                            _ => None,
+                        });
+                let label = field_name
+                    .clone()
+                    .unwrap_or_else(|| "<unnamed>".to_string());
+                let base = self.resolve(&specs, &label)?;
+                let ty = match &sd.node.declarator {
+                    Some(d) => self.apply_derived(base, &d.node.derived, &label)?,
+                    None => base,
+                };
+
+                if let Some(width) = &sd.node.bit_width {
+                    let CType::Scalar(storage) = ty else {
+                        return Err(ImportError::Unsupported {
+                            field: label,
+                            reason: "bitfield of a non-scalar type".to_string(),
+                        });
+                    };
+                    let bits = eval(&width.node).ok_or_else(|| ImportError::Unsupported {
+                        field: label.clone(),
+                        reason: "bitfield width is not an integer constant".to_string(),
+                    })? as u32;
+                    if bits == 0 {
+                        unit = None;
+                        continue;
+                    }
+                    let capacity = storage.size_bytes() as u32 * 8;
+                    let fits = matches!(unit, Some((_, t, used)) if t == storage && used + bits <= capacity);
+                    if !fits {
+                        offset = offset.next_multiple_of(storage.size_bytes());
+                        members.push(Member {
+                            name: field_name
+                                .clone()
+                                .unwrap_or_else(|| format!("_bits{}", offset)),
+                            offset,
+                            kind: MemberKind::Bitmap {
+                                ty: storage,
+                                fields: Vec::new(),
+                            },
+                        });
+                        offset += storage.size_bytes();
+                        align = align.max(storage.size_bytes());
+                        unit = Some((members.len() - 1, storage, 0));
+                    }
+                    if let Some((index, _, used)) = unit.as_mut() {
+                        *used += bits;
+                        if let MemberKind::Bitmap { fields, .. } = &mut members[*index].kind {
+                            fields.push((field_name, bits));
+                        }
+                    }
+                    continue;
+                }
+
+                unit = None;
+                let Some(field_name) = field_name else {
+                    continue;
+                };
+                offset = offset.next_multiple_of(ty.align());
+                align = align.max(ty.align());
+                let size = ty.size();
+                members.push(Member {
+                    name: field_name,
+                    offset,
+                    kind: ty.into_member_kind(),
+                });
+                offset += size;
+            }
+        }
+
+        Ok(ImportedStruct {
+            name: name.to_string(),
+            size: offset.next_multiple_of(align),
+            align,
+            members,
+        })
+    }
+}
+
+fn type_specifiers_of_decl(
+    specs: &[lang_c::span::Node<DeclarationSpecifier>],
+) -> Vec<&TypeSpecifier> {
+    specs
+        .iter()
+        .filter_map(|s| match &s.node {
+            DeclarationSpecifier::TypeSpecifier(t) => Some(&t.node),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Evaluates an integer constant expression.
+fn eval(expr: &Expression) -> Option<u64> {
+    match expr {
+        Expression::Constant(c) => match &c.node {
+            Constant::Integer(int) => {
+                let radix = match int.base {
+                    IntegerBase::Decimal => 10,
+                    IntegerBase::Octal => 8,
+                    IntegerBase::Hexadecimal => 16,
+                    IntegerBase::Binary => 2,
+                };
+                u64::from_str_radix(&int.number, radix).ok()
+            }
+            _ => None,
+        },
+        Expression::BinaryOperator(op) => {
+            let lhs = eval(&op.node.lhs.node)?;
+            let rhs = eval(&op.node.rhs.node)?;
+            match op.node.operator.node {
+                BinaryOperator::Plus => lhs.checked_add(rhs),
+                BinaryOperator::Minus => lhs.checked_sub(rhs),
+                BinaryOperator::Multiply => lhs.checked_mul(rhs),
+                BinaryOperator::Divide => lhs.checked_div(rhs),
+                BinaryOperator::ShiftLeft => lhs.checked_shl(rhs as u32),
+                BinaryOperator::ShiftRight => lhs.checked_shr(rhs as u32),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Strips comments and directives, substituting object-like `#define`s.
+///
+/// Line breaks are kept so parse errors point at the original line.
+fn preprocess(source: &str) -> String {
+    let mut stripped = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        stripped.push('\n');
+                    }
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            _ => stripped.push(c),
+        }
+    }
+
+    let mut defines: HashMap<String, String> = HashMap::new();
+    let mut out = String::with_capacity(stripped.len());
+    let mut lines = stripped.lines();
+    while let Some(line) = lines.next() {
+        let mut directive = line.trim_start().strip_prefix('#').map(str::to_string);
+        let mut joined = 1;
+        while let Some(d) = directive.as_mut().filter(|d| d.ends_with('\\')) {
+            d.pop();
+            d.push_str(lines.next().unwrap_or(""));
+            joined += 1;
+        }
+        match directive {
+            Some(d) => {
+                if let Some(rest) = d.trim_start().strip_prefix("define") {
+                    let rest = rest.trim_start();
+                    let end = rest
+                        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                        .unwrap_or(rest.len());
+                    let (name, value) = rest.split_at(end);
+                    if !name.is_empty() && !value.starts_with('(') {
+                        defines.insert(name.to_string(), substitute(value.trim(), &defines));
+                    }
+                }
+                out.push_str(&"\n".repeat(joined));
+            }
+            None => {
+                out.push_str(&substitute(line, &defines));
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+fn substitute(line: &str, defines: &HashMap<String, String>) -> String {
+    if defines.is_empty() {
+        return line.to_string();
+    }
+    let mut out = String::with_capacity(line.len());
+    let mut word = String::new();
+    for c in line.chars().chain(std::iter::once(' ')) {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            word.push(c);
+            continue;
+        }
+        match defines.get(&word) {
+            Some(value) => out.push_str(value),
+            None => out.push_str(&word),
+        }
+        word.clear();
+        out.push(c);
+    }
+    out.pop();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn struct_layout_follows_c_rules() {
+        let src = r#"
+            #define N_TAPS (2 * 2)
+            /* calibration */
+            typedef struct {
+                uint8_t id;         // 1 byte, then 3 bytes padding
+                uint32_t serial;
+                unsigned int enable : 1;
+                unsigned int mode : 3;
+                int16_t taps[N_TAPS];
+                float table[2][3];
+            } Cal;
+        "#;
+        let layout = import_source(src, "cal.h", "Cal").unwrap();
+        let offsets: Vec<(&str, usize)> = layout
+            .members
+            .iter()
+            .map(|m| (m.name.as_str(), m.offset))
+            .collect();
+        assert_eq!(
+            offsets,
+            vec![
+                ("id", 0),
+                ("serial", 4),
+                ("enable", 8),
+                ("taps", 12),
+                ("table", 20)
+            ]
+        );
+        assert_eq!(layout.size, 44);
+        assert!(matches!(
+            &layout.members[2].kind,
+            MemberKind::Bitmap { ty: ScalarType::U32, fields } if fields.len() == 2
+        ));
+        assert!(matches!(
+            &layout.members[4].kind,
+            MemberKind::Scalar { ty: ScalarType::F32, dims } if dims == &[2, 3]
+        ));
+    }
+
+    #[test]
+    fn finds_struct_by_tag_and_rejects_pointers() {
+        let src = "struct Inner { short a; char b; }; struct Outer { struct Inner x[2]; int *p; };";
+        let inner = import_source(src, "t.h", "Inner").unwrap();
+        assert_eq!((inner.size, inner.align), (4, 2));
+        assert!(matches!(
+            import_source(src, "t.h", "Outer"),
+            Err(ImportError::Unsupported { .. })
+        ));
+        assert!(matches!(
+            import_source(src, "t.h", "Missing"),
+            Err(ImportError::StructNotFound(_))
+        ));
+    }
+}
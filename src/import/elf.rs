@@ -0,0 +1,421 @@
+use super::error::ImportError;
+use super::{CType, ImportedStruct, Member, MemberKind};
+use crate::layout::ScalarType;
+use crate::layout::settings::Endianness;
+use gimli::{AttributeValue, DwAt, DwAte, DwTag, EndianSlice, RunTimeEndian, UnitOffset};
+use object::{Object, ObjectSection, ObjectSymbol};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// What to import from an ELF file.
+#[derive(Debug, Clone, Copy)]
+pub enum ElfTarget<'a> {
+    /// A struct type by tag or typedef name; the layout starts at address 0.
+    Struct(&'a str),
+    /// A global variable; the layout starts at the symbol's address.
+    Symbol(&'a str),
+}
+
+/// A struct layout read from DWARF, with its address and the target's byte order.
+#[derive(Debug, Clone)]
+pub struct ElfImport {
+    pub layout: ImportedStruct,
+    pub address: u64,
+    pub endianness: Endianness,
+}
+
+/// Reads the DWARF type of `target` from the ELF file at `path`.
+pub fn import_elf(path: &Path, target: ElfTarget) -> Result<ElfImport, ImportError> {
+    let data = std::fs::read(path)
+        .map_err(|e| ImportError::FileError(format!("failed to read {}: {}", path.display(), e)))?;
+    let debug_error = |message: String| ImportError::DebugInfo {
+        file: path.display().to_string(),
+        message,
+    };
+    let file = object::File::parse(&*data).map_err(|e| debug_error(e.to_string()))?;
+    let (endian, endianness) = if file.is_little_endian() {
+        (RunTimeEndian::Little, Endianness::Little)
+    } else {
+        (RunTimeEndian::Big, Endianness::Big)
+    };
+
+    let sections = gimli::DwarfSections::load(|id| -> Result<Cow<[u8]>, object::Error> {
+        match file.section_by_name(id.name()) {
+            Some(section) => section.uncompressed_data(),
+            None => Ok(Cow::Borrowed(&[])),
+        }
+    })
+    .map_err(|e| debug_error(e.to_string()))?;
+    let dwarf = sections.borrow(|section| EndianSlice::new(section, endian));
+
+    let (name, tags): (&str, &[DwTag]) = match target {
+        ElfTarget::Struct(name) => (name, &[gimli::DW_TAG_structure_type, gimli::DW_TAG_typedef]),
+        ElfTarget::Symbol(name) => (name, &[gimli::DW_TAG_variable]),
+    };
+
+    let mut headers = dwarf.units();
+    while let Some(header) = headers.next().map_err(|e| debug_error(e.to_string()))? {
+        let unit = dwarf.unit(header).map_err(|e| debug_error(e.to_string()))?;
+        let dies = collect_dies(&dwarf, &unit).map_err(|e| debug_error(e.to_string()))?;
+        let found = dies.iter().find(|(_, die)| {
+            tags.contains(&die.tag)
+                && !die.declaration
+                && die.name.as_deref() == Some(name)
+                && (die.tag != gimli::DW_TAG_variable || die.ty.is_some())
+        });
+        let Some((&offset, die)) = found else {
+            continue;
+        };
+
+        let types = Types {
+            dies: &dies,
+            big_endian: matches!(endianness, Endianness::Big),
+        };
+        let root = match die.tag {
+            gimli::DW_TAG_variable => die
+                .ty
+                .ok_or_else(|| ImportError::SymbolNotFound(name.into()))?,
+            _ => offset,
+        };
+        let CType::Struct(mut layout) = types.ctype(root, name)? else {
+            return Err(ImportError::Unsupported {
+                field: name.to_string(),
+                reason: "not a struct".to_string(),
+            });
+        };
+        layout.name = name.to_string();
+
+        let address = match target {
+            ElfTarget::Struct(_) => 0,
+            ElfTarget::Symbol(name) => file
+                .symbol_by_name(name)
+                .map(|s| s.address())
+                .ok_or_else(|| ImportError::SymbolNotFound(name.to_string()))?,
+        };
+        return Ok(ElfImport {
+            layout,
+            address,
+            endianness,
+        });
+    }
+
+    Err(match target {
+        ElfTarget::Struct(name) => ImportError::StructNotFound(name.to_string()),
+        ElfTarget::Symbol(name) => ImportError::SymbolNotFound(name.to_string()),
+    })
+}
+
+type Reader<'a> = EndianSlice<'a, RunTimeEndian>;
+
+/// The attributes of a DIE that layout import needs.
+#[derive(Debug)]
+struct Die {
+    tag: DwTag,
+    name: Option<String>,
+    ty: Option<UnitOffset>,
+    byte_size: Option<u64>,
+    encoding: Option<DwAte>,
+    member_location: Option<u64>,
+    bit_size: Option<u64>,
+    data_bit_offset: Option<u64>,
+    bit_offset: Option<u64>,
+    count: Option<u64>,
+    upper_bound: Option<u64>,
+    declaration: bool,
+    children: Vec<UnitOffset>,
+}
+
+fn collect_dies(
+    dwarf: &gimli::Dwarf<Reader>,
+    unit: &gimli::Unit<Reader>,
+) -> gimli::Result<HashMap<UnitOffset, Die>> {
+    let mut dies = HashMap::new();
+    let mut tree = unit.entries_tree(None)?;
+    collect_node(dwarf, unit, tree.root()?, &mut dies)?;
+    Ok(dies)
+}
+
+fn collect_node(
+    dwarf: &gimli::Dwarf<Reader>,
+    unit: &gimli::Unit<Reader>,
+    node: gimli::EntriesTreeNode<Reader>,
+    dies: &mut HashMap<UnitOffset, Die>,
+) -> gimli::Result<UnitOffset> {
+    let entry = node.entry();
+    let offset = entry.offset();
+    let udata = |at: DwAt| entry.attr_value(at).and_then(|v| v.udata_value());
+    let mut die = Die {
+        tag: entry.tag(),
+        byte_size: udata(gimli::DW_AT_byte_size),
+        member_location: udata(gimli::DW_AT_data_member_location),
+        bit_size: udata(gimli::DW_AT_bit_size),
+        data_bit_offset: udata(gimli::DW_AT_data_bit_offset),
+        bit_offset: udata(gimli::DW_AT_bit_offset),
+        count: udata(gimli::DW_AT_count),
+        upper_bound: udata(gimli::DW_AT_upper_bound),
+        declaration: matches!(
+            entry.attr_value(gimli::DW_AT_declaration),
+            Some(AttributeValue::Flag(true))
+        ),
+        name: None,
+        ty: None,
+        encoding: None,
+        children: Vec::new(),
+    };
+    if let Some(value) = entry.attr_value(gimli::DW_AT_name) {
+        die.name = Some(
+            dwarf
+                .attr_string(unit, value)?
+                .to_string_lossy()
+                .into_owned(),
+        );
+    }
+    if let Some(AttributeValue::UnitRef(ty)) = entry.attr_value(gimli::DW_AT_type) {
+        die.ty = Some(ty);
+    }
+    if let Some(AttributeValue::Encoding(encoding)) = entry.attr_value(gimli::DW_AT_encoding) {
+        die.encoding = Some(encoding);
+    }
+
+    let mut children = node.children();
+    while let Some(child) = children.next()? {
+        die.children.push(collect_node(dwarf, unit, child, dies)?);
+    }
+    dies.insert(offset, die);
+    Ok(offset)
+}
+
+struct Types<'a> {
+    dies: &'a HashMap<UnitOffset, Die>,
+    big_endian: bool,
+}
+
+impl Types<'_> {
+    fn ctype(&self, offset: UnitOffset, field: &str) -> Result<CType, ImportError> {
+        let unsupported = |reason: &str| ImportError::Unsupported {
+            field: field.to_string(),
+            reason: reason.to_string(),
+        };
+        let die = self
+            .dies
+            .get(&offset)
+            .ok_or_else(|| unsupported("dangling type reference"))?;
+        let target = || {
+            die.ty
+                .ok_or_else(|| unsupported("type reference outside its compilation unit"))
+        };
+        match die.tag {
+            gimli::DW_TAG_base_type => {
+                let size = die.byte_size.unwrap_or(0);
+                scalar(die.encoding, size)
+                    .map(CType::Scalar)
+                    .ok_or_else(|| {
+                        unsupported(&format!(
+                            "base type '{}'",
+                            die.name.as_deref().unwrap_or("?")
+                        ))
+                    })
+            }
+            gimli::DW_TAG_typedef
+            | gimli::DW_TAG_const_type
+            | gimli::DW_TAG_volatile_type
+            | gimli::DW_TAG_atomic_type => self.ctype(target()?, field),
+            gimli::DW_TAG_enumeration_type => {
+                let signed = Some(gimli::DW_ATE_signed);
+                scalar(signed, die.byte_size.unwrap_or(4))
+                    .map(CType::Scalar)
+                    .ok_or_else(|| unsupported("enum size"))
+            }
+            gimli::DW_TAG_array_type => {
+                let elem = self.ctype(target()?, field)?;
+                let mut dims = Vec::new();
+                for child in &die.children {
+                    let Some(sub) = self.dies.get(child) else {
+                        continue;
+                    };
+                    if sub.tag != gimli::DW_TAG_subrange_type {
+                        continue;
+                    }
+                    let n = sub
+                        .count
+                        .or(sub.upper_bound.map(|b| b + 1))
+                        .ok_or_else(|| unsupported("array without a size"))?;
+                    dims.push(n as usize);
+                }
+                Ok(dims
+                    .into_iter()
+                    .rev()
+                    .fold(elem, |ty, n| CType::Array(Box::new(ty), n)))
+            }
+            gimli::DW_TAG_structure_type => {
+                let name = die.name.as_deref().unwrap_or(field);
+                self.layout_struct(die, name).map(CType::Struct)
+            }
+            gimli::DW_TAG_union_type => Err(unsupported("unions are not supported")),
+            gimli::DW_TAG_pointer_type => Err(unsupported("pointer")),
+            _ => Err(unsupported(&format!("DWARF tag {}", die.tag))),
+        }
+    }
+
+    fn layout_struct(&self, die: &Die, name: &str) -> Result<ImportedStruct, ImportError> {
+        let mut members = Vec::new();
+        let mut align = 1;
+        // Bit positions (from the LSB) of the bitfields in each bitmap member.
+        let mut positions: HashMap<usize, Vec<(u32, Option<String>, u32)>> = HashMap::new();
+
+        for child in &die.children {
+            let Some(member) = self.dies.get(child) else {
+                continue;
+            };
+            if member.tag != gimli::DW_TAG_member {
+                continue;
+            }
+            let label = member
+                .name
+                .clone()
+                .unwrap_or_else(|| "<unnamed>".to_string());
+            let unsupported = |reason: &str| ImportError::Unsupported {
+                field: label.clone(),
+                reason: reason.to_string(),
+            };
+            let ty_offset = member
+                .ty
+                .ok_or_else(|| unsupported("member without a type"))?;
+            let ty = self.ctype(ty_offset, &label)?;
+            align = align.max(ty.align());
+
+            if let Some(bits) = member.bit_size {
+                let CType::Scalar(storage) = ty else {
+                    return Err(unsupported("bitfield of a non-scalar type"));
+                };
+                let unit_bits = storage.size_bytes() as u64 * 8;
+                let start = match (member.data_bit_offset, member.bit_offset) {
+                    (Some(dbo), _) => dbo,
+                    (None, Some(bo)) => {
+                        let base = member.member_location.unwrap_or(0) * 8;
+                        if self.big_endian {
+                            base + bo
+                        } else {
+                            base + unit_bits - bo - bits
+                        }
+                    }
+                    (None, None) => member.member_location.unwrap_or(0) * 8,
+                };
+                let offset = (start / unit_bits * unit_bits / 8) as usize;
+                let within = start % unit_bits;
+                if within + bits > unit_bits {
+                    return Err(unsupported("bitfield crosses its storage unit"));
+                }
+                let lsb = if self.big_endian {
+                    unit_bits - within - bits
+                } else {
+                    within
+                };
+
+                let index = match members.last() {
+                    Some(Member {
+                        offset: last,
+                        kind: MemberKind::Bitmap { ty, .. },
+                        ..
+                    }) if *last == offset && *ty == storage => members.len() - 1,
+                    _ => {
+                        members.push(Member {
+                            name: label.clone(),
+                            offset,
+                            kind: MemberKind::Bitmap {
+                                ty: storage,
+                                fields: Vec::new(),
+                            },
+                        });
+                        members.len() - 1
+                    }
+                };
+                positions.entry(index).or_default().push((
+                    lsb as u32,
+                    member.name.clone(),
+                    bits as u32,
+                ));
+                continue;
+            }
+
+            let Some(member_name) = member.name.clone() else {
+                return Err(unsupported("anonymous members are not supported"));
+            };
+            let offset = member
+                .member_location
+                .ok_or_else(|| unsupported("member without a constant offset"))?;
+            members.push(Member {
+                name: member_name,
+                offset: offset as usize,
+                kind: ty.into_member_kind(),
+            });
+        }
+
+        for (index, mut fields) in positions {
+            fields.sort_by_key(|(lsb, _, _)| *lsb);
+            let mut next = 0;
+            let mut packed = Vec::new();
+            for (lsb, name, bits) in fields {
+                if lsb > next {
+                    packed.push((None, lsb - next));
+                }
+                packed.push((name, bits));
+                next = lsb + bits;
+            }
+            if let MemberKind::Bitmap { fields, .. } = &mut members[index].kind {
+                *fields = packed;
+            }
+        }
+
+        let size = die.byte_size.ok_or_else(|| ImportError::Unsupported {
+            field: name.to_string(),
+            reason: "struct without a size".to_string(),
+        })?;
+        Ok(ImportedStruct {
+            name: name.to_string(),
+            size: size as usize,
+            align,
+            members,
+        })
+    }
+}
+
+fn scalar(encoding: Option<DwAte>, size: u64) -> Option<ScalarType> {
+    let encoding = encoding?;
+    let ty = match (encoding, size) {
+        (gimli::DW_ATE_float, 4) => ScalarType::F32,
+        (gimli::DW_ATE_float, 8) => ScalarType::F64,
+        (gimli::DW_ATE_signed | gimli::DW_ATE_signed_char, 1) => ScalarType::I8,
+        (gimli::DW_ATE_signed | gimli::DW_ATE_signed_char, 2) => ScalarType::I16,
+        (gimli::DW_ATE_signed | gimli::DW_ATE_signed_char, 4) => ScalarType::I32,
+        (gimli::DW_ATE_signed | gimli::DW_ATE_signed_char, 8) => ScalarType::I64,
+        (gimli::DW_ATE_float, _) | (gimli::DW_ATE_signed | gimli::DW_ATE_signed_char, _) => {
+            return None;
+        }
+        (_, 1) => ScalarType::U8,
+        (_, 2) => ScalarType::U16,
+        (_, 4) => ScalarType::U32,
+        (_, 8) => ScalarType::U64,
+        _ => return None,
+    };
+    Some(ty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scalar_maps_dwarf_base_types() {
+        assert_eq!(scalar(Some(gimli::DW_ATE_float), 4), Some(ScalarType::F32));
+        assert_eq!(scalar(Some(gimli::DW_ATE_signed), 2), Some(ScalarType::I16));
+        assert_eq!(scalar(Some(gimli::DW_ATE_boolean), 1), Some(ScalarType::U8));
+        assert_eq!(
+            scalar(Some(gimli::DW_ATE_unsigned_char), 1),
+            Some(ScalarType::U8)
+        );
+        assert_eq!(scalar(Some(gimli::DW_ATE_float), 16), None);
+        assert_eq!(scalar(None, 4), None);
+    }
+}
@@ -0,0 +1,47 @@
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error)]
+pub enum ImportError {
+    #[error("File error: {0}.")]
+    FileError(String),
+
+    #[error("Failed to parse {file} at line {line}, column {column}: expected {expected}.")]
+    Parse {
+        file: String,
+        line: usize,
+        column: usize,
+        expected: String,
+    },
+
+    #[error("Struct '{0}' not found.")]
+    StructNotFound(String),
+
+    #[error("Symbol '{0}' not found.")]
+    SymbolNotFound(String),
+
+    #[error("Failed to read debug info from {file}: {message}.")]
+    DebugInfo { file: String, message: String },
+
+    #[error("Unsupported type for '{field}': {reason}.")]
+    Unsupported { field: String, reason: String },
+
+    #[error(
+        "Field '{field}' at offset {offset} is not naturally aligned; packed structs are not supported."
+    )]
+    Misaligned { field: String, offset: usize },
+}
+
+impl ImportError {
+    /// Stable code of this error; see `mint --explain`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ImportError::FileError(_) => "MINT-I001",
+            ImportError::Parse { .. } => "MINT-I002",
+            ImportError::StructNotFound(_) => "MINT-I003",
+            ImportError::SymbolNotFound(_) => "MINT-I004",
+            ImportError::DebugInfo { .. } => "MINT-I005",
+            ImportError::Unsupported { .. } => "MINT-I006",
+            ImportError::Misaligned { .. } => "MINT-I007",
+        }
+    }
+}
@@ -0,0 +1,317 @@
+pub mod c;
+pub mod elf;
+pub mod error;
+
+use crate::layout::ScalarType;
+use crate::layout::settings::Endianness;
+use error::ImportError;
+use std::fmt::Write;
+
+/// A struct recovered from a foreign type description, with C layout offsets.
+#[derive(Debug, Clone)]
+pub struct ImportedStruct {
+    pub name: String,
+    pub size: usize,
+    pub align: usize,
+    pub members: Vec<Member>,
+}
+
+/// A struct member at a byte offset from the start of its struct.
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub name: String,
+    pub offset: usize,
+    pub kind: MemberKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum MemberKind {
+    /// A scalar or an array of scalars (`dims` empty for a scalar).
+    Scalar { ty: ScalarType, dims: Vec<usize> },
+    /// A nested struct, or an array of `count` structs.
+    Struct {
+        layout: ImportedStruct,
+        count: usize,
+    },
+    /// A run of bitfields sharing one storage unit; `None` names are padding.
+    Bitmap {
+        ty: ScalarType,
+        fields: Vec<(Option<String>, u32)>,
+    },
+}
+
+/// A C type, as resolved by an importer before it is flattened into members.
+#[derive(Debug, Clone)]
+pub(crate) enum CType {
+    Scalar(ScalarType),
+    Struct(ImportedStruct),
+    Array(Box<CType>, usize),
+}
+
+impl CType {
+    pub(crate) fn size(&self) -> usize {
+        match self {
+            CType::Scalar(ty) => ty.size_bytes(),
+            CType::Struct(layout) => layout.size,
+            CType::Array(elem, n) => elem.size() * n,
+        }
+    }
+
+    pub(crate) fn align(&self) -> usize {
+        match self {
+            CType::Scalar(ty) => ty.size_bytes(),
+            CType::Struct(layout) => layout.align,
+            CType::Array(elem, _) => elem.align(),
+        }
+    }
+
+    pub(crate) fn into_member_kind(self) -> MemberKind {
+        let mut dims = Vec::new();
+        let mut ty = self;
+        while let CType::Array(elem, n) = ty {
+            dims.push(n);
+            ty = *elem;
+        }
+        match ty {
+            CType::Scalar(ty) => MemberKind::Scalar { ty, dims },
+            CType::Struct(layout) => MemberKind::Struct {
+                layout,
+                count: dims.iter().product(),
+            },
+            CType::Array(..) => unreachable!("arrays are unwrapped above"),
+        }
+    }
+}
+
+/// Renders a layout TOML skeleton for `layout`, as block `layout.name` at
+/// `start_address`.
+///
+/// Data source names follow the C member path; array-of-struct members become
+/// `repeat` branches with `{i}` in their names. Gaps in the C layout that mint's
+/// natural alignment would not reproduce are filled with explicit padding.
+pub fn render_layout(
+    layout: &ImportedStruct,
+    source: &str,
+    start_address: u64,
+    endianness: Endianness,
+) -> Result<String, ImportError> {
+    let mut renderer = Renderer { pads: 0 };
+    let mut lines = Vec::new();
+    renderer.members(layout, &[], "", &mut lines)?;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "# Layout skeleton generated by mint from {} ({}).",
+        source, layout.name
+    );
+    let _ = writeln!(
+        out,
+        "# Check start_address and the data source names before use."
+    );
+    let _ = writeln!(out);
+    let _ = writeln!(out, "[settings]");
+    let endianness = match endianness {
+        Endianness::Little => "little",
+        Endianness::Big => "big",
+    };
+    let _ = writeln!(out, "endianness = \"{}\"", endianness);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "[{}.header]", layout.name);
+    let _ = writeln!(out, "start_address = 0x{:X}", start_address);
+    let _ = writeln!(out, "length = 0x{:X}", layout.size);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "[{}.data]", layout.name);
+    for (key, value) in lines {
+        let _ = writeln!(out, "{} = {}", key, value);
+    }
+    Ok(out)
+}
+
+struct Renderer {
+    pads: usize,
+}
+
+impl Renderer {
+    /// Appends `(key, value)` pairs for the members of `layout`, keyed under
+    /// `keys` and named under `name_prefix`, padded to `layout.size`.
+    fn members(
+        &mut self,
+        layout: &ImportedStruct,
+        keys: &[&str],
+        name_prefix: &str,
+        out: &mut Vec<(String, String)>,
+    ) -> Result<(), ImportError> {
+        let mut cursor: usize = 0;
+        for member in &layout.members {
+            let align = match &member.kind {
+                MemberKind::Scalar { ty, .. } | MemberKind::Bitmap { ty, .. } => ty.size_bytes(),
+                MemberKind::Struct { .. } => 1,
+            };
+            let natural = cursor.next_multiple_of(align);
+            if member.offset < natural {
+                return Err(ImportError::Misaligned {
+                    field: format!("{}{}", name_prefix, member.name),
+                    offset: member.offset,
+                });
+            }
+            if member.offset > cursor {
+                self.pad(keys, member.offset - cursor, out);
+            }
+
+            let mut path = keys.to_vec();
+            path.push(&member.name);
+            let name = format!("{}{}", name_prefix, member.name);
+            match &member.kind {
+                MemberKind::Scalar { ty, dims } => {
+                    out.push((path.join("."), leaf(&name, *ty, dims)));
+                }
+                MemberKind::Bitmap { ty, fields } => {
+                    out.push((path.join("."), bitmap(name_prefix, *ty, fields)));
+                }
+                MemberKind::Struct { layout, count: 1 } => {
+                    self.members(layout, &path, &format!("{}.", name), out)?;
+                }
+                MemberKind::Struct { layout, count } => {
+                    let mut body = Vec::new();
+                    self.members(layout, &[], &format!("{}[{{i}}].", name), &mut body)?;
+                    let mut value = format!("{{ repeat = {}", count);
+                    for (key, entry) in body {
+                        let _ = write!(value, ", {} = {}", key, entry);
+                    }
+                    value.push_str(" }");
+                    out.push((path.join("."), value));
+                }
+            }
+            cursor = member.offset + member_size(&member.kind);
+        }
+        if layout.size > cursor {
+            self.pad(keys, layout.size - cursor, out);
+        }
+        Ok(())
+    }
+
+    fn pad(&mut self, keys: &[&str], len: usize, out: &mut Vec<(String, String)>) {
+        let mut path = keys.to_vec();
+        let key = format!("_pad{}", self.pads);
+        self.pads += 1;
+        path.push(&key);
+        let zeros = vec!["0"; len].join(", ");
+        out.push((
+            path.join("."),
+            format!("{{ value = [{}], type = \"u8\", size = {} }}", zeros, len),
+        ));
+    }
+}
+
+fn member_size(kind: &MemberKind) -> usize {
+    match kind {
+        MemberKind::Scalar { ty, dims } => ty.size_bytes() * dims.iter().product::<usize>(),
+        MemberKind::Bitmap { ty, .. } => ty.size_bytes(),
+        MemberKind::Struct { layout, count } => layout.size * count,
+    }
+}
+
+fn leaf(name: &str, ty: ScalarType, dims: &[usize]) -> String {
+    let size = match dims {
+        [] => String::new(),
+        [n] => format!(", size = {}", n),
+        [rows, cols] => format!(", size = [{}, {}]", rows, cols),
+        _ => format!(", size = {}", dims.iter().product::<usize>()),
+    };
+    format!(
+        "{{ name = \"{}\", type = \"{}\"{} }}",
+        name,
+        ty.layout_name(),
+        size
+    )
+}
+
+fn bitmap(name_prefix: &str, ty: ScalarType, fields: &[(Option<String>, u32)]) -> String {
+    let mut parts: Vec<String> = fields
+        .iter()
+        .map(|(name, bits)| match name {
+            Some(name) => format!("{{ bits = {}, name = \"{}{}\" }}", bits, name_prefix, name),
+            None => format!("{{ bits = {}, value = 0 }}", bits),
+        })
+        .collect();
+    let used: u32 = fields.iter().map(|(_, bits)| bits).sum();
+    let width = ty.size_bytes() as u32 * 8;
+    if used < width {
+        parts.push(format!("{{ bits = {}, value = 0 }}", width - used));
+    }
+    format!(
+        "{{ type = \"{}\", bitmap = [{}] }}",
+        ty.layout_name(),
+        parts.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_pads_to_c_offsets() {
+        let layout = ImportedStruct {
+            name: "Cal".to_string(),
+            size: 8,
+            align: 4,
+            members: vec![
+                Member {
+                    name: "id".to_string(),
+                    offset: 0,
+                    kind: MemberKind::Scalar {
+                        ty: ScalarType::U8,
+                        dims: vec![],
+                    },
+                },
+                Member {
+                    name: "gain".to_string(),
+                    offset: 4,
+                    kind: MemberKind::Scalar {
+                        ty: ScalarType::U16,
+                        dims: vec![],
+                    },
+                },
+            ],
+        };
+        let text = render_layout(&layout, "cal.h", 0, Endianness::Little).unwrap();
+        assert!(text.contains("id = { name = \"id\", type = \"u8\" }\n"));
+        assert!(text.contains("_pad0 = { value = [0, 0, 0], type = \"u8\", size = 3 }\n"));
+        assert!(text.contains("gain = { name = \"gain\", type = \"u16\" }\n"));
+        assert!(text.contains("_pad1 = { value = [0, 0], type = \"u8\", size = 2 }\n"));
+        assert!(text.contains("length = 0x8\n"));
+    }
+
+    #[test]
+    fn render_rejects_packed_members() {
+        let layout = ImportedStruct {
+            name: "Packed".to_string(),
+            size: 3,
+            align: 1,
+            members: vec![
+                Member {
+                    name: "a".to_string(),
+                    offset: 0,
+                    kind: MemberKind::Scalar {
+                        ty: ScalarType::U8,
+                        dims: vec![],
+                    },
+                },
+                Member {
+                    name: "b".to_string(),
+                    offset: 1,
+                    kind: MemberKind::Scalar {
+                        ty: ScalarType::U16,
+                        dims: vec![],
+                    },
+                },
+            ],
+        };
+        assert!(matches!(
+            render_layout(&layout, "p.h", 0, Endianness::Little),
+            Err(ImportError::Misaligned { .. })
+        ));
+    }
+}
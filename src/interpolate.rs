@@ -0,0 +1,414 @@
+use serde::de::{
+    self, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+
+/// Expands `${VAR}` and `${VAR:-fallback}` using the process environment.
+/// `$${` produces a literal `${`.
+pub fn expand_env(text: &str) -> Result<String, String> {
+    expand_with(text, |name| std::env::var(name).ok())
+}
+
+/// Expands variable references using `lookup`. A fallback applies when the
+/// variable is unset or empty; an unset variable without fallback is an error.
+pub fn expand_with<F>(text: &str, lookup: F) -> Result<String, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    expand_refs(text, |name| match is_valid_name(name) {
+        true => Ok(lookup(name)),
+        false => Err(format!("invalid variable name '{}'", name)),
+    })
+}
+
+/// Like [`expand_env`], but also expands `${SCHEME:KEY}` secret references by
+/// calling `secret` with the scheme and key. A secret that cannot be resolved is
+/// an error even with a fallback.
+pub fn expand_env_and_secrets<F>(text: &str, secret: F) -> Result<String, String>
+where
+    F: Fn(&str, &str) -> Result<String, String>,
+{
+    expand_refs(text, |name| match name.split_once(':') {
+        Some((scheme, key)) => secret(scheme, key).map(Some),
+        None if is_valid_name(name) => Ok(std::env::var(name).ok()),
+        None => Err(format!("invalid variable name '{}'", name)),
+    })
+}
+
+/// Expands `${...}` references; `resolve` returns `None` for an unset variable.
+fn expand_refs<F>(text: &str, resolve: F) -> Result<String, String>
+where
+    F: Fn(&str) -> Result<Option<String>, String>,
+{
+    if !text.contains("${") {
+        return Ok(text.to_string());
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find("${") {
+        if rest[..pos].ends_with('$') {
+            out.push_str(&rest[..pos - 1]);
+            out.push_str("${");
+            rest = &rest[pos + 2..];
+            continue;
+        }
+        out.push_str(&rest[..pos]);
+
+        let body_start = pos + 2;
+        let end = rest[body_start..]
+            .find('}')
+            .map(|i| body_start + i)
+            .ok_or_else(|| format!("unterminated '${{' in '{}'", line_of(rest, pos)))?;
+        let body = &rest[body_start..end];
+        let (name, fallback) = match body.split_once(":-") {
+            Some((name, fallback)) => (name, Some(fallback)),
+            None => (body, None),
+        };
+        match (resolve(name)?.filter(|v| !v.is_empty()), fallback) {
+            (Some(value), _) => out.push_str(&value),
+            (None, Some(fallback)) => out.push_str(fallback),
+            (None, None) => return Err(format!("environment variable '{}' is not set", name)),
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Expands the references in one string value.
+pub type Expander<'a> = dyn Fn(&str) -> Result<String, String> + 'a;
+
+/// Deserializer that expands the `${...}` references of each string value
+/// read from `inner` with `expand`. Keys, enum variants, and comments are
+/// left alone, and errors keep the position `inner` reports.
+pub struct Expanding<'a, D> {
+    inner: D,
+    expand: &'a Expander<'a>,
+}
+
+impl<'a, D> Expanding<'a, D> {
+    pub fn new(inner: D, expand: &'a Expander<'a>) -> Self {
+        Self { inner, expand }
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, D::Error> {
+            self.inner.$method($($arg,)* Expand { inner: visitor, expand: self.expand })
+        })*
+    };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for Expanding<'_, D> {
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Wraps the visitors, seeds, and accessors [`Expanding`] hands out.
+struct Expand<'a, T> {
+    inner: T,
+    expand: &'a Expander<'a>,
+}
+
+impl<'a, T> Expand<'a, T> {
+    fn wrap<U>(&self, inner: U) -> Expand<'a, U> {
+        Expand {
+            inner,
+            expand: self.expand,
+        }
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty);)*) => {
+        $(fn $method<E: de::Error>(self, v: $ty) -> Result<V::Value, E> {
+            self.inner.$method(v)
+        })*
+    };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for Expand<'_, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.inner.expecting(f)
+    }
+
+    forward_visit! {
+        visit_bool(bool);
+        visit_i8(i8);
+        visit_i16(i16);
+        visit_i32(i32);
+        visit_i64(i64);
+        visit_i128(i128);
+        visit_u8(u8);
+        visit_u16(u16);
+        visit_u32(u32);
+        visit_u64(u64);
+        visit_u128(u128);
+        visit_f32(f32);
+        visit_f64(f64);
+        visit_char(char);
+        visit_bytes(&[u8]);
+        visit_borrowed_bytes(&'de [u8]);
+        visit_byte_buf(Vec<u8>);
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<V::Value, E> {
+        self.inner
+            .visit_string((self.expand)(v).map_err(E::custom)?)
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<V::Value, E> {
+        match v.contains("${") {
+            true => self.visit_str(v),
+            false => self.inner.visit_borrowed_str(v),
+        }
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<V::Value, E> {
+        match v.contains("${") {
+            true => self.visit_str(&v),
+            false => self.inner.visit_string(v),
+        }
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_unit()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<V::Value, D::Error> {
+        self.inner.visit_some(Expanding::new(d, self.expand))
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, d: D) -> Result<V::Value, D::Error> {
+        self.inner
+            .visit_newtype_struct(Expanding::new(d, self.expand))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+        let seq = self.wrap(seq);
+        self.inner.visit_seq(seq)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        let map = self.wrap(map);
+        self.inner.visit_map(map)
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+        let data = self.wrap(data);
+        self.inner.visit_enum(data)
+    }
+}
+
+impl<'de, T: DeserializeSeed<'de>> DeserializeSeed<'de> for Expand<'_, T> {
+    type Value = T::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, d: D) -> Result<T::Value, D::Error> {
+        self.inner.deserialize(Expanding::new(d, self.expand))
+    }
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for Expand<'_, A> {
+    type Error = A::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, A::Error> {
+        let seed = self.wrap(seed);
+        self.inner.next_element_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for Expand<'_, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        self.inner.next_key_seed(seed)
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, A::Error> {
+        let seed = self.wrap(seed);
+        self.inner.next_value_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, 'de, A: EnumAccess<'de>> EnumAccess<'de> for Expand<'a, A> {
+    type Error = A::Error;
+    type Variant = Expand<'a, A::Variant>;
+
+    fn variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<(T::Value, Self::Variant), A::Error> {
+        let expand = self.expand;
+        let (value, variant) = self.inner.variant_seed(seed)?;
+        Ok((
+            value,
+            Expand {
+                inner: variant,
+                expand,
+            },
+        ))
+    }
+}
+
+impl<'de, A: VariantAccess<'de>> VariantAccess<'de> for Expand<'_, A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.inner.unit_variant()
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, A::Error> {
+        let seed = self.wrap(seed);
+        self.inner.newtype_variant_seed(seed)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, A::Error> {
+        let visitor = self.wrap(visitor);
+        self.inner.tuple_variant(len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error> {
+        let visitor = self.wrap(visitor);
+        self.inner.struct_variant(fields, visitor)
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn line_of(text: &str, pos: usize) -> &str {
+    let start = text[..pos].rfind('\n').map_or(0, |i| i + 1);
+    let end = text[pos..].find('\n').map_or(text.len(), |i| pos + i);
+    text[start..end].trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOST" => Some("db.local".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expands_variables_and_fallbacks() {
+        let text = "url=${HOST}:${PORT:-5432} user=${EMPTY:-admin}";
+        assert_eq!(
+            expand_with(text, lookup).unwrap(),
+            "url=db.local:5432 user=admin"
+        );
+    }
+
+    #[test]
+    fn escaped_and_missing_variables() {
+        assert_eq!(expand_with("$${HOST}", lookup).unwrap(), "${HOST}");
+        let err = expand_with("x = ${MISSING}", lookup).unwrap_err();
+        assert!(err.contains("'MISSING'"), "{err}");
+        assert!(expand_with("${HOST", lookup).is_err());
+    }
+
+    #[test]
+    fn expanding_deserializer_only_touches_string_values() {
+        use serde::Deserialize;
+
+        let expand = |text: &str| expand_with(text, lookup);
+        let text = r#"{"${HOST}": ["${HOST}", 1, {"x": "${PORT:-5432}"}]}"#;
+        let mut de = serde_json::Deserializer::from_str(text);
+        let value = serde_json::Value::deserialize(Expanding::new(&mut de, &expand)).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"${HOST}": ["db.local", 1, {"x": "5432"}]})
+        );
+
+        let text = "a = 1\nb = \"${MISSING}\"\n";
+        let de = toml::Deserializer::parse(text).unwrap();
+        let err = toml::Value::deserialize(Expanding::new(de, &expand)).unwrap_err();
+        assert_eq!(err.span(), Some(10..22));
+    }
+
+    #[test]
+    fn secret_references_go_to_the_resolver() {
+        let secret = |scheme: &str, key: &str| match (scheme, key) {
+            ("secret", "db") => Ok("hunter2".to_string()),
+            _ => Err(format!("no {} secret '{}'", scheme, key)),
+        };
+        assert_eq!(
+            expand_env_and_secrets("pw=${secret:db} $${secret:db}", secret).unwrap(),
+            "pw=hunter2 ${secret:db}"
+        );
+        let err = expand_env_and_secrets("${keyring:mint/ci}", secret).unwrap_err();
+        assert!(err.contains("no keyring secret 'mint/ci'"), "{err}");
+        assert!(expand_with("${secret:db}", lookup).is_err());
+    }
+}
@@ -1,5 +1,10 @@
+use super::convert::LayoutFormat;
 use super::error::LayoutError;
+use super::pattern::glob_match;
+use super::policy::{ConversionPolicy, NonFinite, Policy, parse_non_finite};
+use super::template::parse_define;
 use clap::Args;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
 pub struct BlockNames {
@@ -26,15 +31,214 @@ pub fn parse_block_arg(block: &str) -> Result<BlockNames, LayoutError> {
     }
 }
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Clone, Default)]
 pub struct LayoutArgs {
     #[arg(value_name = "BLOCK@FILE | FILE", num_args = 1.., value_parser = parse_block_arg, help = "One or more blocks as name@layout_file or a layout_file (toml/yaml/json) to build all blocks")]
     pub blocks: Vec<BlockNames>,
 
     #[arg(
         long,
-        help = "Enable strict type conversions; disallow lossy casts during bytestream assembly",
+        help = "Enable strict type conversions; shorthand for --on-lossy-cast error --on-out-of-range error",
         default_value_t = false
     )]
     pub strict: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "POLICY",
+        help = "Policy for float-to-integer truncation and inexact integer-to-float casts"
+    )]
+    pub on_lossy_cast: Option<Policy>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "POLICY",
+        help = "Policy for values saturated to fit their type, bitfield, or size"
+    )]
+    pub on_out_of_range: Option<Policy>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "POLICY",
+        help = "Policy for arrays, strings, and blobs padded up to their size"
+    )]
+    pub on_short_array: Option<Policy>,
+
+    #[arg(
+        long,
+        value_name = "MODE",
+        value_parser = parse_non_finite,
+        help = "Encoding for NaN/Inf values: error, raw (IEEE bits), or a sentinel bit pattern"
+    )]
+    pub on_non_finite: Option<NonFinite>,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        help = "Build only blocks whose name matches the glob (repeatable)"
+    )]
+    pub only: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        help = "Leave out blocks whose name matches the glob (repeatable)"
+    )]
+    pub exclude: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "TAG",
+        help = "Build only blocks whose header lists the tag in `tags` (repeatable)"
+    )]
+    pub tag: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Leave out blocks that fail to build, with a warning each, and write the rest",
+        default_value_t = false
+    )]
+    pub skip_failed_blocks: bool,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Override [settings] with the keys of [profiles.NAME.settings] in each layout"
+    )]
+    pub profile: Option<String>,
+
+    #[arg(
+        short = 'D',
+        long = "define",
+        value_name = "NAME[:TYPE]=VALUE",
+        value_parser = parse_define,
+        help = "Template variable for .j2 layout files (repeatable); a string unless TYPE is int, float, bool, or json"
+    )]
+    pub define: Vec<(String, serde_json::Value)>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        help = "Print the given layout file converted to toml, yaml, or json instead of building"
+    )]
+    pub convert_to: Option<LayoutFormat>,
+
+    #[arg(
+        long,
+        help = "Rewrite the given TOML layout files in canonical style instead of building",
+        default_value_t = false
+    )]
+    pub fmt: bool,
+
+    #[arg(
+        long,
+        requires = "fmt",
+        help = "With --fmt, fail if any layout file is not canonically formatted instead of rewriting it",
+        default_value_t = false
+    )]
+    pub check: bool,
+
+    #[arg(
+        long,
+        help = "Report alignment padding per block and suggest a top-level entry order that needs less, instead of writing output",
+        default_value_t = false
+    )]
+    pub suggest_packing: bool,
+
+    #[arg(
+        long,
+        help = "Print a JSON Schema of the data source names the blocks read and their JSON types, instead of building",
+        default_value_t = false
+    )]
+    pub data_contract: bool,
+
+    #[arg(
+        long,
+        value_name = "HEADER",
+        group = "importer",
+        requires = "import_struct",
+        help = "Print a layout TOML skeleton for a struct in a C header instead of building"
+    )]
+    pub import_c: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "ELF",
+        group = "importer",
+        requires = "import_target",
+        help = "Print a layout TOML skeleton for a struct or global from ELF debug info instead of building"
+    )]
+    pub import_elf: Option<PathBuf>,
+
+    #[arg(
+        long = "struct",
+        value_name = "NAME",
+        group = "import_target",
+        requires = "importer",
+        help = "Struct tag or typedef name to import with --import-c or --import-elf"
+    )]
+    pub import_struct: Option<String>,
+
+    #[arg(
+        long = "symbol",
+        value_name = "NAME",
+        group = "import_target",
+        requires = "import_elf",
+        help = "Global variable to import with --import-elf; the block starts at its address"
+    )]
+    pub import_symbol: Option<String>,
+}
+
+impl LayoutArgs {
+    /// Command-line conversion policy; overrides each layout's `[settings.policy]`.
+    pub fn conversion_policy(&self) -> ConversionPolicy {
+        ConversionPolicy {
+            lossy_cast: self.on_lossy_cast,
+            out_of_range: self.on_out_of_range,
+            short_array: self.on_short_array,
+            non_finite: self.on_non_finite,
+            strict: self.strict,
+        }
+    }
+
+    /// Whether any of `--only`, `--exclude`, or `--tag` was given.
+    pub fn filters_blocks(&self) -> bool {
+        !(self.only.is_empty() && self.exclude.is_empty() && self.tag.is_empty())
+    }
+
+    /// Whether a block named `name` with header `tags` passes `--only`,
+    /// `--exclude`, and `--tag`. Each repeated flag matches if any value does.
+    pub fn selects(&self, name: &str, tags: &[String]) -> bool {
+        (self.only.is_empty() || self.only.iter().any(|p| glob_match(p, name)))
+            && !self.exclude.iter().any(|p| glob_match(p, name))
+            && (self.tag.is_empty() || self.tag.iter().any(|t| tags.contains(t)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_by_glob_and_tag() {
+        let args = LayoutArgs {
+            only: vec!["eeprom_*".to_string()],
+            exclude: vec!["*_debug".to_string()],
+            ..Default::default()
+        };
+        assert!(args.selects("eeprom_cal", &[]));
+        assert!(!args.selects("eeprom_debug", &[]));
+        assert!(!args.selects("flash_cal", &[]));
+
+        let args = LayoutArgs {
+            tag: vec!["eeprom".to_string()],
+            ..Default::default()
+        };
+        assert!(args.selects("cal", &["eeprom".to_string()]));
+        assert!(!args.selects("cal", &[]));
+    }
 }
@@ -1,50 +1,285 @@
-use super::entry::LeafEntry;
+use super::entry::{AutoSource, LEAF_KEYS, LeafEntry, SOURCE_KEYS, ScalarType};
 use super::error::LayoutError;
+use super::expr::BlockRef;
 use super::header::Header;
-use super::settings::{Endianness, Settings};
-use super::used_values::ValueSink;
+use super::migration::Migration;
+use super::policy::ConversionPolicy;
+use super::refs::{BlockRefs, NoBlockRefs};
+use super::settings::{CrcConfig, Endianness, ProfileSettings, Settings, WordAddressing};
+use super::used_values::{TeeSink, ValueCollector, ValueSink, canonical_json, data_value_to_json};
+use super::value::{DataValue, ValueSource};
 use crate::data::DataSource;
+use crate::output::checksum;
 
 use indexmap::IndexMap;
 use serde::Deserialize;
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
 
 /// Mutable state tracked during recursive bytestream building
 struct BuildState {
     buffer: Vec<u8>,
     offset: usize,
     padding_count: u32,
+    spans: Vec<FieldSpan>,
+    /// Auto leaves emitted as zeros, patched once the block is built.
+    placeholders: Vec<Placeholder>,
+    /// Offset and first span index of the data section, which auto leaves in
+    /// the prologue cover.
+    data_start: (usize, usize),
+}
+
+/// Auto leaf whose value depends on the rest of the block.
+struct Placeholder {
+    path: Vec<String>,
+    source: AutoSource,
+    /// Index of the leaf in [`BuildState::spans`].
+    span: usize,
+}
+
+/// Bytes of a built block, before its CRC.
+#[derive(Debug)]
+pub struct BuiltBlock {
+    pub bytes: Vec<u8>,
+    /// Padding bytes among `bytes`, which do not count as used.
+    pub padding_count: u32,
+    /// Span of every leaf, in layout order.
+    pub spans: Vec<FieldSpan>,
+    /// Bytes of the prologue, its padding included.
+    pub prologue_len: u32,
+}
+
+/// Byte range of one leaf within a built block's bytestream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSpan {
+    pub path: String,
+    pub offset: usize,
+    pub len: usize,
+    pub scalar_type: ScalarType,
+    /// Array dimensions in memory order; empty for a scalar.
+    pub dims: Vec<usize>,
 }
 
 /// Immutable configuration for bytestream building
 pub struct BuildConfig<'a> {
     pub endianness: &'a Endianness,
     pub padding: u8,
-    pub strict: bool,
-    pub word_addressing: bool,
+    pub policy: ConversionPolicy,
+    pub word_addressing: WordAddressing,
+    pub blocks: &'a dyn BlockRefs,
+    pub block_name: &'a str,
 }
 
-#[derive(Debug, Deserialize)]
+/// Layout file: global settings, optional named settings profiles, named
+/// constants, and named blocks.
+///
+/// Deserialized by hand rather than via `#[serde(flatten)]` so parsers keep
+/// span information for errors inside blocks.
+#[derive(Debug)]
 pub struct Config {
     pub settings: Settings,
-    #[serde(flatten)]
+    pub profiles: IndexMap<String, Profile>,
+    pub constants: Constants,
     pub blocks: IndexMap<String, Block>,
 }
 
+/// Named values of a layout's `[constants]` table.
+pub type Constants = IndexMap<String, ValueSource>;
+
+/// Alternative settings selectable with `--profile`.
+#[derive(Debug, Deserialize)]
+pub struct Profile {
+    pub settings: ProfileSettings,
+}
+
+impl Config {
+    /// Overrides the global settings with those the named profile sets.
+    /// Returns false if the layout has no such profile.
+    pub fn apply_profile(&mut self, name: &str) -> bool {
+        match self.profiles.swap_remove(name) {
+            Some(profile) => {
+                self.settings.apply(profile.settings);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Evaluates the `start_address` and `length` expressions of every block,
+    /// following references to other blocks in this file. The error names the
+    /// block and key that failed.
+    pub fn resolve_header_expressions(&mut self) -> Result<(), String> {
+        self.place_blocks()?;
+        let names: Vec<String> = self.blocks.keys().cloned().collect();
+        for name in &names {
+            for field in [HeaderField::StartAddress, HeaderField::Length] {
+                self.resolve_header_field(name, field, &mut Vec::new())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces `value = "$NAME"` references in every block with the
+    /// constant they name.
+    pub fn resolve_constants(&mut self) -> Result<(), LayoutError> {
+        self.blocks
+            .values_mut()
+            .try_for_each(|block| block.resolve_constants(&self.constants))
+    }
+
+    /// Gives each block without a `start_address` an expression placing it
+    /// after the previous such block, per `[settings.auto_place]`.
+    fn place_blocks(&mut self) -> Result<(), String> {
+        let mut previous: Option<&str> = None;
+        for (name, block) in &mut self.blocks {
+            if !block.header.expressions.auto_placed {
+                continue;
+            }
+            let place = self.settings.auto_place.as_ref().ok_or_else(|| {
+                format!(
+                    "block '{}' has no start_address and [settings.auto_place] is not set",
+                    name
+                )
+            })?;
+            block.header.expressions.start_address = Some(place.start_after(previous));
+            previous = Some(name);
+        }
+        Ok(())
+    }
+
+    /// Value of `field` in block `name`, evaluating its expression first if it
+    /// has one. `pending` holds the fields being evaluated, to catch cycles.
+    fn resolve_header_field(
+        &mut self,
+        name: &str,
+        field: HeaderField,
+        pending: &mut Vec<(String, HeaderField)>,
+    ) -> Result<u32, String> {
+        let header = &mut self
+            .blocks
+            .get_mut(name)
+            .ok_or_else(|| format!("no block '{}' in this layout file", name))?
+            .header;
+        let (value, expr) = match field {
+            HeaderField::StartAddress => (header.start_address, &header.expressions.start_address),
+            HeaderField::Length => (header.length, &header.expressions.length),
+        };
+        let Some(expr) = expr.clone() else {
+            return Ok(value);
+        };
+        let key = (name.to_string(), field);
+        if pending.contains(&key) {
+            let cycle: Vec<String> = pending
+                .iter()
+                .chain([&key])
+                .map(|(block, field)| format!("{}.{}", block, field.name()))
+                .collect();
+            return Err(format!(
+                "header expressions form a cycle: {}",
+                cycle.join(" -> ")
+            ));
+        }
+        pending.push(key);
+        let value = expr.with_constants(&self.constants).and_then(|expr| {
+            expr.eval(&mut |kind, block| {
+                let mut get = |field| self.resolve_header_field(block, field, pending);
+                Ok(match kind {
+                    BlockRef::StartOf => get(HeaderField::StartAddress)? as u64,
+                    BlockRef::LengthOf => get(HeaderField::Length)? as u64,
+                    BlockRef::EndOf => {
+                        get(HeaderField::StartAddress)? as u64 + get(HeaderField::Length)? as u64
+                    }
+                })
+            })
+        });
+        pending.pop();
+        let value = value
+            .and_then(|v| u32::try_from(v).map_err(|_| format!("0x{:X} does not fit in u32", v)))
+            .map_err(|e| format!("block '{}' {}: {}", name, field.name(), e))?;
+
+        let header = &mut self.blocks[name].header;
+        match field {
+            HeaderField::StartAddress => {
+                header.start_address = value;
+                header.expressions.start_address = None;
+            }
+            HeaderField::Length => {
+                header.length = value;
+                header.expressions.length = None;
+            }
+        }
+        Ok(value)
+    }
+}
+
+/// Header key that may hold an expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HeaderField {
+    StartAddress,
+    Length,
+}
+
+impl HeaderField {
+    fn name(self) -> &'static str {
+        match self {
+            HeaderField::StartAddress => "start_address",
+            HeaderField::Length => "length",
+        }
+    }
+}
+
+/// Leading path segment of the leaves in a block's prologue.
+pub const PROLOGUE: &str = "prologue";
+
 /// Flash block.
 #[derive(Debug, Deserialize)]
 pub struct Block {
+    /// Key of the block in its layout file; empty when deserialized on its own.
+    #[serde(skip)]
+    pub name: String,
     pub header: Header,
+    /// Entries emitted ahead of `data`, such as a structured header whose
+    /// auto leaves describe the data.
+    #[serde(default)]
+    pub prologue: Option<Entry>,
     pub data: Entry,
+    /// How to read images of older schema versions.
+    #[serde(default)]
+    pub migrations: Vec<Migration>,
 }
 
 /// Any entry - should always be either a leaf or a branch (more entries).
-#[derive(Debug, Deserialize)]
-#[serde(untagged)]
+///
+/// A table containing a `type` key is a leaf; any other table is a branch.
+#[derive(Debug, Clone)]
 pub enum Entry {
     Leaf(LeafEntry),
     Branch(IndexMap<String, Entry>),
 }
 
+impl Entry {
+    /// Copy of this entry with `{i}` in data source names replaced by `index`.
+    fn with_index(&self, index: usize) -> Entry {
+        match self {
+            Entry::Leaf(leaf) => {
+                let mut leaf = leaf.clone();
+                leaf.substitute_index(index);
+                Entry::Leaf(leaf)
+            }
+            Entry::Branch(branch) => Entry::Branch(
+                branch
+                    .iter()
+                    .map(|(key, entry)| (key.clone(), entry.with_index(index)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
 impl Block {
     pub fn build_bytestream(
         &self,
@@ -53,29 +288,336 @@ impl Block {
         strict: bool,
         value_sink: &mut dyn ValueSink,
     ) -> Result<(Vec<u8>, u32), LayoutError> {
+        let policy = if strict {
+            ConversionPolicy::strict()
+        } else {
+            ConversionPolicy::default()
+        };
+        self.build_bytestream_with_refs(data_source, settings, &policy, value_sink, &NoBlockRefs)
+    }
+
+    /// Builds the bytestream, resolving `block` entries through `blocks`.
+    /// `policy` overrides the layout's `[settings.policy]`, and the header's
+    /// `strict` overrides its `strict`.
+    pub fn build_bytestream_with_refs(
+        &self,
+        data_source: Option<&dyn DataSource>,
+        settings: &Settings,
+        policy: &ConversionPolicy,
+        value_sink: &mut dyn ValueSink,
+        blocks: &dyn BlockRefs,
+    ) -> Result<(Vec<u8>, u32), LayoutError> {
+        let built =
+            self.build_bytestream_with_spans(data_source, settings, policy, value_sink, blocks)?;
+        Ok((built.bytes, built.padding_count))
+    }
+
+    /// Like [`Block::build_bytestream_with_refs`], also returning the span of
+    /// every leaf in layout order and the size of the prologue.
+    pub fn build_bytestream_with_spans(
+        &self,
+        data_source: Option<&dyn DataSource>,
+        settings: &Settings,
+        policy: &ConversionPolicy,
+        value_sink: &mut dyn ValueSink,
+        blocks: &dyn BlockRefs,
+    ) -> Result<BuiltBlock, LayoutError> {
+        // A CRC at "start" takes the first bytes, then the prologue and any
+        // guard bytes; data is laid out after them.
+        let reserved = self.header.reserved_start(settings.crc.as_ref());
+        let word_size = settings.word_addressing.word_size();
+        if let Some(guard) = &self.header.guard
+            && (guard.before % word_size != 0 || guard.after % word_size != 0)
+        {
+            return Err(LayoutError::DataValueExportFailed(format!(
+                "guard sizes in block '{}' must be multiples of the {}-byte word",
+                self.name, word_size
+            )));
+        }
+        let mut buffer = Vec::with_capacity((self.header.length as usize).min(64 * 1024));
+        buffer.resize(reserved as usize, self.header.padding);
         let mut state = BuildState {
-            buffer: Vec::with_capacity((self.header.length as usize).min(64 * 1024)),
-            offset: 0,
-            padding_count: 0,
+            offset: buffer.len(),
+            buffer,
+            padding_count: reserved,
+            spans: Vec::new(),
+            placeholders: Vec::new(),
+            data_start: (0, 0),
         };
         let config = BuildConfig {
             endianness: &settings.endianness,
             padding: self.header.padding,
-            strict,
+            policy: policy
+                .with_strict(self.header.strict)
+                .resolve(&settings.policy),
             word_addressing: settings.word_addressing,
+            blocks,
+            block_name: &self.name,
         };
 
+        let mut tee = TeeSink {
+            collector: self.has_values_hash().then(ValueCollector::new),
+            inner: value_sink,
+        };
+        if let Some(prologue) = &self.prologue {
+            Self::build_bytestream_inner(
+                prologue,
+                data_source,
+                &mut state,
+                &config,
+                &mut tee,
+                &mut vec![PROLOGUE.to_string()],
+            )?;
+        }
+        let prologue_len = (state.buffer.len() - reserved as usize) as u32;
+        if let Some(guard) = &self.header.guard {
+            Self::pad_to_word(&mut state, word_size, self.header.padding);
+            let len = state.buffer.len() + guard.before as usize;
+            state.buffer.resize(len, guard.value);
+            state.offset = len;
+        }
+        state.data_start = (state.offset, state.spans.len());
+
         let mut field_path = Vec::new();
         Self::build_bytestream_inner(
             &self.data,
             data_source,
             &mut state,
             &config,
-            value_sink,
+            &mut tee,
             &mut field_path,
         )?;
+        let crc = self.header.resolved_crc(settings.crc.as_ref());
+        Self::patch_placeholders(&mut state, &config, &crc, tee)?;
+
+        // Guard bytes after the data start on a word, so they stay together
+        // through the word swap.
+        if let Some(guard) = &self.header.guard
+            && guard.after > 0
+        {
+            Self::pad_to_word(&mut state, word_size, self.header.padding);
+            let len = state.buffer.len() + guard.after as usize;
+            state.buffer.resize(len, guard.value);
+        }
+
+        Ok(BuiltBlock {
+            bytes: state.buffer,
+            padding_count: state.padding_count,
+            spans: state.spans,
+            prologue_len,
+        })
+    }
+
+    /// Pads the built bytes to a whole word.
+    fn pad_to_word(state: &mut BuildState, word_size: u32, padding: u8) {
+        let len = state.buffer.len().next_multiple_of(word_size as usize);
+        state.padding_count += (len - state.buffer.len()) as u32;
+        state.buffer.resize(len, padding);
+        state.offset = len;
+    }
+
+    /// Second pass over the built block: fills the auto leaves that depend on
+    /// the rest of it. Lengths and counts go first and CRCs last, from the back,
+    /// so that each CRC covers the final bytes after it.
+    fn patch_placeholders(
+        state: &mut BuildState,
+        config: &BuildConfig,
+        crc: &CrcConfig,
+        tee: TeeSink,
+    ) -> Result<(), LayoutError> {
+        if state.placeholders.is_empty() {
+            return Ok(());
+        }
+        let mut placeholders = std::mem::take(&mut state.placeholders);
+        placeholders.sort_by_key(|p| match p.source {
+            AutoSource::DataCrc => (2, usize::MAX - p.span),
+            AutoSource::ValuesHash => (1, p.span),
+            _ => (0, p.span),
+        });
+        let digest = placeholders
+            .iter()
+            .any(|p| matches!(p.source, AutoSource::ValuesHash))
+            .then(|| {
+                let values = tee
+                    .collector
+                    .map(ValueCollector::into_value)
+                    .unwrap_or_default();
+                Sha256::digest(canonical_json(&values))
+            });
+
+        for placeholder in placeholders {
+            let span = &state.spans[placeholder.span];
+            let (offset, len, scalar_type) = (span.offset, span.len, span.scalar_type);
+            // Leaves in the prologue describe the data section; others the
+            // leaves after them.
+            let (rest, next_field) = match placeholder.span < state.data_start.1 {
+                true => state.data_start,
+                false => (offset + len, placeholder.span + 1),
+            };
+            let value = match placeholder.source {
+                AutoSource::DataLength => (state.buffer.len() - rest) as u64,
+                AutoSource::FieldCount => (state.spans.len() - next_field) as u64,
+                // Leading digest bytes, read big-endian
+                AutoSource::ValuesHash => digest.as_ref().unwrap()[..len]
+                    .iter()
+                    .fold(0u64, |acc, b| (acc << 8) | u64::from(*b)),
+                AutoSource::DataCrc => {
+                    if !crc.has_algorithm() {
+                        return Err(LayoutError::DataValueExportFailed(
+                            "data_crc requires CRC parameters in [settings.crc] or [header.crc]."
+                                .to_string(),
+                        ));
+                    }
+                    let crc = checksum::compute_checksum(&state.buffer[rest..], crc)
+                        .map_err(|e| LayoutError::DataValueExportFailed(e.to_string()))?;
+                    u64::from(crc)
+                }
+                AutoSource::CrcOf(_) | AutoSource::BlockId(_) => unreachable!(),
+            };
+            let value = DataValue::U64(value);
+            let bytes = value.to_bytes(scalar_type, config.endianness, true)?;
+            state.buffer[offset..offset + len].copy_from_slice(&bytes);
+            tee.inner
+                .record_value(&placeholder.path, data_value_to_json(&value)?)?;
+        }
+        Ok(())
+    }
+
+    /// Resolves relative `file` entry paths against `base`.
+    pub fn resolve_file_paths(&mut self, base: &Path) {
+        fn walk(entry: &mut Entry, base: &Path) {
+            match entry {
+                Entry::Leaf(leaf) => leaf.resolve_file_path(base),
+                Entry::Branch(branch) => branch.values_mut().for_each(|e| walk(e, base)),
+            }
+        }
+        self.prologue.iter_mut().for_each(|e| walk(e, base));
+        walk(&mut self.data, base);
+    }
+
+    /// Replaces `value = "$NAME"` references with the constants they name.
+    pub fn resolve_constants(&mut self, constants: &Constants) -> Result<(), LayoutError> {
+        fn walk(entry: &mut Entry, path: &str, constants: &Constants) -> Result<(), LayoutError> {
+            match entry {
+                Entry::Leaf(leaf) => leaf.resolve_constants(constants),
+                Entry::Branch(branch) => branch.iter_mut().try_for_each(|(key, e)| {
+                    let path = format!("{}.{}", path, key);
+                    walk(e, &path, constants).map_err(|e| match e {
+                        LayoutError::InField { .. } => e,
+                        e => LayoutError::InField {
+                            field: path,
+                            source: Box::new(e),
+                        },
+                    })
+                }),
+            }
+        }
+        self.prologue
+            .iter_mut()
+            .try_for_each(|e| walk(e, &self.name, constants))?;
+        walk(&mut self.data, &self.name, constants)
+    }
 
-        Ok((state.buffer, state.padding_count))
+    /// Migration for reading images of schema version `from`, or `None` if
+    /// they are read as the current layout: the block is unversioned or
+    /// already at `from`.
+    pub fn migration(&self, from: u32) -> Result<Option<&Migration>, LayoutError> {
+        match self.header.schema_version {
+            None => Ok(None),
+            Some(current) if current == from => Ok(None),
+            Some(current) => self
+                .migrations
+                .iter()
+                .find(|m| m.from == from)
+                .map(Some)
+                .ok_or_else(|| {
+                    LayoutError::Migration(format!(
+                        "block '{}' is at schema_version {} and has no migration from {}",
+                        self.name, current, from
+                    ))
+                }),
+        }
+    }
+
+    /// Whether any leaf holds a `values_hash`.
+    fn has_values_hash(&self) -> bool {
+        fn walk(entry: &Entry) -> bool {
+            match entry {
+                Entry::Leaf(leaf) => leaf.is_values_hash(),
+                Entry::Branch(branch) => branch.values().any(walk),
+            }
+        }
+        self.roots().any(walk)
+    }
+
+    /// Names of other blocks in the same layout that this block references.
+    pub fn dependencies(&self) -> Vec<String> {
+        fn walk(entry: &Entry, deps: &mut Vec<String>) {
+            match entry {
+                Entry::Leaf(leaf) => {
+                    if let Some(name) = leaf.block_dependency()
+                        && !deps.iter().any(|d| d == name)
+                    {
+                        deps.push(name.to_string());
+                    }
+                }
+                Entry::Branch(branch) => branch.values().for_each(|e| walk(e, deps)),
+            }
+        }
+        let mut deps = Vec::new();
+        self.roots().for_each(|e| walk(e, &mut deps));
+        deps
+    }
+
+    /// Dotted paths of every leaf, in layout order.
+    pub fn leaf_paths(&self) -> Result<Vec<String>, LayoutError> {
+        Ok(self.leaves()?.into_iter().map(|(path, _)| path).collect())
+    }
+
+    /// Every leaf with its dotted path, in layout order.
+    pub fn leaves(&self) -> Result<Vec<(String, &LeafEntry)>, LayoutError> {
+        let mut leaves = Vec::new();
+        if let Some(prologue) = &self.prologue {
+            Self::collect_leaves(prologue, &mut vec![PROLOGUE.to_string()], &mut leaves)?;
+        }
+        Self::collect_leaves(&self.data, &mut Vec::new(), &mut leaves)?;
+        Ok(leaves)
+    }
+
+    /// The prologue, if any, then the data.
+    fn roots(&self) -> impl Iterator<Item = &Entry> {
+        self.prologue.iter().chain([&self.data])
+    }
+
+    /// Leaf paths that occur more than once, each reported once.
+    pub fn duplicate_paths(&self) -> Result<Vec<String>, LayoutError> {
+        let mut seen = HashSet::new();
+        let mut duplicates = Vec::new();
+        for path in self.leaf_paths()? {
+            if !seen.insert(path.clone()) && !duplicates.contains(&path) {
+                duplicates.push(path);
+            }
+        }
+        Ok(duplicates)
+    }
+
+    fn collect_leaves<'a>(
+        entry: &'a Entry,
+        field_path: &mut Vec<String>,
+        leaves: &mut Vec<(String, &'a LeafEntry)>,
+    ) -> Result<(), LayoutError> {
+        match entry {
+            Entry::Leaf(leaf) => leaves.push((field_path.join("."), leaf)),
+            Entry::Branch(branch) => {
+                for (field_name, v) in branch.iter() {
+                    let path_len = field_path.len();
+                    field_path.extend(split_field_path(field_name)?);
+                    Self::collect_leaves(v, field_path, leaves)?;
+                    field_path.truncate(path_len);
+                }
+            }
+        }
+        Ok(())
     }
 
     fn build_bytestream_inner(
@@ -96,6 +638,20 @@ impl Block {
                 }
 
                 let bytes = leaf.emit_bytes(data_source, config, value_sink, field_path)?;
+                if let Some(source) = leaf.placeholder() {
+                    state.placeholders.push(Placeholder {
+                        path: field_path.clone(),
+                        source: source.clone(),
+                        span: state.spans.len(),
+                    });
+                }
+                state.spans.push(FieldSpan {
+                    path: field_path.join("."),
+                    offset: state.offset,
+                    len: bytes.len(),
+                    scalar_type: leaf.scalar_type,
+                    dims: leaf.memory_dims(bytes.len()),
+                });
                 state.offset += bytes.len();
                 state.buffer.extend(bytes);
             }
@@ -134,3 +690,435 @@ fn split_field_path(field_name: &str) -> Result<Vec<String>, LayoutError> {
     }
     Ok(segments.into_iter().map(|s| s.to_string()).collect())
 }
+
+impl<'de> Deserialize<'de> for Config {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(ConfigVisitor)
+    }
+}
+
+struct ConfigVisitor;
+
+impl<'de> Visitor<'de> for ConfigVisitor {
+    type Value = Config;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a layout table with settings, profiles, constants, and blocks")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Config, A::Error> {
+        let mut settings = None;
+        let mut profiles = None;
+        let mut constants = None;
+        let mut blocks = IndexMap::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "settings" {
+                if settings.is_some() {
+                    return Err(de::Error::duplicate_field("settings"));
+                }
+                settings = Some(map.next_value()?);
+            } else if key == "profiles" {
+                if profiles.is_some() {
+                    return Err(de::Error::duplicate_field("profiles"));
+                }
+                profiles = Some(map.next_value()?);
+            } else if key == "constants" {
+                if constants.is_some() {
+                    return Err(de::Error::duplicate_field("constants"));
+                }
+                constants = Some(map.next_value()?);
+            } else {
+                let mut block: Block = map.next_value()?;
+                block.name = key.clone();
+                if blocks.insert(key.clone(), block).is_some() {
+                    return Err(de::Error::custom(format!("duplicate block '{}'", key)));
+                }
+            }
+        }
+        let settings = settings.ok_or_else(|| de::Error::missing_field("settings"))?;
+        Ok(Config {
+            settings,
+            profiles: profiles.unwrap_or_default(),
+            constants: constants.unwrap_or_default(),
+            blocks,
+        })
+    }
+}
+
+/// Leaf attributes whose value is a table rather than a nested entry.
+const MAP_ATTRS: &[&str] = &["auto", "csv"];
+
+fn quoted_list(keys: &[&str]) -> String {
+    keys.iter()
+        .map(|k| format!("`{}`", k))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Parsed table member: a nested entry or a plain leaf attribute value.
+enum Node {
+    Entry(Entry),
+    Value(Value),
+}
+
+impl<'de> Deserialize<'de> for Entry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match (NodeSeed { key: "", path: "" }).deserialize(deserializer)? {
+            Node::Entry(entry) => Ok(entry),
+            Node::Value(_) => Err(de::Error::custom("expected a table of entries")),
+        }
+    }
+}
+
+/// Checks a single leaf attribute against `LeafEntry` while its value is still
+/// being read, so a bad value is reported at its own position rather than at
+/// the enclosing table.
+fn check_leaf_attr<E: de::Error>(key: &str, path: &str, value: &Value) -> Result<(), E> {
+    if !LEAF_KEYS.contains(&key) {
+        return Ok(());
+    }
+    let mut probe = serde_json::Map::new();
+    probe.insert("type".to_string(), Value::String("u8".to_string()));
+    if !SOURCE_KEYS.contains(&key) {
+        probe.insert("value".to_string(), Value::from(0));
+    }
+    probe.insert(key.to_string(), value.clone());
+    LeafEntry::deserialize(Value::Object(probe))
+        .map(|_| ())
+        .map_err(|e| E::custom(format!("in field '{}': {}", path, e)))
+}
+
+/// Deserializes a `Node`, carrying its key and dotted field path for error messages.
+struct NodeSeed<'a> {
+    key: &'a str,
+    path: &'a str,
+}
+
+impl<'de> DeserializeSeed<'de> for NodeSeed<'_> {
+    type Value = Node;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Node, D::Error> {
+        let (key, path) = (self.key, self.path);
+        let node = deserializer.deserialize_any(self)?;
+        if let Node::Value(value) = &node {
+            check_leaf_attr(key, path, value)?;
+        }
+        Ok(node)
+    }
+}
+
+/// Deserializes a table-valued leaf attribute, checking it unless it is a nested entry.
+struct AttrSeed<'a> {
+    key: &'a str,
+    path: &'a str,
+}
+
+impl<'de> DeserializeSeed<'de> for AttrSeed<'_> {
+    type Value = Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        if value.get("type").is_none() {
+            check_leaf_attr(self.key, self.path, &value)?;
+        }
+        Ok(value)
+    }
+}
+
+impl<'de> Visitor<'de> for NodeSeed<'_> {
+    type Value = Node;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a leaf entry, a table of entries, or a leaf attribute value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Node, E> {
+        Ok(Node::Value(Value::Bool(v)))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Node, E> {
+        Ok(Node::Value(Value::from(v)))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Node, E> {
+        Ok(Node::Value(Value::from(v)))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Node, E> {
+        Ok(Node::Value(Value::from(v)))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Node, E> {
+        Ok(Node::Value(Value::String(v.to_string())))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Node, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element::<Value>()? {
+            items.push(item);
+        }
+        Ok(Node::Value(Value::Array(items)))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Node, A::Error> {
+        let mut attrs = serde_json::Map::new();
+        let mut children = IndexMap::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let path = if self.path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", self.path, key)
+            };
+            let node = if MAP_ATTRS.contains(&key.as_str()) {
+                // A table here is either the attribute itself or a field of the same name.
+                let value = map.next_value_seed(AttrSeed {
+                    key: &key,
+                    path: &path,
+                })?;
+                match value.get("type") {
+                    Some(_) => (NodeSeed {
+                        key: &key,
+                        path: &path,
+                    })
+                    .deserialize(value)
+                    .map_err(de::Error::custom)?,
+                    None => Node::Value(value),
+                }
+            } else {
+                map.next_value_seed(NodeSeed {
+                    key: &key,
+                    path: &path,
+                })?
+            };
+            match node {
+                Node::Value(v) => {
+                    attrs.insert(key, v);
+                }
+                Node::Entry(e) => {
+                    if children.insert(key.clone(), e).is_some() {
+                        return Err(de::Error::custom(format!("duplicate field '{}'", path)));
+                    }
+                }
+            }
+        }
+
+        let location = if self.path.is_empty() {
+            String::new()
+        } else {
+            format!("in field '{}': ", self.path)
+        };
+
+        // Embedded blocks are raw bytes, so their type may be omitted.
+        if attrs.contains_key("block") && !attrs.contains_key("type") {
+            attrs.insert("type".to_string(), Value::String("u8".to_string()));
+        }
+
+        if attrs.contains_key("type") {
+            if let Some(key) = children.keys().next() {
+                return Err(de::Error::custom(format!(
+                    "{}unexpected table '{}' in leaf entry",
+                    location, key
+                )));
+            }
+            if let Some(key) = attrs.keys().find(|k| !LEAF_KEYS.contains(&k.as_str())) {
+                return Err(de::Error::custom(format!(
+                    "{}unknown field `{}`, expected one of {}",
+                    location,
+                    key,
+                    quoted_list(LEAF_KEYS)
+                )));
+            }
+            if !SOURCE_KEYS.iter().any(|k| attrs.contains_key(*k)) {
+                return Err(de::Error::custom(format!(
+                    "{}missing source, expected one of {}",
+                    location,
+                    quoted_list(SOURCE_KEYS)
+                )));
+            }
+            return LeafEntry::deserialize(Value::Object(attrs))
+                .map(|leaf| Node::Entry(Entry::Leaf(leaf)))
+                .map_err(|e| de::Error::custom(format!("{}{}", location, e)));
+        }
+
+        if let Some(count) = attrs.remove("repeat") {
+            let count = count.as_u64().filter(|&n| n > 0).ok_or_else(|| {
+                de::Error::custom(format!("{}`repeat` must be a positive integer", location))
+            })?;
+            let body = Entry::Branch(children);
+            children = (0..count as usize)
+                .map(|i| (i.to_string(), body.with_index(i)))
+                .collect();
+        }
+
+        if let Some(key) = attrs.keys().find(|k| !LEAF_KEYS.contains(&k.as_str())) {
+            return Err(de::Error::custom(format!(
+                "{}expected a table for '{}'",
+                location, key
+            )));
+        }
+        if !attrs.is_empty() {
+            return Err(de::Error::custom(format!(
+                "{}missing field `type`",
+                location
+            )));
+        }
+        Ok(Node::Entry(Entry::Branch(children)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::entry::EntrySource;
+    use crate::layout::settings::CrcArea;
+
+    #[test]
+    fn duplicate_paths_merge_dotted_and_nested_keys() {
+        let block: Block = serde_json::from_str(
+            r#"{
+                "header": { "start_address": 0, "length": 16 },
+                "data": {
+                    "a.b": { "value": 1, "type": "u8" },
+                    "a": { "b": { "value": 2, "type": "u8" }, "c": { "value": 3, "type": "u8" } }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(block.leaf_paths().unwrap(), ["a.b", "a.b", "a.c"]);
+        assert_eq!(block.duplicate_paths().unwrap(), ["a.b"]);
+    }
+
+    #[test]
+    fn apply_profile_overrides_settings_it_sets() {
+        let mut cfg: Config = serde_json::from_str(
+            r#"{
+                "settings": {
+                    "endianness": "little",
+                    "erased_value": 255,
+                    "crc": { "polynomial": 79764919, "area": "data" }
+                },
+                "profiles": { "b": { "settings": {
+                    "endianness": "big",
+                    "virtual_offset": 16,
+                    "crc": { "area": "block_zero_crc" }
+                } } },
+                "blk": {
+                    "header": { "start_address": 0, "length": 16 },
+                    "data": { "x": { "value": 1, "type": "u8" } }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert!(!cfg.apply_profile("a"));
+        assert!(cfg.apply_profile("b"));
+        assert_eq!(cfg.settings.virtual_offset, 16);
+        assert!(matches!(cfg.settings.endianness, Endianness::Big));
+        assert_eq!(cfg.settings.erased_value, Some(0xFF));
+        let crc = cfg.settings.crc.as_ref().unwrap();
+        assert_eq!(crc.polynomial, Some(0x04C11DB7));
+        assert_eq!(crc.area, Some(CrcArea::BlockZeroCrc));
+        assert!(cfg.blocks.contains_key("blk"));
+    }
+
+    #[test]
+    fn block_entries_default_to_u8_and_report_dependencies() {
+        let block: Block = serde_json::from_str(
+            r#"{
+                "header": { "start_address": 0, "length": 64 },
+                "data": {
+                    "a": { "block": "boot" },
+                    "b": { "c": { "block": "boot" }, "d": { "block": "app", "size": 8 } }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(block.dependencies(), ["boot", "app"]);
+    }
+
+    #[test]
+    fn auto_attribute_and_field_named_auto() {
+        let block: Block = serde_json::from_str(
+            r#"{
+                "header": { "start_address": 0, "length": 64 },
+                "data": {
+                    "crc": { "auto": { "crc_of": "app" }, "type": "u32" },
+                    "auto": { "value": 1, "type": "u8" }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(block.leaf_paths().unwrap(), ["crc", "auto"]);
+        assert_eq!(block.dependencies(), ["app"]);
+    }
+
+    #[test]
+    fn values_hash_covers_other_values_in_key_order() {
+        let build = |data: &str| {
+            let block: Block = serde_json::from_str(&format!(
+                r#"{{ "header": {{ "start_address": 0, "length": 16 }}, "data": {} }}"#,
+                data
+            ))
+            .unwrap();
+            let settings: Settings = serde_json::from_str(r#"{ "endianness": "big" }"#).unwrap();
+            block
+                .build_bytestream(
+                    None,
+                    &settings,
+                    false,
+                    &mut crate::layout::used_values::NoopValueSink,
+                )
+                .unwrap()
+                .0
+        };
+        let a = build(
+            r#"{ "hash": { "auto": "values_hash", "type": "u32" },
+                 "x": { "value": 1, "type": "u8" }, "y": { "value": 2, "type": "u8" } }"#,
+        );
+        let b = build(
+            r#"{ "y": { "value": 2, "type": "u8" }, "x": { "value": 1, "type": "u8" },
+                 "hash": { "auto": "values_hash", "type": "u32" } }"#,
+        );
+        let digest = Sha256::digest(br#"{"x":1,"y":2}"#);
+        assert_eq!(a[..4], digest[..4]);
+        assert_eq!(b[4..], digest[..4]);
+    }
+
+    #[test]
+    fn repeat_expands_branch_per_index() {
+        let block: Block = serde_json::from_str(
+            r#"{
+                "header": { "start_address": 0, "length": 64 },
+                "data": {
+                    "chan": {
+                        "repeat": 2,
+                        "offset": { "name": "Chan{i}_Offset", "type": "i16" },
+                        "gain": { "value": 1, "type": "u8" }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            block.leaf_paths().unwrap(),
+            [
+                "chan.0.offset",
+                "chan.0.gain",
+                "chan.1.offset",
+                "chan.1.gain"
+            ]
+        );
+        let Entry::Branch(root) = &block.data else {
+            panic!("expected branch");
+        };
+        let Entry::Branch(chan) = &root["chan"] else {
+            panic!("expected branch");
+        };
+        let Entry::Branch(second) = &chan["1"] else {
+            panic!("expected branch");
+        };
+        let Entry::Leaf(offset) = &second["offset"] else {
+            panic!("expected leaf");
+        };
+        assert!(matches!(&offset.source, EntrySource::Name(n) if n == "Chan1_Offset"));
+    }
+}
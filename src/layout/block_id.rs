@@ -0,0 +1,86 @@
+use serde::Deserialize;
+use twox_hash::{XxHash32, XxHash64};
+
+/// `auto = { block_id = { ... } }`: a hash identifying the block and the
+/// version stack it was built from.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlockIdSpec {
+    #[serde(default)]
+    pub hash: IdHash,
+    /// Prepended to the hashed text, so unrelated projects get distinct IDs.
+    #[serde(default)]
+    pub salt: String,
+}
+
+/// Hash function for block identifiers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdHash {
+    #[default]
+    Fnv1a,
+    Xxhash,
+}
+
+impl BlockIdSpec {
+    /// Hashes `{salt}{block}@{V1/V2/...}` to `bits` (8 to 64) bits. Widths up
+    /// to 32 use the 32-bit hash, keeping its low bits.
+    pub fn compute(&self, block: &str, versions: &[String], bits: u32) -> u64 {
+        let text = format!("{}{}@{}", self.salt, block, versions.join("/"));
+        let bytes = text.as_bytes();
+        let hash = match (self.hash, bits > 32) {
+            (IdHash::Fnv1a, false) => u64::from(fnv1a_32(bytes)),
+            (IdHash::Fnv1a, true) => fnv1a_64(bytes),
+            (IdHash::Xxhash, false) => u64::from(XxHash32::oneshot(0, bytes)),
+            (IdHash::Xxhash, true) => XxHash64::oneshot(0, bytes),
+        };
+        match bits {
+            64 => hash,
+            _ => hash & ((1u64 << bits) - 1),
+        }
+    }
+}
+
+fn fnv1a_32(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, b| {
+        (hash ^ u32::from(*b)).wrapping_mul(0x0100_0193)
+    })
+}
+
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv1a_matches_reference_vectors() {
+        assert_eq!(fnv1a_32(b""), 0x811c_9dc5);
+        assert_eq!(fnv1a_32(b"a"), 0xe40c_292c);
+        assert_eq!(fnv1a_64(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn ids_depend_on_block_stack_and_salt() {
+        let spec = BlockIdSpec::default();
+        let stack = |s: &str| s.split('/').map(String::from).collect::<Vec<_>>();
+        let id = spec.compute("cal", &stack("Debug/Default"), 32);
+        assert_eq!(id, u64::from(fnv1a_32(b"cal@Debug/Default")));
+        assert_ne!(id, spec.compute("cal", &stack("Default"), 32));
+        assert_ne!(id, spec.compute("app", &stack("Debug/Default"), 32));
+
+        let salted = BlockIdSpec {
+            hash: IdHash::Xxhash,
+            salt: "acme:".into(),
+        };
+        let id = salted.compute("cal", &stack("Default"), 16);
+        assert_eq!(
+            id,
+            u64::from(XxHash32::oneshot(0, b"acme:cal@Default")) & 0xFFFF
+        );
+    }
+}
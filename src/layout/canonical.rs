@@ -0,0 +1,230 @@
+use super::error::LayoutError;
+use super::span;
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Key, Table, Value};
+
+/// Order of the keys in a field's inline table; other keys keep their order after these.
+const KEY_ORDER: &[&str] = &[
+    "repeat",
+    "bits",
+    "name",
+    "names",
+    "value",
+    "file",
+    "block",
+    "type",
+    "size",
+    "SIZE",
+    "order",
+    "transpose",
+    "transform",
+    "map",
+    "require_version",
+    "bitmap",
+];
+
+/// Rewrites a TOML layout in canonical style.
+///
+/// - `key = value` with single spaces and no indentation; comments and blank lines are kept.
+/// - Inline tables are written `{ a = 1, b = 2 }`, with field keys in a fixed order.
+///   Branches keep their children in order, after keys such as `repeat`.
+/// - Single-line arrays are written `[1, 2]`; multi-line arrays keep their line breaks.
+/// - Hex integers use upper-case digits.
+///
+/// Entry order, and so the built image, is never changed.
+pub fn format_toml(filename: &str, text: &str) -> Result<String, LayoutError> {
+    let mut doc: DocumentMut = text.parse().map_err(|e: toml_edit::TomlError| {
+        let range = e.span().unwrap_or(0..0);
+        let (line, column) = span::offset_to_line_col(text, range.start);
+        span::parse_error(filename, text, line, column, range.len(), e.message())
+    })?;
+    format_table(doc.as_table_mut());
+    Ok(doc.to_string())
+}
+
+/// Formats each TOML layout file in place and returns the files that changed.
+///
+/// With `check`, files are left untouched and any that would change are an error.
+pub fn format_files(files: &[&str], check: bool) -> Result<Vec<String>, LayoutError> {
+    let mut changed = Vec::new();
+    for &file in files {
+        if !file.to_ascii_lowercase().ends_with(".toml") {
+            return Err(LayoutError::FileError(format!(
+                "{}: only TOML layouts can be formatted",
+                file
+            )));
+        }
+        let text = std::fs::read_to_string(file)
+            .map_err(|_| LayoutError::FileError(format!("failed to open file: {}", file)))?;
+        let formatted = format_toml(file, &text)?;
+        if formatted == text {
+            continue;
+        }
+        if !check {
+            std::fs::write(file, formatted)
+                .map_err(|e| LayoutError::FileError(format!("failed to write {}: {}", file, e)))?;
+        }
+        changed.push(file.to_string());
+    }
+    if check && !changed.is_empty() {
+        return Err(LayoutError::NotFormatted(changed));
+    }
+    Ok(changed)
+}
+
+fn format_table(table: &mut Table) {
+    let dotted = table.is_dotted();
+    for (mut key, item) in table.iter_mut() {
+        let is_value = item.is_value();
+        let prefix = if dotted {
+            String::new()
+        } else {
+            leading_lines(key.leaf_decor().prefix().and_then(|p| p.as_str()))
+        };
+        key.leaf_decor_mut().set_prefix(prefix);
+        key.leaf_decor_mut()
+            .set_suffix(if is_value { " " } else { "" });
+        key.dotted_decor_mut().clear();
+        format_item(item);
+    }
+}
+
+fn format_item(item: &mut Item) {
+    match item {
+        Item::Table(table) => format_table(table),
+        Item::ArrayOfTables(tables) => tables.iter_mut().for_each(format_table),
+        Item::Value(value) => {
+            format_value(value);
+            let comment = value
+                .decor()
+                .suffix()
+                .and_then(|s| s.as_str())
+                .filter(|s| s.contains('#'))
+                .map(str::to_string);
+            value.decor_mut().set_prefix(" ");
+            value.decor_mut().set_suffix(comment.unwrap_or_default());
+        }
+        Item::None => {}
+    }
+}
+
+fn format_value(value: &mut Value) {
+    match value {
+        Value::InlineTable(table) => format_inline_table(table),
+        Value::Array(array) => format_array(array),
+        Value::Integer(int) => {
+            let repr = int.display_repr();
+            if repr.starts_with("0x") && repr.chars().any(|c| c.is_ascii_lowercase() && c != 'x') {
+                let upper = format!("0x{}", repr[2..].to_ascii_uppercase());
+                if let Ok(mut hex) = upper.parse::<Value>() {
+                    *hex.decor_mut() = int.decor().clone();
+                    *value = hex;
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn format_inline_table(table: &mut InlineTable) {
+    let branch = table.iter().any(|(_, v)| v.is_inline_table());
+    table.sort_values_by(|k1, v1, k2, v2| {
+        if branch {
+            // Children are entries whose order is the layout; only lift plain keys.
+            v1.is_inline_table().cmp(&v2.is_inline_table())
+        } else {
+            key_rank(k1).cmp(&key_rank(k2))
+        }
+    });
+    for (_, value) in table.iter_mut() {
+        format_value(value);
+    }
+    table.fmt();
+}
+
+fn key_rank(key: &Key) -> usize {
+    KEY_ORDER
+        .iter()
+        .position(|k| *k == key.get())
+        .unwrap_or(KEY_ORDER.len())
+}
+
+fn format_array(array: &mut Array) {
+    for value in array.iter_mut() {
+        format_value(value);
+    }
+    let multiline = array
+        .iter()
+        .any(|v| decor_has_newline(v.decor().prefix()) || decor_has_newline(v.decor().suffix()))
+        || array.trailing().as_str().is_some_and(|s| s.contains('\n'));
+    if !multiline {
+        array.fmt();
+        array.set_trailing("");
+        array.set_trailing_comma(false);
+    }
+}
+
+fn decor_has_newline(raw: Option<&toml_edit::RawString>) -> bool {
+    raw.and_then(|r| r.as_str())
+        .is_some_and(|s| s.contains('\n'))
+}
+
+/// Keeps the comment and blank lines before a key, dropping its indentation.
+fn leading_lines(prefix: Option<&str>) -> String {
+    match prefix.and_then(|p| p.rfind('\n').map(|i| &p[..=i])) {
+        Some(lines) => lines
+            .lines()
+            .map(str::trim)
+            .map(|l| format!("{}\n", l))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSY: &str = r#"[settings]
+  endianness="little"   # target
+
+[cal.header]
+start_address   =   0x8b000
+
+[cal.data]
+# identity
+id = {type="u8",value=7}
+map = {size=[2,3],type="f32",name="Map"}
+ch = { repeat = 2, off = {type="i16", name="Off{i}"}, gain={name="G{i}",type="f32"} }
+flags = { type = "u16", bitmap = [
+    { name = "A", bits = 1 },   # first
+    { bits = 15, value = 0 },
+] }
+"#;
+
+    const CANONICAL: &str = r#"[settings]
+endianness = "little"   # target
+
+[cal.header]
+start_address = 0x8B000
+
+[cal.data]
+# identity
+id = { value = 7, type = "u8" }
+map = { name = "Map", type = "f32", size = [2, 3] }
+ch = { repeat = 2, off = { name = "Off{i}", type = "i16" }, gain = { name = "G{i}", type = "f32" } }
+flags = { type = "u16", bitmap = [
+    { bits = 1, name = "A" },   # first
+    { bits = 15, value = 0 },
+] }
+"#;
+
+    #[test]
+    fn formats_to_canonical_style() {
+        assert_eq!(format_toml("cal.toml", MESSY).unwrap(), CANONICAL);
+    }
+
+    #[test]
+    fn canonical_style_is_stable() {
+        assert_eq!(format_toml("cal.toml", CANONICAL).unwrap(), CANONICAL);
+    }
+}
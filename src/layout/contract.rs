@@ -0,0 +1,210 @@
+use super::block::{Block, Entry, PROLOGUE};
+use super::entry::{
+    BitmapFieldSource, EntrySource, LeafEntry, NamesSource, ScalarType, SizeSource,
+};
+use indexmap::IndexMap;
+use serde_json::{Value, json};
+
+/// JSON Schema of the object a JSON, HTTP, or Postgres data source must return
+/// for `blocks`, given as (label, block) pairs.
+///
+/// Each data source name becomes a property with the JSON type mint reads it as;
+/// `names` globs become `patternProperties`. `x-mint-fields` lists the
+/// `block.field` paths that read each name.
+pub fn data_contract<'a>(blocks: impl IntoIterator<Item = (&'a str, &'a Block)>) -> Value {
+    let mut contract = Contract::default();
+    for (label, block) in blocks {
+        if let Some(prologue) = &block.prologue {
+            contract.walk(prologue, &format!("{}.{}", label, PROLOGUE));
+        }
+        contract.walk(&block.data, label);
+    }
+
+    let mut schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "mint data contract",
+        "type": "object",
+    });
+    let required: Vec<&String> = contract.properties.keys().collect();
+    schema["required"] = json!(required);
+    schema["properties"] = properties(contract.properties);
+    if !contract.patterns.is_empty() {
+        schema["patternProperties"] = properties(contract.patterns);
+    }
+    schema
+}
+
+#[derive(Default)]
+struct Contract {
+    /// Schema and reading fields per name, in layout order.
+    properties: IndexMap<String, (Value, Vec<String>)>,
+    /// The same per `names` glob, keyed by the equivalent regex.
+    patterns: IndexMap<String, (Value, Vec<String>)>,
+}
+
+impl Contract {
+    fn walk(&mut self, entry: &Entry, path: &str) {
+        match entry {
+            Entry::Leaf(leaf) => self.leaf(leaf, path),
+            Entry::Branch(branch) => {
+                for (key, entry) in branch {
+                    self.walk(entry, &format!("{}.{}", path, key));
+                }
+            }
+        }
+    }
+
+    fn leaf(&mut self, leaf: &LeafEntry, path: &str) {
+        let item = match leaf.map_table() {
+            Some(_) => json!({ "type": "string" }),
+            None => scalar_schema(leaf.scalar_type),
+        };
+        if let Some(table) = leaf.map_table() {
+            let schema = json!({ "type": "object", "additionalProperties": { "type": "number" } });
+            add(&mut self.properties, table, schema, path);
+        }
+        match &leaf.source {
+            EntrySource::Name(name) => {
+                let schema = match leaf.source_size() {
+                    (None, _) => item,
+                    (Some(SizeSource::OneD(len)), strict) => {
+                        let array = array_schema(item, len, strict);
+                        match leaf.scalar_type {
+                            ScalarType::U8 if leaf.map_table().is_none() => {
+                                json!({ "oneOf": [{ "type": "string", "maxLength": len }, array] })
+                            }
+                            _ => array,
+                        }
+                    }
+                    (Some(SizeSource::TwoD([rows, cols])), strict) => {
+                        array_schema(array_schema(item, cols, strict), rows, strict)
+                    }
+                };
+                add(&mut self.properties, name, schema, path);
+            }
+            EntrySource::Names(NamesSource::List(names)) => {
+                for name in names {
+                    add(&mut self.properties, name, item.clone(), path);
+                }
+            }
+            EntrySource::Names(NamesSource::Pattern(glob)) => {
+                add(&mut self.patterns, &glob_regex(glob), item, path);
+            }
+            EntrySource::Bitmap(fields) => {
+                for field in fields {
+                    if let BitmapFieldSource::Name(name) = &field.source {
+                        add(
+                            &mut self.properties,
+                            name,
+                            json!({ "type": "integer" }),
+                            path,
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Records that `field` reads `name`; the first schema seen for a name wins.
+fn add(map: &mut IndexMap<String, (Value, Vec<String>)>, name: &str, schema: Value, field: &str) {
+    let (_, fields) = map.entry(name.to_string()).or_insert((schema, Vec::new()));
+    if !fields.iter().any(|f| f == field) {
+        fields.push(field.to_string());
+    }
+}
+
+fn properties(map: IndexMap<String, (Value, Vec<String>)>) -> Value {
+    map.into_iter()
+        .map(|(name, (mut schema, fields))| {
+            schema["x-mint-fields"] = json!(fields);
+            (name, schema)
+        })
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+fn scalar_schema(scalar_type: ScalarType) -> Value {
+    match scalar_type.is_integer() {
+        true => json!({ "type": "integer" }),
+        false => json!({ "type": "number" }),
+    }
+}
+
+/// Array of up to `len` items; exactly `len` for `SIZE`.
+fn array_schema(items: Value, len: usize, strict: bool) -> Value {
+    let mut schema = json!({ "type": "array", "items": items, "maxItems": len });
+    if strict {
+        schema["minItems"] = json!(len);
+    }
+    schema
+}
+
+/// Anchored regex matching what `glob_match` matches.
+fn glob_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c if "\\.+()[]{}|^$".contains(c) => {
+                regex.push('\\');
+                regex.push(c);
+            }
+            c => regex.push(c),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::block::Config;
+
+    #[test]
+    fn describes_each_name_with_its_json_type() {
+        let config: Config = toml::from_str(
+            r##"
+[settings]
+endianness = "little"
+
+[cal.header]
+start_address = 0
+length = 0x100
+
+[cal.data]
+gain = { name = "Gain", type = "f32" }
+label = { name = "Label", type = "u8", size = 8 }
+map = { name = "Map", type = "i16", SIZE = [2, 3] }
+chan = { names = "Chan_?", type = "u16" }
+flags = { type = "u8", bitmap = [{ bits = 4, name = "Mode" }, { bits = 4, value = 0 }] }
+again = { name = "Gain", type = "f32" }
+state = { name = "State", type = "u8", map = "#States" }
+"##,
+        )
+        .unwrap();
+        let schema = data_contract([("cal", &config.blocks["cal"])]);
+
+        assert_eq!(
+            schema["required"],
+            json!(["Gain", "Label", "Map", "Mode", "States", "State"])
+        );
+        let gain = &schema["properties"]["Gain"];
+        assert_eq!(gain["type"], "number");
+        assert_eq!(gain["x-mint-fields"], json!(["cal.gain", "cal.again"]));
+        assert_eq!(
+            schema["properties"]["Label"]["oneOf"][0],
+            json!({ "type": "string", "maxLength": 8 })
+        );
+        let map = &schema["properties"]["Map"];
+        assert_eq!((&map["maxItems"], &map["minItems"]), (&json!(2), &json!(2)));
+        assert_eq!(map["items"]["maxItems"], 3);
+        assert_eq!(schema["properties"]["Mode"]["type"], "integer");
+        assert_eq!(schema["patternProperties"]["^Chan_.$"]["type"], "integer");
+        assert_eq!(schema["properties"]["State"]["type"], "string");
+        assert_eq!(schema["properties"]["States"]["type"], "object");
+    }
+}
@@ -1,7 +1,10 @@
 use super::entry::ScalarType;
 use super::error::LayoutError;
+use super::policy::{ConversionIssue, NonFinite};
 use super::settings::{EndianBytes, Endianness};
 use super::value::DataValue;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
 
 macro_rules! impl_try_from_data_value {
     ($($t:ty),* $(,)?) => {$(
@@ -38,22 +41,40 @@ macro_rules! err {
     };
 }
 
+macro_rules! lossy {
+    ($msg:expr) => {
+        LayoutError::Conversion {
+            issue: ConversionIssue::LossyCast,
+            message: $msg.to_string(),
+        }
+    };
+}
+
+macro_rules! range {
+    ($msg:expr) => {
+        LayoutError::Conversion {
+            issue: ConversionIssue::OutOfRange,
+            message: $msg.to_string(),
+        }
+    };
+}
+
 macro_rules! impl_try_from_strict_unsigned {
     ($($t:ty),* $(,)?) => {$(
         impl TryFromStrict<&DataValue> for $t {
             fn try_from_strict(value: &DataValue) -> Result<Self, LayoutError> {
                 match value {
                     DataValue::U64(v) => <Self as TryFrom<u64>>::try_from(*v)
-                        .map_err(|_| err!(format!("u64 value {} out of range for {}", v, stringify!($t)))),
+                        .map_err(|_| range!(format!("u64 value {} out of range for {}", v, stringify!($t)))),
                     DataValue::I64(v) => {
-                        if *v < 0 { return Err(err!("negative integer cannot convert to unsigned in strict mode")); }
+                        if *v < 0 { return Err(range!("negative integer cannot convert to unsigned in strict mode")); }
                         <Self as TryFrom<u64>>::try_from(*v as u64)
-                            .map_err(|_| err!(format!("i64 value {} out of range for {}", v, stringify!($t))))
+                            .map_err(|_| range!(format!("i64 value {} out of range for {}", v, stringify!($t))))
                     }
                     DataValue::F64(v) => {
-                        if !v.is_finite() { return Err(err!("non-finite float cannot convert to integer in strict mode")); }
-                        if v.fract() != 0.0 { return Err(err!("float to integer conversion not allowed unless value is an exact integer")); }
-                        if *v < 0.0 || *v > (<$t>::MAX as f64) { return Err(err!(format!("float value {} out of range for {}", v, stringify!($t)))); }
+                        if !v.is_finite() { return Err(range!("non-finite float cannot convert to integer in strict mode")); }
+                        if v.fract() != 0.0 { return Err(lossy!("float to integer conversion not allowed unless value is an exact integer")); }
+                        if *v < 0.0 || *v > (<$t>::MAX as f64) { return Err(range!(format!("float value {} out of range for {}", v, stringify!($t)))); }
                         Ok(*v as $t)
                     }
                     DataValue::Bool(b) => {
@@ -74,14 +95,14 @@ macro_rules! impl_try_from_strict_signed {
                 match value {
                     DataValue::U64(v) => {
                         <Self as TryFrom<i128>>::try_from(*v as i128)
-                            .map_err(|_| err!(format!("u64 value {} out of range for {}", v, stringify!($t))))
+                            .map_err(|_| range!(format!("u64 value {} out of range for {}", v, stringify!($t))))
                     }
                     DataValue::I64(v) => <Self as TryFrom<i64>>::try_from(*v)
-                        .map_err(|_| err!(format!("i64 value {} out of range for {}", v, stringify!($t)))),
+                        .map_err(|_| range!(format!("i64 value {} out of range for {}", v, stringify!($t)))),
                     DataValue::F64(v) => {
-                        if !v.is_finite() { return Err(err!("non-finite float cannot convert to integer in strict mode")); }
-                        if v.fract() != 0.0 { return Err(err!("float to integer conversion not allowed unless value is an exact integer")); }
-                        if *v < (<$t>::MIN as f64) || *v > (<$t>::MAX as f64) { return Err(err!(format!("float value {} out of range for {}", v, stringify!($t)))); }
+                        if !v.is_finite() { return Err(range!("non-finite float cannot convert to integer in strict mode")); }
+                        if v.fract() != 0.0 { return Err(lossy!("float to integer conversion not allowed unless value is an exact integer")); }
+                        if *v < (<$t>::MIN as f64) || *v > (<$t>::MAX as f64) { return Err(range!(format!("float value {} out of range for {}", v, stringify!($t)))); }
                         Ok(*v as $t)
                     }
                     DataValue::Bool(b) => {
@@ -102,13 +123,13 @@ macro_rules! impl_try_from_strict_float_targets {
                 match value {
                     DataValue::F64(v) => {
                         if !v.is_finite() {
-                            return Err(err!("non-finite float not allowed in strict mode"));
+                            return Err(range!("non-finite float not allowed in strict mode"));
                         }
                         let out = *v as $t;
                         if out.is_finite() {
                             Ok(out)
                         } else {
-                            Err(err!(format!(
+                            Err(range!(format!(
                                 "float value {} out of range for {}",
                                 v,
                                 stringify!($t)
@@ -118,13 +139,13 @@ macro_rules! impl_try_from_strict_float_targets {
                     DataValue::U64(v) => {
                         let out = (*v as $t);
                         if !out.is_finite() {
-                            return Err(err!("integer to float produced non-finite value"));
+                            return Err(range!("integer to float produced non-finite value"));
                         }
                         // exactness check via round-trip
                         if (out as u64) == *v {
                             Ok(out)
                         } else {
-                            Err(err!(
+                            Err(lossy!(
                                 "lossy integer to float conversion not allowed in strict mode"
                             ))
                         }
@@ -132,12 +153,12 @@ macro_rules! impl_try_from_strict_float_targets {
                     DataValue::I64(v) => {
                         let out = (*v as $t);
                         if !out.is_finite() {
-                            return Err(err!("integer to float produced non-finite value"));
+                            return Err(range!("integer to float produced non-finite value"));
                         }
                         if (out as i64) == *v {
                             Ok(out)
                         } else {
-                            Err(err!(
+                            Err(lossy!(
                                 "lossy integer to float conversion not allowed in strict mode"
                             ))
                         }
@@ -165,7 +186,7 @@ impl TryFromStrict<&DataValue> for f64 {
                 if (out as u64) == *v {
                     Ok(out)
                 } else {
-                    Err(err!(
+                    Err(lossy!(
                         "lossy integer to float conversion not allowed in strict mode"
                     ))
                 }
@@ -175,7 +196,7 @@ impl TryFromStrict<&DataValue> for f64 {
                 if (out as i64) == *v {
                     Ok(out)
                 } else {
-                    Err(err!(
+                    Err(lossy!(
                         "lossy integer to float conversion not allowed in strict mode"
                     ))
                 }
@@ -255,3 +276,172 @@ pub fn convert_value_to_bytes(
         ScalarType::F64 => to_bytes!(f64),
     }
 }
+
+/// Encodes a NaN or infinite float according to the non-finite policy.
+pub fn encode_non_finite(
+    value: f64,
+    mode: NonFinite,
+    scalar_type: ScalarType,
+    endianness: &Endianness,
+) -> Result<Vec<u8>, LayoutError> {
+    let width = scalar_type.size_bytes();
+    let bits = match mode {
+        NonFinite::Error => {
+            return Err(err!(format!("non-finite float {} not allowed", value)));
+        }
+        NonFinite::Raw => match width {
+            8 => value.to_bits(),
+            4 => (value as f32).to_bits() as u64,
+            _ => {
+                return Err(err!(format!(
+                    "non-finite float cannot be stored raw in a {}-byte type",
+                    width
+                )));
+            }
+        },
+        NonFinite::Sentinel(bits) => bits,
+    };
+    if width < 8 && bits >> (width * 8) != 0 {
+        return Err(err!(format!(
+            "non-finite sentinel 0x{:X} does not fit in {} bytes",
+            bits, width
+        )));
+    }
+    Ok(match endianness {
+        Endianness::Little => bits.to_le_bytes()[..width].to_vec(),
+        Endianness::Big => bits.to_be_bytes()[8 - width..].to_vec(),
+    })
+}
+
+/// Parses a hex byte string such as `"DE AD BE EF"` or `"0xDEADBEEF"`.
+/// Whitespace, commas, and a leading `0x` are ignored.
+pub fn parse_hex_bytes(text: &str) -> Result<Vec<u8>, LayoutError> {
+    let trimmed = text.trim();
+    let body = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+    let digits: Vec<u8> = body
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace() && *b != b',')
+        .collect();
+
+    if !digits.len().is_multiple_of(2) {
+        return Err(LayoutError::DataValueExportFailed(format!(
+            "Hex string '{}' has an odd number of digits.",
+            text
+        )));
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|s| u8::from_str_radix(s, 16).ok())
+                .ok_or_else(|| {
+                    LayoutError::DataValueExportFailed(format!(
+                        "Invalid hex digits '{}' in '{}'.",
+                        String::from_utf8_lossy(pair),
+                        text
+                    ))
+                })
+        })
+        .collect()
+}
+
+/// Decodes a `0x`-prefixed string of hex digits into bytes; `None` for any other string.
+pub fn decode_prefixed_hex(text: &str) -> Option<Vec<u8>> {
+    let body = text
+        .strip_prefix("0x")
+        .or_else(|| text.strip_prefix("0X"))?;
+    if body.is_empty()
+        || !body
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() || c.is_ascii_whitespace())
+    {
+        return None;
+    }
+    parse_hex_bytes(body).ok()
+}
+
+/// Decodes a string holding raw bytes: Postgres `bytea` hex output (`\x0102`),
+/// `base64:` followed by standard base64, or with `prefixed_hex` also `0x` hex
+/// digits. `None` for any other string, which is then text.
+pub fn decode_byte_string(text: &str, prefixed_hex: bool) -> Result<Option<Vec<u8>>, LayoutError> {
+    if let Some(body) = text.strip_prefix("\\x") {
+        return parse_hex_bytes(body).map(Some);
+    }
+    if let Some(body) = text.strip_prefix("base64:") {
+        return STANDARD.decode(body.trim()).map(Some).map_err(|e| {
+            LayoutError::DataValueExportFailed(format!("Invalid base64 in '{}': {}.", text, e))
+        });
+    }
+    Ok(decode_prefixed_hex(text).filter(|_| prefixed_hex))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hex_bytes_accepts_separators_and_prefix() {
+        assert_eq!(
+            parse_hex_bytes("DE AD, be ef").unwrap(),
+            [0xDE, 0xAD, 0xBE, 0xEF]
+        );
+        assert_eq!(parse_hex_bytes("0x0102").unwrap(), [1, 2]);
+        assert!(parse_hex_bytes("ABC").is_err());
+        assert!(parse_hex_bytes("GG").is_err());
+    }
+
+    #[test]
+    fn encode_non_finite_modes() {
+        let le = &Endianness::Little;
+        assert!(encode_non_finite(f64::NAN, NonFinite::Error, ScalarType::F32, le).is_err());
+        assert_eq!(
+            encode_non_finite(f64::INFINITY, NonFinite::Raw, ScalarType::F32, le).unwrap(),
+            f32::INFINITY.to_le_bytes()
+        );
+        assert!(encode_non_finite(f64::NAN, NonFinite::Raw, ScalarType::U16, le).is_err());
+        assert_eq!(
+            encode_non_finite(
+                f64::NAN,
+                NonFinite::Sentinel(0xBEEF),
+                ScalarType::U16,
+                &Endianness::Big
+            )
+            .unwrap(),
+            [0xBE, 0xEF]
+        );
+        assert!(
+            encode_non_finite(f64::NAN, NonFinite::Sentinel(0x100), ScalarType::U8, le).is_err()
+        );
+    }
+
+    #[test]
+    fn decode_byte_string_reads_bytea_and_base64() {
+        assert_eq!(
+            decode_byte_string("\\xcafe", false).unwrap(),
+            Some(vec![0xCA, 0xFE])
+        );
+        assert_eq!(
+            decode_byte_string("base64:yv4=", false).unwrap(),
+            Some(vec![0xCA, 0xFE])
+        );
+        assert_eq!(decode_byte_string("0xCAFE", false).unwrap(), None);
+        assert_eq!(
+            decode_byte_string("0xCAFE", true).unwrap(),
+            Some(vec![0xCA, 0xFE])
+        );
+        assert_eq!(decode_byte_string("device", true).unwrap(), None);
+        assert!(decode_byte_string("base64:yv4", false).is_err());
+        assert!(decode_byte_string("\\xcaf", false).is_err());
+    }
+
+    #[test]
+    fn decode_prefixed_hex_ignores_plain_strings() {
+        assert_eq!(decode_prefixed_hex("0xCAFE"), Some(vec![0xCA, 0xFE]));
+        assert_eq!(decode_prefixed_hex("0xCAFE device"), None);
+        assert_eq!(decode_prefixed_hex("CAFE"), None);
+    }
+}
@@ -0,0 +1,197 @@
+use super::error::LayoutError;
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Layout file format for `--convert-to`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LayoutFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+/// Reads a layout file and returns its contents in `to` format.
+///
+/// The document is converted as written: key order is kept, and environment
+/// references are carried over unexpanded. Hex and other literal styles are not
+/// preserved, since YAML and JSON have no equivalent.
+pub fn convert_layout(filename: &str, to: LayoutFormat) -> Result<String, LayoutError> {
+    let text = std::fs::read_to_string(filename)
+        .map_err(|_| LayoutError::FileError(format!("failed to open file: {}", filename)))?;
+    let ext = Path::new(filename)
+        .extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_ascii_lowercase())
+        .unwrap_or_default();
+    if ext == "j2" {
+        return Err(LayoutError::FileError(format!(
+            "{}: templates cannot be converted; render them first",
+            filename
+        )));
+    }
+    // References are carried over unexpanded.
+    let keep = |text: &str| Ok(text.to_string());
+    let document: Value = super::parse_text(filename, &text, &ext, &keep)?;
+    let Value::Object(root) = &document else {
+        return Err(LayoutError::FileError(format!(
+            "{}: layout must be a table at the top level",
+            filename
+        )));
+    };
+
+    match to {
+        LayoutFormat::Json => serde_json::to_string_pretty(&document)
+            .map(|s| s + "\n")
+            .map_err(|e| LayoutError::FileError(e.to_string())),
+        LayoutFormat::Yaml => {
+            serde_yaml::to_string(&document).map_err(|e| LayoutError::FileError(e.to_string()))
+        }
+        LayoutFormat::Toml => to_toml(root),
+    }
+}
+
+/// Writes a document in the layout style: one `[section]` per block part, with
+/// entries as dotted keys so their order is kept exactly.
+fn to_toml(root: &Map<String, Value>) -> Result<String, LayoutError> {
+    let mut out = String::new();
+    for (key, value) in root {
+        if !value.is_object() {
+            write_entry(&mut out, &[key], value)?;
+        }
+    }
+    for (key, value) in root {
+        if let Value::Object(table) = value {
+            write_section(&mut out, &[key], table)?;
+        }
+    }
+    Ok(out)
+}
+
+/// Tables whose values are all tables are split into subsections, down to
+/// `[block.data]`; anything deeper is written as dotted keys.
+fn write_section(
+    out: &mut String,
+    path: &[&String],
+    table: &Map<String, Value>,
+) -> Result<(), LayoutError> {
+    if path.len() < 2 && !table.is_empty() && table.values().all(Value::is_object) {
+        for (key, value) in table {
+            let mut child = path.to_vec();
+            child.push(key);
+            if let Value::Object(sub) = value {
+                write_section(out, &child, sub)?;
+            }
+        }
+        return Ok(());
+    }
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    let header: Vec<String> = path.iter().map(|k| toml_key(k)).collect();
+    let _ = writeln!(out, "[{}]", header.join("."));
+    for (key, value) in table {
+        write_entry(out, &[key], value)?;
+    }
+    Ok(())
+}
+
+/// Writes `value` under the dotted key `path`, splitting tables that contain
+/// other tables into one line per entry.
+fn write_entry(out: &mut String, path: &[&String], value: &Value) -> Result<(), LayoutError> {
+    match value {
+        Value::Object(table) if !table.is_empty() && table.values().any(Value::is_object) => {
+            for (key, child) in table {
+                let mut child_path = path.to_vec();
+                child_path.push(key);
+                write_entry(out, &child_path, child)?;
+            }
+        }
+        _ => {
+            let key: Vec<String> = path.iter().map(|k| toml_key(k)).collect();
+            let _ = writeln!(out, "{} = {}", key.join("."), inline(value)?);
+        }
+    }
+    Ok(())
+}
+
+fn inline(value: &Value) -> Result<String, LayoutError> {
+    Ok(match value {
+        Value::Null => {
+            return Err(LayoutError::FileError(
+                "null values cannot be written as TOML".to_string(),
+            ));
+        }
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => i.to_string(),
+            (None, Some(f)) if n.is_f64() => toml::Value::Float(f).to_string(),
+            _ => n.to_string(),
+        },
+        Value::String(s) => toml::Value::String(s.clone()).to_string(),
+        Value::Array(items) => {
+            let items: Result<Vec<String>, LayoutError> = items.iter().map(inline).collect();
+            format!("[{}]", items?.join(", "))
+        }
+        Value::Object(table) if table.is_empty() => "{}".to_string(),
+        Value::Object(table) => {
+            let entries: Result<Vec<String>, LayoutError> = table
+                .iter()
+                .map(|(k, v)| Ok(format!("{} = {}", toml_key(k), inline(v)?)))
+                .collect();
+            format!("{{ {} }}", entries?.join(", "))
+        }
+    })
+}
+
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        toml::Value::String(key.to_string()).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_keeps_entry_order_with_dotted_keys() {
+        let doc: Value = serde_json::from_str(
+            r#"{"settings": {"endianness": "little", "crc": {"polynomial": 79764919}},
+                "b": {"header": {"start_address": 4096, "length": 16},
+                      "data": {"z": {"value": 1.0, "type": "f32"},
+                               "ch": {"repeat": 2, "g": {"name": "G{i}", "type": "u8"}},
+                               "a b": {"value": [1, 2], "type": "u8", "size": 2}}}}"#,
+        )
+        .unwrap();
+        let Value::Object(root) = &doc else {
+            unreachable!()
+        };
+        let text = to_toml(root).unwrap();
+        assert_eq!(
+            text,
+            "[settings]\n\
+             endianness = \"little\"\n\
+             crc = { polynomial = 79764919 }\n\
+             \n\
+             [b.header]\n\
+             start_address = 4096\n\
+             length = 16\n\
+             \n\
+             [b.data]\n\
+             z = { value = 1.0, type = \"f32\" }\n\
+             ch.repeat = 2\n\
+             ch.g = { name = \"G{i}\", type = \"u8\" }\n\
+             \"a b\" = { value = [1, 2], type = \"u8\", size = 2 }\n"
+        );
+        let back: Value = toml::from_str(&text).unwrap();
+        assert_eq!(back, doc);
+    }
+}
@@ -1,27 +1,93 @@
-use super::block::BuildConfig;
-use super::conversions::clamp_bitfield_value;
+use super::block::{BuildConfig, Constants};
+use super::block_id::BlockIdSpec;
+use super::conversions::{
+    clamp_bitfield_value, decode_byte_string, encode_non_finite, parse_hex_bytes,
+};
 use super::error::LayoutError;
+use super::pattern::{glob_match, natural_cmp};
+use super::policy::ConversionIssue;
+use super::transform::{Transform, apply_chain};
 use super::used_values::{
     ValueSink, array_2d_to_json, array_to_json, data_value_to_json, i128_to_json,
 };
 use super::value::{DataValue, ValueSource};
-use crate::data::DataSource;
+use crate::data::args::DecimalSeparator;
+use crate::data::{DataSource, read_csv};
 use serde::Deserialize;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Keys accepted on leaf entries; kept in step with `LeafEntry` by a test.
+pub(super) const LEAF_KEYS: &[&str] = &[
+    "type",
+    "name",
+    "names",
+    "value",
+    "size",
+    "SIZE",
+    "order",
+    "transpose",
+    "transform",
+    "map",
+    "require_version",
+    "bytes",
+    "bitmap",
+    "hex",
+    "file",
+    "csv",
+    "block",
+    "auto",
+];
+
+/// Leaf keys that select the value source; exactly one is required.
+pub(super) const SOURCE_KEYS: &[&str] = &[
+    "name", "names", "value", "bitmap", "hex", "file", "csv", "block", "auto",
+];
 
 /// Leaf entry representing an item to add to the flash block.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct LeafEntry {
     #[serde(rename = "type")]
     pub scalar_type: ScalarType,
     #[serde(flatten, default)]
     size_keys: SizeKeys,
+    /// Emission order of 2D arrays.
+    #[serde(default)]
+    order: Option<ArrayOrder>,
+    /// Swap rows and columns of 2D source data before emission.
+    #[serde(default)]
+    transpose: bool,
+    /// Steps applied to each numeric value before conversion.
+    #[serde(default)]
+    transform: Vec<Transform>,
+    /// Data source table (`#Table`) translating string values into numbers.
+    #[serde(default)]
+    map: Option<String>,
+    /// Version whose column must provide the value, rather than a fallback.
+    #[serde(default)]
+    require_version: Option<String>,
+    /// Decode `0x` hex strings from the data source as bytes rather than text.
+    #[serde(default)]
+    bytes: bool,
     #[serde(flatten)]
     pub source: EntrySource,
 }
 
+/// Memory order of 2D array elements.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArrayOrder {
+    /// Each row is contiguous.
+    #[default]
+    Row,
+    /// Each column is contiguous.
+    Column,
+}
+
 /// Scalar type enum derived from 'type' string in leaf entries.
-#[derive(Debug, Clone, Copy, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
 pub enum ScalarType {
     #[serde(rename = "u8")]
     U8,
@@ -54,7 +120,7 @@ pub enum SizeSource {
 }
 
 /// Helper struct to capture both 'size' and 'SIZE' keys.
-#[derive(Debug, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 struct SizeKeys {
     #[serde(rename = "size")]
     size: Option<SizeSource>,
@@ -76,18 +142,128 @@ impl SizeKeys {
 }
 
 /// Mutually exclusive source enum.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub enum EntrySource {
     #[serde(rename = "name")]
     Name(String),
+    #[serde(rename = "names")]
+    Names(NamesSource),
     #[serde(rename = "value")]
     Value(ValueSource),
     #[serde(rename = "bitmap")]
     Bitmap(Vec<BitmapField>),
+    #[serde(rename = "hex")]
+    Hex(String),
+    #[serde(rename = "file")]
+    File(PathBuf),
+    #[serde(rename = "csv")]
+    Csv(CsvSource),
+    #[serde(rename = "block")]
+    Block(String),
+    #[serde(rename = "auto")]
+    Auto(AutoSource),
+}
+
+/// CSV file of numbers for a 1D or 2D array: a path, or a table with the
+/// path and the decimal separator of its cells.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "CsvSpec")]
+pub struct CsvSource {
+    pub path: PathBuf,
+    pub decimal_separator: DecimalSeparator,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CsvSpec {
+    Path(PathBuf),
+    Table {
+        path: PathBuf,
+        #[serde(default)]
+        decimal_separator: DecimalSeparator,
+    },
+}
+
+impl From<CsvSpec> for CsvSource {
+    fn from(spec: CsvSpec) -> Self {
+        match spec {
+            CsvSpec::Path(path) => Self {
+                path,
+                decimal_separator: DecimalSeparator::default(),
+            },
+            CsvSpec::Table {
+                path,
+                decimal_separator,
+            } => Self {
+                path,
+                decimal_separator,
+            },
+        }
+    }
+}
+
+/// Data source names whose single values make up a 1D array.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum NamesSource {
+    /// Glob over every name in the data source, in natural order.
+    Pattern(String),
+    /// Explicit names, in the order given.
+    List(Vec<String>),
+}
+
+/// Value computed by mint from other build output.
+#[derive(Debug, Clone, Deserialize)]
+pub enum AutoSource {
+    /// CRC of another block in the same layout file.
+    #[serde(rename = "crc_of")]
+    CrcOf(String),
+    /// Hash of the values used by the rest of this block, patched in by
+    /// [`Block`](super::block::Block) once they are known.
+    #[serde(rename = "values_hash")]
+    ValuesHash,
+    /// Hash of the block name and the version stack.
+    #[serde(rename = "block_id")]
+    BlockId(BlockIdSpec),
+    /// Byte length of the data following this entry in the block.
+    #[serde(rename = "data_length")]
+    DataLength,
+    /// CRC of the data following this entry, with the block's CRC parameters.
+    #[serde(rename = "data_crc")]
+    DataCrc,
+    /// Number of fields following this entry in the block.
+    #[serde(rename = "field_count")]
+    FieldCount,
+}
+
+impl AutoSource {
+    /// Key or keyword naming this source in the layout.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AutoSource::CrcOf(_) => "crc_of",
+            AutoSource::ValuesHash => "values_hash",
+            AutoSource::BlockId(_) => "block_id",
+            AutoSource::DataLength => "data_length",
+            AutoSource::DataCrc => "data_crc",
+            AutoSource::FieldCount => "field_count",
+        }
+    }
+
+    /// Whether the value depends on the rest of the block, so it is patched
+    /// in after the block is built.
+    pub fn is_placeholder(&self) -> bool {
+        matches!(
+            self,
+            AutoSource::ValuesHash
+                | AutoSource::DataLength
+                | AutoSource::DataCrc
+                | AutoSource::FieldCount
+        )
+    }
 }
 
 /// Single bitmap field within a bitmap entry.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct BitmapField {
     pub bits: usize,
@@ -96,7 +272,7 @@ pub struct BitmapField {
 }
 
 /// Source for a bitmap field (no arrays allowed).
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub enum BitmapFieldSource {
     #[serde(rename = "name")]
     Name(String),
@@ -130,6 +306,21 @@ impl LeafEntry {
         self.scalar_type.size_bytes()
     }
 
+    /// Array dimensions in memory order for `len` emitted bytes: empty for a
+    /// scalar, `[n]` for a 1D array, `[outer, inner]` for a 2D array.
+    pub fn memory_dims(&self, len: usize) -> Vec<usize> {
+        let count = len / self.scalar_type.size_bytes();
+        match self.size_keys.resolve() {
+            Ok((Some(SizeSource::TwoD([rows, cols])), _)) => match self.order.unwrap_or_default() {
+                ArrayOrder::Row => vec![rows, cols],
+                ArrayOrder::Column => vec![cols, rows],
+            },
+            Ok((Some(SizeSource::OneD(_)), _)) => vec![count],
+            _ if count == 1 => Vec::new(),
+            _ => vec![count],
+        }
+    }
+
     pub fn emit_bytes(
         &self,
         data_source: Option<&dyn DataSource>,
@@ -137,17 +328,72 @@ impl LeafEntry {
         value_sink: &mut dyn ValueSink,
         field_path: &[String],
     ) -> Result<Vec<u8>, LayoutError> {
-        if config.word_addressing && matches!(self.scalar_type, ScalarType::U8 | ScalarType::I8) {
+        let word_size = config.word_addressing.word_size() as usize;
+        if config.word_addressing.enabled()
+            && self.scalar_type.size_bytes() < word_size
+            && !matches!(self.source, EntrySource::Block(_))
+        {
+            let bits = self.scalar_type.size_bytes() * 8;
+            return Err(LayoutError::DataValueExportFailed(format!(
+                "u{bits}/i{bits} types are not supported with word_addressing enabled ({word_size}-byte words)."
+            )));
+        }
+
+        if (self.order.is_some() || self.transpose)
+            && !matches!(self.size_keys.resolve()?.0, Some(SizeSource::TwoD(_)))
+        {
+            return Err(LayoutError::DataValueExportFailed(
+                "order/transpose require a 2D size.".into(),
+            ));
+        }
+
+        if !self.transform.is_empty()
+            && !matches!(
+                self.source,
+                EntrySource::Name(_)
+                    | EntrySource::Names(_)
+                    | EntrySource::Value(_)
+                    | EntrySource::Csv(_)
+            )
+        {
             return Err(LayoutError::DataValueExportFailed(
-                "u8/i8 types are not supported with word_addressing enabled.".into(),
+                "transform requires a name, names, value, or csv source.".into(),
             ));
         }
 
+        if let Some(version) = &self.require_version {
+            self.check_version(version, data_source)?;
+        }
+
+        if let Some(table) = &self.map {
+            return self.emit_mapped(table, data_source, config, value_sink, field_path);
+        }
+
         if let EntrySource::Bitmap(fields) = &self.source {
             self.validate_bitmap(fields)?;
             return self.emit_bitmap(fields, data_source, config, value_sink, field_path);
         }
 
+        if let EntrySource::Names(names) = &self.source {
+            return self.emit_names(names, data_source, config, value_sink, field_path);
+        }
+
+        if let EntrySource::Hex(text) = &self.source {
+            return self.emit_hex(text, config, value_sink, field_path);
+        }
+
+        if let EntrySource::File(path) = &self.source {
+            return self.emit_file(path, config, value_sink, field_path);
+        }
+
+        if let EntrySource::Block(name) = &self.source {
+            return self.emit_block(name, config, value_sink, field_path);
+        }
+
+        if let EntrySource::Auto(auto) = &self.source {
+            return self.emit_auto(auto, data_source, config, value_sink, field_path);
+        }
+
         let (size, strict_len) = self.size_keys.resolve()?;
         match size {
             None => self.emit_bytes_single(data_source, config, value_sink, field_path),
@@ -170,6 +416,47 @@ impl LeafEntry {
         }
     }
 
+    /// Fails unless `version` provides every name this leaf reads. Without a
+    /// data source the read itself fails.
+    fn check_version(
+        &self,
+        version: &str,
+        data_source: Option<&dyn DataSource>,
+    ) -> Result<(), LayoutError> {
+        if !matches!(self.source, EntrySource::Name(_) | EntrySource::Names(_)) {
+            return Err(LayoutError::DataValueExportFailed(
+                "require_version requires a name or names source.".into(),
+            ));
+        }
+        let Some(ds) = data_source else {
+            return Ok(());
+        };
+        let names = match &self.source {
+            EntrySource::Name(name) => vec![name.clone()],
+            EntrySource::Names(NamesSource::List(names)) => names.clone(),
+            EntrySource::Names(NamesSource::Pattern(pattern)) => pattern_names(ds, pattern),
+            _ => unreachable!(),
+        };
+        for name in names {
+            match ds.version_of(&name) {
+                Some(found) if found == version => {}
+                Some(found) => {
+                    return Err(LayoutError::WrongVersion(format!(
+                        "'{}' comes from '{}', not the required version '{}'",
+                        name, found, version
+                    )));
+                }
+                None => {
+                    return Err(LayoutError::WrongVersion(format!(
+                        "'{}' is not provided by the required version '{}'",
+                        name, version
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Validates bitmap entry rules.
     fn validate_bitmap(&self, fields: &[BitmapField]) -> Result<(), LayoutError> {
         if self.size_keys.size.is_some() || self.size_keys.strict_size.is_some() {
@@ -219,7 +506,13 @@ impl LeafEntry {
         let mut offset: usize = 0;
         for field in fields {
             let value = field.resolve_value(data_source)?;
-            let clamped = clamp_bitfield_value(&value, field.bits, signed, config.strict)?;
+            let clamped = match clamp_bitfield_value(&value, field.bits, signed, true) {
+                Ok(v) => v,
+                Err(e) => {
+                    apply_policy(config, e, field_path)?;
+                    clamp_bitfield_value(&value, field.bits, signed, false)?
+                }
+            };
 
             let mask = (1u128 << field.bits) - 1;
             let pattern = (clamped as u128) & mask;
@@ -235,6 +528,477 @@ impl LeafEntry {
         DataValue::U64(accumulator as u64).to_bytes(self.scalar_type, config.endianness, false)
     }
 
+    /// Emits the strings of a `name` or `value` field as the values `table` maps
+    /// them to: a single string, or a 1D array of strings with `size`.
+    fn emit_mapped(
+        &self,
+        table: &str,
+        data_source: Option<&dyn DataSource>,
+        config: &BuildConfig,
+        value_sink: &mut dyn ValueSink,
+        field_path: &[String],
+    ) -> Result<Vec<u8>, LayoutError> {
+        let table = table.strip_prefix('#').ok_or_else(|| {
+            LayoutError::DataValueExportFailed(format!(
+                "map must name a table as '#Table', got '{}'.",
+                table
+            ))
+        })?;
+        let Some(ds) = data_source else {
+            return Err(LayoutError::MissingDataSheet(format!(
+                "Field '{}' maps values through table '{}', but no data source was provided.",
+                field_path.join("."),
+                table
+            )));
+        };
+        let source = match &self.source {
+            EntrySource::Name(name) => ds.retrieve_1d_array_or_string(name)?,
+            EntrySource::Value(value) => value.clone(),
+            _ => {
+                return Err(LayoutError::DataValueExportFailed(
+                    "map requires a name or value source.".into(),
+                ));
+            }
+        };
+        let mapping = ds.retrieve_map(table)?;
+        let lookup = |value: &DataValue| match value {
+            DataValue::Str(s) => mapping.get(s.trim()).cloned().ok_or_else(|| {
+                LayoutError::DataValueExportFailed(format!(
+                    "'{}' is not in mapping table '{}'.",
+                    s, table
+                ))
+            }),
+            _ => Err(LayoutError::DataValueExportFailed(
+                "map expects string values.".into(),
+            )),
+        };
+
+        match (source, self.size_keys.resolve()?) {
+            (ValueSource::Single(value), (None, _)) => {
+                value_sink.record_value(field_path, data_value_to_json(&value)?)?;
+                self.value_to_bytes(&lookup(&value)?, config, field_path)
+            }
+            (ValueSource::Array(values), (Some(SizeSource::OneD(size)), strict_len)) => {
+                value_sink.record_value(field_path, array_to_json(&values)?)?;
+                let total_bytes = size.checked_mul(self.scalar_type.size_bytes()).ok_or(
+                    LayoutError::DataValueExportFailed("Array size overflow".into()),
+                )?;
+                let mut out = Vec::with_capacity(total_bytes);
+                for value in &values {
+                    out.extend(self.value_to_bytes(&lookup(value)?, config, field_path)?);
+                }
+                fit_to_size(out, total_bytes, strict_len, config, field_path)
+            }
+            _ => Err(LayoutError::DataValueExportFailed(
+                "map requires a single string, or a 1D array of strings with size.".into(),
+            )),
+        }
+    }
+
+    /// Emits the single values of the listed names, or of every data source
+    /// name matching a glob pattern in natural order (`Gain_2` before `Gain_10`).
+    fn emit_names(
+        &self,
+        source: &NamesSource,
+        data_source: Option<&dyn DataSource>,
+        config: &BuildConfig,
+        value_sink: &mut dyn ValueSink,
+        field_path: &[String],
+    ) -> Result<Vec<u8>, LayoutError> {
+        let Some(ds) = data_source else {
+            return Err(LayoutError::MissingDataSheet(format!(
+                "Field '{}' requires values from a data source, but none was provided.",
+                field_path.join(".")
+            )));
+        };
+        let names = match source {
+            NamesSource::List(names) => names.clone(),
+            NamesSource::Pattern(pattern) => {
+                let names = pattern_names(ds, pattern);
+                if names.is_empty() {
+                    return Err(LayoutError::DataValueExportFailed(format!(
+                        "No data source names match '{}'.",
+                        pattern
+                    )));
+                }
+                names
+            }
+        };
+
+        let values = names
+            .iter()
+            .map(|name| ds.retrieve_single_value(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        value_sink.record_value(field_path, array_to_json(&values)?)?;
+
+        let mut out = Vec::with_capacity(values.len() * self.scalar_type.size_bytes());
+        for v in &values {
+            out.extend(self.value_to_bytes(v, config, field_path)?);
+        }
+
+        match self.size_keys.resolve()? {
+            (None, _) => Ok(out),
+            (Some(SizeSource::OneD(size)), strict_len) => {
+                let total_bytes = size.checked_mul(self.scalar_type.size_bytes()).ok_or(
+                    LayoutError::DataValueExportFailed("Array size overflow".into()),
+                )?;
+                fit_to_size(out, total_bytes, strict_len, config, field_path)
+            }
+            (Some(SizeSource::TwoD(_)), _) => Err(LayoutError::DataValueExportFailed(
+                "Name patterns cannot have a 2D size.".to_string(),
+            )),
+        }
+    }
+
+    /// Emits a hex blob verbatim, padded or checked against `size`/`SIZE` if given.
+    fn emit_hex(
+        &self,
+        text: &str,
+        config: &BuildConfig,
+        value_sink: &mut dyn ValueSink,
+        field_path: &[String],
+    ) -> Result<Vec<u8>, LayoutError> {
+        if !matches!(self.scalar_type, ScalarType::U8) {
+            return Err(LayoutError::DataValueExportFailed(
+                "Hex blobs should have type u8.".to_string(),
+            ));
+        }
+        let bytes = parse_hex_bytes(text)?;
+        value_sink.record_value(field_path, Value::String(text.to_string()))?;
+
+        match self.size_keys.resolve()? {
+            (None, _) => Ok(bytes),
+            (Some(SizeSource::OneD(size)), strict_len) => {
+                fit_to_size(bytes, size, strict_len, config, field_path)
+            }
+            (Some(SizeSource::TwoD(_)), _) => Err(LayoutError::DataValueExportFailed(
+                "Hex blobs cannot have a 2D size.".to_string(),
+            )),
+        }
+    }
+
+    /// Emits the contents of a binary file. `size` pads short files and truncates
+    /// long ones (subject to the out-of-range policy); `SIZE` requires an exact fit.
+    fn emit_file(
+        &self,
+        path: &Path,
+        config: &BuildConfig,
+        value_sink: &mut dyn ValueSink,
+        field_path: &[String],
+    ) -> Result<Vec<u8>, LayoutError> {
+        if !matches!(self.scalar_type, ScalarType::U8) {
+            return Err(LayoutError::DataValueExportFailed(
+                "File entries should have type u8.".to_string(),
+            ));
+        }
+        let mut bytes = std::fs::read(path).map_err(|e| {
+            LayoutError::FileError(format!("failed to read file {}: {}", path.display(), e))
+        })?;
+        value_sink.record_value(
+            field_path,
+            json!({
+                "file": path.display().to_string(),
+                "size": bytes.len(),
+                "sha256": sha256_hex(&bytes),
+            }),
+        )?;
+
+        match self.size_keys.resolve()? {
+            (None, _) => Ok(bytes),
+            (Some(SizeSource::OneD(size)), strict_len) => {
+                if bytes.len() > size && !strict_len {
+                    config.policy.apply(
+                        ConversionIssue::OutOfRange,
+                        LayoutError::DataValueExportFailed(
+                            "File is larger than defined size.".to_string(),
+                        ),
+                        field_path,
+                    )?;
+                    bytes.truncate(size);
+                }
+                fit_to_size(bytes, size, strict_len, config, field_path)
+            }
+            (Some(SizeSource::TwoD(_)), _) => Err(LayoutError::DataValueExportFailed(
+                "File entries cannot have a 2D size.".to_string(),
+            )),
+        }
+    }
+
+    /// Emits the built image of another block, padded or checked against `size`/`SIZE`.
+    fn emit_block(
+        &self,
+        name: &str,
+        config: &BuildConfig,
+        value_sink: &mut dyn ValueSink,
+        field_path: &[String],
+    ) -> Result<Vec<u8>, LayoutError> {
+        if !matches!(self.scalar_type, ScalarType::U8) {
+            return Err(LayoutError::DataValueExportFailed(
+                "Block entries should have type u8.".to_string(),
+            ));
+        }
+        let bytes = config
+            .blocks
+            .image(name)
+            .ok_or_else(|| LayoutError::BlockNotFound(name.to_string()))?;
+        value_sink.record_value(
+            field_path,
+            json!({
+                "block": name,
+                "size": bytes.len(),
+                "sha256": sha256_hex(&bytes),
+            }),
+        )?;
+
+        let bytes = match self.size_keys.resolve()? {
+            (None, _) => bytes,
+            (Some(SizeSource::OneD(size)), strict_len) => {
+                fit_to_size(bytes, size, strict_len, config, field_path)?
+            }
+            (Some(SizeSource::TwoD(_)), _) => {
+                return Err(LayoutError::DataValueExportFailed(
+                    "Block entries cannot have a 2D size.".to_string(),
+                ));
+            }
+        };
+        let word_size = config.word_addressing.word_size() as usize;
+        if !bytes.len().is_multiple_of(word_size) {
+            return Err(LayoutError::DataValueExportFailed(format!(
+                "Block entry of {} bytes is not a whole number of {}-byte words.",
+                bytes.len(),
+                word_size
+            )));
+        }
+        Ok(bytes)
+    }
+
+    /// Emits a value computed from other build output.
+    fn emit_auto(
+        &self,
+        auto: &AutoSource,
+        data_source: Option<&dyn DataSource>,
+        config: &BuildConfig,
+        value_sink: &mut dyn ValueSink,
+        field_path: &[String],
+    ) -> Result<Vec<u8>, LayoutError> {
+        if self.size_keys.size.is_some() || self.size_keys.strict_size.is_some() {
+            return Err(LayoutError::DataValueExportFailed(
+                "size/SIZE keys are forbidden with auto.".into(),
+            ));
+        }
+        let value = match auto {
+            AutoSource::CrcOf(name) => {
+                let crc = config.blocks.crc(name).ok_or_else(|| {
+                    LayoutError::DataValueExportFailed(format!(
+                        "Block '{}' has no CRC to reference.",
+                        name
+                    ))
+                })?;
+                DataValue::U64(crc as u64)
+            }
+            AutoSource::BlockId(spec) => {
+                self.require_unsigned("block_id")?;
+                let versions = data_source.map(|ds| ds.versions()).unwrap_or_default();
+                let bits = self.scalar_type.size_bytes() as u32 * 8;
+                DataValue::U64(spec.compute(config.block_name, &versions, bits))
+            }
+            // Patched by the block once the rest of it is built
+            AutoSource::ValuesHash
+            | AutoSource::DataLength
+            | AutoSource::DataCrc
+            | AutoSource::FieldCount => {
+                self.require_unsigned(auto.name())?;
+                return Ok(vec![0; self.scalar_type.size_bytes()]);
+            }
+        };
+        value_sink.record_value(field_path, data_value_to_json(&value)?)?;
+        value.to_bytes(self.scalar_type, config.endianness, true)
+    }
+
+    fn require_unsigned(&self, what: &str) -> Result<(), LayoutError> {
+        match self.scalar_type.is_integer() && !self.scalar_type.is_signed() {
+            true => Ok(()),
+            false => Err(LayoutError::DataValueExportFailed(format!(
+                "{} requires an unsigned integer type.",
+                what
+            ))),
+        }
+    }
+
+    /// Converts a scalar value after the `transform` chain, applying the non-finite
+    /// policy to NaN/Inf if one is set and falling back to a saturating cast where
+    /// the conversion policy allows it. Without a non-finite policy NaN/Inf count
+    /// as out of range.
+    fn value_to_bytes(
+        &self,
+        value: &DataValue,
+        config: &BuildConfig,
+        field_path: &[String],
+    ) -> Result<Vec<u8>, LayoutError> {
+        let transformed = apply_chain(&self.transform, value)?;
+        let value = &transformed;
+        if let DataValue::F64(v) = value
+            && !v.is_finite()
+            && let Some(mode) = config.policy.non_finite
+        {
+            return encode_non_finite(*v, mode, self.scalar_type, config.endianness);
+        }
+        match value.to_bytes(self.scalar_type, config.endianness, true) {
+            Err(e) => {
+                apply_policy(config, e, field_path)?;
+                value.to_bytes(self.scalar_type, config.endianness, false)
+            }
+            ok => ok,
+        }
+    }
+
+    /// `size`/`SIZE` as the data source provides it: 2D dimensions are
+    /// swapped by `transpose`. The flag is set for `SIZE`.
+    pub(crate) fn source_size(&self) -> (Option<SizeSource>, bool) {
+        match self.size_keys.resolve() {
+            Ok((Some(SizeSource::TwoD([rows, cols])), strict)) if self.transpose => {
+                (Some(SizeSource::TwoD([cols, rows])), strict)
+            }
+            Ok(size) => size,
+            Err(_) => (None, false),
+        }
+    }
+
+    /// Data source name whose value can be read back from the emitted bytes:
+    /// a `name` source without `transform` or `map`.
+    pub(crate) fn image_name(&self) -> Option<&str> {
+        match &self.source {
+            EntrySource::Name(name) if self.transform.is_empty() && self.map.is_none() => {
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+
+    /// Value a data source would give for `values` emitted in memory order:
+    /// a scalar, an array, or rows in source order (undoing `order` and
+    /// `transpose`).
+    pub(crate) fn source_value(&self, values: Vec<serde_json::Value>) -> serde_json::Value {
+        use serde_json::Value;
+        match self.size_keys.resolve() {
+            Ok((Some(SizeSource::TwoD([rows, cols])), _)) => {
+                let at = |r: usize, c: usize| match self.order.unwrap_or_default() {
+                    ArrayOrder::Row => values[r * cols + c].clone(),
+                    ArrayOrder::Column => values[c * rows + r].clone(),
+                };
+                let (outer, inner) = match self.transpose {
+                    true => (cols, rows),
+                    false => (rows, cols),
+                };
+                Value::Array(
+                    (0..outer)
+                        .map(|i| {
+                            Value::Array(
+                                (0..inner)
+                                    .map(|j| match self.transpose {
+                                        true => at(j, i),
+                                        false => at(i, j),
+                                    })
+                                    .collect(),
+                            )
+                        })
+                        .collect(),
+                )
+            }
+            Ok((Some(SizeSource::OneD(_)), _)) => Value::Array(values),
+            _ if values.len() == 1 => values.into_iter().next().unwrap(),
+            _ => Value::Array(values),
+        }
+    }
+
+    /// Mapping table named by `map`, without its `#`.
+    pub(crate) fn map_table(&self) -> Option<&str> {
+        self.map
+            .as_deref()
+            .map(|t| t.strip_prefix('#').unwrap_or(t))
+    }
+
+    /// Whether this entry holds the block's `values_hash`.
+    pub(crate) fn is_values_hash(&self) -> bool {
+        matches!(self.source, EntrySource::Auto(AutoSource::ValuesHash))
+    }
+
+    /// Auto source this entry emits as zeros for the block to patch.
+    pub(crate) fn placeholder(&self) -> Option<&AutoSource> {
+        match &self.source {
+            EntrySource::Auto(auto) if auto.is_placeholder() => Some(auto),
+            _ => None,
+        }
+    }
+
+    /// Name of the block this entry embeds or references, if any.
+    pub fn block_dependency(&self) -> Option<&str> {
+        match &self.source {
+            EntrySource::Block(name) | EntrySource::Auto(AutoSource::CrcOf(name)) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Makes a relative `file` path or `@` CSV reference relative to `base`.
+    pub(crate) fn resolve_file_path(&mut self, base: &Path) {
+        match &mut self.source {
+            EntrySource::File(path) if path.is_relative() => *path = base.join(&*path),
+            EntrySource::Csv(csv) if csv.path.is_relative() => csv.path = base.join(&csv.path),
+            _ => {}
+        }
+    }
+
+    /// Replaces `"$NAME"` values with the constant `NAME`; `"$$"` escapes a
+    /// literal leading `$`.
+    pub(crate) fn resolve_constants(&mut self, constants: &Constants) -> Result<(), LayoutError> {
+        match &mut self.source {
+            EntrySource::Value(ValueSource::Single(DataValue::Str(text))) => {
+                if let Some(value) = lookup_constant(text, constants)? {
+                    self.source = EntrySource::Value(value.clone());
+                }
+            }
+            EntrySource::Value(ValueSource::Array(items)) => {
+                for item in items {
+                    if let DataValue::Str(text) = item
+                        && let Some(value) = lookup_constant(text, constants)?
+                    {
+                        *item = single_constant(text, value)?;
+                    }
+                }
+            }
+            EntrySource::Bitmap(fields) => {
+                for field in fields {
+                    if let BitmapFieldSource::Value(DataValue::Str(text)) = &mut field.source
+                        && let Some(value) = lookup_constant(text, constants)?
+                    {
+                        field.source = BitmapFieldSource::Value(single_constant(text, value)?);
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Replaces `{i}` in data source names with `index`.
+    pub(crate) fn substitute_index(&mut self, index: usize) {
+        let substitute = |name: &mut String| *name = name.replace("{i}", &index.to_string());
+        match &mut self.source {
+            EntrySource::Name(name) | EntrySource::Names(NamesSource::Pattern(name)) => {
+                substitute(name)
+            }
+            EntrySource::Names(NamesSource::List(names)) => names.iter_mut().for_each(substitute),
+            EntrySource::Bitmap(fields) => {
+                for field in fields {
+                    if let BitmapFieldSource::Name(name) = &mut field.source {
+                        substitute(name);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
     fn emit_bytes_single(
         &self,
         data_source: Option<&dyn DataSource>,
@@ -252,16 +1016,24 @@ impl LeafEntry {
                 };
                 let value = ds.retrieve_single_value(name)?;
                 value_sink.record_value(field_path, data_value_to_json(&value)?)?;
-                value.to_bytes(self.scalar_type, config.endianness, config.strict)
+                self.value_to_bytes(&value, config, field_path)
             }
             EntrySource::Value(ValueSource::Single(v)) => {
                 value_sink.record_value(field_path, data_value_to_json(v)?)?;
-                v.to_bytes(self.scalar_type, config.endianness, config.strict)
+                self.value_to_bytes(v, config, field_path)
             }
             EntrySource::Value(_) => Err(LayoutError::DataValueExportFailed(
                 "Single value expected for scalar type.".to_string(),
             )),
-            EntrySource::Bitmap(_) => unreachable!("bitmap handled in emit_bytes"),
+            EntrySource::Csv(_) => Err(LayoutError::DataValueExportFailed(
+                "CSV entries need a 1D or 2D size.".to_string(),
+            )),
+            EntrySource::Bitmap(_)
+            | EntrySource::Names(_)
+            | EntrySource::Hex(_)
+            | EntrySource::File(_)
+            | EntrySource::Block(_)
+            | EntrySource::Auto(_) => unreachable!("raw and auto sources handled in emit_bytes"),
         }
     }
 
@@ -290,24 +1062,36 @@ impl LeafEntry {
                         name
                     )));
                 };
-                match ds.retrieve_1d_array_or_string(name)? {
+                // Only u8 entries can hold a string.
+                let source = match self.scalar_type {
+                    ScalarType::U8 => ds.retrieve_1d_array_or_string(name)?,
+                    _ => ds.retrieve_1d_array(name)?,
+                };
+                match source {
                     ValueSource::Single(v) => {
                         if !matches!(self.scalar_type, ScalarType::U8) {
                             return Err(LayoutError::DataValueExportFailed(
                                 "Strings should have type u8.".to_string(),
                             ));
                         }
+                        if !self.transform.is_empty() {
+                            return Err(LayoutError::DataValueExportFailed(
+                                "transform cannot be applied to a string.".to_string(),
+                            ));
+                        }
                         value_sink.record_value(field_path, data_value_to_json(&v)?)?;
-                        out.extend(v.string_to_bytes()?);
+                        match &v {
+                            DataValue::Str(s) => match decode_byte_string(s, self.bytes)? {
+                                Some(bytes) => out.extend(bytes),
+                                None => out.extend(v.string_to_bytes()?),
+                            },
+                            _ => out.extend(v.string_to_bytes()?),
+                        }
                     }
                     ValueSource::Array(v) => {
                         value_sink.record_value(field_path, array_to_json(&v)?)?;
                         for v in v {
-                            out.extend(v.to_bytes(
-                                self.scalar_type,
-                                config.endianness,
-                                config.strict,
-                            )?);
+                            out.extend(self.value_to_bytes(&v, config, field_path)?);
                         }
                     }
                 }
@@ -315,7 +1099,17 @@ impl LeafEntry {
             EntrySource::Value(ValueSource::Array(v)) => {
                 value_sink.record_value(field_path, array_to_json(v)?)?;
                 for v in v {
-                    out.extend(v.to_bytes(self.scalar_type, config.endianness, config.strict)?);
+                    out.extend(self.value_to_bytes(v, config, field_path)?);
+                }
+            }
+            EntrySource::Csv(csv) => {
+                let values: Vec<DataValue> = read_csv(&csv.path, csv.decimal_separator)?
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                value_sink.record_value(field_path, array_to_json(&values)?)?;
+                for v in &values {
+                    out.extend(self.value_to_bytes(v, config, field_path)?);
                 }
             }
             EntrySource::Value(ValueSource::Single(v)) => {
@@ -327,23 +1121,15 @@ impl LeafEntry {
                 value_sink.record_value(field_path, data_value_to_json(v)?)?;
                 out.extend(v.string_to_bytes()?);
             }
-            EntrySource::Bitmap(_) => unreachable!("bitmap handled in emit_bytes"),
+            EntrySource::Bitmap(_)
+            | EntrySource::Names(_)
+            | EntrySource::Hex(_)
+            | EntrySource::File(_)
+            | EntrySource::Block(_)
+            | EntrySource::Auto(_) => unreachable!("raw and auto sources handled in emit_bytes"),
         }
 
-        if out.len() > total_bytes {
-            return Err(LayoutError::DataValueExportFailed(
-                "Array/string is larger than defined size.".to_string(),
-            ));
-        }
-        if strict_len && out.len() < total_bytes {
-            return Err(LayoutError::DataValueExportFailed(
-                "Array/string is smaller than defined size (strict SIZE).".to_string(),
-            ));
-        }
-        while out.len() < total_bytes {
-            out.push(config.padding);
-        }
-        Ok(out)
+        fit_to_size(out, total_bytes, strict_len, config, field_path)
     }
 
     fn emit_bytes_2d(
@@ -355,7 +1141,7 @@ impl LeafEntry {
         value_sink: &mut dyn ValueSink,
         field_path: &[String],
     ) -> Result<Vec<u8>, LayoutError> {
-        match &self.source {
+        let data = match &self.source {
             EntrySource::Name(name) => {
                 let Some(ds) = data_source else {
                     return Err(LayoutError::MissingDataSheet(format!(
@@ -363,69 +1149,197 @@ impl LeafEntry {
                         name
                     )));
                 };
-                let data = ds.retrieve_2d_array(name)?;
-
-                let rows = size[0];
-                let cols = size[1];
-
-                let elem = self.scalar_type.size_bytes();
-                let total_elems =
-                    rows.checked_mul(cols)
-                        .ok_or(LayoutError::DataValueExportFailed(
-                            "2D size overflow".into(),
-                        ))?;
-                let total_bytes =
-                    total_elems
-                        .checked_mul(elem)
-                        .ok_or(LayoutError::DataValueExportFailed(
-                            "2D byte count overflow".into(),
-                        ))?;
-
-                if data.iter().any(|row| row.len() != cols) {
-                    return Err(LayoutError::DataValueExportFailed(
-                        "2D array column count mismatch.".to_string(),
-                    ));
-                }
+                ds.retrieve_2d_array(name)?
+            }
+            EntrySource::Csv(csv) => read_csv(&csv.path, csv.decimal_separator)?,
+            EntrySource::Value(_) => {
+                return Err(LayoutError::DataValueExportFailed(
+                    "2D arrays within the layout file are not supported.".to_string(),
+                ));
+            }
+            EntrySource::Bitmap(_)
+            | EntrySource::Names(_)
+            | EntrySource::Hex(_)
+            | EntrySource::File(_)
+            | EntrySource::Block(_)
+            | EntrySource::Auto(_) => unreachable!("raw and auto sources handled in emit_bytes"),
+        };
+        let data = if self.transpose {
+            transpose(data)?
+        } else {
+            data
+        };
 
-                if data.len() > rows {
-                    return Err(LayoutError::DataValueExportFailed(
-                        "2D array row count greater than defined size.".to_string(),
-                    ));
-                }
+        let rows = size[0];
+        let cols = size[1];
 
-                if strict_len && data.len() < rows {
-                    return Err(LayoutError::DataValueExportFailed(
-                        "2D array row count smaller than defined size (strict SIZE).".to_string(),
-                    ));
-                }
+        let elem = self.scalar_type.size_bytes();
+        let total_elems = rows
+            .checked_mul(cols)
+            .ok_or(LayoutError::DataValueExportFailed(
+                "2D size overflow".into(),
+            ))?;
+        let total_bytes =
+            total_elems
+                .checked_mul(elem)
+                .ok_or(LayoutError::DataValueExportFailed(
+                    "2D byte count overflow".into(),
+                ))?;
 
-                value_sink.record_value(field_path, array_2d_to_json(&data)?)?;
+        if data.iter().any(|row| row.len() > cols) {
+            return Err(LayoutError::DataValueExportFailed(
+                "2D array column count greater than defined size.".to_string(),
+            ));
+        }
+        if data.iter().any(|row| row.len() < cols) {
+            if strict_len {
+                return Err(LayoutError::DataValueExportFailed(
+                    "2D array column count smaller than defined size (strict SIZE).".to_string(),
+                ));
+            }
+            config.policy.apply(
+                ConversionIssue::ShortArray,
+                LayoutError::DataValueExportFailed(
+                    "2D array column count smaller than defined size.".to_string(),
+                ),
+                field_path,
+            )?;
+        }
 
-                let mut out = Vec::with_capacity(total_bytes);
-                for row in data {
+        if data.len() > rows {
+            return Err(LayoutError::DataValueExportFailed(
+                "2D array row count greater than defined size.".to_string(),
+            ));
+        }
+
+        if strict_len && data.len() < rows {
+            return Err(LayoutError::DataValueExportFailed(
+                "2D array row count smaller than defined size (strict SIZE).".to_string(),
+            ));
+        }
+        if data.len() < rows {
+            config.policy.apply(
+                ConversionIssue::ShortArray,
+                LayoutError::DataValueExportFailed(
+                    "2D array row count smaller than defined size.".to_string(),
+                ),
+                field_path,
+            )?;
+        }
+
+        value_sink.record_value(field_path, array_2d_to_json(&data)?)?;
+
+        let mut out = Vec::with_capacity(total_bytes);
+        match self.order.unwrap_or_default() {
+            ArrayOrder::Row => {
+                for row in &data {
                     for v in row {
-                        out.extend(v.to_bytes(
-                            self.scalar_type,
-                            config.endianness,
-                            config.strict,
-                        )?);
+                        out.extend(self.value_to_bytes(v, config, field_path)?);
                     }
+                    out.extend(std::iter::repeat_n(
+                        config.padding,
+                        (cols - row.len()) * elem,
+                    ));
                 }
-
-                while out.len() < total_bytes {
-                    out.push(config.padding);
+            }
+            ArrayOrder::Column => {
+                for col in 0..cols {
+                    for row in 0..rows {
+                        match data.get(row).and_then(|values| values.get(col)) {
+                            Some(v) => out.extend(self.value_to_bytes(v, config, field_path)?),
+                            None => out.extend(std::iter::repeat_n(config.padding, elem)),
+                        }
+                    }
                 }
-
-                Ok(out)
             }
-            EntrySource::Value(_) => Err(LayoutError::DataValueExportFailed(
-                "2D arrays within the layout file are not supported.".to_string(),
-            )),
-            EntrySource::Bitmap(_) => unreachable!("bitmap handled in emit_bytes"),
         }
+
+        while out.len() < total_bytes {
+            out.push(config.padding);
+        }
+
+        Ok(out)
     }
 }
 
+/// Swaps the rows and columns of a rectangular 2D array.
+/// Data source names matching `pattern`, in natural order.
+fn pattern_names(ds: &dyn DataSource, pattern: &str) -> Vec<String> {
+    let mut names: Vec<String> = ds
+        .names()
+        .into_iter()
+        .filter(|n| glob_match(pattern, n))
+        .collect();
+    // Names equal in natural order (`Gain_1`, `Gain_01`) fall back to byte
+    // order, since data source names come in no fixed order.
+    names.sort_by(|a, b| natural_cmp(a, b).then_with(|| a.cmp(b)));
+    names
+}
+
+fn transpose(data: Vec<Vec<DataValue>>) -> Result<Vec<Vec<DataValue>>, LayoutError> {
+    let width = data.first().map_or(0, Vec::len);
+    if data.iter().any(|row| row.len() != width) {
+        return Err(LayoutError::DataValueExportFailed(
+            "2D array column count mismatch.".to_string(),
+        ));
+    }
+    Ok((0..width)
+        .map(|col| data.iter().map(|row| row[col].clone()).collect())
+        .collect())
+}
+
+/// Pads `out` to `total_bytes`, rejecting data that is too long, or too short
+/// when `strict_len` is set. Otherwise short data is subject to the short-array policy.
+fn fit_to_size(
+    mut out: Vec<u8>,
+    total_bytes: usize,
+    strict_len: bool,
+    config: &BuildConfig,
+    field_path: &[String],
+) -> Result<Vec<u8>, LayoutError> {
+    if out.len() > total_bytes {
+        return Err(LayoutError::DataValueExportFailed(
+            "Array/string is larger than defined size.".to_string(),
+        ));
+    }
+    if out.len() < total_bytes {
+        if strict_len {
+            return Err(LayoutError::DataValueExportFailed(
+                "Array/string is smaller than defined size (strict SIZE).".to_string(),
+            ));
+        }
+        config.policy.apply(
+            ConversionIssue::ShortArray,
+            LayoutError::DataValueExportFailed(
+                "Array/string is smaller than defined size.".to_string(),
+            ),
+            field_path,
+        )?;
+    }
+    out.resize(total_bytes, config.padding);
+    Ok(out)
+}
+
+/// Applies the conversion policy to a failed strict conversion. Errors that
+/// are not policy-controlled are returned unchanged.
+fn apply_policy(
+    config: &BuildConfig,
+    err: LayoutError,
+    field_path: &[String],
+) -> Result<(), LayoutError> {
+    match err.conversion_issue() {
+        Some(issue) => config.policy.apply(issue, err, field_path),
+        None => Err(err),
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
 fn bitmap_field_key(field: &BitmapField, offset: usize) -> String {
     match &field.source {
         BitmapFieldSource::Name(name) => name.clone(),
@@ -434,6 +1348,38 @@ fn bitmap_field_key(field: &BitmapField, offset: usize) -> String {
 }
 
 impl ScalarType {
+    /// C type name from `<stdint.h>` (or `float`/`double`).
+    pub fn c_type(&self) -> &'static str {
+        match self {
+            ScalarType::U8 => "uint8_t",
+            ScalarType::U16 => "uint16_t",
+            ScalarType::U32 => "uint32_t",
+            ScalarType::U64 => "uint64_t",
+            ScalarType::I8 => "int8_t",
+            ScalarType::I16 => "int16_t",
+            ScalarType::I32 => "int32_t",
+            ScalarType::I64 => "int64_t",
+            ScalarType::F32 => "float",
+            ScalarType::F64 => "double",
+        }
+    }
+
+    /// Type name as written in a layout's `type` key.
+    pub fn layout_name(&self) -> &'static str {
+        match self {
+            ScalarType::U8 => "u8",
+            ScalarType::U16 => "u16",
+            ScalarType::U32 => "u32",
+            ScalarType::U64 => "u64",
+            ScalarType::I8 => "i8",
+            ScalarType::I16 => "i16",
+            ScalarType::I32 => "i32",
+            ScalarType::I64 => "i64",
+            ScalarType::F32 => "f32",
+            ScalarType::F64 => "f64",
+        }
+    }
+
     /// Returns the size of the scalar type in bytes.
     pub fn size_bytes(&self) -> usize {
         match self {
@@ -457,3 +1403,144 @@ impl ScalarType {
         )
     }
 }
+
+/// Constant named by a `"$NAME"` value, unescaping `"$$..."` in place.
+fn lookup_constant<'c>(
+    text: &mut String,
+    constants: &'c Constants,
+) -> Result<Option<&'c ValueSource>, LayoutError> {
+    if text.starts_with("$$") {
+        text.remove(0);
+        return Ok(None);
+    }
+    let Some(name) = text.strip_prefix('$') else {
+        return Ok(None);
+    };
+    constants
+        .get(name)
+        .map(Some)
+        .ok_or_else(|| LayoutError::Constant(format!("unknown constant '{}'", name)))
+}
+
+/// Scalar `value` of the constant referenced as `text`, for array elements and
+/// bitmap fields.
+fn single_constant(text: &str, value: &ValueSource) -> Result<DataValue, LayoutError> {
+    match value {
+        ValueSource::Single(v) => Ok(v.clone()),
+        ValueSource::Array(_) => Err(LayoutError::Constant(format!(
+            "constant '{}' is an array where a single value is expected",
+            &text[1..]
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transpose_swaps_rows_and_columns() {
+        let data = vec![
+            vec![DataValue::U64(1), DataValue::U64(2), DataValue::U64(3)],
+            vec![DataValue::U64(4), DataValue::U64(5), DataValue::U64(6)],
+        ];
+        let out = transpose(data).unwrap();
+        let flat: Vec<String> = out.iter().flatten().map(|v| format!("{:?}", v)).collect();
+        assert_eq!(out.len(), 3);
+        assert_eq!(
+            flat,
+            ["U64(1)", "U64(4)", "U64(2)", "U64(5)", "U64(3)", "U64(6)"]
+        );
+
+        assert!(transpose(vec![vec![DataValue::U64(1)], vec![]]).is_err());
+    }
+
+    #[test]
+    fn leaf_keys_match_leaf_entry_fields() {
+        // Destructured without `..` so a new field fails to compile until listed here.
+        let leaf: LeafEntry = serde_json::from_value(json!({ "type": "u8", "value": 0 })).unwrap();
+        let LeafEntry {
+            scalar_type: _,
+            size_keys:
+                SizeKeys {
+                    size: _,
+                    strict_size: _,
+                },
+            order: _,
+            transpose: _,
+            transform: _,
+            map: _,
+            require_version: _,
+            bytes: _,
+            source,
+        } = leaf;
+        let source_key = |source: &EntrySource| match source {
+            EntrySource::Name(_) => "name",
+            EntrySource::Names(_) => "names",
+            EntrySource::Value(_) => "value",
+            EntrySource::Bitmap(_) => "bitmap",
+            EntrySource::Hex(_) => "hex",
+            EntrySource::File(_) => "file",
+            EntrySource::Csv(_) => "csv",
+            EntrySource::Block(_) => "block",
+            EntrySource::Auto(_) => "auto",
+        };
+        assert_eq!(source_key(&source), "value");
+
+        let fields = [
+            "type",
+            "size",
+            "SIZE",
+            "order",
+            "transpose",
+            "transform",
+            "map",
+            "require_version",
+            "bytes",
+        ];
+        let sources = [
+            ("name", json!("a")),
+            ("names", json!(["a"])),
+            ("value", json!(1)),
+            ("bitmap", json!([])),
+            ("hex", json!("00")),
+            ("file", json!("a.bin")),
+            ("csv", json!("a.csv")),
+            ("block", json!("b")),
+            ("auto", json!({ "crc_of": "b" })),
+        ];
+        let mut expected: Vec<&str> = fields.to_vec();
+        expected.extend(sources.iter().map(|(key, _)| *key));
+        let mut listed = LEAF_KEYS.to_vec();
+        listed.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(listed, expected);
+        assert_eq!(SOURCE_KEYS.len(), sources.len());
+
+        // Each source key selects its own variant.
+        for (key, value) in &sources {
+            assert!(SOURCE_KEYS.contains(key), "{key} missing from SOURCE_KEYS");
+            let leaf: LeafEntry = serde_json::from_value(json!({ "type": "u8", *key: value }))
+                .unwrap_or_else(|e| panic!("{key}: {e}"));
+            assert_eq!(source_key(&leaf.source), *key);
+        }
+        // Every other key is read rather than ignored, so a bad value is rejected.
+        for key in fields {
+            let probe = json!({ "type": "u8", "value": 0, key: { "probe": [] } });
+            assert!(
+                serde_json::from_value::<LeafEntry>(probe).is_err(),
+                "{key} is not read by LeafEntry"
+            );
+        }
+        let unknown = json!({ "type": "u8", "value": 0, "probe": 0 });
+        assert!(serde_json::from_value::<LeafEntry>(unknown).is_err());
+    }
+
+    #[test]
+    fn sha256_hex_matches_known_digest() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use super::policy::ConversionIssue;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -5,18 +6,64 @@ pub enum LayoutError {
     #[error("File error: {0}.")]
     FileError(String),
 
+    #[error("Failed to parse {file} at line {line}, column {column}: {message}\n{snippet}")]
+    Parse {
+        file: String,
+        line: usize,
+        column: usize,
+        message: String,
+        snippet: String,
+    },
+
+    #[error("Duplicate field paths in {file}: {}.", .paths.join(", "))]
+    DuplicatePaths { file: String, paths: Vec<String> },
+
+    #[error("Profile '{profile}' not found in {file} (available: {available}).")]
+    ProfileNotFound {
+        profile: String,
+        file: String,
+        available: String,
+    },
+
+    #[error("Layout files are not canonically formatted: {}.", .0.join(", "))]
+    NotFormatted(Vec<String>),
+
     #[error("Block not found: {0}.")]
     BlockNotFound(String),
 
+    #[error("Block dependency cycle between: {0}.")]
+    DependencyCycle(String),
+
     #[error("Data value export failed: {0}.")]
     DataValueExportFailed(String),
 
+    #[error("Data value export failed: {message}.")]
+    Conversion {
+        issue: ConversionIssue,
+        message: String,
+    },
+
     #[error("Invalid block argument: {0}.")]
     InvalidBlockArgument(String),
 
     #[error("No blocks provided.")]
     NoBlocksProvided,
 
+    #[error("No blocks match the --only, --exclude, and --tag filters.")]
+    NoBlocksSelected,
+
+    #[error("Invalid header expression: {0}.")]
+    Expression(String),
+
+    #[error("Invalid migration: {0}.")]
+    Migration(String),
+
+    #[error("Value from the wrong version: {0}.")]
+    WrongVersion(String),
+
+    #[error("Invalid constant reference: {0}.")]
+    Constant(String),
+
     #[error("Missing datasheet: {0}")]
     MissingDataSheet(String),
 
@@ -41,3 +88,40 @@ pub enum LayoutError {
     #[error(transparent)]
     Data(#[from] crate::data::error::DataError),
 }
+
+impl LayoutError {
+    /// Stable code of the underlying error; see `mint --explain`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            LayoutError::FileError(_) => "MINT-L001",
+            LayoutError::Parse { .. } => "MINT-L002",
+            LayoutError::DuplicatePaths { .. } => "MINT-L003",
+            LayoutError::ProfileNotFound { .. } => "MINT-L004",
+            LayoutError::NotFormatted(_) => "MINT-L005",
+            LayoutError::BlockNotFound(_) => "MINT-L006",
+            LayoutError::DependencyCycle(_) => "MINT-L007",
+            LayoutError::DataValueExportFailed(_) => "MINT-L008",
+            LayoutError::Conversion { .. } => "MINT-L009",
+            LayoutError::InvalidBlockArgument(_) => "MINT-L010",
+            LayoutError::NoBlocksProvided => "MINT-L011",
+            LayoutError::MissingDataSheet(_) => "MINT-L012",
+            LayoutError::BitfieldOutOfRange { .. } => "MINT-L013",
+            LayoutError::NoBlocksSelected => "MINT-L014",
+            LayoutError::Migration(_) => "MINT-L015",
+            LayoutError::Expression(_) => "MINT-L016",
+            LayoutError::WrongVersion(_) => "MINT-L017",
+            LayoutError::Constant(_) => "MINT-L018",
+            LayoutError::InField { source, .. } => source.code(),
+            LayoutError::Data(e) => e.code(),
+        }
+    }
+
+    /// Policy-controlled issue behind this error, if any.
+    pub fn conversion_issue(&self) -> Option<ConversionIssue> {
+        match self {
+            LayoutError::Conversion { issue, .. } => Some(*issue),
+            LayoutError::BitfieldOutOfRange { .. } => Some(ConversionIssue::OutOfRange),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use super::block::Constants;
+use super::value::{DataValue, ValueSource};
+use serde::Deserialize;
+use serde::de::{self, Deserializer, Visitor};
+use std::fmt;
+use std::str::FromStr;
+
+/// Integer arithmetic over numbers, layout constants, and other blocks'
+/// headers, for header addresses and lengths, e.g. `"END_OF(boot) + 0x100"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Number(u64),
+    /// Name of an integer in the layout's `[constants]`.
+    Const(String),
+    Ref(BlockRef, String),
+    Binary(Box<Expr>, Op, Box<Expr>),
+}
+
+/// Header value of another block in the same layout file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockRef {
+    /// `START_OF(block)`: its `start_address`.
+    StartOf,
+    /// `END_OF(block)`: its `start_address + length`.
+    EndOf,
+    /// `LENGTH_OF(block)`: its `length`.
+    LengthOf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Expr {
+    /// The expression with each constant replaced by its value from `constants`.
+    pub fn with_constants(self, constants: &Constants) -> Result<Expr, String> {
+        Ok(match self {
+            Expr::Const(name) => match constants.get(&name) {
+                Some(ValueSource::Single(DataValue::U64(n))) => Expr::Number(*n),
+                Some(ValueSource::Single(DataValue::I64(n))) if *n >= 0 => Expr::Number(*n as u64),
+                Some(_) => return Err(format!("constant '{}' is not an unsigned integer", name)),
+                None => return Err(format!("unknown constant '{}'", name)),
+            },
+            Expr::Binary(lhs, op, rhs) => Expr::Binary(
+                Box::new(lhs.with_constants(constants)?),
+                op,
+                Box::new(rhs.with_constants(constants)?),
+            ),
+            expr => expr,
+        })
+    }
+
+    /// Evaluates the expression, resolving block references through `lookup`.
+    pub fn eval(
+        &self,
+        lookup: &mut dyn FnMut(BlockRef, &str) -> Result<u64, String>,
+    ) -> Result<u64, String> {
+        match self {
+            Expr::Number(n) => Ok(*n),
+            Expr::Const(name) => Err(format!("unresolved constant '{}'", name)),
+            Expr::Ref(kind, block) => lookup(*kind, block),
+            Expr::Binary(lhs, op, rhs) => {
+                let (a, b) = (lhs.eval(lookup)?, rhs.eval(lookup)?);
+                match op {
+                    Op::Add => a.checked_add(b).ok_or("addition overflows"),
+                    Op::Sub => a.checked_sub(b).ok_or("subtraction goes below zero"),
+                    Op::Mul => a.checked_mul(b).ok_or("multiplication overflows"),
+                    Op::Div => a.checked_div(b).ok_or("division by zero"),
+                }
+                .map_err(str::to_string)
+            }
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let mut parser = Parser { text, pos: 0 };
+        let expr = parser
+            .expr()
+            .and_then(|expr| match parser.peek() {
+                None => Ok(expr),
+                Some(c) => Err(format!("unexpected '{}'", c)),
+            })
+            .map_err(|e| format!("invalid expression '{}': {}", text, e))?;
+        Ok(expr)
+    }
+}
+
+/// Recursive-descent parser: `*` and `/` bind tighter than `+` and `-`, and
+/// operators of equal precedence group to the left.
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+        self.text[self.pos..].chars().next()
+    }
+
+    /// Consumes the run of characters matching `f`.
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let rest = &self.text[self.pos..];
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        match self.peek() {
+            Some(found) if found == c => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(found) => Err(format!("expected '{}', found '{}'", c, found)),
+            None => Err(format!("expected '{}'", c)),
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.term()?;
+        while let Some(op) = match self.peek() {
+            Some('+') => Some(Op::Add),
+            Some('-') => Some(Op::Sub),
+            _ => None,
+        } {
+            self.pos += 1;
+            lhs = Expr::Binary(Box::new(lhs), op, Box::new(self.term()?));
+        }
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.factor()?;
+        while let Some(op) = match self.peek() {
+            Some('*') => Some(Op::Mul),
+            Some('/') => Some(Op::Div),
+            _ => None,
+        } {
+            self.pos += 1;
+            lhs = Expr::Binary(Box::new(lhs), op, Box::new(self.factor()?));
+        }
+        Ok(lhs)
+    }
+
+    fn factor(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let expr = self.expr()?;
+                self.expect(')')?;
+                Ok(expr)
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let literal = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+                parse_number(literal)
+                    .map(Expr::Number)
+                    .ok_or_else(|| format!("invalid number '{}'", literal))
+            }
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+                if self.peek() != Some('(') {
+                    return Ok(Expr::Const(name.to_string()));
+                }
+                let kind = match name {
+                    "START_OF" => BlockRef::StartOf,
+                    "END_OF" => BlockRef::EndOf,
+                    "LENGTH_OF" => BlockRef::LengthOf,
+                    _ => {
+                        return Err(format!(
+                            "unknown function '{}'; expected START_OF, END_OF, or LENGTH_OF",
+                            name
+                        ));
+                    }
+                };
+                self.expect('(')?;
+                let block = self.take_while(|c| c != ')').trim().to_string();
+                self.expect(')')?;
+                if block.is_empty() {
+                    return Err(format!("{} needs a block name", name));
+                }
+                Ok(Expr::Ref(kind, block))
+            }
+            Some(c) => Err(format!("unexpected '{}'", c)),
+            None => Err("unexpected end".to_string()),
+        }
+    }
+}
+
+/// Decimal, `0x` hex, `0o` octal, or `0b` binary, with optional `_` separators.
+fn parse_number(literal: &str) -> Option<u64> {
+    let digits = literal.replace('_', "");
+    let lower = digits.to_ascii_lowercase();
+    let (radix, digits) = match lower.get(..2) {
+        Some("0x") => (16, &lower[2..]),
+        Some("0o") => (8, &lower[2..]),
+        Some("0b") => (2, &lower[2..]),
+        _ => (10, lower.as_str()),
+    };
+    u64::from_str_radix(digits, radix).ok()
+}
+
+/// Header `start_address` or `length`: a number, or an expression evaluated
+/// once the whole layout file is loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderValue {
+    Number(u32),
+    Expr(Expr),
+}
+
+impl<'de> Deserialize<'de> for HeaderValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct HeaderValueVisitor;
+
+        impl Visitor<'_> for HeaderValueVisitor {
+            type Value = HeaderValue;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a u32 or an expression string")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<HeaderValue, E> {
+                u32::try_from(v)
+                    .map(HeaderValue::Number)
+                    .map_err(|_| E::custom(format!("{} does not fit in u32", v)))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<HeaderValue, E> {
+                u64::try_from(v)
+                    .map_err(|_| E::custom(format!("{} is negative", v)))
+                    .and_then(|v| self.visit_u64(v))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<HeaderValue, E> {
+                match v.parse().map_err(E::custom)? {
+                    Expr::Number(n) => self.visit_u64(n),
+                    expr => Ok(HeaderValue::Expr(expr)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(HeaderValueVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(text: &str) -> Result<u64, String> {
+        text.parse::<Expr>()?
+            .eval(&mut |kind, block| match (kind, block) {
+                (BlockRef::StartOf, "boot") => Ok(0x8000),
+                (BlockRef::LengthOf, "boot") => Ok(0x400),
+                (BlockRef::EndOf, "boot") => Ok(0x8400),
+                _ => Err(format!("no block '{}'", block)),
+            })
+    }
+
+    #[test]
+    fn evaluates_arithmetic_with_precedence() {
+        assert_eq!(eval("0x8000_0000 + 3*0x400"), Ok(0x8000_0C00));
+        assert_eq!(eval("(1 + 2) * 4 - 10 / 2"), Ok(7));
+        assert_eq!(eval("10 - 2 - 3"), Ok(5));
+        assert_eq!(eval("0b1000 + 0o10"), Ok(16));
+    }
+
+    #[test]
+    fn resolves_block_references() {
+        assert_eq!(eval("END_OF(boot)"), Ok(0x8400));
+        assert_eq!(eval("START_OF( boot ) + 2 * LENGTH_OF(boot)"), Ok(0x8800));
+        assert_eq!(eval("END_OF(app)"), Err("no block 'app'".to_string()));
+    }
+
+    #[test]
+    fn substitutes_constants() {
+        let constants: Constants = [
+            (
+                "FLASH_BASE".to_string(),
+                ValueSource::Single(DataValue::U64(0x8000)),
+            ),
+            ("GAIN".to_string(), ValueSource::Single(DataValue::F64(1.5))),
+        ]
+        .into_iter()
+        .collect();
+        let resolve = |text: &str| {
+            text.parse::<Expr>()?
+                .with_constants(&constants)?
+                .eval(&mut |_, block| Err(format!("no block '{}'", block)))
+        };
+        assert_eq!(resolve("FLASH_BASE + 0x10"), Ok(0x8010));
+        assert_eq!(
+            resolve("GAIN * 2"),
+            Err("constant 'GAIN' is not an unsigned integer".to_string())
+        );
+        assert_eq!(resolve("BASE"), Err("unknown constant 'BASE'".to_string()));
+    }
+
+    #[test]
+    fn reports_syntax_and_range_errors() {
+        assert_eq!(
+            eval("1 +"),
+            Err("invalid expression '1 +': unexpected end".to_string())
+        );
+        assert_eq!(
+            eval("SIZE_OF(boot)"),
+            Err("invalid expression 'SIZE_OF(boot)': unknown function 'SIZE_OF'; expected START_OF, END_OF, or LENGTH_OF".to_string())
+        );
+        assert_eq!(
+            eval("(1 + 2"),
+            Err("invalid expression '(1 + 2': expected ')'".to_string())
+        );
+        assert_eq!(
+            eval("1 - 2"),
+            Err("subtraction goes below zero".to_string())
+        );
+        assert_eq!(eval("1 / 0"), Err("division by zero".to_string()));
+    }
+}
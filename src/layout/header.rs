@@ -1,16 +1,160 @@
+use super::expr::{Expr, HeaderValue};
 use super::settings::CrcConfig;
+use crate::data::SourceKind;
+use crate::output::args::OutputFormat;
 use serde::Deserialize;
+use std::ops::Range;
 
 /// Block header defining memory region and optional CRC configuration.
 #[derive(Debug, Deserialize)]
+#[serde(from = "RawHeader")]
 pub struct Header {
     pub start_address: u32,
     pub length: u32,
+    /// Expressions for `start_address` and `length`, which hold 0 until
+    /// [`Config::resolve_header_expressions`](super::block::Config::resolve_header_expressions)
+    /// evaluates them.
+    pub expressions: HeaderExpressions,
     /// Per-header CRC settings. Merged with `[settings.crc]` at runtime.
     #[serde(default)]
     pub crc: Option<CrcConfig>,
     #[serde(default = "default_padding")]
     pub padding: u8,
+    /// Labels for selecting the block with `--tag`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Format of this block's `--block-out-dir` file, overriding `--format`.
+    #[serde(default)]
+    pub format: Option<OutputFormat>,
+    /// Version of the block's field layout, for reading older images through
+    /// the block's migrations.
+    #[serde(default)]
+    pub schema_version: Option<u32>,
+    /// Sentinel bytes written around the data.
+    #[serde(default)]
+    pub guard: Option<Guard>,
+    /// Strict conversions for this block, overriding `--strict`.
+    #[serde(default)]
+    pub strict: Option<bool>,
+    /// Configured data source this block reads names from, instead of the default.
+    #[serde(default)]
+    pub data_source: Option<SourceKind>,
+}
+
+/// Sentinel bytes just before and after a block's data, within its length,
+/// for firmware to detect overruns at runtime.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Guard {
+    /// Bytes ahead of the data, after any CRC at `"start"`.
+    #[serde(default)]
+    pub before: u32,
+    /// Bytes after the data.
+    #[serde(default)]
+    pub after: u32,
+    /// Value of every guard byte.
+    pub value: u8,
+    /// Leaves the guard bytes out of the block CRC.
+    #[serde(default)]
+    pub exclude_from_crc: bool,
+}
+
+/// Unevaluated header expressions.
+#[derive(Debug, Clone, Default)]
+pub struct HeaderExpressions {
+    pub start_address: Option<Expr>,
+    pub length: Option<Expr>,
+    /// `start_address` was left out, for `[settings.auto_place]` to choose.
+    pub auto_placed: bool,
+}
+
+/// [`Header`] as written, with `start_address` and `length` as numbers or
+/// expressions.
+#[derive(Deserialize)]
+struct RawHeader {
+    #[serde(default)]
+    start_address: Option<HeaderValue>,
+    length: HeaderValue,
+    #[serde(default)]
+    crc: Option<CrcConfig>,
+    #[serde(default = "default_padding")]
+    padding: u8,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    format: Option<OutputFormat>,
+    #[serde(default)]
+    schema_version: Option<u32>,
+    #[serde(default)]
+    guard: Option<Guard>,
+    #[serde(default)]
+    strict: Option<bool>,
+    #[serde(default)]
+    data_source: Option<SourceKind>,
+}
+
+impl From<RawHeader> for Header {
+    fn from(raw: RawHeader) -> Self {
+        let split = |value| match value {
+            HeaderValue::Number(n) => (n, None),
+            HeaderValue::Expr(expr) => (0, Some(expr)),
+        };
+        let auto_placed = raw.start_address.is_none();
+        let (start_address, start_expr) = raw.start_address.map_or((0, None), split);
+        let (length, length_expr) = split(raw.length);
+        Header {
+            start_address,
+            length,
+            expressions: HeaderExpressions {
+                start_address: start_expr,
+                length: length_expr,
+                auto_placed,
+            },
+            crc: raw.crc,
+            padding: raw.padding,
+            tags: raw.tags,
+            format: raw.format,
+            schema_version: raw.schema_version,
+            guard: raw.guard,
+            strict: raw.strict,
+            data_source: raw.data_source,
+        }
+    }
+}
+
+impl Header {
+    /// Header CRC settings merged over the global `[settings.crc]`.
+    pub fn resolved_crc(&self, global: Option<&CrcConfig>) -> CrcConfig {
+        self.crc
+            .as_ref()
+            .map(|hc| hc.resolve(global))
+            .unwrap_or_else(|| global.cloned().unwrap_or_default())
+    }
+
+    /// Bytes reserved ahead of the data for a CRC at `"start"`.
+    pub fn reserved_start(&self, global: Option<&CrcConfig>) -> u32 {
+        let crc = self.resolved_crc(global);
+        if crc.location.as_ref().is_some_and(|l| l.is_start()) {
+            4
+        } else {
+            0
+        }
+    }
+
+    /// Guard byte ranges the CRC leaves out, in a block whose data, guards
+    /// included, ends at `data_len`.
+    pub fn crc_excluded(&self, global: Option<&CrcConfig>, data_len: usize) -> Vec<Range<usize>> {
+        let Some(guard) = self.guard.as_ref().filter(|g| g.exclude_from_crc) else {
+            return Vec::new();
+        };
+        let start = self.reserved_start(global) as usize;
+        let before = start..start + guard.before as usize;
+        let after = data_len.saturating_sub(guard.after as usize)..data_len;
+        [before, after]
+            .into_iter()
+            .filter(|r| !r.is_empty())
+            .collect()
+    }
 }
 
 fn default_padding() -> u8 {
@@ -0,0 +1,114 @@
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+/// Where a block's fields sat in images of an older `schema_version`, so
+/// those images can be read into the current layout.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Migration {
+    /// Schema version of the images this migration reads.
+    pub from: u32,
+    /// Current field path to the field's path in that version.
+    #[serde(default)]
+    pub renamed: IndexMap<String, String>,
+    /// Current field path to the field's byte offset within the block, for
+    /// fields that have moved since that version.
+    #[serde(default)]
+    pub offsets: IndexMap<String, usize>,
+    /// Current field paths that images of that version do not hold.
+    #[serde(default)]
+    pub absent: Vec<String>,
+}
+
+impl Migration {
+    /// Offset in older images of the field at `path`, currently at `offset`;
+    /// `None` if those images do not hold it.
+    pub fn offset(&self, path: &str, offset: usize) -> Option<usize> {
+        match self.absent.iter().any(|p| p == path) {
+            true => None,
+            false => Some(self.offsets.get(path).copied().unwrap_or(offset)),
+        }
+    }
+
+    /// Path of the field in the older version.
+    pub fn old_path<'a>(&'a self, path: &'a str) -> &'a str {
+        self.renamed.get(path).map_or(path, String::as_str)
+    }
+
+    /// Problems with the migration for a block at `schema_version` with
+    /// leaves at `paths`.
+    pub fn check(&self, schema_version: Option<u32>, paths: &[String]) -> Vec<String> {
+        let mut problems = Vec::new();
+        match schema_version {
+            None => problems.push("migrations need header.schema_version".to_string()),
+            Some(current) if self.from >= current => problems.push(format!(
+                "migration from schema version {} is not older than schema_version {}",
+                self.from, current
+            )),
+            Some(_) => {}
+        }
+        let named = self
+            .renamed
+            .keys()
+            .chain(self.offsets.keys())
+            .chain(&self.absent);
+        for path in named {
+            if !paths.contains(path) {
+                problems.push(format!(
+                    "migration from schema version {} names unknown field '{}'",
+                    self.from, path
+                ));
+            }
+        }
+        for path in self.offsets.keys() {
+            if self.absent.contains(path) {
+                problems.push(format!(
+                    "migration from schema version {} both moves and drops '{}'",
+                    self.from, path
+                ));
+            }
+        }
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migration() -> Migration {
+        Migration {
+            from: 1,
+            renamed: [("gain_q8".to_string(), "gain".to_string())].into(),
+            offsets: [("gain_q8".to_string(), 4)].into(),
+            absent: vec!["serial".to_string()],
+        }
+    }
+
+    #[test]
+    fn maps_offsets_and_paths_to_the_older_version() {
+        let m = migration();
+        assert_eq!(m.offset("gain_q8", 8), Some(4));
+        assert_eq!(m.offset("id", 0), Some(0));
+        assert_eq!(m.offset("serial", 2), None);
+        assert_eq!(m.old_path("gain_q8"), "gain");
+        assert_eq!(m.old_path("id"), "id");
+    }
+
+    #[test]
+    fn check_reports_versions_and_unknown_fields() {
+        let paths = ["gain_q8".to_string(), "serial".to_string()];
+        assert!(migration().check(Some(2), &paths).is_empty());
+        assert_eq!(
+            migration().check(Some(1), &paths[..1]),
+            [
+                "migration from schema version 1 is not older than schema_version 1",
+                "migration from schema version 1 names unknown field 'serial'",
+            ]
+        );
+        assert_eq!(
+            migration().check(None, &paths),
+            ["migrations need header.schema_version"]
+        );
+    }
+}
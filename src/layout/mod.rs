@@ -1,36 +1,155 @@
 pub mod args;
 pub mod block;
+mod block_id;
+pub mod canonical;
+pub mod contract;
 mod conversions;
+pub mod convert;
 mod entry;
 pub mod error;
+pub mod expr;
 pub mod header;
+pub mod migration;
+mod pattern;
+pub mod policy;
+pub mod refs;
 pub mod settings;
+mod span;
+pub mod template;
+pub mod transform;
 pub mod used_values;
 pub mod value;
 
+pub(crate) use conversions::decode_byte_string;
+pub use entry::ScalarType;
+pub(crate) use entry::{LeafEntry, SizeSource};
+
+use crate::interpolate::{Expander, Expanding};
 use block::Config;
 use error::LayoutError;
+use serde::de::DeserializeOwned;
 use std::path::Path;
 
 pub fn load_layout(filename: &str) -> Result<Config, LayoutError> {
+    load_layout_with_vars(filename, &[])
+}
+
+/// Loads a layout, rendering `.j2` files as templates with `vars` first.
+pub fn load_layout_with_vars(
+    filename: &str,
+    vars: &[(String, serde_json::Value)],
+) -> Result<Config, LayoutError> {
+    load_layout_with_profile(filename, vars, None)
+}
+
+/// Like [`load_layout_with_vars`], switching to the settings of `profile`
+/// before header addresses are resolved.
+pub fn load_layout_with_profile(
+    filename: &str,
+    vars: &[(String, serde_json::Value)],
+    profile: Option<&str>,
+) -> Result<Config, LayoutError> {
     let text = std::fs::read_to_string(filename)
         .map_err(|_| LayoutError::FileError(format!("failed to open file: {}", filename)))?;
 
-    let ext = Path::new(filename)
-        .extension()
-        .and_then(|s| s.to_str())
-        .map(|s| s.to_ascii_lowercase())
-        .unwrap_or_default();
+    let extension = |path: &Path| {
+        path.extension()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_ascii_lowercase())
+            .unwrap_or_default()
+    };
+    let mut ext = extension(Path::new(filename));
+    let text = if ext == "j2" {
+        ext = extension(Path::new(
+            Path::new(filename).file_stem().unwrap_or_default(),
+        ));
+        template::render(&text, vars)
+            .map_err(|e| LayoutError::FileError(format!("{}: {}", filename, e)))?
+    } else {
+        text
+    };
 
-    let cfg: Config = match ext.as_str() {
-        "toml" => toml::from_str(&text).map_err(|e| {
-            LayoutError::FileError(format!("failed to parse file {}: {}", filename, e))
-        })?,
-        "yaml" | "yml" => serde_yaml::from_str(&text).map_err(|e| {
-            LayoutError::FileError(format!("failed to parse file {}: {}", filename, e))
+    let mut cfg: Config = parse_text(filename, &text, &ext, &crate::interpolate::expand_env)?;
+    if let Some(name) = profile
+        && !cfg.apply_profile(name)
+    {
+        let available: Vec<&str> = cfg.profiles.keys().map(|k| k.as_str()).collect();
+        return Err(LayoutError::ProfileNotFound {
+            profile: name.to_string(),
+            file: filename.to_string(),
+            available: if available.is_empty() {
+                "none".to_string()
+            } else {
+                available.join(", ")
+            },
+        });
+    }
+    cfg.resolve_header_expressions()
+        .map_err(|e| LayoutError::Expression(format!("{}: {}", filename, e)))?;
+
+    check_duplicate_paths(filename, &cfg)?;
+    check_migrations(filename, &cfg)?;
+
+    cfg.resolve_constants()?;
+    let base = Path::new(filename).parent().unwrap_or(Path::new(""));
+    for block in cfg.blocks.values_mut() {
+        block.resolve_file_paths(base);
+    }
+    Ok(cfg)
+}
+
+/// Deserializes layout `text` in the format named by the extension `ext`,
+/// passing each string value through `expand`.
+fn parse_text<T: DeserializeOwned>(
+    filename: &str,
+    text: &str,
+    ext: &str,
+    expand: &Expander,
+) -> Result<T, LayoutError> {
+    let parsed = match ext {
+        "toml" => toml::Deserializer::parse(text)
+            .and_then(|de| T::deserialize(Expanding::new(de, expand)))
+            .map_err(|e| {
+                let span = e.span().unwrap_or(0..0);
+                let (line, column) = span::offset_to_line_col(text, span.start);
+                span::parse_error(filename, text, line, column, span.len(), e.message())
+            })?,
+        "yaml" | "yml" => T::deserialize(Expanding::new(
+            serde_yaml::Deserializer::from_str(text),
+            expand,
+        ))
+        .map_err(|e| {
+            let (line, column) = e
+                .location()
+                .map(|l| (l.line(), l.column()))
+                .unwrap_or((1, 1));
+            let message = e.to_string();
+            span::parse_error(
+                filename,
+                text,
+                line,
+                column,
+                1,
+                span::strip_location_suffix(&message),
+            )
         })?,
-        "json" => serde_json::from_str(&text).map_err(|e| {
-            LayoutError::FileError(format!("failed to parse file {}: {}", filename, e))
+        "json" => {
+            let mut de = serde_json::Deserializer::from_str(text);
+            T::deserialize(Expanding::new(&mut de, expand)).and_then(|parsed| {
+                de.end()?;
+                Ok(parsed)
+            })
+        }
+        .map_err(|e| {
+            let message = e.to_string();
+            span::parse_error(
+                filename,
+                text,
+                e.line(),
+                e.column().max(1),
+                1,
+                span::strip_location_suffix(&message),
+            )
         })?,
         _ => {
             return Err(LayoutError::FileError(
@@ -38,6 +157,56 @@ pub fn load_layout(filename: &str) -> Result<Config, LayoutError> {
             ));
         }
     };
+    Ok(parsed)
+}
 
-    Ok(cfg)
+/// Rejects blocks where two leaves resolve to the same dotted path, listing
+/// every duplicate as `block.path`.
+fn check_duplicate_paths(filename: &str, cfg: &Config) -> Result<(), LayoutError> {
+    let mut paths = Vec::new();
+    for (name, block) in &cfg.blocks {
+        // Malformed field names are reported with full context at build time.
+        let Ok(duplicates) = block.duplicate_paths() else {
+            continue;
+        };
+        paths.extend(duplicates.into_iter().map(|p| format!("{}.{}", name, p)));
+    }
+    if paths.is_empty() {
+        Ok(())
+    } else {
+        Err(LayoutError::DuplicatePaths {
+            file: filename.to_string(),
+            paths,
+        })
+    }
+}
+
+/// Rejects migrations that do not fit their block, listing every problem as
+/// `block: problem`.
+fn check_migrations(filename: &str, cfg: &Config) -> Result<(), LayoutError> {
+    let mut problems = Vec::new();
+    for (name, block) in &cfg.blocks {
+        if block.migrations.is_empty() {
+            continue;
+        }
+        let Ok(paths) = block.leaf_paths() else {
+            continue;
+        };
+        for migration in &block.migrations {
+            problems.extend(
+                migration
+                    .check(block.header.schema_version, &paths)
+                    .into_iter()
+                    .map(|p| format!("{}: {}", name, p)),
+            );
+        }
+    }
+    match problems.is_empty() {
+        true => Ok(()),
+        false => Err(LayoutError::Migration(format!(
+            "{}: {}",
+            filename,
+            problems.join("; ")
+        ))),
+    }
 }
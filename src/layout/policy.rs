@@ -0,0 +1,184 @@
+use super::error::LayoutError;
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// How to handle a conversion issue during bytestream assembly.
+#[derive(Debug, Deserialize, ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Policy {
+    Error,
+    Warn,
+    #[default]
+    Allow,
+}
+
+/// Encoding for NaN and infinite floats. Unset, they are out of range and
+/// follow `out_of_range`, which casts them (NaN to 0 for integers) by default.
+///
+/// In layouts: `"error"`, `"raw"`, or `{ sentinel = 0x7FC00000 }`.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NonFinite {
+    /// Reject the value.
+    Error,
+    /// Store the IEEE bits unchanged; requires a 4- or 8-byte type.
+    Raw,
+    /// Store this bit pattern instead.
+    Sentinel(u64),
+}
+
+/// Parses `error`, `raw`, or a sentinel bit pattern (decimal or `0x` hex).
+pub fn parse_non_finite(text: &str) -> Result<NonFinite, String> {
+    match text {
+        "error" => Ok(NonFinite::Error),
+        "raw" => Ok(NonFinite::Raw),
+        _ => {
+            let parsed = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+                Some(hex) => u64::from_str_radix(hex, 16),
+                None => text.parse(),
+            };
+            parsed
+                .map(NonFinite::Sentinel)
+                .map_err(|_| format!("expected error, raw, or a sentinel value, got '{}'", text))
+        }
+    }
+}
+
+/// Kind of lossy conversion a policy applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionIssue {
+    /// Float with a fractional part to integer, or inexact integer to float.
+    LossyCast,
+    /// Value outside the range of the target type or bitfield.
+    OutOfRange,
+    /// Array, string, or blob shorter than its `size`.
+    ShortArray,
+}
+
+/// Per-issue policies from `[settings.policy]` or the command line.
+/// Unset fields fall back to the next layer, and finally to `allow`.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ConversionPolicy {
+    pub lossy_cast: Option<Policy>,
+    pub out_of_range: Option<Policy>,
+    pub short_array: Option<Policy>,
+    pub non_finite: Option<NonFinite>,
+    /// `--strict`: lossy and out-of-range casts left unset here are errors.
+    /// Kept apart from the fields so a block's `strict` can override it.
+    #[serde(skip)]
+    pub strict: bool,
+}
+
+impl ConversionPolicy {
+    /// Policy equivalent to `--strict`: lossy and out-of-range casts are errors.
+    pub fn strict() -> Self {
+        Self {
+            strict: true,
+            ..Default::default()
+        }
+    }
+
+    /// Merge this policy with a base policy. Self, `strict` included, takes
+    /// precedence.
+    pub fn resolve(&self, base: &ConversionPolicy) -> ConversionPolicy {
+        let layer = |issue| self.layer(issue).or(base.layer(issue));
+        ConversionPolicy {
+            lossy_cast: layer(ConversionIssue::LossyCast),
+            out_of_range: layer(ConversionIssue::OutOfRange),
+            short_array: layer(ConversionIssue::ShortArray),
+            non_finite: self.non_finite.or(base.non_finite),
+            strict: false,
+        }
+    }
+
+    /// This policy with `--strict` replaced by `strict`, if given.
+    pub fn with_strict(&self, strict: Option<bool>) -> ConversionPolicy {
+        ConversionPolicy {
+            strict: strict.unwrap_or(self.strict),
+            ..*self
+        }
+    }
+
+    pub fn get(&self, issue: ConversionIssue) -> Policy {
+        self.layer(issue).unwrap_or_default()
+    }
+
+    /// Policy this layer sets for `issue`, if any.
+    fn layer(&self, issue: ConversionIssue) -> Option<Policy> {
+        let strict = self.strict.then_some(Policy::Error);
+        match issue {
+            ConversionIssue::LossyCast => self.lossy_cast.or(strict),
+            ConversionIssue::OutOfRange => self.out_of_range.or(strict),
+            ConversionIssue::ShortArray => self.short_array,
+        }
+    }
+
+    /// Returns `err` if the issue is an error under this policy, otherwise
+    /// warns (if configured) and lets the caller fall back to the lax behaviour.
+    pub fn apply(
+        &self,
+        issue: ConversionIssue,
+        err: LayoutError,
+        field_path: &[String],
+    ) -> Result<(), LayoutError> {
+        match self.get(issue) {
+            Policy::Error => Err(err),
+            Policy::Warn => {
+                crate::warnings::warn(format!("{}: {}", field_path.join("."), err));
+                Ok(())
+            }
+            Policy::Allow => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_prefers_self_and_defaults_to_allow() {
+        let cli = ConversionPolicy {
+            lossy_cast: Some(Policy::Warn),
+            ..Default::default()
+        };
+        let merged = cli.resolve(&ConversionPolicy::strict());
+        assert_eq!(merged.get(ConversionIssue::LossyCast), Policy::Warn);
+        assert_eq!(merged.get(ConversionIssue::OutOfRange), Policy::Error);
+        assert_eq!(merged.get(ConversionIssue::ShortArray), Policy::Allow);
+    }
+
+    #[test]
+    fn with_strict_overrides_strict_but_not_explicit_policies() {
+        let cli = ConversionPolicy {
+            lossy_cast: Some(Policy::Warn),
+            strict: true,
+            ..Default::default()
+        };
+        let settings = ConversionPolicy {
+            out_of_range: Some(Policy::Warn),
+            ..Default::default()
+        };
+        let lax = cli.with_strict(Some(false)).resolve(&settings);
+        assert_eq!(lax.get(ConversionIssue::LossyCast), Policy::Warn);
+        assert_eq!(lax.get(ConversionIssue::OutOfRange), Policy::Warn);
+
+        let strict = ConversionPolicy::default()
+            .with_strict(Some(true))
+            .resolve(&settings);
+        assert_eq!(strict.get(ConversionIssue::OutOfRange), Policy::Error);
+        assert_eq!(cli.with_strict(None), cli);
+    }
+
+    #[test]
+    fn parse_non_finite_accepts_modes_and_sentinels() {
+        assert_eq!(parse_non_finite("raw"), Ok(NonFinite::Raw));
+        assert_eq!(
+            parse_non_finite("0x7FC00000"),
+            Ok(NonFinite::Sentinel(0x7FC0_0000))
+        );
+        assert_eq!(parse_non_finite("65535"), Ok(NonFinite::Sentinel(0xFFFF)));
+        assert!(parse_non_finite("nan").is_err());
+    }
+}
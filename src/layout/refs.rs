@@ -0,0 +1,22 @@
+/// Built output of other blocks in the same layout file, for entries that
+/// reference another block.
+pub trait BlockRefs {
+    /// Full image of the named block including its CRC, or `None` if it has not been built.
+    fn image(&self, name: &str) -> Option<Vec<u8>>;
+
+    /// CRC of the named block, or `None` if it has not been built or has no CRC.
+    fn crc(&self, name: &str) -> Option<u32>;
+}
+
+/// Resolver for builds without cross-block references.
+pub struct NoBlockRefs;
+
+impl BlockRefs for NoBlockRefs {
+    fn image(&self, _name: &str) -> Option<Vec<u8>> {
+        None
+    }
+
+    fn crc(&self, _name: &str) -> Option<u32> {
+        None
+    }
+}
@@ -1,3 +1,5 @@
+use super::expr::{BlockRef, Expr, Op};
+use super::policy::ConversionPolicy;
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
@@ -6,9 +8,159 @@ pub struct Settings {
     #[serde(default = "default_offset")]
     pub virtual_offset: u32,
     #[serde(default)]
-    pub word_addressing: bool,
+    pub word_addressing: WordAddressing,
     #[serde(default)]
     pub crc: Option<CrcConfig>,
+    #[serde(default)]
+    pub ecc: Option<EccConfig>,
+    #[serde(default)]
+    pub policy: ConversionPolicy,
+    /// Value of erased flash; blocks padding with anything else are warned about.
+    #[serde(default)]
+    pub erased_value: Option<u8>,
+    /// Placement of blocks that give no `start_address`.
+    #[serde(default)]
+    pub auto_place: Option<AutoPlace>,
+}
+
+/// Settings of a profile: any of the `[settings]` keys, each overriding the
+/// global value. `crc` and `policy` are merged key by key.
+#[derive(Debug, Deserialize, Default)]
+pub struct ProfileSettings {
+    pub endianness: Option<Endianness>,
+    pub virtual_offset: Option<u32>,
+    pub word_addressing: Option<WordAddressing>,
+    pub crc: Option<CrcConfig>,
+    pub ecc: Option<EccConfig>,
+    pub policy: Option<ConversionPolicy>,
+    pub erased_value: Option<u8>,
+    pub auto_place: Option<AutoPlace>,
+}
+
+impl Settings {
+    /// Applies the keys a profile sets over these settings.
+    pub fn apply(&mut self, profile: ProfileSettings) {
+        if let Some(endianness) = profile.endianness {
+            self.endianness = endianness;
+        }
+        if let Some(offset) = profile.virtual_offset {
+            self.virtual_offset = offset;
+        }
+        if let Some(word_addressing) = profile.word_addressing {
+            self.word_addressing = word_addressing;
+        }
+        if let Some(crc) = profile.crc {
+            self.crc = Some(crc.resolve(self.crc.as_ref()));
+        }
+        if let Some(ecc) = profile.ecc {
+            self.ecc = Some(ecc);
+        }
+        if let Some(policy) = profile.policy {
+            self.policy = policy.resolve(&self.policy);
+        }
+        if let Some(erased) = profile.erased_value {
+            self.erased_value = Some(erased);
+        }
+        if let Some(auto_place) = profile.auto_place {
+            self.auto_place = Some(auto_place);
+        }
+    }
+}
+
+/// Places blocks without a `start_address` one after another, in file order.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AutoPlace {
+    /// Lowest address of the first placed block.
+    pub base: u32,
+    /// Each placed block starts on a multiple of this.
+    #[serde(default = "default_alignment")]
+    pub alignment: u32,
+    /// Addresses left free between the end of one placed block and the next.
+    #[serde(default)]
+    pub gap: u32,
+}
+
+impl AutoPlace {
+    /// Start address of a placed block following the placed block `previous`,
+    /// or the first if there is none.
+    pub fn start_after(&self, previous: Option<&str>) -> Expr {
+        let next = match previous {
+            Some(block) => Expr::Binary(
+                Box::new(Expr::Ref(BlockRef::EndOf, block.to_string())),
+                Op::Add,
+                Box::new(Expr::Number(self.gap as u64)),
+            ),
+            None => Expr::Number(self.base as u64),
+        };
+        match self.alignment {
+            0 | 1 => next,
+            // (next + alignment - 1) / alignment * alignment
+            alignment => {
+                let alignment = Box::new(Expr::Number(alignment as u64));
+                let rounded = Expr::Binary(
+                    Box::new(next),
+                    Op::Add,
+                    Box::new(Expr::Number(self.alignment as u64 - 1)),
+                );
+                Expr::Binary(
+                    Box::new(Expr::Binary(Box::new(rounded), Op::Div, alignment.clone())),
+                    Op::Mul,
+                    alignment,
+                )
+            }
+        }
+    }
+}
+
+fn default_alignment() -> u32 {
+    1
+}
+
+/// Address unit of the target: `false` for bytes, `true` for 16-bit words, or
+/// the word size in bytes (`2` or `4`).
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "WordAddressingValue")]
+pub struct WordAddressing(u32);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WordAddressingValue {
+    Enabled(bool),
+    WordSize(u32),
+}
+
+impl TryFrom<WordAddressingValue> for WordAddressing {
+    type Error = String;
+
+    fn try_from(value: WordAddressingValue) -> Result<Self, Self::Error> {
+        match value {
+            WordAddressingValue::Enabled(false) => Ok(Self(1)),
+            WordAddressingValue::Enabled(true) => Ok(Self(2)),
+            WordAddressingValue::WordSize(size @ (2 | 4)) => Ok(Self(size)),
+            WordAddressingValue::WordSize(size) => Err(format!(
+                "word_addressing must be true, false, 2, or 4, got {}",
+                size
+            )),
+        }
+    }
+}
+
+impl Default for WordAddressing {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl WordAddressing {
+    pub fn enabled(self) -> bool {
+        self.0 > 1
+    }
+
+    /// Bytes per address: 1 for byte addressing.
+    pub fn word_size(self) -> u32 {
+        self.0
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, Copy)]
@@ -34,6 +186,8 @@ pub enum CrcArea {
 /// CRC location: keyword or absolute address.
 /// - `"end_data"`: CRC placed after data (4-byte aligned)
 /// - `"end_block"`: CRC in final 4 bytes of block
+/// - `"start"`: CRC in first 4 bytes of block, data follows it
+/// - `"end-8"`: CRC 8 bytes before the end of block
 /// - `0x8FF0`: Absolute address within block
 #[derive(Debug, Deserialize, Clone)]
 #[serde(untagged)]
@@ -42,9 +196,65 @@ pub enum CrcLocation {
     Address(u32),
 }
 
+impl CrcLocation {
+    /// True for `"start"`, where space for the CRC is reserved ahead of the data.
+    pub fn is_start(&self) -> bool {
+        matches!(self, CrcLocation::Keyword(kw) if kw == "start")
+    }
+
+    /// Distance of the CRC from the end of block for `"end-N"` (decimal or `0x` hex).
+    pub fn from_end(&self) -> Option<u32> {
+        let CrcLocation::Keyword(kw) = self else {
+            return None;
+        };
+        let n = kw.strip_prefix("end-")?.trim();
+        match n.strip_prefix("0x").or_else(|| n.strip_prefix("0X")) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok(),
+            None => n.parse().ok(),
+        }
+    }
+}
+
+/// Per-word error-correcting code added to the output image.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct EccConfig {
+    pub scheme: EccScheme,
+    /// Data bytes covered by each ECC byte: 1, 2, 4, or 8.
+    #[serde(default = "default_ecc_word_size")]
+    pub word_size: u32,
+    pub placement: EccPlacement,
+    /// With an address placement, the data address whose ECC byte is at that address.
+    #[serde(default)]
+    pub base: u32,
+}
+
+fn default_ecc_word_size() -> u32 {
+    8
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EccScheme {
+    /// Hamming code with an overall parity bit: corrects one bit, detects two.
+    Secded,
+    /// One even parity bit per data byte.
+    EvenParity,
+    /// One odd parity bit per data byte.
+    OddParity,
+}
+
+/// ECC placement: `"interleave"` after each word, or the address of a companion range.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum EccPlacement {
+    Keyword(String),
+    Address(u32),
+}
+
 /// Unified CRC configuration used in both `[settings.crc]` and `[header.crc]`.
 /// All fields are optional; header values override settings values.
-/// At settings level, `location` must be "end_data" or "end_block" (not an address).
+/// At settings level, `location` must be a keyword (not an address).
 #[derive(Debug, Deserialize, Clone, Default)]
 pub struct CrcConfig {
     pub location: Option<CrcLocation>,
@@ -54,6 +264,9 @@ pub struct CrcConfig {
     pub ref_in: Option<bool>,
     pub ref_out: Option<bool>,
     pub area: Option<CrcArea>,
+    /// External command that reads the covered bytes on stdin and prints the
+    /// checksum as hex; replaces the polynomial parameters.
+    pub command: Option<String>,
 }
 
 impl CrcConfig {
@@ -70,6 +283,10 @@ impl CrcConfig {
             ref_in: self.ref_in.or_else(|| base.and_then(|b| b.ref_in)),
             ref_out: self.ref_out.or_else(|| base.and_then(|b| b.ref_out)),
             area: self.area.or_else(|| base.and_then(|b| b.area)),
+            command: self
+                .command
+                .clone()
+                .or_else(|| base.and_then(|b| b.command.clone())),
         }
     }
 
@@ -80,12 +297,18 @@ impl CrcConfig {
 
     /// Returns true if all required CRC parameters are present.
     pub fn is_complete(&self) -> bool {
-        self.polynomial.is_some()
-            && self.start.is_some()
-            && self.xor_out.is_some()
-            && self.ref_in.is_some()
-            && self.ref_out.is_some()
-            && self.area.is_some()
+        self.has_algorithm() && self.area.is_some()
+    }
+
+    /// Returns true if the checksum itself can be computed: a command or the
+    /// full set of polynomial parameters.
+    pub fn has_algorithm(&self) -> bool {
+        self.command.is_some()
+            || (self.polynomial.is_some()
+                && self.start.is_some()
+                && self.xor_out.is_some()
+                && self.ref_in.is_some()
+                && self.ref_out.is_some())
     }
 }
 
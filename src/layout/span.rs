@@ -0,0 +1,79 @@
+use super::error::LayoutError;
+
+/// Builds a `LayoutError::Parse` from a 1-based line/column position.
+pub fn parse_error(
+    file: &str,
+    text: &str,
+    line: usize,
+    column: usize,
+    width: usize,
+    message: &str,
+) -> LayoutError {
+    LayoutError::Parse {
+        file: file.to_string(),
+        line,
+        column,
+        message: message.trim().to_string(),
+        snippet: render_snippet(text, line, column, width),
+    }
+}
+
+/// Converts a byte offset into a 1-based (line, column) pair.
+pub fn offset_to_line_col(text: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(text.len());
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+/// Renders the source line with a caret marker under the given column.
+fn render_snippet(text: &str, line: usize, column: usize, width: usize) -> String {
+    let Some(source_line) = text.lines().nth(line.saturating_sub(1)) else {
+        return String::new();
+    };
+    let gutter = " ".repeat(line.to_string().len());
+    let available = source_line.chars().count().saturating_sub(column - 1);
+    let marker = "^".repeat(width.clamp(1, available.max(1)));
+    format!(
+        "{gutter} |\n{line} | {source_line}\n{gutter} | {}{marker}",
+        " ".repeat(column.saturating_sub(1))
+    )
+}
+
+/// Strips a trailing " at line X column Y" suffix added by serde_json/serde_yaml.
+pub fn strip_location_suffix(message: &str) -> &str {
+    match message.rfind(" at line ") {
+        Some(idx) if message[idx..].contains(" column ") => &message[..idx],
+        _ => message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_maps_to_line_and_column() {
+        let text = "a = 1\nbb = 2\n";
+        assert_eq!(offset_to_line_col(text, 0), (1, 1));
+        assert_eq!(offset_to_line_col(text, 8), (2, 3));
+    }
+
+    #[test]
+    fn snippet_marks_column() {
+        let text = "first\nkey = \"x\"\n";
+        let snippet = render_snippet(text, 2, 7, 3);
+        assert_eq!(snippet, "  |\n2 | key = \"x\"\n  |       ^^^");
+    }
+
+    #[test]
+    fn strips_serde_location_suffix() {
+        assert_eq!(
+            strip_location_suffix("invalid type at line 3 column 4"),
+            "invalid type"
+        );
+        assert_eq!(strip_location_suffix("plain message"), "plain message");
+    }
+}
@@ -0,0 +1,84 @@
+use minijinja::value::{Serde, Value};
+use minijinja::{Environment, UndefinedBehavior};
+use std::collections::BTreeMap;
+
+/// Parses a `NAME[:TYPE]=VALUE` template variable. The value is a string
+/// unless `TYPE` is `int`, `float`, `bool`, or `json`.
+pub fn parse_define(text: &str) -> Result<(String, serde_json::Value), String> {
+    let (name, value) = match text.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => (name.trim(), value),
+        _ => return Err(format!("expected NAME[:TYPE]=VALUE, got '{}'", text)),
+    };
+    let Some((name, ty)) = name.split_once(':') else {
+        return Ok((name.to_string(), value.into()));
+    };
+    let invalid = || format!("invalid {} value for {}: '{}'", ty, name, value);
+    let value = match ty {
+        "int" => value.parse::<i64>().map_err(|_| invalid())?.into(),
+        "float" => value.parse::<f64>().map_err(|_| invalid())?.into(),
+        "bool" => value.parse::<bool>().map_err(|_| invalid())?.into(),
+        "json" => serde_json::from_str(value).map_err(|_| invalid())?,
+        _ => {
+            return Err(format!(
+                "unknown type '{}' for {}; expected int, float, bool, or json",
+                ty, name
+            ));
+        }
+    };
+    Ok((name.trim().to_string(), value))
+}
+
+/// Renders a layout template with minijinja. Undefined variables are an error.
+pub fn render(text: &str, vars: &[(String, serde_json::Value)]) -> Result<String, String> {
+    let mut env = Environment::new();
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    let context: BTreeMap<&str, &serde_json::Value> = vars
+        .iter()
+        .map(|(name, value)| (name.as_str(), value))
+        .collect();
+    env.render_str(text, Value::from(Serde(context)))
+        .map_err(|e| match e.line() {
+            Some(line) => format!("template error at line {}: {}", line, e),
+            None => format!("template error: {}", e),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_uses_typed_values_and_rejects_undefined() {
+        let vars = [
+            parse_define("CHANNELS:int=2").unwrap(),
+            parse_define("FAMILY=pro").unwrap(),
+        ];
+        let text = "{% for i in range(CHANNELS) %}c{{ i }}={{ FAMILY }};{% endfor %}";
+        assert_eq!(render(text, &vars).unwrap(), "c0=pro;c1=pro;");
+
+        let err = render("{{ MISSING }}", &vars).unwrap_err();
+        assert!(err.contains("undefined"), "{err}");
+    }
+
+    #[test]
+    fn parse_define_requires_name_and_value() {
+        assert_eq!(parse_define("A=b=c"), Ok(("A".to_string(), "b=c".into())));
+        assert!(parse_define("=1").is_err());
+        assert!(parse_define("A").is_err());
+    }
+
+    #[test]
+    fn parse_define_keeps_strings_unless_typed() {
+        assert_eq!(parse_define("V=1.10").unwrap().1, "1.10");
+        assert_eq!(parse_define("V=true").unwrap().1, "true");
+        assert_eq!(parse_define("V:int=8").unwrap().1, 8);
+        assert_eq!(parse_define("V:float=1.5").unwrap().1, 1.5);
+        assert_eq!(parse_define("V:bool=true").unwrap().1, true);
+        assert_eq!(
+            parse_define("V:json=[1, 2]").unwrap().1,
+            serde_json::json!([1, 2])
+        );
+        assert!(parse_define("V:int=x").is_err());
+        assert!(parse_define("V:list=1").is_err());
+    }
+}
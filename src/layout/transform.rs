@@ -0,0 +1,134 @@
+use super::error::LayoutError;
+use super::value::DataValue;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// One step of a field's `transform` chain, e.g. `"round"` or `"clamp(0,100)"`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Transform {
+    Round,
+    Floor,
+    Ceil,
+    Abs,
+    Scale(f64),
+    Offset(f64),
+    Clamp(f64, f64),
+}
+
+impl FromStr for Transform {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let (op, args) = match text.split_once('(') {
+            Some((op, rest)) => {
+                let inner = rest
+                    .strip_suffix(')')
+                    .ok_or_else(|| format!("transform '{}' is missing ')'", text))?;
+                let args = inner
+                    .split(',')
+                    .map(|a| {
+                        a.trim().parse::<f64>().map_err(|_| {
+                            format!("invalid number '{}' in transform '{}'", a.trim(), text)
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                (op.trim(), args)
+            }
+            None => (text, Vec::new()),
+        };
+        match (op, args.as_slice()) {
+            ("round", []) => Ok(Transform::Round),
+            ("floor", []) => Ok(Transform::Floor),
+            ("ceil", []) => Ok(Transform::Ceil),
+            ("abs", []) => Ok(Transform::Abs),
+            ("scale", [k]) => Ok(Transform::Scale(*k)),
+            ("offset", [k]) => Ok(Transform::Offset(*k)),
+            ("clamp", [lo, hi]) if lo <= hi => Ok(Transform::Clamp(*lo, *hi)),
+            ("clamp", [_, _]) => Err(format!("transform '{}' has min above max", text)),
+            _ => Err(format!(
+                "unknown transform '{}'; expected round, floor, ceil, abs, scale(k), offset(k), or clamp(min,max)",
+                text
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for Transform {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        text.parse()
+    }
+}
+
+impl Transform {
+    fn apply(self, v: f64) -> f64 {
+        match self {
+            Transform::Round => v.round(),
+            Transform::Floor => v.floor(),
+            Transform::Ceil => v.ceil(),
+            Transform::Abs => v.abs(),
+            Transform::Scale(k) => v * k,
+            Transform::Offset(k) => v + k,
+            Transform::Clamp(lo, hi) => v.clamp(lo, hi),
+        }
+    }
+}
+
+/// Runs `value` through `chain` in order. Values are computed as `f64`, so
+/// integers beyond 2^53 lose precision; an empty chain returns `value` unchanged.
+pub fn apply_chain(chain: &[Transform], value: &DataValue) -> Result<DataValue, LayoutError> {
+    if chain.is_empty() {
+        return Ok(value.clone());
+    }
+    let start = match value {
+        DataValue::Bool(b) => f64::from(u8::from(*b)),
+        DataValue::U64(v) => *v as f64,
+        DataValue::I64(v) => *v as f64,
+        DataValue::F64(v) => *v,
+        DataValue::Str(s) => {
+            return Err(LayoutError::DataValueExportFailed(format!(
+                "transform requires a numeric value, got string '{}'.",
+                s
+            )));
+        }
+    };
+    Ok(DataValue::F64(chain.iter().fold(start, |v, t| t.apply(v))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(steps: &[&str]) -> Vec<Transform> {
+        steps.iter().map(|s| s.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn applies_steps_in_order() {
+        let steps = chain(&["scale(0.1)", "round", "clamp(0, 100)"]);
+        let out = apply_chain(&steps, &DataValue::U64(1234)).unwrap();
+        assert!(matches!(out, DataValue::F64(v) if v == 100.0));
+        let out = apply_chain(&steps, &DataValue::I64(-46)).unwrap();
+        assert!(matches!(out, DataValue::F64(v) if v == 0.0));
+        let out = apply_chain(&chain(&["offset(-0.5)", "abs"]), &DataValue::F64(0.25)).unwrap();
+        assert!(matches!(out, DataValue::F64(v) if v == 0.25));
+    }
+
+    #[test]
+    fn rejects_bad_steps_and_strings() {
+        for bad in [
+            "trunc",
+            "scale",
+            "scale(x)",
+            "clamp(5,1)",
+            "clamp(1",
+            "round(2)",
+        ] {
+            assert!(bad.parse::<Transform>().is_err(), "{bad}");
+        }
+        assert!(apply_chain(&chain(&["round"]), &DataValue::Str("1".into())).is_err());
+    }
+}
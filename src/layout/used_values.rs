@@ -42,16 +42,49 @@ impl ValueSink for NoopValueSink {
     }
 }
 
+/// Records every value in `inner`, also collecting it when `collector` is set.
+pub struct TeeSink<'a> {
+    pub collector: Option<ValueCollector>,
+    pub inner: &'a mut dyn ValueSink,
+}
+
+impl ValueSink for TeeSink<'_> {
+    fn record_value(&mut self, path: &[String], value: Value) -> Result<(), LayoutError> {
+        if let Some(collector) = &mut self.collector {
+            collector.record_value(path, value.clone())?;
+        }
+        self.inner.record_value(path, value)
+    }
+}
+
+/// Compact JSON with object keys sorted, so equal values serialize identically.
+pub fn canonical_json(value: &Value) -> String {
+    fn sorted(value: &Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                Value::Object(
+                    keys.into_iter()
+                        .map(|k| (k.clone(), sorted(&map[k])))
+                        .collect(),
+                )
+            }
+            Value::Array(items) => Value::Array(items.iter().map(sorted).collect()),
+            other => other.clone(),
+        }
+    }
+    sorted(value).to_string()
+}
+
 pub fn data_value_to_json(value: &DataValue) -> Result<Value, LayoutError> {
     match value {
         DataValue::Bool(v) => Ok(Value::Number(Number::from(if *v { 1 } else { 0 }))),
         DataValue::U64(v) => Ok(Value::Number(Number::from(*v))),
         DataValue::I64(v) => Ok(Value::Number(Number::from(*v))),
-        DataValue::F64(v) => Number::from_f64(*v).map(Value::Number).ok_or_else(|| {
-            LayoutError::DataValueExportFailed(
-                "Non-finite float cannot be serialized to JSON.".to_string(),
-            )
-        }),
+        DataValue::F64(v) => Ok(Number::from_f64(*v)
+            .map(Value::Number)
+            .unwrap_or_else(|| Value::String(v.to_string()))),
         DataValue::Str(v) => Ok(Value::String(v.clone())),
     }
 }
@@ -4,7 +4,7 @@ use super::error::LayoutError;
 use super::settings::Endianness;
 use serde::Deserialize;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
 pub enum ValueSource {
     Single(DataValue),
@@ -2,6 +2,11 @@ pub mod args;
 pub mod commands;
 pub mod data;
 pub mod error;
+pub mod import;
+pub mod interpolate;
 pub mod layout;
 pub mod output;
+pub mod project;
+pub mod shell;
 pub mod visuals;
+pub mod warnings;
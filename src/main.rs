@@ -1,16 +1,128 @@
-use clap::Parser;
-
 use mint_cli::args::Args;
 use mint_cli::commands;
+use mint_cli::commands::stats::BuildStats;
 use mint_cli::data;
 use mint_cli::error::*;
+use mint_cli::import;
 use mint_cli::layout;
+use mint_cli::output;
+use mint_cli::output::args::{LogFormat, StatsView};
+use mint_cli::output::error::OutputError;
+use mint_cli::project::ProjectConfig;
 use mint_cli::visuals;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let (args, project) = match parse_args() {
+        Ok(parsed) => parsed,
+        Err(e) => return report(&e, LogFormat::Text),
+    };
+    if let Some(code) = &args.output.explain {
+        return explain_code(code);
+    }
+    mint_cli::warnings::set_json(args.output.log_format == LogFormat::Json);
+    match run(&args, project.as_ref()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => report(&e, args.output.log_format),
+    }
+}
+
+fn parse_args() -> Result<(Args, Option<ProjectConfig>), MintError> {
+    let project = ProjectConfig::discover(std::path::Path::new("."))?;
+    let matches = Args::command_with_project(project.as_ref()).get_matches();
+    Ok((Args::from_project_matches(&matches)?, project))
+}
 
-fn main() -> Result<(), MintError> {
-    let args = Args::parse();
+/// Prints `e` to stderr and returns the exit code for its category.
+fn report(e: &MintError, format: LogFormat) -> ExitCode {
+    match format {
+        LogFormat::Text => eprintln!("Error [{}]: {}", e.code(), e),
+        LogFormat::Json => eprintln!(
+            "{}",
+            serde_json::json!({
+                "level": "error",
+                "code": e.code(),
+                "exit_code": e.exit_code(),
+                "message": e.to_string(),
+            })
+        ),
+    }
+    ExitCode::from(e.exit_code())
+}
+
+fn explain_code(code: &str) -> ExitCode {
+    match explain(code) {
+        Some((code, text)) => {
+            println!("{}: {}", code, text);
+            ExitCode::SUCCESS
+        }
+        None => {
+            eprintln!("Unknown error code '{}'; codes look like MINT-L002.", code);
+            ExitCode::from(exit_code::USAGE)
+        }
+    }
+}
 
-    let data_source = data::create_data_source(&args.data)?;
+fn run(args: &Args, project: Option<&ProjectConfig>) -> Result<(), MintError> {
+    if let Some(path) = &args.output.rebuild {
+        let verified = commands::rebuild(path)?;
+        if !args.output.quiet {
+            println!(
+                "Rebuilt {} outputs byte-identical to {}",
+                verified,
+                path.display()
+            );
+        }
+        return Ok(());
+    }
+
+    if let Some(text) = import_layout(&args.layout)? {
+        print!("{}", text);
+        return Ok(());
+    }
+
+    if let Some(to) = args.layout.convert_to {
+        let [input] = args.layout.blocks.as_slice() else {
+            return Err(layout::error::LayoutError::InvalidBlockArgument(
+                "--convert-to takes exactly one layout file".to_string(),
+            )
+            .into());
+        };
+        print!("{}", layout::convert::convert_layout(&input.file, to)?);
+        return Ok(());
+    }
+
+    if args.layout.fmt {
+        let mut files: Vec<&str> = args.layout.blocks.iter().map(|b| b.file.as_str()).collect();
+        files.sort_unstable();
+        files.dedup();
+        let changed = layout::canonical::format_files(&files, args.layout.check)?;
+        if !args.output.quiet {
+            for file in changed {
+                println!("Formatted {}", file);
+            }
+        }
+        return Ok(());
+    }
+
+    if args.layout.data_contract {
+        println!("{:#}", commands::data_contract(args)?);
+        return Ok(());
+    }
+
+    if let Some(command) = args.output.pre_build.as_deref() {
+        commands::hooks::run_hook(command, &[])?;
+    }
+
+    if let Some(path) = &args.data.snapshot {
+        let versions = data::write_snapshot(&args.data, path)?;
+        if !args.output.quiet {
+            println!("Wrote {} versions to {}", versions, path.display());
+        }
+        return Ok(());
+    }
+
+    let data_source = commands::open_data_source(args)?;
 
     // Check if blocks are provided
     args.layout
@@ -18,15 +130,112 @@ fn main() -> Result<(), MintError> {
         .first()
         .ok_or(layout::error::LayoutError::NoBlocksProvided)?;
 
-    let stats = commands::build(&args, data_source.as_deref())?;
+    if args.output.compare.is_some() {
+        let comparisons = commands::compare(args, data_source.as_deref())?;
+        if !args.output.quiet {
+            visuals::print_compare(&comparisons);
+        }
+        let mismatched = comparisons.iter().filter(|c| !c.matches()).count();
+        if mismatched > 0 {
+            return Err(MintError::CompareMismatch(mismatched));
+        }
+        return Ok(());
+    }
+
+    if let Some(path) = &args.output.view {
+        let image = output::image::Image::read(path)?;
+        let fields = commands::view_fields(args, data_source.as_deref())?;
+        visuals::viewer::run(&mut visuals::viewer::Viewer::new(image, fields))
+            .map_err(|e| OutputError::FileError(format!("terminal error: {}", e)))?;
+        return Ok(());
+    }
+
+    if args.layout.suggest_packing {
+        let reports = commands::suggest_packing(args, data_source.as_deref())?;
+        if !args.output.quiet {
+            visuals::print_packing(&reports);
+        }
+        return Ok(());
+    }
+
+    if args.data.version_matrix.is_some() {
+        let results = commands::build_matrix(args, data_source.as_deref())?;
+        if !args.output.quiet {
+            if let Some(view) = args.output.stats {
+                for (stack, stats) in &results {
+                    println!("Version stack: {}", stack);
+                    print_stats(stats, view, args);
+                    println!();
+                }
+            }
+            visuals::print_matrix(&results);
+            for (stack, stats) in &results {
+                visuals::print_skipped(&stats.skipped, Some(stack));
+            }
+        }
+        return Ok(());
+    }
+
+    let invocation = commands::Invocation {
+        argv: std::env::args().skip(1).collect(),
+        project: project.cloned(),
+    };
+    let stats = commands::build_recorded(args, data_source.as_deref(), &invocation)?;
 
     if !args.output.quiet {
-        if args.output.stats {
-            visuals::print_detailed(&stats);
-        } else {
-            visuals::print_summary(&stats);
+        match args.output.stats {
+            Some(view) => print_stats(&stats, view, args),
+            None => visuals::print_summary(&stats),
+        }
+        if args.output.baseline.is_some() {
+            visuals::print_diff(&stats.changes);
         }
+        visuals::print_skipped(&stats.skipped, None);
     }
 
     Ok(())
 }
+
+/// Renders the layout skeleton requested by `--import-c` or `--import-elf`, if any.
+fn import_layout(args: &layout::args::LayoutArgs) -> Result<Option<String>, MintError> {
+    if let (Some(header), Some(name)) = (&args.import_c, &args.import_struct) {
+        let layout = import::c::import_header(header, name)?;
+        let source = header.display().to_string();
+        return Ok(Some(import::render_layout(
+            &layout,
+            &source,
+            0,
+            layout::settings::Endianness::Little,
+        )?));
+    }
+    if let Some(elf) = &args.import_elf {
+        let target = match (&args.import_struct, &args.import_symbol) {
+            (Some(name), _) => import::elf::ElfTarget::Struct(name),
+            (None, Some(name)) => import::elf::ElfTarget::Symbol(name),
+            (None, None) => return Ok(None),
+        };
+        let imported = import::elf::import_elf(elf, target)?;
+        let source = elf.display().to_string();
+        return Ok(Some(import::render_layout(
+            &imported.layout,
+            &source,
+            imported.address,
+            imported.endianness,
+        )?));
+    }
+    Ok(None)
+}
+
+fn print_stats(stats: &BuildStats, view: StatsView, args: &Args) {
+    match view {
+        StatsView::Blocks => {
+            visuals::print_detailed(stats);
+            visuals::print_padding(stats, args.output.padding_threshold);
+            visuals::print_fallbacks(stats);
+        }
+        StatsView::Fields => {
+            visuals::print_summary(stats);
+            visuals::print_fields(stats, args.output.stats_top);
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use crate::layout::ScalarType;
+use crate::layout::block::FieldSpan;
+use crate::layout::settings::Endianness;
+
+/// Bytes shown per dump line.
+const LINE_BYTES: usize = 16;
+
+/// Built block to dump, for [`render_annotated`].
+pub struct AnnotatedBlock<'a> {
+    pub label: &'a str,
+    pub start_address: u32,
+    /// Block bytes in layout order (before word-addressing swaps), CRC included.
+    pub image: &'a [u8],
+    pub spans: &'a [FieldSpan],
+    /// Offset of the CRC within `image`, if the block has one.
+    pub crc_offset: Option<usize>,
+    pub endianness: Endianness,
+}
+
+/// Hex dump of each block with a comment per field naming it and its decoded
+/// value. Bytes not covered by a field are marked as padding.
+pub fn render_annotated(blocks: &[AnnotatedBlock]) -> String {
+    let mut out = String::from("# Generated by mint\n");
+    for block in blocks {
+        out.push_str(&format!(
+            "\n# {} @ 0x{:08X}, {} bytes\n",
+            block.label,
+            block.start_address,
+            block.image.len()
+        ));
+        let little = matches!(block.endianness, Endianness::Little);
+        let mut regions: Vec<(usize, usize, String)> = block
+            .spans
+            .iter()
+            .map(|span| {
+                let bytes = &block.image[span.offset..span.offset + span.len];
+                let comment = format!(
+                    "{}: {} = {}",
+                    span.path,
+                    type_name(span),
+                    read_values(bytes, span.scalar_type, little)
+                );
+                (span.offset, span.len, comment)
+            })
+            .collect();
+        if let Some(offset) = block.crc_offset
+            && let Some(bytes) = block.image.get(offset..offset + 4)
+        {
+            let bytes: [u8; 4] = bytes.try_into().unwrap();
+            let crc = match little {
+                true => u32::from_le_bytes(bytes),
+                false => u32::from_be_bytes(bytes),
+            };
+            regions.push((offset, 4, format!("crc = 0x{:08X}", crc)));
+        }
+        regions.sort_by_key(|(offset, _, _)| *offset);
+
+        let mut pos = 0;
+        for (offset, len, comment) in regions {
+            if offset > pos {
+                dump(&mut out, block, pos, offset - pos, "padding");
+            }
+            dump(&mut out, block, offset, len, &comment);
+            pos = pos.max(offset + len);
+        }
+        if pos < block.image.len() {
+            dump(&mut out, block, pos, block.image.len() - pos, "padding");
+        }
+    }
+    out
+}
+
+/// Dump lines for `len` bytes at `offset`, with `comment` on the first.
+fn dump(out: &mut String, block: &AnnotatedBlock, offset: usize, len: usize, comment: &str) {
+    let bytes = &block.image[offset..offset + len];
+    for (i, line) in bytes.chunks(LINE_BYTES).enumerate() {
+        let hex: Vec<String> = line.iter().map(|b| format!("{:02X}", b)).collect();
+        let address = block.start_address as usize + offset + i * LINE_BYTES;
+        let text = format!(
+            "{:08X}  {:<width$}",
+            address,
+            hex.join(" "),
+            width = LINE_BYTES * 3 - 1
+        );
+        match i {
+            0 => out.push_str(&format!("{}  ; {}\n", text, comment)),
+            _ => out.push_str(&format!("{}\n", text.trim_end())),
+        }
+    }
+}
+
+fn type_name(span: &FieldSpan) -> String {
+    span.dims
+        .iter()
+        .fold(span.scalar_type.layout_name().to_string(), |name, dim| {
+            format!("{}[{}]", name, dim)
+        })
+}
+
+/// Formats `bytes` as values of `scalar_type`, bracketed if there are several.
+pub(crate) fn read_values(bytes: &[u8], scalar_type: ScalarType, little: bool) -> String {
+    let size = scalar_type.size_bytes();
+    let bits = size as u32 * 8;
+    let values: Vec<String> = bytes
+        .chunks(size)
+        .map(|chunk| {
+            let raw = read_raw(chunk, little);
+            match scalar_type {
+                ScalarType::F32 => f32::from_bits(raw as u32).to_string(),
+                ScalarType::F64 => f64::from_bits(raw).to_string(),
+                t if t.is_signed() => (((raw << (64 - bits)) as i64) >> (64 - bits)).to_string(),
+                _ => raw.to_string(),
+            }
+        })
+        .collect();
+    match values.len() {
+        1 => values[0].clone(),
+        _ => format!("[{}]", values.join(", ")),
+    }
+}
+
+/// Unsigned value of `chunk` in the given byte order.
+pub(crate) fn read_raw(chunk: &[u8], little: bool) -> u64 {
+    let fold = |acc: u64, b: &u8| (acc << 8) | u64::from(*b);
+    match little {
+        true => chunk.iter().rev().fold(0, fold),
+        false => chunk.iter().fold(0, fold),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dump_names_fields_padding_and_crc() {
+        let spans = [
+            FieldSpan {
+                path: "id".to_string(),
+                offset: 0,
+                len: 1,
+                scalar_type: ScalarType::U8,
+                dims: Vec::new(),
+            },
+            FieldSpan {
+                path: "gain".to_string(),
+                offset: 2,
+                len: 2,
+                scalar_type: ScalarType::I16,
+                dims: Vec::new(),
+            },
+        ];
+        let image = [7, 0xFF, 0xFE, 0xFF, 0x78, 0x56, 0x34, 0x12];
+        let text = render_annotated(&[AnnotatedBlock {
+            label: "cal",
+            start_address: 0x8000,
+            image: &image,
+            spans: &spans,
+            crc_offset: Some(4),
+            endianness: Endianness::Little,
+        }]);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[2], "# cal @ 0x00008000, 8 bytes");
+        assert!(lines[3].starts_with("00008000  07 "));
+        assert!(lines[3].ends_with("; id: u8 = 7"));
+        assert!(lines[4].ends_with("; padding"));
+        assert!(lines[5].starts_with("00008002  FE FF "));
+        assert!(lines[5].ends_with("; gain: i16 = -2"));
+        assert!(lines[6].ends_with("; crc = 0x12345678"));
+        assert_eq!(lines.len(), 7);
+    }
+
+    #[test]
+    fn long_fields_wrap_without_repeating_the_comment() {
+        let spans = [FieldSpan {
+            path: "table".to_string(),
+            offset: 0,
+            len: 20,
+            scalar_type: ScalarType::U8,
+            dims: vec![20],
+        }];
+        let image = [1u8; 20];
+        let text = render_annotated(&[AnnotatedBlock {
+            label: "cal",
+            start_address: 0,
+            image: &image,
+            spans: &spans,
+            crc_offset: None,
+            endianness: Endianness::Big,
+        }]);
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[3].contains("; table: u8[20] = [1, 1,"));
+        assert_eq!(lines[4], "00000010  01 01 01 01");
+    }
+}
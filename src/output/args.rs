@@ -1,13 +1,99 @@
 use std::path::PathBuf;
 
-use clap::{Args, ValueEnum};
+use clap::{Args, Parser, ValueEnum};
+use serde::Deserialize;
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+use crate::output::emitter::EmitOptions;
+use crate::output::flash_script::FlashTool;
+use crate::output::records::{RecordStyle, SrecOptions};
+use crate::output::regions::{OutputRegion, parse_region};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     Hex,
     Mot,
 }
 
+/// Intel HEX address record family.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum IhexAddressing {
+    /// No extended records up to 64 KiB, extended linear records above.
+    #[default]
+    Auto,
+    /// Data records only, 16-bit addresses (up to 64 KiB).
+    I8hex,
+    /// Extended segment address records, type 02 (up to 1 MiB).
+    I16hex,
+    /// Extended linear address records, type 04 (up to 4 GiB).
+    I32hex,
+}
+
+/// Case of hex digits in emitted records.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum HexCase {
+    #[default]
+    Upper,
+    Lower,
+}
+
+/// Document format of the used-values export.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Yaml => "yaml",
+            ExportFormat::Toml => "toml",
+        }
+    }
+}
+
+/// Table printed by `--stats`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum StatsView {
+    /// Build summary and one row per block.
+    Blocks,
+    /// Largest leaf entries of each block.
+    Fields,
+}
+
+/// What a build does when another build is writing to the same output directory.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum LockMode {
+    /// Fail at once with an error.
+    Fail,
+    /// Wait until the other build finishes.
+    Wait,
+    /// Do not lock the output directories.
+    #[default]
+    Off,
+}
+
+/// Format of errors and warnings printed to stderr.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+/// Line terminator between emitted records.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
 /// Output configuration for the build command.
 #[derive(Args, Debug, Clone)]
 pub struct OutputArgs {
@@ -26,11 +112,76 @@ pub struct OutputArgs {
         long,
         value_name = "N",
         default_value_t = 32u16,
-        value_parser = clap::value_parser!(u16).range(1..=64),
-        help = "Number of bytes per HEX data record (1..=64)",
+        value_parser = clap::value_parser!(u16).range(1..=255),
+        help = "Number of bytes per data record (1..=255; S-records allow up to 250-252)",
     )]
     pub record_width: u16,
 
+    /// Bytes per Intel HEX data record, overriding `--record-width`.
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u16).range(1..=255),
+        help = "Number of bytes per Intel HEX data record, overriding --record-width"
+    )]
+    pub hex_record_width: Option<u16>,
+
+    /// Bytes per S-record data record, overriding `--record-width`.
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u16).range(1..=252),
+        help = "Number of bytes per S-record data record, overriding --record-width"
+    )]
+    pub mot_record_width: Option<u16>,
+
+    /// Entry address written in the S-record termination record.
+    #[arg(
+        long,
+        value_name = "ADDR",
+        value_parser = parse_address,
+        help = "Write an S7/S8/S9 termination record with this entry address (decimal or 0x hex)"
+    )]
+    pub mot_entry_address: Option<u32>,
+
+    /// Offset added to S-record addresses when writing them.
+    #[arg(
+        long,
+        value_name = "OFFSET",
+        default_value = "0",
+        value_parser = parse_offset,
+        allow_hyphen_values = true,
+        help = "Add OFFSET (may be negative, decimal or 0x hex) to every S-record data address"
+    )]
+    pub mot_address_offset: i64,
+
+    /// Intel HEX extended address record family.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = IhexAddressing::Auto,
+        help = "Intel HEX address records: auto, i8hex (none), i16hex (extended segment), or i32hex (extended linear)"
+    )]
+    pub hex_addressing: IhexAddressing,
+
+    /// Case of hex digits in records.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = HexCase::Upper,
+        help = "Case of hex digits in records: upper or lower"
+    )]
+    pub hex_case: HexCase,
+
+    /// Line terminator between records.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = LineEnding::Lf,
+        help = "Line terminator between records: lf or crlf"
+    )]
+    pub line_ending: LineEnding,
+
     /// Output format: hex or mot.
     #[arg(
         long,
@@ -40,15 +191,341 @@ pub struct OutputArgs {
     )]
     pub format: OutputFormat,
 
+    /// Emit blocks in command-line order instead of by address.
+    #[arg(
+        long,
+        help = "Emit combined output records in block order instead of ascending address order"
+    )]
+    pub preserve_block_order: bool,
+
+    /// Also write each block to its own file in this directory.
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Also write each block to DIR/<block>.<format> alongside the combined output"
+    )]
+    pub block_out_dir: Option<PathBuf>,
+
+    /// Also write the CRC of each block, and nothing else, to this file.
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Also write a --format file holding only each block's CRC bytes at their CRC addresses"
+    )]
+    pub crc_out: Option<PathBuf>,
+
+    /// Only emit bytes that differ from this previously flashed image.
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Emit only records whose bytes differ from this baseline .hex/.mot image"
+    )]
+    pub baseline: Option<PathBuf>,
+
+    /// Alignment and size of the chunks compared against the baseline.
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1u32,
+        value_parser = clap::value_parser!(u32).range(1..),
+        requires = "baseline",
+        help = "Compare against the baseline in N-byte chunks aligned to multiples of N",
+    )]
+    pub baseline_granularity: u32,
+
+    /// Compare a raw read-back image against the build instead of writing output.
+    #[arg(
+        long,
+        value_name = "IMAGE",
+        conflicts_with = "version_matrix",
+        help = "Compare a raw device dump against the reference build and report differing fields and CRCs"
+    )]
+    pub compare: Option<PathBuf>,
+
+    /// Address of the first byte of the `--compare` image.
+    #[arg(
+        long,
+        value_name = "ADDR",
+        default_value = "0",
+        value_parser = parse_address,
+        requires = "compare",
+        help = "Address of the first byte of the --compare image (decimal or 0x hex)"
+    )]
+    pub base_address: u32,
+
+    /// Open a read-back image in the interactive field inspector.
+    #[arg(
+        long,
+        value_name = "IMAGE",
+        conflicts_with_all = ["compare", "version_matrix"],
+        help = "Browse a hex or S-record image in a terminal viewer that highlights each field's bytes"
+    )]
+    pub view: Option<PathBuf>,
+
+    /// Named address ranges written to separate files.
+    #[arg(
+        long = "region",
+        value_name = "NAME=START..END",
+        value_parser = parse_region,
+        conflicts_with_all = ["split", "flash_script"],
+        help = "Write blocks in START..END (END exclusive) to NAME.<ext> next to -o instead of one combined file (repeatable)"
+    )]
+    pub regions: Vec<OutputRegion>,
+
+    /// Split the combined output into even and odd lanes for dual-die flash.
+    #[arg(
+        long,
+        value_name = "BYTES",
+        value_parser = clap::value_parser!(u32).range(1..=8),
+        conflicts_with = "flash_script",
+        help = "Write the combined output as two files of alternating BYTES-wide words (<out>.even / <out>.odd)"
+    )]
+    pub split: Option<u32>,
+
+    /// Address of the first word of the even lane.
+    #[arg(
+        long,
+        value_name = "ADDR",
+        default_value = "0",
+        value_parser = parse_address,
+        requires = "split",
+        help = "Start address of the even --split lane (decimal or 0x hex)"
+    )]
+    pub split_even_base: u32,
+
+    /// Address of the first word of the odd lane.
+    #[arg(
+        long,
+        value_name = "ADDR",
+        default_value = "0",
+        value_parser = parse_address,
+        requires = "split",
+        help = "Start address of the odd --split lane (decimal or 0x hex)"
+    )]
+    pub split_odd_base: u32,
+
+    /// Write a debugger script that flashes the output.
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Write a TRACE32 (.cmm) or J-Link (.jlink) script that erases each block and flashes the output"
+    )]
+    pub flash_script: Option<PathBuf>,
+
+    /// Script format for `--flash-script`; inferred from its extension if unset.
+    #[arg(
+        long,
+        value_enum,
+        requires = "flash_script",
+        help = "Flash script format (default: from the --flash-script extension)"
+    )]
+    pub flash_tool: Option<FlashTool>,
+
+    /// Write a GNU ld fragment reserving each block's address range.
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Write a GNU ld script fragment that reserves each block's address range"
+    )]
+    pub linker_script: Option<PathBuf>,
+
+    /// Write a GDB script with a typed pointer per field.
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Write a GDB script defining a typed pointer convenience variable per field"
+    )]
+    pub gdb_script: Option<PathBuf>,
+
+    /// Write a hex dump of each block with a comment per field.
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Write a hex dump of each block annotated with the field and decoded value of each byte range"
+    )]
+    pub annotate: Option<PathBuf>,
+
+    /// Write a manifest for reproducing this build with `--rebuild`.
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with = "version_matrix",
+        help = "Write a JSON manifest of this build's arguments, input hashes, data snapshot, and output hashes for --rebuild"
+    )]
+    pub manifest: Option<PathBuf>,
+
+    /// Re-run the build recorded in a manifest and verify its outputs.
+    #[arg(
+        long,
+        value_name = "MANIFEST",
+        conflicts_with = "manifest",
+        help = "Re-run the build recorded by --manifest, verifying input hashes and that every output is byte-identical"
+    )]
+    pub rebuild: Option<PathBuf>,
+
+    /// Write SHA-256 checksums of every generated file.
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Write SHA-256 checksums of every generated file to FILE (sha256sum format)"
+    )]
+    pub checksums: Option<PathBuf>,
+
+    /// Shell command run before the data source is loaded.
+    #[arg(
+        long,
+        value_name = "CMD",
+        help = "Shell command to run before the data source is loaded"
+    )]
+    pub pre_build: Option<String>,
+
+    /// Shell command run after all outputs are written.
+    #[arg(
+        long,
+        value_name = "CMD",
+        help = "Shell command to run after outputs are written; gets MINT_OUTPUT, MINT_CHECKSUMS with --checksums and MINT_BUILD_MANIFEST with --manifest"
+    )]
+    pub post_build: Option<String>,
+
     /// Export used values as a JSON report.
     #[arg(long, value_name = "FILE", help = "Export used values as JSON")]
     pub export_json: Option<PathBuf>,
 
+    /// Document format of the used-values export.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = ExportFormat::Json,
+        help = "Format of the --export-json report: json, yaml, or toml"
+    )]
+    pub export_format: ExportFormat,
+
+    /// Write one used-values document per block into the `--export-json` directory.
+    #[arg(
+        long,
+        requires = "export_json",
+        help = "Treat --export-json as a directory and write one <block>.<format> document per block"
+    )]
+    pub export_per_block: bool,
+
     /// Show detailed build statistics.
-    #[arg(long, help = "Show detailed build statistics")]
-    pub stats: bool,
+    #[arg(
+        long,
+        value_enum,
+        value_name = "VIEW",
+        num_args = 0..=1,
+        default_missing_value = "blocks",
+        help = "Show detailed build statistics: blocks (default) or fields"
+    )]
+    pub stats: Option<StatsView>,
+
+    /// Number of fields per block listed by `--stats fields`.
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 10,
+        help = "Number of largest fields per block listed by --stats fields"
+    )]
+    pub stats_top: usize,
+
+    /// Padding bytes inside a block above which `--stats` flags it.
+    #[arg(
+        long,
+        value_name = "BYTES",
+        help = "Flag blocks with more than BYTES of alignment padding between entries in --stats"
+    )]
+    pub padding_threshold: Option<usize>,
+
+    /// Handling of concurrent builds writing to the same output directory.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = LockMode::Off,
+        help = "Lock the output directories against concurrent builds and, when another build holds them, fail or wait; off by default"
+    )]
+    pub lock: LockMode,
+
+    /// Build reproducibly: fixed block order and no recorded durations.
+    #[arg(
+        long,
+        help = "Build blocks one at a time in a fixed order and leave durations out of stats"
+    )]
+    pub deterministic: bool,
+
+    /// Format of errors and warnings on stderr.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = LogFormat::Text,
+        help = "Format of errors and warnings on stderr: text or json (one object per line, with error codes)"
+    )]
+    pub log_format: LogFormat,
+
+    /// Print the explanation of an error code and exit.
+    #[arg(
+        long,
+        value_name = "CODE",
+        help = "Explain an error code such as MINT-L002 and exit"
+    )]
+    pub explain: Option<String>,
+
+    /// Fail the build if any warning was emitted.
+    #[arg(
+        long,
+        help = "Fail with exit code 8, before writing outputs, if any warning was emitted"
+    )]
+    pub deny_warnings: bool,
 
     /// Suppress all output except errors.
     #[arg(long, help = "Suppress all output except errors")]
     pub quiet: bool,
 }
+
+impl Default for OutputArgs {
+    /// The values of a command line that passes no output flags.
+    fn default() -> Self {
+        crate::args::Args::parse_from(["mint"]).output
+    }
+}
+
+impl OutputArgs {
+    /// Emitter options for the record width, order, and style flags.
+    pub fn emit_options(&self) -> EmitOptions {
+        EmitOptions {
+            record_width: self.record_width as usize,
+            hex_record_width: self.hex_record_width.map(usize::from),
+            mot_record_width: self.mot_record_width.map(usize::from),
+            preserve_order: self.preserve_block_order,
+            style: RecordStyle {
+                case: self.hex_case,
+                line_ending: self.line_ending,
+            },
+            srec: SrecOptions {
+                entry_address: self.mot_entry_address,
+                address_offset: self.mot_address_offset,
+            },
+            ihex: self.hex_addressing,
+        }
+    }
+}
+
+/// Parses a decimal or `0x`-prefixed hex address.
+fn parse_address(text: &str) -> Result<u32, String> {
+    let parsed = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(&hex.replace('_', ""), 16),
+        None => text.replace('_', "").parse(),
+    };
+    parsed.map_err(|_| format!("expected a decimal or 0x hex address, got '{}'", text))
+}
+
+/// Parses a decimal or `0x`-prefixed hex offset with an optional `-` sign.
+fn parse_offset(text: &str) -> Result<i64, String> {
+    let (negative, magnitude) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let magnitude = parse_address(magnitude)
+        .map_err(|_| format!("expected a decimal or 0x hex offset, got '{}'", text))?
+        as i64;
+    Ok(if negative { -magnitude } else { magnitude })
+}
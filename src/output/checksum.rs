@@ -1,4 +1,56 @@
+use std::io::Write;
+use std::process::Stdio;
+
 use crate::layout::settings::CrcConfig;
+use crate::output::error::OutputError;
+use crate::shell::shell_command;
+
+/// Checksum of `data`: from the configured external command if set,
+/// otherwise the CRC32 defined by the polynomial parameters.
+pub fn compute_checksum(data: &[u8], crc_settings: &CrcConfig) -> Result<u32, OutputError> {
+    match &crc_settings.command {
+        Some(command) => run_checksum_command(command, data),
+        None => Ok(calculate_crc(data, crc_settings)),
+    }
+}
+
+/// Runs `command` through the platform shell with `data` on stdin and parses
+/// its stdout as a hex value (optionally `0x`-prefixed) of at most 32 bits.
+fn run_checksum_command(command: &str, data: &[u8]) -> Result<u32, OutputError> {
+    let fail = |msg: String| {
+        OutputError::HexOutputError(format!("Checksum command '{}' {}", command, msg))
+    };
+    let mut child = shell_command(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| fail(format!("could not be started: {}", e)))?;
+
+    // A command that ignores stdin may exit before reading it all.
+    let mut stdin = child.stdin.take().unwrap();
+    let written = stdin.write_all(data);
+    drop(stdin);
+    let output = child
+        .wait_with_output()
+        .map_err(|e| fail(format!("failed: {}", e)))?;
+    if !output.status.success() {
+        return Err(fail(format!("failed: {}", output.status)));
+    }
+    written.map_err(|e| fail(format!("did not read its input: {}", e)))?;
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    let text = text.trim();
+    let digits = text
+        .strip_prefix("0x")
+        .or_else(|| text.strip_prefix("0X"))
+        .unwrap_or(text);
+    u32::from_str_radix(digits, 16).map_err(|_| {
+        fail(format!(
+            "printed '{}', expected a hex value of at most 32 bits",
+            text
+        ))
+    })
+}
 
 /// Hand-rolled CRC32 calculation matching the crc crate's NoTable implementation.
 /// This removes the need for static state and allows each block to use its own CRC settings.
@@ -60,6 +112,23 @@ mod tests {
     use super::*;
     use crate::layout::settings::CrcArea;
 
+    #[cfg(unix)]
+    #[test]
+    fn checksum_command_reads_stdin_and_parses_hex() {
+        let config = CrcConfig {
+            command: Some("printf '0x%08x' $(wc -c)".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(compute_checksum(&[0; 300], &config).unwrap(), 300);
+
+        let config = CrcConfig {
+            command: Some("cat > /dev/null; echo nope".to_string()),
+            ..Default::default()
+        };
+        let err = compute_checksum(b"x", &config).unwrap_err().to_string();
+        assert!(err.contains("printed 'nope'"), "{err}");
+    }
+
     fn standard_crc_config() -> CrcConfig {
         CrcConfig {
             location: None,
@@ -69,6 +138,7 @@ mod tests {
             ref_in: Some(true),
             ref_out: Some(true),
             area: Some(CrcArea::Data),
+            command: None,
         }
     }
 
@@ -101,6 +171,7 @@ mod tests {
             ref_in: Some(false),
             ref_out: Some(false),
             area: Some(CrcArea::Data),
+            command: None,
         };
 
         // CRC-32/MPEG-2 parameters (non-reflected) over "123456789" should produce 0x0376E6E7
@@ -0,0 +1,67 @@
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::output::error::OutputError;
+
+/// Writes `sha256sum`-style lines for `files` to `path`. File names are
+/// relative to the directory of `path` where possible, so `sha256sum -c`
+/// can be run from there.
+pub fn write_checksums(path: &Path, files: &[PathBuf]) -> Result<(), OutputError> {
+    let base = path.parent().unwrap_or(Path::new(""));
+    let mut contents = String::new();
+    for file in files {
+        let bytes = std::fs::read(file).map_err(|e| {
+            OutputError::FileError(format!("failed to read {}: {}", file.display(), e))
+        })?;
+        let name = file.strip_prefix(base).unwrap_or(file);
+        contents.push_str(&format!(
+            "{}  {}\n",
+            sha256_hex(&bytes),
+            name.to_string_lossy().replace('\\', "/")
+        ));
+    }
+
+    if !base.as_os_str().is_empty() {
+        std::fs::create_dir_all(base).map_err(|e| {
+            OutputError::FileError(format!(
+                "failed to create directory {}: {}",
+                base.display(),
+                e
+            ))
+        })?;
+    }
+    std::fs::write(path, contents)
+        .map_err(|e| OutputError::FileError(format!("failed to write {}: {}", path.display(), e)))
+}
+
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums_are_relative_to_manifest_directory() {
+        let dir = std::env::temp_dir().join(format!("mint_checksums_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("blocks")).unwrap();
+        std::fs::write(dir.join("fw.hex"), b"abc").unwrap();
+        std::fs::write(dir.join("blocks/a.hex"), b"").unwrap();
+
+        let manifest = dir.join("SHA256SUMS");
+        write_checksums(&manifest, &[dir.join("fw.hex"), dir.join("blocks/a.hex")]).unwrap();
+        let text = std::fs::read_to_string(&manifest).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            text,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  fw.hex\n\
+             e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  blocks/a.hex\n"
+        );
+    }
+}
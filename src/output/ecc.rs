@@ -0,0 +1,202 @@
+use crate::layout::settings::{EccConfig, EccPlacement, EccScheme};
+use crate::output::DataRange;
+use crate::output::error::OutputError;
+
+/// Applies `config` to a block's range, returning the ranges to emit in its place.
+///
+/// The block image, with its CRC, is padded with `padding` to whole words. With
+/// `"interleave"`, each word is followed by its ECC byte in a single range at the
+/// block start. With an address placement, the range is kept and a companion range
+/// holds one ECC byte per word at `address + (word address - base) / word_size`.
+pub fn apply(
+    range: DataRange,
+    config: &EccConfig,
+    padding: u8,
+) -> Result<Vec<DataRange>, OutputError> {
+    let word_size = config.word_size as usize;
+    if !matches!(word_size, 1 | 2 | 4 | 8) {
+        return Err(OutputError::HexOutputError(format!(
+            "ECC word_size must be 1, 2, 4, or 8, got {}.",
+            word_size
+        )));
+    }
+    let mut image = range.image();
+    image.resize(image.len().next_multiple_of(word_size), padding);
+    let codes: Vec<u8> = image
+        .chunks_exact(word_size)
+        .map(|word| code(config.scheme, word))
+        .collect();
+
+    match &config.placement {
+        EccPlacement::Keyword(k) if k == "interleave" => {
+            let bytestream: Vec<u8> = image
+                .chunks_exact(word_size)
+                .zip(&codes)
+                .flat_map(|(word, code)| word.iter().chain(std::iter::once(code)))
+                .copied()
+                .collect();
+            let scale = |n: u32| n / config.word_size * (config.word_size + 1);
+            Ok(vec![DataRange {
+                start_address: range.start_address,
+                used_size: bytestream.len() as u32,
+                allocated_size: scale(range.allocated_size.next_multiple_of(config.word_size)),
+                bytestream,
+                crc_address: 0,
+                crc_bytestream: Vec::new(),
+                padding: range.padding,
+            }])
+        }
+        EccPlacement::Keyword(k) => Err(OutputError::HexOutputError(format!(
+            "Unknown ECC placement '{}'; use \"interleave\" or an address.",
+            k
+        ))),
+        EccPlacement::Address(address) => {
+            let offset = range
+                .start_address
+                .checked_sub(config.base)
+                .filter(|o| o.is_multiple_of(config.word_size))
+                .ok_or_else(|| {
+                    OutputError::HexOutputError(format!(
+                        "Block at 0x{:X} is not word-aligned above the ECC base 0x{:X}.",
+                        range.start_address, config.base
+                    ))
+                })?;
+            let ecc = DataRange {
+                start_address: address + offset / config.word_size,
+                used_size: codes.len() as u32,
+                allocated_size: range.allocated_size.div_ceil(config.word_size),
+                bytestream: codes,
+                crc_address: 0,
+                crc_bytestream: Vec::new(),
+                padding: range.padding,
+            };
+            Ok(vec![range, ecc])
+        }
+    }
+}
+
+/// ECC byte for one word. Data bit `8 * i + j` is bit `j` of byte `i`.
+pub fn code(scheme: EccScheme, word: &[u8]) -> u8 {
+    match scheme {
+        EccScheme::Secded => secded(word),
+        EccScheme::EvenParity => parity(word),
+        EccScheme::OddParity => !parity(word) & mask(word.len()),
+    }
+}
+
+fn mask(bits: usize) -> u8 {
+    ((1u16 << bits) - 1) as u8
+}
+
+/// Bit `i` makes byte `i` have an even number of set bits.
+fn parity(word: &[u8]) -> u8 {
+    word.iter()
+        .enumerate()
+        .fold(0, |acc, (i, b)| acc | (((b.count_ones() & 1) as u8) << i))
+}
+
+/// Hamming check bits in bits `0..r`, then an even parity bit over data and check bits.
+///
+/// Data bits fill the codeword positions that are not powers of two, in order
+/// from position 3; check bit `i` covers the positions with bit `i` set.
+fn secded(word: &[u8]) -> u8 {
+    let data_bits = word.len() * 8;
+    let mut check_bits = 0;
+    while (1usize << check_bits) < data_bits + check_bits + 1 {
+        check_bits += 1;
+    }
+
+    let mut syndrome = 0usize;
+    let mut ones = 0u32;
+    let mut position = 1usize;
+    for bit in 0..data_bits {
+        position += 1;
+        while position.is_power_of_two() {
+            position += 1;
+        }
+        if word[bit / 8] >> (bit % 8) & 1 == 1 {
+            syndrome ^= position;
+            ones += 1;
+        }
+    }
+    let check = syndrome as u8;
+    let overall = (ones + check.count_ones()) & 1;
+    check | (overall as u8) << check_bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(scheme: EccScheme, word_size: u32, placement: EccPlacement) -> EccConfig {
+        EccConfig {
+            scheme,
+            word_size,
+            placement,
+            base: 0x1000,
+        }
+    }
+
+    fn range(bytes: Vec<u8>) -> DataRange {
+        let len = bytes.len() as u32;
+        DataRange {
+            start_address: 0x1008,
+            bytestream: bytes,
+            crc_address: 0,
+            crc_bytestream: Vec::new(),
+            used_size: len,
+            allocated_size: len,
+            padding: 0xFF,
+        }
+    }
+
+    #[test]
+    fn secded_locates_single_bit_errors() {
+        let word = [0x5A, 0x00, 0xFF, 0x81];
+        let stored = secded(&word);
+        let mut syndromes = Vec::new();
+        for bit in 0..32 {
+            let mut received = word;
+            received[bit / 8] ^= 1 << (bit % 8);
+            let syndrome = ((secded(&received) ^ stored) & 0x3F) as usize;
+            assert!(syndrome > 2 && !syndrome.is_power_of_two(), "bit {bit}");
+            // Odd parity over the received data and the stored code flags one error.
+            let ones: u32 = received.iter().map(|b| b.count_ones()).sum();
+            assert_eq!((ones + stored.count_ones()) % 2, 1);
+            syndromes.push(syndrome);
+        }
+        syndromes.sort_unstable();
+        syndromes.dedup();
+        assert_eq!(syndromes.len(), 32);
+        assert_eq!(secded(&[0; 8]), 0);
+    }
+
+    #[test]
+    fn parity_bit_per_byte() {
+        assert_eq!(code(EccScheme::EvenParity, &[0x01, 0x03]), 0b01);
+        assert_eq!(code(EccScheme::OddParity, &[0x01, 0x03]), 0b10);
+    }
+
+    #[test]
+    fn interleave_and_companion_placement() {
+        let scheme = EccScheme::EvenParity;
+        let interleaved = apply(
+            range(vec![1, 3, 7]),
+            &config(scheme, 2, EccPlacement::Keyword("interleave".into())),
+            0xFF,
+        )
+        .unwrap();
+        assert_eq!(interleaved[0].start_address, 0x1008);
+        assert_eq!(interleaved[0].bytestream, [1, 3, 0b01, 7, 0xFF, 0b01]);
+
+        let companion = apply(
+            range(vec![1, 3, 7]),
+            &config(scheme, 2, EccPlacement::Address(0x8000)),
+            0xFF,
+        )
+        .unwrap();
+        assert_eq!(companion[0].bytestream, [1, 3, 7]);
+        assert_eq!(companion[1].start_address, 0x8004);
+        assert_eq!(companion[1].bytestream, [0b01, 0b01]);
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use crate::output::args::{IhexAddressing, OutputFormat};
+use crate::output::error::OutputError;
+use crate::output::records::{RecordStyle, SrecOptions};
+use crate::output::{DataRange, write_records};
+
+/// Options shared by all emitters, taken from the output arguments.
+#[derive(Debug, Clone, Copy)]
+pub struct EmitOptions {
+    pub record_width: usize,
+    /// Per-format overrides of `record_width`.
+    pub hex_record_width: Option<usize>,
+    pub mot_record_width: Option<usize>,
+    /// Emit ranges in the given order instead of by address.
+    pub preserve_order: bool,
+    pub style: RecordStyle,
+    pub srec: SrecOptions,
+    pub ihex: IhexAddressing,
+}
+
+impl EmitOptions {
+    /// Bytes per data record for `format`.
+    pub fn record_width_for(&self, format: OutputFormat) -> usize {
+        let width = match format {
+            OutputFormat::Hex => self.hex_record_width,
+            OutputFormat::Mot => self.mot_record_width,
+        };
+        width.unwrap_or(self.record_width)
+    }
+}
+
+/// Renders the combined block ranges into the `-o` output and any sidecar
+/// files next to it, streaming each into a writer.
+///
+/// Library users implement this for formats that are not built in and pass
+/// it to [`crate::commands::build_with_emitter`].
+pub trait ImageEmitter: Send + Sync {
+    /// Extensions of the sidecar files written next to the `-o` output.
+    fn sidecars(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Writes the `-o` output when `sidecar` is `None`, otherwise the
+    /// sidecar with that extension, into `out`.
+    fn emit(
+        &self,
+        ranges: &[DataRange],
+        opts: &EmitOptions,
+        sidecar: Option<&str>,
+        out: &mut dyn Write,
+    ) -> Result<(), OutputError>;
+}
+
+/// Built-in Intel HEX / Motorola S-record emitter.
+#[derive(Debug, Clone, Copy)]
+pub struct HexEmitter(pub OutputFormat);
+
+impl ImageEmitter for HexEmitter {
+    fn emit(
+        &self,
+        ranges: &[DataRange],
+        opts: &EmitOptions,
+        _sidecar: Option<&str>,
+        out: &mut dyn Write,
+    ) -> Result<(), OutputError> {
+        write_records(ranges, self.0, opts, out)
+    }
+}
+
+/// Emitters by name, starting with the built-in `hex` and `mot`.
+pub struct EmitterRegistry {
+    emitters: BTreeMap<String, Box<dyn ImageEmitter>>,
+}
+
+impl Default for EmitterRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            emitters: BTreeMap::new(),
+        };
+        registry.register("hex", HexEmitter(OutputFormat::Hex));
+        registry.register("mot", HexEmitter(OutputFormat::Mot));
+        registry
+    }
+}
+
+impl EmitterRegistry {
+    /// Adds or replaces the emitter for `name`.
+    pub fn register(&mut self, name: &str, emitter: impl ImageEmitter + 'static) {
+        self.emitters.insert(name.to_string(), Box::new(emitter));
+    }
+
+    pub fn get(&self, name: &str) -> Result<&dyn ImageEmitter, OutputError> {
+        self.emitters.get(name).map(|e| e.as_ref()).ok_or_else(|| {
+            OutputError::HexOutputError(format!(
+                "Unknown output format '{}'; available: {}",
+                name,
+                self.names().join(", ")
+            ))
+        })
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.emitters.keys().map(String::as_str).collect()
+    }
+
+    /// The built-in emitter for `format`.
+    pub fn for_format(&self, format: OutputFormat) -> Result<&dyn ImageEmitter, OutputError> {
+        self.get(match format {
+            OutputFormat::Hex => "hex",
+            OutputFormat::Mot => "mot",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Raw;
+
+    impl ImageEmitter for Raw {
+        fn emit(
+            &self,
+            ranges: &[DataRange],
+            _opts: &EmitOptions,
+            _sidecar: Option<&str>,
+            out: &mut dyn Write,
+        ) -> Result<(), OutputError> {
+            for range in ranges {
+                out.write_all(&range.image())
+                    .map_err(|e| OutputError::FileError(e.to_string()))?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn registry_holds_builtin_and_custom_emitters() {
+        let mut registry = EmitterRegistry::default();
+        registry.register("raw", Raw);
+        assert_eq!(registry.names(), ["hex", "mot", "raw"]);
+
+        let range = DataRange {
+            start_address: 0x10,
+            bytestream: vec![0xAB],
+            crc_address: 0,
+            crc_bytestream: Vec::new(),
+            used_size: 1,
+            allocated_size: 1,
+            padding: 0xFF,
+        };
+        let opts = EmitOptions {
+            record_width: 16,
+            hex_record_width: None,
+            mot_record_width: None,
+            preserve_order: false,
+            style: RecordStyle::default(),
+            srec: SrecOptions::default(),
+            ihex: IhexAddressing::default(),
+        };
+        let emit = |name: &str| {
+            let mut out = Vec::new();
+            registry
+                .get(name)
+                .unwrap()
+                .emit(std::slice::from_ref(&range), &opts, None, &mut out)
+                .unwrap();
+            out
+        };
+        assert_eq!(emit("raw"), [0xAB]);
+        assert!(emit("hex").starts_with(b":01001000AB"));
+
+        let err = registry.get("oem").err().unwrap().to_string();
+        assert!(err.contains("available: hex, mot, raw"), "{err}");
+    }
+}
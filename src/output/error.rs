@@ -8,6 +8,21 @@ pub enum OutputError {
     #[error("Hex output error: {0}.")]
     HexOutputError(String),
 
+    #[error("Block overflow: {0}.")]
+    BlockOverflow(String),
+
     #[error("Block memory overlap detected: {0}")]
     BlockOverlapError(String),
 }
+
+impl OutputError {
+    /// Stable code of this error; see `mint --explain`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            OutputError::FileError(_) => "MINT-O001",
+            OutputError::HexOutputError(_) => "MINT-O002",
+            OutputError::BlockOverflow(_) => "MINT-O003",
+            OutputError::BlockOverlapError(_) => "MINT-O004",
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+
+use crate::output::BlockRegion;
+use crate::output::args::OutputFormat;
+
+/// Debugger whose script format `--flash-script` writes.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum FlashTool {
+    /// Lauterbach TRACE32 PRACTICE script (`.cmm`).
+    Trace32,
+    /// SEGGER J-Link Commander script (`.jlink`).
+    Jlink,
+}
+
+impl FlashTool {
+    /// Tool implied by a script file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "cmm" => Some(Self::Trace32),
+            "jlink" => Some(Self::Jlink),
+            _ => None,
+        }
+    }
+}
+
+/// Script that erases each range (inclusive end addresses) and then programs
+/// `image`, given relative to the script's directory.
+pub fn render_flash_script(
+    tool: FlashTool,
+    erase: &[BlockRegion],
+    image: &str,
+    format: OutputFormat,
+) -> String {
+    let mut out = String::new();
+    match tool {
+        FlashTool::Trace32 => {
+            out.push_str("; Generated by mint\n");
+            for range in erase {
+                out.push_str(&format!(
+                    "; {}\nFLASH.Erase 0x{:X}--0x{:X}\n",
+                    range.label,
+                    range.start,
+                    range.start + range.length - 1
+                ));
+            }
+            let load = match format {
+                OutputFormat::Hex => "Data.LOAD.IntelHex",
+                OutputFormat::Mot => "Data.LOAD.S3record",
+            };
+            out.push_str("FLASH.Program ALL\n");
+            out.push_str(&format!("{} \"~~~~/{}\"\n", load, image));
+            out.push_str("FLASH.Program OFF\nENDDO\n");
+        }
+        FlashTool::Jlink => {
+            out.push_str("// Generated by mint\nr\nh\n");
+            for range in erase {
+                out.push_str(&format!(
+                    "// {}\nerase 0x{:X} 0x{:X}\n",
+                    range.label,
+                    range.start,
+                    range.start + range.length - 1
+                ));
+            }
+            out.push_str(&format!("loadfile {}\nr\ng\nexit\n", image));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_erase_each_block_then_load_image() {
+        let erase = [BlockRegion {
+            label: "config".to_string(),
+            start: 0x8000,
+            length: 0x100,
+        }];
+
+        let cmm = render_flash_script(FlashTool::Trace32, &erase, "fw.mot", OutputFormat::Mot);
+        assert!(
+            cmm.contains("; config\nFLASH.Erase 0x8000--0x80FF\n"),
+            "{cmm}"
+        );
+        assert!(
+            cmm.contains("Data.LOAD.S3record \"~~~~/fw.mot\"\n"),
+            "{cmm}"
+        );
+
+        let jlink = render_flash_script(FlashTool::Jlink, &erase, "fw.hex", OutputFormat::Hex);
+        assert!(
+            jlink.contains("// config\nerase 0x8000 0x80FF\n"),
+            "{jlink}"
+        );
+        assert!(jlink.ends_with("loadfile fw.hex\nr\ng\nexit\n"), "{jlink}");
+
+        assert_eq!(
+            FlashTool::from_path(Path::new("out/flash.CMM")),
+            Some(FlashTool::Trace32)
+        );
+        assert_eq!(FlashTool::from_path(Path::new("flash.txt")), None);
+    }
+}
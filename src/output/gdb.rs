@@ -0,0 +1,76 @@
+use crate::layout::block::FieldSpan;
+
+/// Fields of one built block, for [`render_gdb_script`].
+pub struct GdbBlock<'a> {
+    pub label: &'a str,
+    pub start_address: u32,
+    pub spans: &'a [FieldSpan],
+}
+
+/// GDB script defining a typed pointer convenience variable per field, named
+/// `$<block>_<path>` with other characters replaced by `_`.
+pub fn render_gdb_script(blocks: &[GdbBlock]) -> String {
+    let mut out = String::from("# Generated by mint\n");
+    for block in blocks {
+        out.push_str(&format!("\n# {}\n", block.label));
+        for span in block.spans {
+            let dims: String = span.dims.iter().map(|d| format!("[{}]", d)).collect();
+            let pointer = if dims.is_empty() {
+                format!("{} *", span.scalar_type.c_type())
+            } else {
+                format!("{} (*){}", span.scalar_type.c_type(), dims)
+            };
+            out.push_str(&format!(
+                "set ${} = ({}) 0x{:X}\n",
+                variable_name(block.label, &span.path),
+                pointer,
+                block.start_address as usize + span.offset
+            ));
+        }
+    }
+    out
+}
+
+fn variable_name(label: &str, path: &str) -> String {
+    format!("{}_{}", label, path)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::ScalarType;
+
+    #[test]
+    fn gdb_script_declares_typed_pointers() {
+        let spans = [
+            FieldSpan {
+                path: "limits.max".to_string(),
+                offset: 2,
+                len: 2,
+                scalar_type: ScalarType::U16,
+                dims: Vec::new(),
+            },
+            FieldSpan {
+                path: "matrix".to_string(),
+                offset: 4,
+                len: 16,
+                scalar_type: ScalarType::F32,
+                dims: vec![2, 2],
+            },
+        ];
+        let script = render_gdb_script(&[GdbBlock {
+            label: "cal",
+            start_address: 0x8000,
+            spans: &spans,
+        }]);
+        assert_eq!(
+            script,
+            "# Generated by mint\n\n# cal\n\
+             set $cal_limits_max = (uint16_t *) 0x8002\n\
+             set $cal_matrix = (float (*)[2][2]) 0x8004\n"
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::output::DataRange;
+use crate::output::error::OutputError;
+use crate::output::parse::{ParseError, parse_records};
+use crate::output::records;
+
+/// Sparse memory image read from an Intel HEX or S-record file.
+#[derive(Debug, Default)]
+pub struct Image {
+    /// Contiguous runs of bytes keyed by start address.
+    runs: BTreeMap<u64, Vec<u8>>,
+}
+
+impl Image {
+    /// Reads an Intel HEX or S-record file, detected from its first record.
+    pub fn read(path: &Path) -> Result<Self, OutputError> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            OutputError::FileError(format!("failed to read {}: {}", path.display(), e))
+        })?;
+        Self::parse(&text).map_err(|e| {
+            OutputError::HexOutputError(format!("failed to parse {}: {}", path.display(), e))
+        })
+    }
+
+    /// Parses Intel HEX or S-record text, validating every record.
+    pub(crate) fn parse(text: &str) -> Result<Self, ParseError> {
+        let (_, records) = parse_records(text)?;
+        let mut image = Image::default();
+        for record in records {
+            image.insert(record.address, &record.data);
+        }
+        Ok(image)
+    }
+
+    /// Adds `data` at `address`, merging with an adjacent preceding run.
+    fn insert(&mut self, address: u64, data: &[u8]) {
+        if let Some((start, run)) = self.runs.range_mut(..=address).next_back()
+            && start + run.len() as u64 == address
+        {
+            run.extend_from_slice(data);
+            return;
+        }
+        self.runs.insert(address, data.to_vec());
+    }
+
+    /// Byte at `address`, or `None` if the image does not cover it.
+    pub fn byte_at(&self, address: u64) -> Option<u8> {
+        let (start, run) = self.runs.range(..=address).next_back()?;
+        run.get((address - start) as usize).copied()
+    }
+}
+
+/// Ranges holding only the `granularity`-aligned chunks of `ranges` that differ
+/// from `baseline` (or are not covered by it), with CRCs folded in.
+pub fn diff_ranges(
+    ranges: &[DataRange],
+    baseline: &Image,
+    granularity: u64,
+) -> Result<Vec<DataRange>, OutputError> {
+    let mut out: Vec<DataRange> = Vec::new();
+    for segment in records::collect_segments(ranges, false)? {
+        let bytes = segment.parts.concat();
+        let mut offset = 0usize;
+        while offset < bytes.len() {
+            let address = segment.address + offset as u64;
+            let chunk_end = (address / granularity + 1) * granularity;
+            let len = ((chunk_end - address) as usize).min(bytes.len() - offset);
+            let chunk = &bytes[offset..offset + len];
+            let differs = chunk
+                .iter()
+                .enumerate()
+                .any(|(i, &b)| baseline.byte_at(address + i as u64) != Some(b));
+            if differs {
+                match out.last_mut() {
+                    Some(prev)
+                        if prev.start_address as u64 + prev.bytestream.len() as u64 == address =>
+                    {
+                        prev.bytestream.extend_from_slice(chunk);
+                    }
+                    _ => out.push(DataRange {
+                        start_address: address as u32,
+                        bytestream: chunk.to_vec(),
+                        crc_address: address as u32,
+                        crc_bytestream: Vec::new(),
+                        used_size: 0,
+                        allocated_size: 0,
+                        padding: 0xFF,
+                    }),
+                }
+            }
+            offset += len;
+        }
+    }
+    for range in &mut out {
+        range.used_size = range.bytestream.len() as u32;
+        range.allocated_size = range.used_size;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_ihex_and_srec() {
+        let ihex = Image::parse(":020000040001F9\n:020010001122BB\n:00000001FF\n").unwrap();
+        assert_eq!(ihex.byte_at(0x1_0010), Some(0x11));
+        assert_eq!(ihex.byte_at(0x1_0011), Some(0x22));
+        assert_eq!(ihex.byte_at(0x1_0012), None);
+
+        let srec = Image::parse("S1051000AABB85\nS9030000FC\n").unwrap();
+        assert_eq!(srec.byte_at(0x1001), Some(0xBB));
+
+        assert!(Image::parse(":020010001122BC\n").is_err());
+    }
+
+    #[test]
+    fn diff_keeps_only_changed_chunks() {
+        let baseline = Image::parse(":0400100001020304E2\n:00000001FF\n").unwrap();
+        let range = DataRange {
+            start_address: 0x10,
+            bytestream: vec![1, 2, 9, 4, 5, 6],
+            crc_address: 0x10,
+            crc_bytestream: Vec::new(),
+            used_size: 6,
+            allocated_size: 6,
+            padding: 0xFF,
+        };
+        let diff = diff_ranges(std::slice::from_ref(&range), &baseline, 1).unwrap();
+        let got: Vec<(u32, Vec<u8>)> = diff
+            .iter()
+            .map(|r| (r.start_address, r.bytestream.clone()))
+            .collect();
+        assert_eq!(got, [(0x12, vec![9]), (0x14, vec![5, 6])]);
+
+        let diff = diff_ranges(&[range], &baseline, 4).unwrap();
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].bytestream, [1, 2, 9, 4, 5, 6]);
+    }
+}
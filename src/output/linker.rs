@@ -0,0 +1,64 @@
+use crate::output::BlockRegion;
+
+/// GNU ld fragment with start/end/length symbols and a `NOLOAD` output
+/// section per block, so the linker rejects sections placed over a block.
+pub fn render_linker_script(regions: &[BlockRegion]) -> String {
+    let mut out = String::from("/* Generated by mint */\n\n");
+    for region in regions {
+        let name = symbol_name(&region.label);
+        out.push_str(&format!(
+            "__mint_{0}_start = 0x{1:X};\n__mint_{0}_end = 0x{2:X};\n__mint_{0}_length = 0x{3:X};\n",
+            name,
+            region.start,
+            region.start as u64 + region.length as u64,
+            region.length
+        ));
+    }
+
+    out.push_str("\nSECTIONS\n{\n");
+    for region in regions {
+        out.push_str(&format!(
+            "  .mint_{0} 0x{1:X} (NOLOAD) :\n  {{\n    . = . + 0x{2:X};\n  }}\n",
+            symbol_name(&region.label),
+            region.start,
+            region.length
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn symbol_name(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linker_script_reserves_each_block() {
+        let script = render_linker_script(&[BlockRegion {
+            label: "cal.toml:config".to_string(),
+            start: 0x8000,
+            length: 0x100,
+        }]);
+        assert!(
+            script.contains("__mint_cal_toml_config_start = 0x8000;\n"),
+            "{script}"
+        );
+        assert!(
+            script.contains("__mint_cal_toml_config_end = 0x8100;\n"),
+            "{script}"
+        );
+        assert!(
+            script.contains(
+                "  .mint_cal_toml_config 0x8000 (NOLOAD) :\n  {\n    . = . + 0x100;\n  }\n"
+            ),
+            "{script}"
+        );
+    }
+}
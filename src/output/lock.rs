@@ -0,0 +1,89 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+
+use crate::output::args::LockMode;
+use crate::output::error::OutputError;
+
+/// Lock file created in each directory a build writes to.
+pub const LOCK_FILE: &str = ".mint.lock";
+
+/// Exclusive lock on an output directory, released when dropped.
+#[derive(Debug)]
+pub struct OutputLock {
+    _file: File,
+}
+
+/// Directory the output file `path` is written to.
+pub fn output_dir(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// Locks every directory in `dirs` per `mode`: waits for another build to
+/// finish, fails at once if one is running, or does not lock at all.
+/// Directories are compared by canonical path, so each is locked once
+/// however it is spelled, and locked in sorted order so concurrent builds
+/// cannot deadlock.
+pub fn acquire(dirs: Vec<PathBuf>, mode: LockMode) -> Result<Vec<OutputLock>, OutputError> {
+    if mode == LockMode::Off {
+        return Ok(Vec::new());
+    }
+    let mut dirs = dirs
+        .iter()
+        .map(|dir| canonical_dir(dir))
+        .collect::<Result<Vec<_>, _>>()?;
+    dirs.sort();
+    dirs.dedup();
+    dirs.iter().map(|dir| lock_dir(dir, mode)).collect()
+}
+
+/// Creates `dir` if needed and resolves it to its canonical path.
+fn canonical_dir(dir: &Path) -> Result<PathBuf, OutputError> {
+    let fail = |e: std::io::Error| {
+        OutputError::FileError(format!("failed to lock {}: {}", dir.display(), e))
+    };
+    std::fs::create_dir_all(dir).map_err(fail)?;
+    dir.canonicalize().map_err(fail)
+}
+
+fn lock_dir(dir: &Path, mode: LockMode) -> Result<OutputLock, OutputError> {
+    let path = dir.join(LOCK_FILE);
+    let fail = |e: std::io::Error| {
+        OutputError::FileError(format!("failed to lock {}: {}", path.display(), e))
+    };
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .map_err(fail)?;
+
+    match mode {
+        LockMode::Wait => file.lock().map_err(fail)?,
+        _ => match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                return Err(OutputError::FileError(format!(
+                    "another mint build is writing to this directory (locked by {}); \
+                     retry when it finishes or use --lock wait to queue",
+                    path.display()
+                )));
+            }
+            Err(TryLockError::Error(e)) => return Err(fail(e)),
+        },
+    }
+    Ok(OutputLock { _file: file })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outputs_are_locked_by_directory() {
+        assert_eq!(output_dir(Path::new("build/fw.hex")), Path::new("build"));
+        assert_eq!(output_dir(Path::new("fw.hex")), Path::new("."));
+    }
+}
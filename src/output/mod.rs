@@ -1,22 +1,47 @@
+pub mod annotate;
 pub mod args;
 pub mod checksum;
+pub mod checksums;
+pub mod ecc;
+pub mod emitter;
 pub mod error;
+pub mod flash_script;
+pub mod gdb;
+pub mod image;
+pub mod linker;
+pub mod lock;
+pub mod parse;
+pub mod records;
+pub mod regions;
 pub mod report;
+pub mod split;
 
 use crate::layout::header::Header;
 use crate::layout::settings::{CrcArea, CrcConfig, CrcLocation, Endianness, Settings};
-use crate::output::args::OutputFormat;
+use crate::output::args::{IhexAddressing, OutputFormat};
+use crate::output::emitter::EmitOptions;
 use error::OutputError;
+use records::{LineSink, RecordStyle, SrecOptions};
 
-use bin_file::{BinFile, IHexFormat};
+use std::borrow::Cow;
+use std::io::Write;
+use std::ops::Range;
 
-/// Swaps bytes pairwise for word-addressing mode.
-fn byte_swap_inplace(bytes: &mut [u8]) {
-    for chunk in bytes.chunks_exact_mut(2) {
-        chunk.swap(0, 1);
+/// Reverses the bytes of each `word_size`-byte word for word-addressing mode.
+fn byte_swap_inplace(bytes: &mut [u8], word_size: u32) {
+    for chunk in bytes.chunks_exact_mut(word_size as usize) {
+        chunk.reverse();
     }
 }
 
+/// Address range allocated to a block, in output addresses.
+#[derive(Debug, Clone)]
+pub struct BlockRegion {
+    pub label: String,
+    pub start: u32,
+    pub length: u32,
+}
+
 #[derive(Debug, Clone)]
 pub struct DataRange {
     pub start_address: u32,
@@ -25,6 +50,47 @@ pub struct DataRange {
     pub crc_bytestream: Vec<u8>,
     pub used_size: u32,
     pub allocated_size: u32,
+    /// Fill byte for any gap between the bytestream and the CRC.
+    pub padding: u8,
+}
+
+impl DataRange {
+    /// Contiguous block image with the CRC written over the bytestream.
+    pub fn image(&self) -> Vec<u8> {
+        let mut image = self.bytestream.clone();
+        if !self.crc_bytestream.is_empty() {
+            let offset = (self.crc_address - self.start_address) as usize;
+            let end = offset + self.crc_bytestream.len();
+            image.resize(self.image_len(), self.padding);
+            image[offset..end].copy_from_slice(&self.crc_bytestream);
+        }
+        image
+    }
+
+    /// Length of [`DataRange::image`], without building it.
+    pub fn image_len(&self) -> usize {
+        if self.crc_bytestream.is_empty() {
+            return self.bytestream.len();
+        }
+        let offset = (self.crc_address - self.start_address) as usize;
+        self.bytestream
+            .len()
+            .max(offset + self.crc_bytestream.len())
+    }
+
+    /// Range holding only the CRC bytes at their address, if the block has a CRC.
+    pub fn crc_range(&self) -> Option<DataRange> {
+        let len = self.crc_bytestream.len() as u32;
+        (len > 0).then(|| DataRange {
+            start_address: self.crc_address,
+            bytestream: self.crc_bytestream.clone(),
+            crc_address: 0,
+            crc_bytestream: Vec::new(),
+            used_size: len,
+            allocated_size: len,
+            padding: self.padding,
+        })
+    }
 }
 
 /// Resolves CRC config from header + settings, validates location, returns offset + config.
@@ -35,11 +101,7 @@ fn resolve_crc(
     block_len_bytes: u32,
 ) -> Result<Option<(u32, CrcConfig)>, OutputError> {
     // Merge header CRC with settings CRC
-    let resolved = header
-        .crc
-        .as_ref()
-        .map(|hc| hc.resolve(settings.crc.as_ref()))
-        .unwrap_or_else(|| settings.crc.clone().unwrap_or_default());
+    let resolved = header.resolved_crc(settings.crc.as_ref());
 
     // Check if CRC is disabled
     if resolved.is_disabled() {
@@ -66,36 +128,47 @@ fn resolve_crc(
             let raw_offset = address.checked_sub(header.start_address).ok_or_else(|| {
                 OutputError::HexOutputError("CRC address before block start.".to_string())
             })?;
-            let crc_offset = if settings.word_addressing {
-                raw_offset.checked_mul(2).ok_or_else(|| {
+            let crc_offset = raw_offset
+                .checked_mul(settings.word_addressing.word_size())
+                .ok_or_else(|| {
                     OutputError::HexOutputError("CRC address overflows block length.".to_string())
-                })?
-            } else {
-                raw_offset
-            };
+                })?;
 
             if crc_offset < length as u32 {
-                return Err(OutputError::HexOutputError(
-                    "CRC overlaps with payload.".to_string(),
+                return Err(OutputError::BlockOverflow(
+                    "CRC overlaps with payload".to_string(),
                 ));
             }
 
             crc_offset
         }
+        CrcLocation::Keyword(_) if location.is_start() => 0,
+        CrcLocation::Keyword(_) if location.from_end().is_some() => {
+            let from_end = location.from_end().unwrap();
+            let offset = block_len_bytes.checked_sub(from_end).ok_or_else(|| {
+                OutputError::BlockOverflow("CRC location before block start".to_string())
+            })?;
+            if offset < length as u32 {
+                return Err(OutputError::BlockOverflow(
+                    "CRC at end-relative location overlaps with payload data".to_string(),
+                ));
+            }
+            offset
+        }
         CrcLocation::Keyword(option) => match option.as_str() {
             "end_data" => (length as u32 + 3) & !3,
             "end_block" => {
                 let offset = block_len_bytes.saturating_sub(4);
                 if offset < length as u32 {
-                    return Err(OutputError::HexOutputError(
-                        "CRC at end_block overlaps with payload data.".to_string(),
+                    return Err(OutputError::BlockOverflow(
+                        "CRC at end_block overlaps with payload data".to_string(),
                     ));
                 }
                 offset
             }
             _ => {
                 return Err(OutputError::HexOutputError(format!(
-                    "Invalid CRC location: '{}'. Use 'end_data', 'end_block', or an address.",
+                    "Invalid CRC location: '{}'. Use 'end_data', 'end_block', 'start', 'end-N', or an address.",
                     option
                 )));
             }
@@ -103,8 +176,8 @@ fn resolve_crc(
     };
 
     if block_len_bytes < crc_offset + 4 {
-        return Err(OutputError::HexOutputError(
-            "CRC location would overrun block.".to_string(),
+        return Err(OutputError::BlockOverflow(
+            "CRC location would overrun block".to_string(),
         ));
     }
 
@@ -125,23 +198,22 @@ pub fn bytestream_to_datarange(
     settings: &Settings,
     padding_bytes: u32,
 ) -> Result<DataRange, OutputError> {
-    let addr_mult: u32 = if settings.word_addressing { 2 } else { 1 };
+    let addr_mult = settings.word_addressing.word_size();
     let block_len_bytes = header.length.checked_mul(addr_mult).ok_or_else(|| {
         OutputError::HexOutputError("Block length overflows address space.".to_string())
     })?;
 
     if bytestream.len() > block_len_bytes as usize {
-        return Err(OutputError::HexOutputError(
-            "Bytestream length exceeds block length.".to_string(),
+        return Err(OutputError::BlockOverflow(
+            "Bytestream length exceeds block length".to_string(),
         ));
     }
 
     // Apply byte swap for word-addressing mode BEFORE CRC calculation
-    if settings.word_addressing {
-        if !bytestream.len().is_multiple_of(2) {
-            bytestream.push(header.padding);
-        }
-        byte_swap_inplace(&mut bytestream);
+    if settings.word_addressing.enabled() {
+        let len = bytestream.len().next_multiple_of(addr_mult as usize);
+        bytestream.resize(len, header.padding);
+        byte_swap_inplace(&mut bytestream, addr_mult);
     }
 
     // Resolve CRC configuration (location + settings) from header + global defaults
@@ -158,163 +230,340 @@ pub fn bytestream_to_datarange(
             crc_bytestream: Vec::new(),
             used_size,
             allocated_size: block_len_bytes,
+            padding: header.padding,
         });
     };
 
     used_size = used_size.saturating_add(4);
 
-    let area = crc_settings.area.unwrap(); // Safe: is_complete() verified
-    let is_end_block = matches!(
-        &crc_settings.location,
-        Some(CrcLocation::Keyword(kw)) if kw == "end_block"
-    );
-
-    // Prepare bytestream and compute CRC based on area
-    let crc_val = match area {
-        CrcArea::Data => {
-            // For end_data: pad to crc_offset before CRC calculation (aligning the CRC to be appended to the struct)
-            // For end_block: CRC over raw data, pad afterwards
-            if !is_end_block {
-                bytestream.resize(crc_offset as usize, header.padding);
-            }
-            let crc = checksum::calculate_crc(&bytestream, &crc_settings);
-            if is_end_block {
-                bytestream.resize(crc_offset as usize, header.padding);
-            }
-            crc
-        }
+    let data_len = bytestream.len();
+    let at_start = crc_settings.location.as_ref().is_some_and(|l| l.is_start());
+    let full_len = match crc_settings.area {
+        Some(CrcArea::Data) if at_start => data_len as u32,
+        Some(CrcArea::Data) => crc_offset,
+        _ => block_len_bytes,
+    };
+    bytestream.resize(full_len as usize, header.padding);
+    if crc_settings.area == Some(CrcArea::BlockZeroCrc) {
+        bytestream[crc_offset as usize..(crc_offset + 4) as usize].fill(0);
+    }
+    let excluded = header.crc_excluded(settings.crc.as_ref(), data_len);
+    let crc_val = area_crc(
+        &bytestream,
+        data_len,
+        crc_offset,
+        &crc_settings,
+        header.padding,
+        &excluded,
+    )?;
+
+    let crc_bytes = crc_to_bytes(crc_val, settings);
+
+    let start_address = header.start_address * addr_mult + settings.virtual_offset;
+
+    Ok(DataRange {
+        start_address,
+        bytestream,
+        crc_address: start_address + crc_offset,
+        crc_bytestream: crc_bytes.to_vec(),
+        used_size,
+        allocated_size: block_len_bytes,
+        padding: header.padding,
+    })
+}
+
+/// CRC over `image` for the configured area, leaving out the `excluded`
+/// byte ranges. `image` holds the block padded to the CRC (`data` area) or to
+/// the full block; `data_len` is the unpadded payload length.
+fn area_crc(
+    image: &[u8],
+    data_len: usize,
+    crc_offset: u32,
+    crc_settings: &CrcConfig,
+    padding: u8,
+    excluded: &[Range<usize>],
+) -> Result<u32, OutputError> {
+    let crc_range = crc_offset as usize..(crc_offset + 4) as usize;
+    let location = crc_settings.location.as_ref();
+    let is_end_block = location.is_some_and(|l| {
+        l.from_end().is_some() || matches!(l, CrcLocation::Keyword(kw) if kw == "end_block")
+    });
+    let mut image = Cow::Borrowed(image);
+    let mut omitted = excluded.to_vec();
+    let covered = match crc_settings.area.unwrap() {
+        // end_data covers the padding up to the CRC, end_block only the raw data,
+        // start the data after the reserved CRC bytes
+        CrcArea::Data if location.is_some_and(CrcLocation::is_start) => 4..data_len.max(4),
+        CrcArea::Data if is_end_block => 0..data_len,
+        CrcArea::Data => 0..crc_offset as usize,
         CrcArea::BlockZeroCrc => {
-            // Pad to full block, zero CRC location, then calculate
-            bytestream.resize(block_len_bytes as usize, header.padding);
-            bytestream[crc_offset as usize..(crc_offset + 4) as usize].fill(0);
-            checksum::calculate_crc(&bytestream, &crc_settings)
+            image.to_mut()[crc_range].fill(0);
+            0..image.len()
         }
         CrcArea::BlockPadCrc => {
-            // Pad to full block (CRC location contains padding), then calculate
-            bytestream.resize(block_len_bytes as usize, header.padding);
-            checksum::calculate_crc(&bytestream, &crc_settings)
+            image.to_mut()[crc_range].fill(padding);
+            0..image.len()
         }
         CrcArea::BlockOmitCrc => {
-            // Pad to full block, calculate CRC excluding CRC bytes
-            bytestream.resize(block_len_bytes as usize, header.padding);
-            let before = &bytestream[..crc_offset as usize];
-            let after = &bytestream[(crc_offset + 4) as usize..];
-            let combined: Vec<u8> = [before, after].concat();
-            checksum::calculate_crc(&combined, &crc_settings)
+            omitted.push(crc_range);
+            0..image.len()
         }
     };
+    if omitted.is_empty() {
+        return checksum::compute_checksum(&image[covered], crc_settings);
+    }
+    let bytes: Vec<u8> = covered
+        .filter(|i| !omitted.iter().any(|r| r.contains(i)))
+        .map(|i| image[i])
+        .collect();
+    checksum::compute_checksum(&bytes, crc_settings)
+}
 
+/// Stored CRC bytes in target byte order.
+fn crc_to_bytes(crc_val: u32, settings: &Settings) -> [u8; 4] {
     let mut crc_bytes: [u8; 4] = match settings.endianness {
         Endianness::Big => crc_val.to_be_bytes(),
         Endianness::Little => crc_val.to_le_bytes(),
     };
 
-    // Swap CRC bytes for word-addressing mode (bytestream already swapped above)
-    if settings.word_addressing {
-        byte_swap_inplace(&mut crc_bytes);
+    // Swap CRC bytes for word-addressing mode (bytestream already swapped)
+    if settings.word_addressing.enabled() {
+        byte_swap_inplace(&mut crc_bytes, settings.word_addressing.word_size());
     }
+    crc_bytes
+}
 
-    let start_address = header.start_address * addr_mult + settings.virtual_offset;
+/// CRC stored in a block read back from a device, compared with the CRC
+/// computed over the same bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrcCheck {
+    pub stored: [u8; 4],
+    pub computed: [u8; 4],
+}
 
-    Ok(DataRange {
-        start_address,
-        bytestream,
-        crc_address: start_address + crc_offset,
-        crc_bytestream: crc_bytes.to_vec(),
-        used_size,
-        allocated_size: block_len_bytes,
-    })
+impl CrcCheck {
+    pub fn matches(&self) -> bool {
+        self.stored == self.computed
+    }
+}
+
+/// Checks the CRC of a block image read back from a device. `image` is the
+/// full block as stored (`header.length` words); `data_len` is the length of
+/// the reference payload in bytes. Returns `None` if the block has no CRC.
+pub fn verify_crc(
+    image: &[u8],
+    data_len: usize,
+    header: &Header,
+    settings: &Settings,
+) -> Result<Option<CrcCheck>, OutputError> {
+    let Some((crc_offset, crc_settings)) =
+        resolve_crc(data_len, header, settings, image.len() as u32)?
+    else {
+        return Ok(None);
+    };
+    let excluded = header.crc_excluded(settings.crc.as_ref(), data_len);
+    let crc_val = area_crc(
+        image,
+        data_len,
+        crc_offset,
+        &crc_settings,
+        header.padding,
+        &excluded,
+    )?;
+    let offset = crc_offset as usize;
+    Ok(Some(CrcCheck {
+        stored: image[offset..offset + 4].try_into().unwrap(),
+        computed: crc_to_bytes(crc_val, settings),
+    }))
 }
 
+/// Renders ranges into an in-memory hex/mot string.
 pub fn emit_hex(
     ranges: &[DataRange],
     record_width: usize,
     format: OutputFormat,
 ) -> Result<String, OutputError> {
-    if !(1..=128).contains(&record_width) {
-        return Err(OutputError::HexOutputError(
-            "Record width must be between 1 and 128".to_string(),
-        ));
+    let opts = EmitOptions {
+        record_width,
+        hex_record_width: None,
+        mot_record_width: None,
+        preserve_order: false,
+        style: RecordStyle::default(),
+        srec: SrecOptions::default(),
+        ihex: IhexAddressing::default(),
+    };
+    render_records(ranges, format, &opts)
+}
+
+/// Represents an output file to be written.
+#[derive(Debug, Clone)]
+pub struct OutputFile {
+    pub ranges: Vec<DataRange>,
+    pub format: OutputFormat,
+    pub record_width: usize,
+    /// Emit ranges in the given order instead of by address.
+    pub preserve_order: bool,
+    pub style: RecordStyle,
+    pub srec: SrecOptions,
+    pub ihex: IhexAddressing,
+}
+
+impl OutputFile {
+    /// Render this file's contents as a hex/mot string.
+    pub fn render(&self) -> Result<String, OutputError> {
+        render_records(&self.ranges, self.format, &self.options())
     }
 
-    // Use bin_file to format output.
-    let mut bf = BinFile::new();
-    let mut max_end: usize = 0;
-
-    for range in ranges {
-        bf.add_bytes(
-            range.bytestream.as_slice(),
-            Some(range.start_address as usize),
-            false,
-        )
-        .map_err(|e| OutputError::HexOutputError(format!("Failed to add bytes: {}", e)))?;
-
-        // Only add CRC bytes if CRC is enabled for this block
-        if !range.crc_bytestream.is_empty() {
-            bf.add_bytes(
-                range.crc_bytestream.as_slice(),
-                Some(range.crc_address as usize),
-                true,
-            )
-            .map_err(|e| OutputError::HexOutputError(format!("Failed to add bytes: {}", e)))?;
-        }
+    /// Streams this file's contents as hex/mot records into `out`; see
+    /// [`write_records`].
+    pub fn write_to<W: Write>(&self, out: W) -> Result<(), OutputError> {
+        write_records(&self.ranges, self.format, &self.options(), out)
+    }
 
-        let end = (range.start_address as usize).saturating_add(range.bytestream.len());
-        if end > max_end {
-            max_end = end;
-        }
-        if !range.crc_bytestream.is_empty() {
-            let end = (range.crc_address as usize).saturating_add(range.crc_bytestream.len());
-            if end > max_end {
-                max_end = end;
-            }
+    fn options(&self) -> EmitOptions {
+        EmitOptions {
+            record_width: self.record_width,
+            hex_record_width: None,
+            mot_record_width: None,
+            preserve_order: self.preserve_order,
+            style: self.style,
+            srec: self.srec,
+            ihex: self.ihex,
         }
     }
+}
+
+/// Renders `ranges` as `format` records into a string; see [`write_records`].
+fn render_records(
+    ranges: &[DataRange],
+    format: OutputFormat,
+    opts: &EmitOptions,
+) -> Result<String, OutputError> {
+    let mut buf = Vec::new();
+    write_records(ranges, format, opts, &mut buf)?;
+    String::from_utf8(buf)
+        .map_err(|e| OutputError::HexOutputError(format!("Invalid output encoding: {}", e)))
+}
+
+/// Streams `ranges` as `format` records into `out`, one record at a time.
+/// Records are in ascending address order unless `opts.preserve_order` is
+/// set, in which case each range is emitted in turn.
+pub fn write_records<W: Write>(
+    ranges: &[DataRange],
+    format: OutputFormat,
+    opts: &EmitOptions,
+    out: W,
+) -> Result<(), OutputError> {
+    let record_width = opts.record_width_for(format);
+    if !(1..=255).contains(&record_width) {
+        return Err(OutputError::HexOutputError(
+            "Record width must be between 1 and 255".to_string(),
+        ));
+    }
+
+    let segments = records::collect_segments(ranges, opts.preserve_order)?;
+    let max_end = segments.iter().map(|s| s.end()).max().unwrap_or(0);
+    let mut sink = LineSink::with_style(out, opts.style);
 
     match format {
         OutputFormat::Hex => {
-            let ihex_format = if max_end <= 0x1_0000 {
-                IHexFormat::IHex16
-            } else {
-                IHexFormat::IHex32
+            // Extended address record type and the end address it reaches
+            let (extended, limit) = match opts.ihex {
+                IhexAddressing::Auto if max_end > 0x1_0000 => (Some(0x04), 0x1_0000_0000),
+                IhexAddressing::Auto | IhexAddressing::I8hex => (None, 0x1_0000),
+                IhexAddressing::I16hex => (Some(0x02), 0x10_0000),
+                IhexAddressing::I32hex => (Some(0x04), 0x1_0000_0000),
             };
-            let lines = bf.to_ihex(Some(record_width), ihex_format).map_err(|e| {
-                OutputError::HexOutputError(format!("Failed to generate Intel HEX: {}", e))
-            })?;
-            Ok(lines.join("\n"))
+            if max_end > limit {
+                return Err(OutputError::HexOutputError(format!(
+                    "Address exceeds Intel HEX range (0x{:X} for {:?} addressing)",
+                    limit, opts.ihex
+                )));
+            }
+            // Auto leaves the first 64 KiB without an extended record
+            let mut upper = (opts.ihex == IhexAddressing::Auto).then_some(0);
+            for segment in &segments {
+                records::for_each_chunk(segment, record_width, Some(0x1_0000), |addr, data| {
+                    if let Some(record_type) = extended
+                        && upper != Some(addr >> 16)
+                    {
+                        upper = Some(addr >> 16);
+                        let base = match record_type {
+                            0x02 => (addr >> 16) << 12,
+                            _ => addr >> 16,
+                        };
+                        records::write_ihex_record(
+                            &mut sink,
+                            record_type,
+                            0,
+                            &(base as u16).to_be_bytes(),
+                        )?;
+                    }
+                    records::write_ihex_record(&mut sink, 0x00, addr as u16, data)
+                })?;
+            }
+            records::write_ihex_record(&mut sink, 0x01, 0, &[])
         }
         OutputFormat::Mot => {
-            use bin_file::SRecordAddressLength;
-            let addr_len = if max_end <= 0x1_0000 {
-                SRecordAddressLength::Length16
+            let offset = |addr: u64| -> Result<u32, OutputError> {
+                u32::try_from(addr as i64 + opts.srec.address_offset).map_err(|_| {
+                    OutputError::HexOutputError(format!(
+                        "Address 0x{:X} moved outside the S-record range by the address offset",
+                        addr
+                    ))
+                })
+            };
+            let first = segments.iter().map(|s| s.address).min().unwrap_or(0);
+            offset(first)?;
+            let max_end = match max_end {
+                0 => 0,
+                end => offset(end - 1)? as u64 + 1,
+            };
+            let max_end = max_end.max(opts.srec.entry_address.map_or(0, |a| a as u64 + 1));
+            let (data_type, addr_bytes) = if max_end <= 0x1_0000 {
+                (1, 2)
             } else if max_end <= 0x100_0000 {
-                SRecordAddressLength::Length24
+                (2, 3)
             } else {
-                SRecordAddressLength::Length32
+                (3, 4)
             };
-            let lines = bf.to_srec(Some(record_width), addr_len).map_err(|e| {
-                OutputError::HexOutputError(format!("Failed to generate S-Record: {}", e))
-            })?;
-            Ok(lines.join("\n"))
+            // The count byte covers the address, data, and checksum.
+            let max_width = 255 - addr_bytes - 1;
+            if record_width > max_width {
+                return Err(OutputError::HexOutputError(format!(
+                    "Record width {} exceeds the S{} record maximum of {} bytes",
+                    record_width, data_type, max_width
+                )));
+            }
+            let mut count: u32 = 0;
+            for segment in &segments {
+                records::for_each_chunk(segment, record_width, None, |addr, data| {
+                    count += 1;
+                    records::write_srec_record(
+                        &mut sink,
+                        data_type,
+                        offset(addr)?,
+                        addr_bytes,
+                        data,
+                    )
+                })?;
+            }
+            if count <= 0xFFFF {
+                records::write_srec_record(&mut sink, 5, count, 2, &[])?;
+            } else {
+                records::write_srec_record(&mut sink, 6, count, 3, &[])?;
+            }
+            match opts.srec.entry_address {
+                // S1 data ends with S9, S2 with S8, S3 with S7.
+                Some(entry) => {
+                    records::write_srec_record(&mut sink, 10 - data_type, entry, addr_bytes, &[])
+                }
+                None => Ok(()),
+            }
         }
     }
 }
 
-/// Represents an output file to be written.
-#[derive(Debug, Clone)]
-pub struct OutputFile {
-    pub ranges: Vec<DataRange>,
-    pub format: OutputFormat,
-    pub record_width: usize,
-}
-
-impl OutputFile {
-    /// Render this file's contents as a hex/mot string.
-    pub fn render(&self) -> Result<String, OutputError> {
-        emit_hex(&self.ranges, self.record_width, self.format)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -332,6 +581,7 @@ mod tests {
             ref_in: Some(true),
             ref_out: Some(true),
             area: Some(CrcArea::Data),
+            command: None,
         }
     }
 
@@ -339,8 +589,12 @@ mod tests {
         Settings {
             endianness: Endianness::Little,
             virtual_offset: 0,
-            word_addressing: false,
+            word_addressing: Default::default(),
+            ecc: None,
             crc: Some(sample_crc_config()),
+            policy: Default::default(),
+            erased_value: None,
+            auto_place: None,
         }
     }
 
@@ -353,6 +607,13 @@ mod tests {
                 ..Default::default()
             }),
             padding: 0xFF,
+            tags: Vec::new(),
+            format: None,
+            schema_version: None,
+            guard: None,
+            strict: None,
+            data_source: None,
+            expressions: Default::default(),
         }
     }
 
@@ -362,6 +623,13 @@ mod tests {
             length: len,
             crc: None,
             padding: 0xFF,
+            tags: Vec::new(),
+            format: None,
+            schema_version: None,
+            guard: None,
+            strict: None,
+            data_source: None,
+            expressions: Default::default(),
         }
     }
 
@@ -529,6 +797,57 @@ mod tests {
         assert!(!dr.crc_bytestream.is_empty());
     }
 
+    #[test]
+    fn start_crc_covers_data_after_it() {
+        let settings = sample_settings();
+        let header = Header {
+            crc: Some(CrcConfig {
+                location: Some(CrcLocation::Keyword("start".to_string())),
+                ..Default::default()
+            }),
+            ..sample_header(32)
+        };
+
+        // First 4 bytes are the reserve emitted by the block builder
+        let bytestream = vec![0xFF, 0xFF, 0xFF, 0xFF, 1, 2, 3, 4];
+        let dr = bytestream_to_datarange(bytestream, &header, &settings, 4)
+            .expect("data range generation failed");
+
+        assert_eq!(dr.crc_address, dr.start_address);
+        assert_eq!(dr.bytestream.len(), 8);
+        assert_eq!(dr.used_size, 8);
+        let crc =
+            checksum::compute_checksum(&[1, 2, 3, 4], &header.resolved_crc(settings.crc.as_ref()))
+                .unwrap();
+        assert_eq!(dr.crc_bytestream, crc_to_bytes(crc, &settings));
+    }
+
+    #[test]
+    fn end_relative_crc_location() {
+        let settings = sample_settings();
+        let header = Header {
+            crc: Some(CrcConfig {
+                location: Some(CrcLocation::Keyword("end-8".to_string())),
+                ..Default::default()
+            }),
+            ..sample_header(32)
+        };
+
+        let dr = bytestream_to_datarange(vec![1, 2, 3, 4], &header, &settings, 0)
+            .expect("data range generation failed");
+        assert_eq!(dr.crc_address, 24);
+
+        let header = Header {
+            crc: Some(CrcConfig {
+                location: Some(CrcLocation::Keyword("end-0x1E".to_string())),
+                ..Default::default()
+            }),
+            ..sample_header(32)
+        };
+        let err = bytestream_to_datarange(vec![1, 2, 3, 4], &header, &settings, 0).unwrap_err();
+        assert!(err.to_string().contains("overlaps"));
+    }
+
     #[test]
     fn crc_location_set_but_settings_missing_errors() {
         let settings = Settings {
@@ -659,6 +978,13 @@ mod tests {
                 ..Default::default()
             }),
             padding: 0xFF,
+            tags: Vec::new(),
+            format: None,
+            schema_version: None,
+            guard: None,
+            strict: None,
+            data_source: None,
+            expressions: Default::default(),
         };
 
         let bytestream = vec![1u8, 2, 3, 4];
@@ -683,6 +1009,13 @@ mod tests {
                 ..Default::default()
             }),
             padding: 0xFF,
+            tags: Vec::new(),
+            format: None,
+            schema_version: None,
+            guard: None,
+            strict: None,
+            data_source: None,
+            expressions: Default::default(),
         };
 
         let bytestream = vec![1u8; 16]; // Data fills entire block
@@ -696,4 +1029,129 @@ mod tests {
                 .contains("overlaps with payload")
         );
     }
+
+    #[test]
+    fn image_places_crc_after_data() {
+        let settings = sample_settings();
+        let header = sample_header(16);
+        let dr = bytestream_to_datarange(vec![1, 2, 3], &header, &settings, 0).unwrap();
+        let image = dr.image();
+        assert_eq!(image.len(), 8);
+        assert_eq!(&image[..4], &[1, 2, 3, 0xFF]);
+        assert_eq!(&image[4..], dr.crc_bytestream.as_slice());
+    }
+
+    #[test]
+    fn image_fills_gap_before_crc_with_padding() {
+        let dr = DataRange {
+            start_address: 0x10,
+            bytestream: vec![1, 2],
+            crc_address: 0x14,
+            crc_bytestream: vec![9; 4],
+            used_size: 6,
+            allocated_size: 8,
+            padding: 0xAA,
+        };
+        assert_eq!(dr.image(), [1, 2, 0xAA, 0xAA, 9, 9, 9, 9]);
+    }
+
+    #[test]
+    fn verify_crc_recomputes_each_area() {
+        let mut settings = sample_settings();
+        for area in [
+            CrcArea::Data,
+            CrcArea::BlockZeroCrc,
+            CrcArea::BlockPadCrc,
+            CrcArea::BlockOmitCrc,
+        ] {
+            settings.crc.as_mut().unwrap().area = Some(area);
+            let header = sample_header(16);
+            let dr = bytestream_to_datarange(vec![1, 2, 3], &header, &settings, 0).unwrap();
+            let mut device = dr.image();
+            device.resize(16, 0xFF);
+
+            let check = verify_crc(&device, 3, &header, &settings).unwrap().unwrap();
+            assert!(check.matches(), "{area:?}");
+
+            device[1] ^= 0x01;
+            let check = verify_crc(&device, 3, &header, &settings).unwrap().unwrap();
+            assert!(!check.matches(), "{area:?}");
+        }
+    }
+
+    #[test]
+    fn ihex_addressing_selects_extended_record_family() {
+        let render = |start_address: u32, ihex: IhexAddressing| {
+            OutputFile {
+                ranges: vec![DataRange {
+                    start_address,
+                    bytestream: vec![0xAB],
+                    crc_address: 0,
+                    crc_bytestream: Vec::new(),
+                    used_size: 1,
+                    allocated_size: 1,
+                    padding: 0xFF,
+                }],
+                format: OutputFormat::Hex,
+                record_width: 16,
+                preserve_order: false,
+                style: RecordStyle::default(),
+                srec: SrecOptions::default(),
+                ihex,
+            }
+            .render()
+        };
+
+        // Auto keeps low images free of extended records
+        let auto = render(0x10, IhexAddressing::Auto).unwrap();
+        assert!(auto.starts_with(":01001000AB"), "{auto}");
+
+        let linear = render(0x10, IhexAddressing::I32hex).unwrap();
+        assert!(
+            linear.starts_with(":020000040000FA\n:01001000AB"),
+            "{linear}"
+        );
+
+        let segment = render(0x2_0010, IhexAddressing::I16hex).unwrap();
+        assert!(
+            segment.starts_with(":020000022000DC\n:01001000AB"),
+            "{segment}"
+        );
+
+        let err = render(0x2_0010, IhexAddressing::I8hex).unwrap_err();
+        assert!(err.to_string().contains("exceeds Intel HEX range"), "{err}");
+        let err = render(0x10_0010, IhexAddressing::I16hex).unwrap_err();
+        assert!(err.to_string().contains("exceeds Intel HEX range"), "{err}");
+    }
+
+    #[test]
+    fn srec_offset_below_zero_errors() {
+        let range = DataRange {
+            start_address: 0x10,
+            bytestream: vec![0xAB],
+            crc_address: 0,
+            crc_bytestream: Vec::new(),
+            used_size: 1,
+            allocated_size: 1,
+            padding: 0xFF,
+        };
+        let srec = SrecOptions {
+            entry_address: None,
+            address_offset: -0x20,
+        };
+        let file = OutputFile {
+            ranges: vec![range],
+            format: OutputFormat::Mot,
+            record_width: 16,
+            preserve_order: false,
+            style: RecordStyle::default(),
+            srec,
+            ihex: IhexAddressing::Auto,
+        };
+        let err = file.write_to(Vec::new()).unwrap_err();
+        assert!(
+            err.to_string().contains("outside the S-record range"),
+            "{err}"
+        );
+    }
 }
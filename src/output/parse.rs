@@ -0,0 +1,322 @@
+use std::collections::BTreeMap;
+
+use thiserror::Error;
+
+/// Record format of a hex/mot file, taken from its first record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    Ihex,
+    Srec,
+}
+
+/// Data record read from an Intel HEX or S-record file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataRecord {
+    /// 1-based line the record is on.
+    pub line: usize,
+    pub address: u64,
+    pub data: Vec<u8>,
+}
+
+/// Problem with a record, on the 1-based line it was found.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("line {line}: {message}")]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Reads the data records of an Intel HEX or S-record file, checking each
+/// line's length and checksum, that the file keeps to one format, that no two
+/// records write the same address, and that it ends as its format requires:
+/// Intel HEX with an end-of-file record, and S-record counts matching the
+/// number of data records.
+pub fn parse_records(text: &str) -> Result<(RecordFormat, Vec<DataRecord>), ParseError> {
+    let mut parser = Parser::default();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        parser.line = index + 1;
+        parser.record(line)?;
+    }
+    parser.finish()
+}
+
+#[derive(Default)]
+struct Parser {
+    line: usize,
+    format: Option<RecordFormat>,
+    records: Vec<DataRecord>,
+    /// Start address of each data record, mapped to its end and line.
+    written: BTreeMap<u64, (u64, usize)>,
+    /// Intel HEX base from the last extended address record.
+    upper: u64,
+    /// Line of the end-of-file or S7/S8/S9 termination record.
+    ended: Option<usize>,
+}
+
+impl Parser {
+    fn fail(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn record(&mut self, line: &str) -> Result<(), ParseError> {
+        let start = match line.as_bytes()[0] {
+            b':' => line.get(1..).map(|body| (RecordFormat::Ihex, body)),
+            b'S' => line.get(2..).map(|body| (RecordFormat::Srec, body)),
+            _ => None,
+        };
+        let Some((format, body)) = start else {
+            return Err(self.fail("not an Intel HEX or S-record line"));
+        };
+        match self.format {
+            None => self.format = Some(format),
+            Some(expected) if expected != format => {
+                return Err(self.fail(format!(
+                    "{} line in an {} file",
+                    format_name(format),
+                    format_name(expected)
+                )));
+            }
+            Some(_) => {}
+        }
+        if let Some(end) = self.ended {
+            return Err(self.fail(format!("record after the end record on line {}", end)));
+        }
+        let bytes = decode_hex(body).ok_or_else(|| self.fail("invalid hex digits"))?;
+        match format {
+            RecordFormat::Ihex => self.ihex(&bytes),
+            RecordFormat::Srec => self.srec(line.as_bytes()[1], &bytes),
+        }
+    }
+
+    fn ihex(&mut self, bytes: &[u8]) -> Result<(), ParseError> {
+        let [len, hi, lo, record_type, ..] = bytes[..] else {
+            return Err(self.fail("record too short"));
+        };
+        if bytes.len() != len as usize + 5 {
+            return Err(self.fail(format!(
+                "length byte says {} data bytes, record has {}",
+                len,
+                bytes.len().saturating_sub(5)
+            )));
+        }
+        self.checksum(bytes, 0)?;
+        let data = &bytes[4..bytes.len() - 1];
+        let offset = u16::from_be_bytes([hi, lo]) as u64;
+        match record_type {
+            0x00 => {
+                if offset + data.len() as u64 > 0x1_0000 {
+                    return Err(self.fail("data record crosses a 64 KiB boundary"));
+                }
+                self.data(self.upper + offset, data)
+            }
+            0x01 => {
+                self.ended = Some(self.line);
+                Ok(())
+            }
+            0x02 | 0x04 => {
+                let [a, b] = data[..] else {
+                    return Err(self.fail("extended address record needs 2 data bytes"));
+                };
+                let base = u16::from_be_bytes([a, b]) as u64;
+                self.upper = match record_type {
+                    0x02 => base << 4,
+                    _ => base << 16,
+                };
+                Ok(())
+            }
+            0x03 | 0x05 => Ok(()),
+            t => Err(self.fail(format!("unsupported record type {:02X}", t))),
+        }
+    }
+
+    fn srec(&mut self, kind: u8, bytes: &[u8]) -> Result<(), ParseError> {
+        let addr_len = match kind {
+            b'0' | b'1' | b'5' | b'9' => 2,
+            b'2' | b'6' | b'8' => 3,
+            b'3' | b'7' => 4,
+            _ => {
+                return Err(self.fail(format!("unsupported record type S{}", char::from(kind))));
+            }
+        };
+        if bytes.first().map(|&n| n as usize + 1) != Some(bytes.len()) {
+            return Err(self.fail(format!(
+                "count byte says {} bytes, record has {}",
+                bytes.first().copied().unwrap_or(0),
+                bytes.len().saturating_sub(1)
+            )));
+        }
+        if bytes.len() < addr_len + 2 {
+            return Err(self.fail("record too short"));
+        }
+        self.checksum(bytes, 0xFF)?;
+        let address = bytes[1..=addr_len]
+            .iter()
+            .fold(0u64, |acc, &b| (acc << 8) | b as u64);
+        match kind {
+            b'1'..=b'3' => self.data(address, &bytes[addr_len + 1..bytes.len() - 1]),
+            b'5' | b'6' => match self.records.len() as u64 {
+                n if n == address => Ok(()),
+                n => Err(self.fail(format!(
+                    "count record says {} data records, file has {}",
+                    address, n
+                ))),
+            },
+            b'7'..=b'9' => {
+                self.ended = Some(self.line);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that all bytes but the last sum, with it, to `total`.
+    fn checksum(&self, bytes: &[u8], total: u8) -> Result<(), ParseError> {
+        let (stored, body) = bytes.split_last().unwrap();
+        let sum = body.iter().fold(0u8, |acc, b| acc.wrapping_add(*b));
+        let expected = total.wrapping_sub(sum);
+        match *stored == expected {
+            true => Ok(()),
+            false => Err(self.fail(format!(
+                "checksum {:02X}, expected {:02X}",
+                stored, expected
+            ))),
+        }
+    }
+
+    fn data(&mut self, address: u64, data: &[u8]) -> Result<(), ParseError> {
+        let end = address + data.len() as u64;
+        let before = self.written.range(..end).next_back();
+        if let Some((&start, &(prev_end, line))) = before
+            && prev_end > address
+            && !data.is_empty()
+        {
+            return Err(self.fail(format!(
+                "data at 0x{:X} overlaps 0x{:X}..0x{:X} from line {}",
+                address, start, prev_end, line
+            )));
+        }
+        if !data.is_empty() {
+            self.written.insert(address, (end, self.line));
+        }
+        self.records.push(DataRecord {
+            line: self.line,
+            address,
+            data: data.to_vec(),
+        });
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(RecordFormat, Vec<DataRecord>), ParseError> {
+        let format = self.format.unwrap_or(RecordFormat::Ihex);
+        if format == RecordFormat::Ihex && self.ended.is_none() {
+            return Err(self.fail("missing end-of-file record"));
+        }
+        Ok((format, self.records))
+    }
+}
+
+fn format_name(format: RecordFormat) -> &'static str {
+    match format {
+        RecordFormat::Ihex => "Intel HEX",
+        RecordFormat::Srec => "S-record",
+    }
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> String {
+        parse_records(text).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn reads_ihex_with_extended_addresses() {
+        let (format, records) = parse_records(
+            ":020000040001F9\n:020010001122BB\n:020000021000EC\n:0100000033CC\n:00000001FF\n",
+        )
+        .unwrap();
+        assert_eq!(format, RecordFormat::Ihex);
+        assert_eq!(records[0].address, 0x1_0010);
+        assert_eq!(records[0].data, [0x11, 0x22]);
+        assert_eq!(records[1].address, 0x1_0000);
+        assert_eq!(records[1].line, 4);
+    }
+
+    #[test]
+    fn reads_srec_and_checks_count() {
+        let (format, records) =
+            parse_records("S00600004844521B\nS1051000AABB85\nS5030001FB\nS9030000FC\n").unwrap();
+        assert_eq!(format, RecordFormat::Srec);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].address, 0x1000);
+
+        assert_eq!(
+            error("S1051000AABB85\nS5030002FA\n"),
+            "line 2: count record says 2 data records, file has 1"
+        );
+    }
+
+    #[test]
+    fn reports_checksum_and_length_by_line() {
+        assert_eq!(
+            error(":020010001122BB\n:020010001122BC\n"),
+            "line 2: checksum BC, expected BB"
+        );
+        assert_eq!(
+            error("\n:030010001122BB\n"),
+            "line 2: length byte says 3 data bytes, record has 2"
+        );
+        assert_eq!(
+            error("S1051000AABB84\n"),
+            "line 1: checksum 84, expected 85"
+        );
+        assert_eq!(error(":0200100011ZZBB\n"), "line 1: invalid hex digits");
+        assert_eq!(error(":02é\n"), "line 1: invalid hex digits");
+        assert_eq!(
+            error(":00000001FF\nSé\n"),
+            "line 2: not an Intel HEX or S-record line"
+        );
+    }
+
+    #[test]
+    fn rejects_overlaps_and_broken_structure() {
+        assert_eq!(
+            error(":020010001122BB\n:0100110033BB\n:00000001FF\n"),
+            "line 2: data at 0x11 overlaps 0x10..0x12 from line 1"
+        );
+        assert_eq!(
+            error(":02FFFF001122CD\n:00000001FF\n"),
+            "line 1: data record crosses a 64 KiB boundary"
+        );
+        assert_eq!(
+            error(":00000001FF\n:020010001122BB\n"),
+            "line 2: record after the end record on line 1"
+        );
+        assert_eq!(
+            error(":020010001122BB\nS1051000AABB85\n"),
+            "line 2: S-record line in an Intel HEX file"
+        );
+        assert_eq!(
+            error(":020010001122BB\n"),
+            "line 1: missing end-of-file record"
+        );
+    }
+}
@@ -0,0 +1,347 @@
+use std::io::Write;
+
+use crate::output::DataRange;
+use crate::output::args::{HexCase, LineEnding};
+use crate::output::error::OutputError;
+
+/// Hex digit case and line terminator of emitted records.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecordStyle {
+    pub case: HexCase,
+    pub line_ending: LineEnding,
+}
+
+/// S-record emission settings that do not change the image.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SrecOptions {
+    /// Entry address for an S7/S8/S9 termination record; none is written without it.
+    pub entry_address: Option<u32>,
+    /// Added to every data record address.
+    pub address_offset: i64,
+}
+
+/// Contiguous run of memory assembled from borrowed slices.
+pub struct Segment<'a> {
+    pub address: u64,
+    pub parts: Vec<&'a [u8]>,
+    pub len: u64,
+}
+
+impl Segment<'_> {
+    /// Exclusive end address of the segment.
+    pub fn end(&self) -> u64 {
+        self.address + self.len
+    }
+}
+
+/// Borrowed slice placed at an absolute address.
+struct Piece<'a> {
+    address: u64,
+    data: &'a [u8],
+    /// Index of the range the slice belongs to.
+    range: usize,
+}
+
+/// Splits each range into address-ordered pieces, with CRC bytes overriding the
+/// bytestream wherever they overlap, and merges adjacent pieces into segments.
+/// Segments are in ascending address order, or in the order of `ranges` when
+/// `preserve_order` is set. No block data is copied.
+pub fn collect_segments(
+    ranges: &[DataRange],
+    preserve_order: bool,
+) -> Result<Vec<Segment<'_>>, OutputError> {
+    let mut pieces = Vec::new();
+    for (index, range) in ranges.iter().enumerate() {
+        let start = range.start_address as u64;
+        let data = range.bytestream.as_slice();
+
+        if range.crc_bytestream.is_empty() {
+            pieces.push(Piece {
+                address: start,
+                data,
+                range: index,
+            });
+            continue;
+        }
+
+        let crc_start = range.crc_address as u64;
+        let crc_end = crc_start + range.crc_bytestream.len() as u64;
+        let data_end = start + data.len() as u64;
+
+        let before_end = crc_start.clamp(start, data_end);
+        let after_start = crc_end.clamp(start, data_end);
+        pieces.push(Piece {
+            address: start,
+            data: &data[..(before_end - start) as usize],
+            range: index,
+        });
+        pieces.push(Piece {
+            address: crc_start,
+            data: range.crc_bytestream.as_slice(),
+            range: index,
+        });
+        pieces.push(Piece {
+            address: after_start,
+            data: &data[(after_start - start) as usize..],
+            range: index,
+        });
+    }
+
+    pieces.retain(|p| !p.data.is_empty());
+    pieces.sort_by_key(|p| p.address);
+    if let Some(pair) = pieces
+        .windows(2)
+        .find(|w| w[0].address + w[0].data.len() as u64 > w[1].address)
+    {
+        return Err(OutputError::HexOutputError(format!(
+            "Overlapping data at 0x{:08X}",
+            pair[1].address
+        )));
+    }
+    if preserve_order {
+        // Stable, so pieces stay in address order within each range.
+        pieces.sort_by_key(|p| p.range);
+    }
+
+    let mut segments: Vec<Segment> = Vec::new();
+    for piece in pieces {
+        let len = piece.data.len() as u64;
+        match segments.last_mut() {
+            Some(seg) if seg.end() == piece.address => {
+                seg.parts.push(piece.data);
+                seg.len += len;
+            }
+            _ => segments.push(Segment {
+                address: piece.address,
+                parts: vec![piece.data],
+                len,
+            }),
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Calls `emit` for each record-sized chunk of a segment. Chunks start at the
+/// segment start and never cross a multiple of `boundary` (if given).
+pub fn for_each_chunk<F>(
+    segment: &Segment,
+    width: usize,
+    boundary: Option<u64>,
+    mut emit: F,
+) -> Result<(), OutputError>
+where
+    F: FnMut(u64, &[u8]) -> Result<(), OutputError>,
+{
+    let mut buf: Vec<u8> = Vec::with_capacity(width);
+    let mut record_start = segment.address;
+
+    for part in &segment.parts {
+        let mut rest = *part;
+        while !rest.is_empty() {
+            let cursor = record_start + buf.len() as u64;
+            let mut take = (width - buf.len()).min(rest.len());
+            if let Some(b) = boundary {
+                let to_boundary = b - (cursor % b);
+                take = take.min(to_boundary as usize);
+            }
+            buf.extend_from_slice(&rest[..take]);
+            rest = &rest[take..];
+
+            let at_boundary =
+                boundary.is_some_and(|b| (record_start + buf.len() as u64).is_multiple_of(b));
+            if buf.len() == width || at_boundary {
+                emit(record_start, &buf)?;
+                record_start += buf.len() as u64;
+                buf.clear();
+            }
+        }
+    }
+
+    if !buf.is_empty() {
+        emit(record_start, &buf)?;
+    }
+    Ok(())
+}
+
+/// Writes newline-separated records to an underlying writer.
+pub struct LineSink<W: Write> {
+    out: W,
+    started: bool,
+    line: String,
+    style: RecordStyle,
+}
+
+impl<W: Write> LineSink<W> {
+    pub fn new(out: W) -> Self {
+        Self::with_style(out, RecordStyle::default())
+    }
+
+    pub fn with_style(out: W, style: RecordStyle) -> Self {
+        Self {
+            out,
+            started: false,
+            line: String::with_capacity(600),
+            style,
+        }
+    }
+
+    /// Writes one record: `prefix` followed by the hex encoding of `bytes`.
+    pub fn record(&mut self, prefix: &str, bytes: &[u8]) -> Result<(), OutputError> {
+        use std::fmt::Write as _;
+
+        self.line.clear();
+        if self.started {
+            self.line.push_str(match self.style.line_ending {
+                LineEnding::Lf => "\n",
+                LineEnding::Crlf => "\r\n",
+            });
+        }
+        self.line.push_str(prefix);
+        for b in bytes {
+            let _ = match self.style.case {
+                HexCase::Upper => write!(self.line, "{:02X}", b),
+                HexCase::Lower => write!(self.line, "{:02x}", b),
+            };
+        }
+        self.started = true;
+        self.out
+            .write_all(self.line.as_bytes())
+            .map_err(|e| OutputError::FileError(format!("failed to write output: {}", e)))
+    }
+}
+
+/// Writes an Intel HEX data or control record.
+pub fn write_ihex_record<W: Write>(
+    sink: &mut LineSink<W>,
+    record_type: u8,
+    offset: u16,
+    data: &[u8],
+) -> Result<(), OutputError> {
+    let mut bytes = Vec::with_capacity(data.len() + 5);
+    bytes.push(data.len() as u8);
+    bytes.extend_from_slice(&offset.to_be_bytes());
+    bytes.push(record_type);
+    bytes.extend_from_slice(data);
+    let sum = bytes.iter().fold(0u8, |acc, b| acc.wrapping_add(*b));
+    bytes.push(0u8.wrapping_sub(sum));
+    sink.record(":", &bytes)
+}
+
+/// Writes a Motorola S-Record with the given type and address width.
+pub fn write_srec_record<W: Write>(
+    sink: &mut LineSink<W>,
+    record_type: u8,
+    address: u32,
+    address_bytes: usize,
+    data: &[u8],
+) -> Result<(), OutputError> {
+    let mut bytes = Vec::with_capacity(data.len() + address_bytes + 2);
+    bytes.push((address_bytes + data.len() + 1) as u8);
+    bytes.extend_from_slice(&address.to_be_bytes()[4 - address_bytes..]);
+    bytes.extend_from_slice(data);
+    let sum = bytes.iter().fold(0u8, |acc, b| acc.wrapping_add(*b));
+    bytes.push(!sum);
+    let prefix = format!("S{}", record_type);
+    sink.record(&prefix, &bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: u32, bytes: Vec<u8>, crc_address: u32, crc: Vec<u8>) -> DataRange {
+        let len = bytes.len() as u32;
+        DataRange {
+            start_address: start,
+            bytestream: bytes,
+            crc_address,
+            crc_bytestream: crc,
+            used_size: len,
+            allocated_size: len,
+            padding: 0xFF,
+        }
+    }
+
+    #[test]
+    fn crc_inside_bytestream_overrides_bytes() {
+        let ranges = [range(0x10, vec![0xFF; 8], 0x14, vec![1, 2, 3, 4])];
+        let segments = collect_segments(&ranges, false).unwrap();
+        assert_eq!(segments.len(), 1);
+        let flat: Vec<u8> = segments[0].parts.concat();
+        assert_eq!(flat, [0xFF, 0xFF, 0xFF, 0xFF, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn segments_are_sorted_unless_order_is_preserved() {
+        let ranges = [
+            range(0x20, vec![2; 4], 0, vec![]),
+            range(0x10, vec![1; 4], 0, vec![]),
+            range(0x14, vec![3; 4], 0, vec![]),
+        ];
+        let starts = |preserve| -> Vec<u64> {
+            collect_segments(&ranges, preserve)
+                .unwrap()
+                .iter()
+                .map(|s| s.address)
+                .collect()
+        };
+        assert_eq!(starts(false), [0x10, 0x20]);
+        assert_eq!(starts(true), [0x20, 0x10]);
+
+        let overlapping = [
+            range(0x10, vec![0; 8], 0, vec![]),
+            range(0x14, vec![0; 4], 0, vec![]),
+        ];
+        assert!(collect_segments(&overlapping, true).is_err());
+    }
+
+    #[test]
+    fn chunks_split_at_boundary() {
+        let data = vec![0u8; 8];
+        let segment = Segment {
+            address: 0xFFFC,
+            parts: vec![&data],
+            len: 8,
+        };
+        let mut chunks = Vec::new();
+        for_each_chunk(&segment, 16, Some(0x1_0000), |addr, bytes| {
+            chunks.push((addr, bytes.len()));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(chunks, [(0xFFFC, 4), (0x1_0000, 4)]);
+    }
+
+    #[test]
+    fn ihex_record_checksum() {
+        let mut out = Vec::new();
+        let mut sink = LineSink::new(&mut out);
+        write_ihex_record(&mut sink, 0x01, 0, &[]).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), ":00000001FF");
+    }
+
+    #[test]
+    fn record_style_sets_case_and_line_ending() {
+        let mut out = Vec::new();
+        let style = RecordStyle {
+            case: HexCase::Lower,
+            line_ending: LineEnding::Crlf,
+        };
+        let mut sink = LineSink::with_style(&mut out, style);
+        write_ihex_record(&mut sink, 0x00, 0, &[0xAB]).unwrap();
+        write_ihex_record(&mut sink, 0x01, 0, &[]).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            ":01000000ab54\r\n:00000001ff"
+        );
+    }
+
+    #[test]
+    fn srec_count_record() {
+        let mut out = Vec::new();
+        let mut sink = LineSink::new(&mut out);
+        write_srec_record(&mut sink, 5, 2, 2, &[]).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "S5030002FA");
+    }
+}
@@ -0,0 +1,127 @@
+use std::path::{Path, PathBuf};
+
+use crate::output::DataRange;
+use crate::output::error::OutputError;
+
+/// Named address range written to its own file by `--region`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputRegion {
+    pub name: String,
+    pub start: u64,
+    /// Exclusive end address.
+    pub end: u64,
+}
+
+/// Parses `NAME=START..END`, with decimal or `0x` hex addresses and END exclusive.
+pub fn parse_region(text: &str) -> Result<OutputRegion, String> {
+    let fail = || format!("expected NAME=START..END, got '{}'", text);
+    let (name, span) = text.split_once('=').ok_or_else(fail)?;
+    let (start, end) = span.split_once("..").ok_or_else(fail)?;
+    let number = |s: &str| {
+        let s = s.trim().replace('_', "");
+        match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => s.parse(),
+        }
+        .map_err(|_| fail())
+    };
+    let region = OutputRegion {
+        name: name.trim().to_string(),
+        start: number(start)?,
+        end: number(end)?,
+    };
+    if region.name.is_empty() || region.end <= region.start {
+        return Err(fail());
+    }
+    Ok(region)
+}
+
+/// Routes each range to the region containing it, returning one list per region.
+///
+/// A range outside every region, or crossing a region boundary, is an error.
+pub fn route(
+    ranges: Vec<DataRange>,
+    regions: &[OutputRegion],
+) -> Result<Vec<Vec<DataRange>>, OutputError> {
+    for (i, a) in regions.iter().enumerate() {
+        if let Some(b) = regions[i + 1..]
+            .iter()
+            .find(|b| a.start < b.end && b.start < a.end)
+        {
+            return Err(OutputError::HexOutputError(format!(
+                "regions '{}' and '{}' overlap",
+                a.name, b.name
+            )));
+        }
+    }
+
+    let mut routed = vec![Vec::new(); regions.len()];
+    for range in ranges {
+        let start = range.start_address as u64;
+        let end = start + range.image().len() as u64;
+        let index = regions
+            .iter()
+            .position(|r| r.start <= start && end <= r.end)
+            .ok_or_else(|| {
+                OutputError::HexOutputError(format!(
+                    "data at 0x{:X}..0x{:X} is not inside any --region",
+                    start, end
+                ))
+            })?;
+        routed[index].push(range);
+    }
+    Ok(routed)
+}
+
+/// Output path for a region: `out`'s directory and extension with the region name.
+pub fn region_path(out: &Path, name: &str) -> PathBuf {
+    match out.extension() {
+        Some(ext) => out.with_file_name(format!("{}.{}", name, ext.to_string_lossy())),
+        None => out.with_file_name(name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: u32, len: usize) -> DataRange {
+        DataRange {
+            start_address: start,
+            bytestream: vec![0; len],
+            crc_address: 0,
+            crc_bytestream: Vec::new(),
+            used_size: len as u32,
+            allocated_size: len as u32,
+            padding: 0xFF,
+        }
+    }
+
+    #[test]
+    fn parses_name_and_exclusive_range() {
+        assert_eq!(
+            parse_region("eeprom=0x1000..0x2000"),
+            Ok(OutputRegion {
+                name: "eeprom".to_string(),
+                start: 0x1000,
+                end: 0x2000,
+            })
+        );
+        assert!(parse_region("eeprom=0x2000..0x1000").is_err());
+        assert!(parse_region("0x1000..0x2000").is_err());
+    }
+
+    #[test]
+    fn routes_ranges_by_address() {
+        let regions = [
+            parse_region("a=0..0x100").unwrap(),
+            parse_region("b=0x100..0x200").unwrap(),
+        ];
+        let routed = route(vec![range(0x180, 8), range(0x10, 8)], &regions).unwrap();
+        assert_eq!(routed[0][0].start_address, 0x10);
+        assert_eq!(routed[1][0].start_address, 0x180);
+
+        let err = route(vec![range(0xF8, 16)], &regions).unwrap_err();
+        assert!(err.to_string().contains("not inside any --region"), "{err}");
+    }
+}
@@ -2,12 +2,22 @@ use std::path::Path;
 
 use serde_json::Value;
 
+use crate::output::args::ExportFormat;
 use crate::output::error::OutputError;
 
-/// Write used values JSON report to disk.
-pub fn write_used_values_json(path: &Path, report: &Value) -> Result<(), OutputError> {
-    let contents = serde_json::to_string_pretty(report)
-        .map_err(|e| OutputError::FileError(format!("failed to serialize JSON report: {}", e)))?;
+/// Write a used values report to disk in `format`.
+pub fn write_used_values(
+    path: &Path,
+    report: &Value,
+    format: ExportFormat,
+) -> Result<(), OutputError> {
+    let contents = serialize(report, format).map_err(|e| {
+        OutputError::FileError(format!(
+            "failed to serialize {} report: {}",
+            format.extension().to_uppercase(),
+            e
+        ))
+    })?;
 
     if let Some(parent) = path.parent()
         && !parent.as_os_str().is_empty()
@@ -23,7 +33,8 @@ pub fn write_used_values_json(path: &Path, report: &Value) -> Result<(), OutputE
 
     std::fs::write(path, contents).map_err(|e| {
         OutputError::FileError(format!(
-            "failed to write JSON report {}: {}",
+            "failed to write {} report {}: {}",
+            format.extension().to_uppercase(),
             path.display(),
             e
         ))
@@ -31,3 +42,29 @@ pub fn write_used_values_json(path: &Path, report: &Value) -> Result<(), OutputE
 
     Ok(())
 }
+
+fn serialize(report: &Value, format: ExportFormat) -> Result<String, String> {
+    match format {
+        ExportFormat::Json => serde_json::to_string_pretty(report).map_err(|e| e.to_string()),
+        ExportFormat::Yaml => serde_yaml::to_string(report).map_err(|e| e.to_string()),
+        ExportFormat::Toml => toml::to_string_pretty(report).map_err(|e| e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_report_in_each_format() {
+        let report = serde_json::json!({"block": "cal", "values": {"gain": 3}});
+        assert_eq!(
+            serialize(&report, ExportFormat::Yaml).unwrap(),
+            "block: cal\nvalues:\n  gain: 3\n"
+        );
+        assert_eq!(
+            serialize(&report, ExportFormat::Toml).unwrap(),
+            "block = \"cal\"\n\n[values]\ngain = 3\n"
+        );
+    }
+}
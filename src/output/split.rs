@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+
+use crate::output::DataRange;
+
+/// Names of the two split outputs, in lane order.
+pub const LANES: [&str; 2] = ["even", "odd"];
+
+/// Splits ranges into two interleaved lanes of `unit`-byte words for dual-die flash.
+///
+/// Word `n` counted from the origin (the lowest address, rounded down to
+/// `2 * unit`) goes to lane `n % 2`. Each lane is packed: its addresses run
+/// from `bases[lane]` at the origin, so a lane holds half the address span.
+pub fn split_ranges(ranges: &[DataRange], unit: u32, bases: [u32; 2]) -> [Vec<DataRange>; 2] {
+    let stride = 2 * unit as u64;
+    let origin = ranges
+        .iter()
+        .map(|r| r.start_address as u64)
+        .min()
+        .map_or(0, |a| a - a % stride);
+
+    let mut lanes: [Vec<DataRange>; 2] = [Vec::new(), Vec::new()];
+    for range in ranges {
+        let mut parts: [Option<(u32, Vec<u8>)>; 2] = [None, None];
+        for (i, byte) in range.image().into_iter().enumerate() {
+            let offset = range.start_address as u64 + i as u64 - origin;
+            let lane = ((offset / unit as u64) % 2) as usize;
+            let address = (offset / stride) * unit as u64 + offset % unit as u64;
+            parts[lane]
+                .get_or_insert_with(|| (bases[lane] + address as u32, Vec::new()))
+                .1
+                .push(byte);
+        }
+        for (lane, part) in parts.into_iter().enumerate() {
+            if let Some((start_address, bytestream)) = part {
+                let len = bytestream.len() as u32;
+                lanes[lane].push(DataRange {
+                    start_address,
+                    bytestream,
+                    crc_address: 0,
+                    crc_bytestream: Vec::new(),
+                    used_size: len,
+                    allocated_size: len,
+                    padding: range.padding,
+                });
+            }
+        }
+    }
+    lanes
+}
+
+/// `out` with the lane name before its extension, e.g. `image.even.hex`.
+pub fn lane_path(out: &Path, lane: &str) -> PathBuf {
+    let stem = out.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match out.extension() {
+        Some(ext) => format!("{}.{}.{}", stem, lane, ext.to_string_lossy()),
+        None => format!("{}.{}", stem, lane),
+    };
+    out.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: u32, bytes: Vec<u8>) -> DataRange {
+        let len = bytes.len() as u32;
+        DataRange {
+            start_address: start,
+            bytestream: bytes,
+            crc_address: 0,
+            crc_bytestream: Vec::new(),
+            used_size: len,
+            allocated_size: len,
+            padding: 0xFF,
+        }
+    }
+
+    #[test]
+    fn splits_words_across_lanes() {
+        let ranges = [range(0x1002, vec![0, 1, 2, 3, 4, 5])];
+        let [even, odd] = split_ranges(&ranges, 2, [0, 0x100]);
+        // Origin 0x1000: words at 0x1002 (odd), 0x1004 (even), 0x1006 (odd).
+        assert_eq!(even.len(), 1);
+        assert_eq!(
+            (even[0].start_address, &even[0].bytestream[..]),
+            (2, &[2, 3][..])
+        );
+        assert_eq!(
+            (odd[0].start_address, &odd[0].bytestream[..]),
+            (0x100, &[0, 1, 4, 5][..])
+        );
+    }
+
+    #[test]
+    fn lane_path_inserts_lane_name() {
+        assert_eq!(
+            lane_path(Path::new("out/image.hex"), "odd"),
+            Path::new("out/image.odd.hex")
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::error::MintError;
+use crate::interpolate::Expanding;
+
+/// Project config file name, looked up in the working directory.
+pub const PROJECT_FILE: &str = "mint.toml";
+
+/// Default CLI options from `mint.toml`. Flags given on the command line win.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectConfig {
+    pub xlsx: Option<String>,
+    pub main_sheet: Option<String>,
+    pub postgres: Option<String>,
+    pub http: Option<String>,
+    pub json: Option<String>,
+    pub secrets: Option<String>,
+    pub version: Option<String>,
+    pub out: Option<String>,
+    pub format: Option<String>,
+    pub record_width: Option<u16>,
+    pub hex_record_width: Option<u16>,
+    pub mot_record_width: Option<u16>,
+    pub hex_case: Option<String>,
+    pub line_ending: Option<String>,
+    pub lock: Option<String>,
+    pub checksums: Option<String>,
+    pub pre_build: Option<String>,
+    pub post_build: Option<String>,
+    /// Template variables as `NAME[:TYPE]=VALUE`; replaced entirely by `-D` flags.
+    pub define: Option<Vec<String>>,
+    /// Output regions as `NAME=START..END`; replaced entirely by `--region` flags.
+    pub region: Option<Vec<String>>,
+}
+
+impl ProjectConfig {
+    /// Loads `mint.toml` from `dir`, returning `None` if it does not exist.
+    pub fn discover(dir: &Path) -> Result<Option<Self>, MintError> {
+        let path = dir.join(PROJECT_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let text = std::fs::read_to_string(&path).map_err(|e| {
+            MintError::ProjectConfig(format!("failed to read {}: {}", path.display(), e))
+        })?;
+        let config = toml::Deserializer::parse(&text)
+            .and_then(|de| {
+                let expand = crate::interpolate::expand_env;
+                Self::deserialize(Expanding::new(de, &expand))
+            })
+            .map_err(|e| {
+                MintError::ProjectConfig(format!("failed to parse {}: {}", path.display(), e))
+            })?;
+        Ok(Some(config))
+    }
+
+    /// Defaults keyed by clap argument id.
+    pub fn defaults(&self) -> Vec<(&'static str, String)> {
+        let entries = [
+            ("xlsx", self.xlsx.clone()),
+            ("main_sheet", self.main_sheet.clone()),
+            ("postgres", self.postgres.clone()),
+            ("http", self.http.clone()),
+            ("json", self.json.clone()),
+            ("secrets", self.secrets.clone()),
+            ("version", self.version.clone()),
+            ("out", self.out.clone()),
+            ("format", self.format.clone()),
+            ("record_width", self.record_width.map(|w| w.to_string())),
+            (
+                "hex_record_width",
+                self.hex_record_width.map(|w| w.to_string()),
+            ),
+            (
+                "mot_record_width",
+                self.mot_record_width.map(|w| w.to_string()),
+            ),
+            ("hex_case", self.hex_case.clone()),
+            ("line_ending", self.line_ending.clone()),
+            ("lock", self.lock.clone()),
+            ("checksums", self.checksums.clone()),
+            ("pre_build", self.pre_build.clone()),
+            ("post_build", self.post_build.clone()),
+        ];
+        entries
+            .into_iter()
+            .filter_map(|(id, value)| value.map(|v| (id, v)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_skip_unset_keys() {
+        let config: ProjectConfig =
+            toml::from_str("xlsx = \"data.xlsx\"\nversion = \"Debug/Default\"\nrecord_width = 16")
+                .unwrap();
+        assert_eq!(
+            config.defaults(),
+            [
+                ("xlsx", "data.xlsx".to_string()),
+                ("version", "Debug/Default".to_string()),
+                ("record_width", "16".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(toml::from_str::<ProjectConfig>("verison = \"Default\"").is_err());
+    }
+}
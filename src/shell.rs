@@ -0,0 +1,19 @@
+//! Commands run through the platform shell, for build hooks and checksum commands.
+
+use std::process::Command;
+
+/// `command` as a script for the platform shell: `cmd /C` on Windows,
+/// `sh -c` elsewhere.
+pub fn shell_command(command: &str) -> Command {
+    let mut shell = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    shell.arg(command);
+    shell
+}
@@ -0,0 +1,105 @@
+use crate::commands::stats::FieldChange;
+use ratatui::crossterm::style::{StyledContent, Stylize};
+use std::fmt::Write;
+
+const ROW_BYTES: usize = 16;
+
+/// Changed fields grouped by block, each with `-`/`+` rows of its old and new
+/// bytes. Only 16-byte rows containing a change are shown, so large arrays stay
+/// readable; with `color`, removed bytes are red, added bytes green, and bytes
+/// that did not change are dimmed.
+pub fn render_diff(changes: &[FieldChange], color: bool) -> String {
+    let mut out = String::new();
+    let mut block = None;
+    for change in changes {
+        if block != Some(&change.block) {
+            block = Some(&change.block);
+            let _ = writeln!(out, "{}", paint(&change.block, color, |s| s.bold()));
+        }
+        let _ = writeln!(out, "  {}", paint(&change.field, color, |s| s.yellow()));
+        for start in (0..change.new.len()).step_by(ROW_BYTES) {
+            let end = (start + ROW_BYTES).min(change.new.len());
+            let old = &change.old[start..end];
+            let new = &change.new[start..end];
+            if old.iter().zip(new).all(|(o, n)| *o == Some(*n)) {
+                continue;
+            }
+            let address = format!("0x{:08X}", change.address as usize + start);
+            let mut minus = String::new();
+            let mut plus = String::new();
+            for (o, n) in old.iter().zip(new) {
+                let changed = *o != Some(*n);
+                let o = o.map_or("--".to_string(), |b| format!("{:02X}", b));
+                let n = format!("{:02X}", n);
+                let (o, n) = match (color, changed) {
+                    (false, _) => (o, n),
+                    (true, true) => (o.red().to_string(), n.green().to_string()),
+                    (true, false) => (o.dim().to_string(), n.dim().to_string()),
+                };
+                let _ = write!(minus, " {}", o);
+                let _ = write!(plus, " {}", n);
+            }
+            let _ = writeln!(
+                out,
+                "    {}  {}{}",
+                address,
+                paint("-", color, |s| s.red()),
+                minus
+            );
+            let _ = writeln!(
+                out,
+                "    {:10}  {}{}",
+                "",
+                paint("+", color, |s| s.green()),
+                plus
+            );
+        }
+    }
+    out
+}
+
+fn paint(text: &str, color: bool, style: fn(String) -> StyledContent<String>) -> String {
+    match color {
+        true => style(text.to_string()).to_string(),
+        false => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_changed_rows_under_fields() {
+        let mut old: Vec<Option<u8>> = vec![Some(0); 20];
+        old[19] = None;
+        let mut new = vec![0u8; 20];
+        new[18] = 1;
+        let changes = [
+            FieldChange {
+                block: "cal".to_string(),
+                field: "pid.kp".to_string(),
+                address: 0x1000,
+                old: vec![Some(0x00), Some(0x00), Some(0x80), Some(0x3F)],
+                new: vec![0x00, 0x00, 0x00, 0x40],
+            },
+            FieldChange {
+                block: "cal".to_string(),
+                field: "map".to_string(),
+                address: 0x1010,
+                old,
+                new,
+            },
+        ];
+        assert_eq!(
+            render_diff(&changes, false),
+            "cal\n  \
+             pid.kp\n    \
+             0x00001000  - 00 00 80 3F\n                \
+             + 00 00 00 40\n  \
+             map\n    \
+             0x00001020  - 00 00 00 --\n                \
+             + 00 00 01 00\n"
+        );
+    }
+}
@@ -17,6 +17,22 @@ pub fn format_address_range(start: u32, allocated: u32) -> String {
     format!("0x{:X}-0x{:X}", start, end)
 }
 
+/// Space-separated hex bytes, truncated after 16 bytes.
+pub fn format_hex_bytes(bytes: &[u8]) -> String {
+    if bytes.is_empty() {
+        return "-".to_string();
+    }
+    let mut text: Vec<String> = bytes
+        .iter()
+        .take(16)
+        .map(|b| format!("{:02X}", b))
+        .collect();
+    if bytes.len() > 16 {
+        text.push(format!("... ({} bytes)", bytes.len()));
+    }
+    text.join(" ")
+}
+
 pub fn format_efficiency(used: u32, allocated: u32) -> String {
     if allocated == 0 {
         "0.0%".to_string()
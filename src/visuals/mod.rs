@@ -1,14 +1,30 @@
+mod diff;
 mod formatters;
+mod progress;
+pub mod viewer;
 
-use crate::commands::stats::BuildStats;
+use crate::commands::stats::{BuildStats, FieldChange, SkippedBlock};
+use crate::commands::{BlockComparison, PackingReport};
 use comfy_table::{Attribute, Cell, ContentArrangement, Table};
-use formatters::{format_address_range, format_bytes, format_duration, format_efficiency};
+use formatters::{
+    format_address_range, format_bytes, format_duration, format_efficiency, format_hex_bytes,
+};
+use std::io::IsTerminal;
+
+pub use progress::{progress_bar, show_progress};
+
+/// Blocks listed in the slowest-blocks table of the detailed stats.
+const SLOWEST_BLOCKS: usize = 5;
 
 pub fn print_summary(stats: &BuildStats) {
+    let time = match stats.has_timings() {
+        true => format!(" in {}", format_duration(stats.total_duration)),
+        false => String::new(),
+    };
     println!(
-        "✓ Built {} blocks in {} ({:.1}% efficiency)",
+        "✓ Built {} blocks{} ({:.1}% efficiency)",
         stats.blocks_processed,
-        format_duration(stats.total_duration),
+        time,
         stats.space_efficiency()
     );
 }
@@ -24,7 +40,9 @@ pub fn print_detailed(stats: &BuildStats) {
             Cell::new(""),
         ]);
 
-    summary_table.add_row(vec!["Build Time", &format_duration(stats.total_duration)]);
+    if stats.has_timings() {
+        summary_table.add_row(vec!["Build Time", &format_duration(stats.total_duration)]);
+    }
     summary_table.add_row(vec![
         "Blocks Processed",
         &format!("{}", stats.blocks_processed),
@@ -38,6 +56,12 @@ pub fn print_detailed(stats: &BuildStats) {
         "Space Efficiency",
         &format!("{:.1}%", stats.space_efficiency()),
     ]);
+    if let Some(base) = &stats.base_version {
+        summary_table.add_row(vec![
+            "Base Fallbacks",
+            &format!("{} from {}", stats.base_fallback_count(), base),
+        ]);
+    }
 
     println!("{summary_table}\n");
 
@@ -55,15 +79,26 @@ pub fn print_detailed(stats: &BuildStats) {
     for block in &stats.block_stats {
         detail_table.add_row(vec![
             Cell::new(&block.name),
-            Cell::new(format_address_range(
-                block.start_address,
-                block.allocated_size,
-            )),
-            Cell::new(format!(
-                "{}/{}",
-                format_bytes(block.used_size as usize),
-                format_bytes(block.allocated_size as usize)
-            )),
+            Cell::new(match block.auto_placed {
+                true => format!(
+                    "{} (auto)",
+                    format_address_range(block.start_address, block.allocated_size)
+                ),
+                false => format_address_range(block.start_address, block.allocated_size),
+            }),
+            Cell::new(match block.prologue_size {
+                0 => format!(
+                    "{}/{}",
+                    format_bytes(block.used_size as usize),
+                    format_bytes(block.allocated_size as usize)
+                ),
+                prologue => format!(
+                    "{}/{} ({} prologue)",
+                    format_bytes(block.used_size as usize),
+                    format_bytes(block.allocated_size as usize),
+                    format_bytes(prologue as usize)
+                ),
+            }),
             Cell::new(format_efficiency(block.used_size, block.allocated_size)),
             Cell::new(match block.crc_value {
                 Some(v) => format!("0x{:08X}", v),
@@ -73,4 +108,290 @@ pub fn print_detailed(stats: &BuildStats) {
     }
 
     println!("{detail_table}");
+
+    if stats.block_stats.len() < 2 || !stats.has_timings() {
+        return;
+    }
+    let mut timing_table = Table::new();
+    timing_table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Slowest Blocks").add_attribute(Attribute::Bold),
+            Cell::new("Total").add_attribute(Attribute::Bold),
+            Cell::new("Data Fetch").add_attribute(Attribute::Bold),
+            Cell::new("Emit").add_attribute(Attribute::Bold),
+        ]);
+    for block in stats.slowest_blocks(SLOWEST_BLOCKS) {
+        timing_table.add_row(vec![
+            Cell::new(&block.name),
+            Cell::new(format_duration(block.fetch_duration + block.emit_duration)),
+            Cell::new(format_duration(block.fetch_duration)),
+            Cell::new(format_duration(block.emit_duration)),
+        ]);
+    }
+    println!("\n{timing_table}");
+}
+
+/// Alignment padding between block entries, then each block over `threshold` bytes.
+pub fn print_padding(stats: &BuildStats, threshold: Option<usize>) {
+    if stats.gaps.is_empty() {
+        return;
+    }
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Block").add_attribute(Attribute::Bold),
+            Cell::new("Address").add_attribute(Attribute::Bold),
+            Cell::new("Padding").add_attribute(Attribute::Bold),
+            Cell::new("Between").add_attribute(Attribute::Bold),
+        ]);
+    for gap in &stats.gaps {
+        table.add_row(vec![
+            Cell::new(&gap.block),
+            Cell::new(format!("0x{:08X}", gap.address)),
+            Cell::new(format_bytes(gap.size)),
+            Cell::new(format!(
+                "{} .. {}",
+                gap.after.as_deref().unwrap_or("(block start)"),
+                gap.before
+            )),
+        ]);
+    }
+    println!("\n{table}");
+
+    let Some(threshold) = threshold else {
+        return;
+    };
+    for block in &stats.block_stats {
+        let padding = stats.padding_of(&block.name);
+        if padding > threshold {
+            println!(
+                "⚠ {}: {} of padding exceeds the threshold of {}",
+                block.name,
+                format_bytes(padding),
+                format_bytes(threshold)
+            );
+        }
+    }
+}
+
+/// Values each block takes from the base version of a version stack, where
+/// none of the higher-priority versions provide them.
+pub fn print_fallbacks(stats: &BuildStats) {
+    let Some(base) = &stats.base_version else {
+        return;
+    };
+    if stats.base_fallback_count() == 0 {
+        return;
+    }
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Block").add_attribute(Attribute::Bold),
+            Cell::new(format!("Values from {}", base)).add_attribute(Attribute::Bold),
+        ]);
+    for block in stats
+        .block_stats
+        .iter()
+        .filter(|b| !b.base_fallbacks.is_empty())
+    {
+        table.add_row(vec![
+            Cell::new(&block.name),
+            Cell::new(block.base_fallbacks.join(", ")),
+        ]);
+    }
+    println!("\n{table}");
+}
+
+/// Blocks left out by `--skip-failed-blocks`, with the error each failed with.
+pub fn print_skipped(skipped: &[SkippedBlock], stack: Option<&str>) {
+    if skipped.is_empty() {
+        return;
+    }
+    let scope = stack.map_or(String::new(), |s| format!(" in version stack {}", s));
+    println!(
+        "\n⚠ Skipped {} failed block(s){}; the output does not contain them:",
+        skipped.len(),
+        scope
+    );
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Skipped Block").add_attribute(Attribute::Bold),
+            Cell::new("Error").add_attribute(Attribute::Bold),
+        ]);
+    for block in skipped {
+        table.add_row(vec![Cell::new(&block.name), Cell::new(&block.error)]);
+    }
+    println!("{table}");
+}
+
+/// The `top` largest leaf entries of each block, with their share of the block.
+pub fn print_fields(stats: &BuildStats, top: usize) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Block").add_attribute(Attribute::Bold),
+            Cell::new("Field").add_attribute(Attribute::Bold),
+            Cell::new("Type").add_attribute(Attribute::Bold),
+            Cell::new("Size").add_attribute(Attribute::Bold),
+            Cell::new("Share").add_attribute(Attribute::Bold),
+        ]);
+
+    for block in &stats.block_stats {
+        for field in stats.largest_fields(&block.name, top) {
+            table.add_row(vec![
+                Cell::new(&block.name),
+                Cell::new(&field.path),
+                Cell::new(&field.type_name),
+                Cell::new(format_bytes(field.size)),
+                Cell::new(format_efficiency(field.size as u32, block.allocated_size)),
+            ]);
+        }
+    }
+
+    println!("{table}");
+}
+
+/// One row per `--version-matrix` stack.
+pub fn print_matrix(results: &[(String, BuildStats)]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Version Stack").add_attribute(Attribute::Bold),
+            Cell::new("Blocks").add_attribute(Attribute::Bold),
+            Cell::new("Used/Alloc").add_attribute(Attribute::Bold),
+            Cell::new("Efficiency").add_attribute(Attribute::Bold),
+            Cell::new("Build Time").add_attribute(Attribute::Bold),
+        ]);
+
+    for (stack, stats) in results {
+        table.add_row(vec![
+            Cell::new(stack),
+            Cell::new(stats.blocks_processed),
+            Cell::new(format!(
+                "{}/{}",
+                format_bytes(stats.total_used),
+                format_bytes(stats.total_allocated)
+            )),
+            Cell::new(format!("{:.1}%", stats.space_efficiency())),
+            Cell::new(match stats.has_timings() {
+                true => format_duration(stats.total_duration),
+                false => "-".to_string(),
+            }),
+        ]);
+    }
+
+    println!("{table}");
+}
+
+/// One row per block with its CRC status, then one row per differing field.
+pub fn print_compare(comparisons: &[BlockComparison]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Block").add_attribute(Attribute::Bold),
+            Cell::new("Address").add_attribute(Attribute::Bold),
+            Cell::new("CRC").add_attribute(Attribute::Bold),
+            Cell::new("Differing Fields").add_attribute(Attribute::Bold),
+        ]);
+
+    for block in comparisons {
+        let crc = match (&block.crc, block.covered) {
+            (_, false) => "not in image".to_string(),
+            (None, true) => "N/A".to_string(),
+            (Some(c), true) if c.matches() => "ok".to_string(),
+            (Some(c), true) => format!(
+                "stored {} != computed {}",
+                format_hex_bytes(&c.stored),
+                format_hex_bytes(&c.computed)
+            ),
+        };
+        table.add_row(vec![
+            Cell::new(&block.name),
+            Cell::new(format!("0x{:X}", block.start_address)),
+            Cell::new(crc),
+            Cell::new(block.differences.len()),
+        ]);
+    }
+    println!("{table}");
+
+    if comparisons.iter().all(|c| c.differences.is_empty()) {
+        return;
+    }
+
+    let mut fields = Table::new();
+    fields
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Block").add_attribute(Attribute::Bold),
+            Cell::new("Field").add_attribute(Attribute::Bold),
+            Cell::new("Address").add_attribute(Attribute::Bold),
+            Cell::new("Expected").add_attribute(Attribute::Bold),
+            Cell::new("Actual").add_attribute(Attribute::Bold),
+        ]);
+    for block in comparisons {
+        for diff in &block.differences {
+            fields.add_row(vec![
+                Cell::new(&block.name),
+                Cell::new(&diff.field),
+                Cell::new(format!("0x{:X}", diff.address)),
+                Cell::new(format_hex_bytes(&diff.expected)),
+                Cell::new(match &diff.byte_swapped {
+                    Some(r) => format!(
+                        "{}\nbyte-swapped: reads {} as little-endian, {} as big-endian",
+                        format_hex_bytes(&diff.actual),
+                        r.little,
+                        r.big
+                    ),
+                    None => format_hex_bytes(&diff.actual),
+                }),
+            ]);
+        }
+    }
+    println!("\n{fields}");
+}
+
+pub fn print_packing(reports: &[PackingReport]) {
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Block").add_attribute(Attribute::Bold),
+            Cell::new("Padding").add_attribute(Attribute::Bold),
+            Cell::new("Suggested Padding").add_attribute(Attribute::Bold),
+            Cell::new("Suggested Order").add_attribute(Attribute::Bold),
+        ]);
+
+    for report in reports {
+        let (padding, order) = match &report.suggestion {
+            Some(s) => (s.padding.to_string(), s.order.join(", ")),
+            None => ("-".to_string(), "no improvement".to_string()),
+        };
+        table.add_row(vec![
+            Cell::new(&report.name),
+            Cell::new(report.padding),
+            Cell::new(padding),
+            Cell::new(order),
+        ]);
+    }
+    println!("{table}");
+}
+
+/// Field-annotated diff of a `--baseline` build, colored when stdout is a terminal.
+pub fn print_diff(changes: &[FieldChange]) {
+    if changes.is_empty() {
+        println!("No fields changed against the baseline.");
+        return;
+    }
+    print!(
+        "{}",
+        diff::render_diff(changes, std::io::stdout().is_terminal())
+    );
 }
@@ -0,0 +1,36 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use std::io::IsTerminal;
+
+/// Builds with more blocks than this show progress bars.
+pub const MIN_BLOCKS: usize = 20;
+
+/// Whether a build of `blocks` blocks shows progress: only above [`MIN_BLOCKS`],
+/// without `--quiet`, and when stderr is a terminal.
+pub fn show_progress(blocks: usize, quiet: bool) -> bool {
+    blocks > MIN_BLOCKS && !quiet && std::io::stderr().is_terminal()
+}
+
+/// Progress bar on stderr over `len` steps, or a hidden one when not `visible`.
+pub fn progress_bar(len: usize, message: &'static str, visible: bool) -> ProgressBar {
+    if !visible {
+        return ProgressBar::hidden();
+    }
+    let style = ProgressStyle::with_template("{msg:>9} [{bar:40}] {pos}/{len} ({elapsed})")
+        .expect("valid progress template")
+        .progress_chars("=> ");
+    ProgressBar::new(len as u64)
+        .with_style(style)
+        .with_message(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_or_quiet_builds_hide_progress() {
+        assert!(!show_progress(MIN_BLOCKS, false));
+        assert!(!show_progress(MIN_BLOCKS + 1, true));
+        assert!(progress_bar(3, "Building", false).is_hidden());
+    }
+}
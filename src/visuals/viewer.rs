@@ -0,0 +1,204 @@
+use crate::commands::ViewField;
+use crate::output::image::Image;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+const ROW_BYTES: u64 = 16;
+
+/// One row of the hex pane: 16 bytes from `address`, `None` where the image has no data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexRow {
+    pub address: u64,
+    pub bytes: Vec<Option<u8>>,
+    /// Whether each byte belongs to the selected field.
+    pub selected: Vec<bool>,
+}
+
+/// Field tree and hex pane over a read-back image.
+pub struct Viewer {
+    image: Image,
+    fields: Vec<ViewField>,
+    selected: usize,
+}
+
+impl Viewer {
+    pub fn new(image: Image, fields: Vec<ViewField>) -> Self {
+        Self {
+            image,
+            fields,
+            selected: 0,
+        }
+    }
+
+    pub fn selected(&self) -> Option<&ViewField> {
+        self.fields.get(self.selected)
+    }
+
+    /// Moves the selection by `delta` fields, stopping at either end.
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.fields.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    /// `height` rows around the selected field, which starts a third of the way down.
+    pub fn hex_rows(&self, height: usize) -> Vec<HexRow> {
+        let Some(field) = self.selected() else {
+            return Vec::new();
+        };
+        let selected = field.address..field.address + field.len as u64;
+        let first = (field.address / ROW_BYTES).saturating_sub(height as u64 / 3) * ROW_BYTES;
+        (0..height as u64)
+            .map(|row| {
+                let address = first + row * ROW_BYTES;
+                let addresses = address..address + ROW_BYTES;
+                HexRow {
+                    address,
+                    bytes: addresses.clone().map(|a| self.image.byte_at(a)).collect(),
+                    selected: addresses.map(|a| selected.contains(&a)).collect(),
+                }
+            })
+            .collect()
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [body, help] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+        let [tree, hex] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(body);
+
+        let items: Vec<ListItem> = self
+            .fields
+            .iter()
+            .map(|f| ListItem::new(format!("{}.{}", f.block, f.path)))
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(" Fields "))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(list, tree, &mut state);
+
+        let title = match self.selected() {
+            Some(f) => format!(" 0x{:08X} ({} bytes) ", f.address, f.len),
+            None => " No fields ".to_string(),
+        };
+        let lines: Vec<Line> = self
+            .hex_rows(hex.height.saturating_sub(2) as usize)
+            .iter()
+            .map(hex_line)
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(title)),
+            hex,
+        );
+
+        frame.render_widget(
+            Paragraph::new("↑/↓ select  PgUp/PgDn page  Home/End jump  q quit")
+                .style(Style::default().add_modifier(Modifier::DIM)),
+            help,
+        );
+    }
+}
+
+fn hex_line(row: &HexRow) -> Line<'static> {
+    let highlight = Style::default().fg(Color::Black).bg(Color::Yellow);
+    let mut spans = vec![Span::raw(format!("{:08X}  ", row.address))];
+    for (byte, &selected) in row.bytes.iter().zip(&row.selected) {
+        let text = byte.map_or("--".to_string(), |b| format!("{:02X}", b));
+        spans.push(match selected {
+            true => Span::styled(text, highlight),
+            false => Span::raw(text),
+        });
+        spans.push(Span::raw(" "));
+    }
+    spans.push(Span::raw(" "));
+    for (byte, &selected) in row.bytes.iter().zip(&row.selected) {
+        let c = match byte {
+            Some(b) if b.is_ascii_graphic() || *b == b' ' => *b as char,
+            Some(_) => '.',
+            None => ' ',
+        };
+        spans.push(match selected {
+            true => Span::styled(c.to_string(), highlight),
+            false => Span::raw(c.to_string()),
+        });
+    }
+    Line::from(spans)
+}
+
+/// Runs the viewer until the user quits.
+pub fn run(viewer: &mut Viewer) -> std::io::Result<()> {
+    ratatui::run(|terminal| event_loop(terminal, viewer))
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, viewer: &mut Viewer) -> std::io::Result<()> {
+    loop {
+        let size = terminal.draw(|frame| viewer.draw(frame))?.area;
+        let page = size.height.saturating_sub(4).max(1) as isize;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Down | KeyCode::Char('j') => viewer.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => viewer.move_selection(-1),
+            KeyCode::PageDown => viewer.move_selection(page),
+            KeyCode::PageUp => viewer.move_selection(-page),
+            KeyCode::Home => viewer.move_selection(isize::MIN),
+            KeyCode::End => viewer.move_selection(isize::MAX),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(path: &str, address: u64, len: usize) -> ViewField {
+        ViewField {
+            block: "cal".to_string(),
+            path: path.to_string(),
+            address,
+            len,
+        }
+    }
+
+    fn viewer() -> Viewer {
+        // Four bytes 07 00 34 12 at 0x1000.
+        let image = Image::parse(":04100000070034129F\n:00000001FF\n").unwrap();
+        Viewer::new(
+            image,
+            vec![field("id", 0x1000, 2), field("gain", 0x1002, 2)],
+        )
+    }
+
+    #[test]
+    fn highlights_selected_field_bytes() {
+        let mut viewer = viewer();
+        viewer.move_selection(1);
+        let rows = viewer.hex_rows(1);
+        assert_eq!(rows[0].address, 0x1000);
+        assert_eq!(
+            rows[0].bytes[..5],
+            [Some(0x07), Some(0x00), Some(0x34), Some(0x12), None]
+        );
+        assert_eq!(rows[0].selected[..5], [false, false, true, true, false]);
+    }
+
+    #[test]
+    fn selection_stops_at_either_end() {
+        let mut viewer = viewer();
+        viewer.move_selection(-1);
+        assert_eq!(viewer.selected().unwrap().path, "id");
+        viewer.move_selection(isize::MAX);
+        assert_eq!(viewer.selected().unwrap().path, "gain");
+    }
+}
@@ -0,0 +1,39 @@
+//! Warnings printed during a run, counted so `--deny-warnings` can fail the build.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+static EMITTED: AtomicUsize = AtomicUsize::new(0);
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// Prints warnings as JSON lines instead of text, for `--log-format json`.
+pub fn set_json(json: bool) {
+    JSON.store(json, Ordering::Relaxed);
+}
+
+/// Prints `message` to stderr as a warning and counts it.
+pub fn warn(message: impl std::fmt::Display) {
+    EMITTED.fetch_add(1, Ordering::Relaxed);
+    if JSON.load(Ordering::Relaxed) {
+        let line = serde_json::json!({ "level": "warn", "message": message.to_string() });
+        eprintln!("{}", line);
+    } else {
+        eprintln!("[WARN] {}", message);
+    }
+}
+
+/// Number of warnings printed so far in this process.
+pub fn count() -> usize {
+    EMITTED.load(Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warn_increments_count() {
+        let before = count();
+        warn("test warning");
+        assert!(count() > before);
+    }
+}
@@ -0,0 +1,47 @@
+use mint_cli::commands;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "big"
+
+[settings.crc]
+location = "end_data"
+polynomial = 0x04C11DB7
+start = 0xFFFFFFFF
+xor_out = 0xFFFFFFFF
+ref_in = true
+ref_out = true
+area = "data"
+
+[cal.header]
+start_address = 0x8000
+length = 0x40
+
+[cal.data]
+id = { value = 7, type = "u8" }
+limit = { value = -300, type = "i16" }
+gains = { value = [1.5, 2.0], type = "f32", size = 2 }
+"#;
+
+#[test]
+fn annotated_dump_names_every_byte_range() {
+    let mut args = common::layout_args("annotate", LAYOUT, "cal");
+    args.output.annotate = Some("out/annotate.txt".into());
+    let stats = commands::build(&args, None).expect("build");
+    let crc = stats.block_stats[0].crc_value.unwrap();
+
+    let dump = std::fs::read_to_string("out/annotate.txt").unwrap();
+    let lines: Vec<&str> = dump.lines().skip(2).collect();
+    assert_eq!(lines[0], "# cal @ 0x00008000, 16 bytes");
+    assert!(lines[1].starts_with("00008000  07 "));
+    assert!(lines[1].ends_with("; id: u8 = 7"));
+    assert!(lines[2].ends_with("; padding"));
+    assert!(lines[3].starts_with("00008002  FE D4 "));
+    assert!(lines[3].ends_with("; limit: i16 = -300"));
+    assert!(lines[4].starts_with("00008004  3F C0 00 00 40 00 00 00 "));
+    assert!(lines[4].ends_with("; gains: f32[2] = [1.5, 2]"));
+    assert!(lines[5].ends_with(&format!("; crc = 0x{:08X}", crc)));
+    assert_eq!(lines.len(), 6);
+}
@@ -0,0 +1,122 @@
+use mint_cli::data::args::DataArgs;
+use mint_cli::data::create_data_source;
+use mint_cli::layout::used_values::NoopValueSink;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const DATA: &str = r#"{
+    "Default": {
+        "Table": [[1, 2, 3], [4, 5, 6]],
+        "Jagged": [[1, 2, 3], [4]]
+    }
+}"#;
+
+fn build(name: &str, entry: &str) -> Result<Vec<u8>, String> {
+    let layout = format!(
+        r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x20
+padding = 0x00
+
+[block.data]
+table = {entry}
+"#
+    );
+    let path = common::write_layout_file(name, &layout);
+    let cfg = mint_cli::layout::load_layout(&path).map_err(|e| e.to_string())?;
+    let args = DataArgs {
+        json: Some(DATA.to_string()),
+        version: Some("Default".to_string()),
+        ..Default::default()
+    };
+    let ds = create_data_source(&args).unwrap();
+    cfg.blocks["block"]
+        .build_bytestream(ds.as_deref(), &cfg.settings, false, &mut NoopValueSink)
+        .map(|(bytes, _)| bytes)
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn column_order_emits_columns_contiguously() {
+    let bytes = build(
+        "order_column",
+        r#"{ name = "Table", type = "u8", size = [2, 3], order = "column" }"#,
+    )
+    .unwrap();
+    assert_eq!(bytes, [1, 4, 2, 5, 3, 6]);
+
+    let bytes = build(
+        "order_column_padded",
+        r#"{ name = "Table", type = "u8", size = [3, 3], order = "column" }"#,
+    )
+    .unwrap();
+    assert_eq!(bytes, [1, 4, 0, 2, 5, 0, 3, 6, 0]);
+}
+
+#[test]
+fn transpose_swaps_source_rows_and_columns() {
+    let bytes = build(
+        "order_transpose",
+        r#"{ name = "Table", type = "u8", size = [3, 2], transpose = true }"#,
+    )
+    .unwrap();
+    assert_eq!(bytes, [1, 4, 2, 5, 3, 6]);
+
+    let bytes = build(
+        "order_transpose_column",
+        r#"{ name = "Table", type = "u8", size = [3, 2], transpose = true, order = "column" }"#,
+    )
+    .unwrap();
+    assert_eq!(bytes, [1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn order_requires_2d_size() {
+    let err = build(
+        "order_1d",
+        r#"{ name = "Table", type = "u8", size = 6, order = "column" }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("order/transpose require a 2D size"), "{err}");
+}
+
+#[test]
+fn jagged_rows_pad_to_declared_width() {
+    let bytes = build(
+        "jagged_rows",
+        r#"{ name = "Jagged", type = "u8", size = [2, 3] }"#,
+    )
+    .unwrap();
+    assert_eq!(bytes, [1, 2, 3, 4, 0, 0]);
+
+    let bytes = build(
+        "jagged_column",
+        r#"{ name = "Jagged", type = "u8", size = [2, 3], order = "column" }"#,
+    )
+    .unwrap();
+    assert_eq!(bytes, [1, 4, 2, 0, 3, 0]);
+
+    let err = build(
+        "jagged_strict",
+        r#"{ name = "Jagged", type = "u8", SIZE = [2, 3] }"#,
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("column count smaller than defined size"),
+        "{err}"
+    );
+
+    let err = build(
+        "jagged_narrow",
+        r#"{ name = "Jagged", type = "u8", size = [2, 2] }"#,
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("column count greater than defined size"),
+        "{err}"
+    );
+}
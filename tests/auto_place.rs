@@ -0,0 +1,87 @@
+use mint_cli::commands;
+use mint_cli::layout::load_layout;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[settings.auto_place]
+base = 0x8000
+alignment = 0x100
+gap = 0x10
+
+[a.header]
+length = 0x30
+
+[b.header]
+length = 0x100
+
+[fixed.header]
+start_address = 0x20000
+length = 0x10
+
+[c.header]
+length = 0x10
+
+[a.data]
+id = { value = 1, type = "u8" }
+
+[b.data]
+id = { value = 2, type = "u8" }
+
+[fixed.data]
+id = { value = 3, type = "u8" }
+
+[c.data]
+id = { value = 4, type = "u8" }
+"#;
+
+#[test]
+fn blocks_without_start_address_are_placed_in_order() {
+    let args = common::layout_args("auto_place", LAYOUT, "");
+    let cfg = load_layout(&args.layout.blocks[0].file).unwrap();
+    let starts: Vec<u32> = ["a", "b", "fixed", "c"]
+        .iter()
+        .map(|name| cfg.blocks[*name].header.start_address)
+        .collect();
+    assert_eq!(starts, [0x8000, 0x8100, 0x20000, 0x8300]);
+
+    let stats = commands::build(&args, None).unwrap();
+    let placed: Vec<(u32, bool)> = stats
+        .block_stats
+        .iter()
+        .map(|b| (b.start_address, b.auto_placed))
+        .collect();
+    assert_eq!(
+        placed,
+        [
+            (0x8000, true),
+            (0x8100, true),
+            (0x20000, false),
+            (0x8300, true)
+        ]
+    );
+}
+
+#[test]
+fn missing_start_address_needs_auto_place() {
+    let path = common::write_layout_file(
+        "auto_place_unset",
+        r#"[settings]
+endianness = "little"
+
+[a.header]
+length = 0x30
+
+[a.data]
+id = { value = 1, type = "u8" }
+"#,
+    );
+    let err = load_layout(&path).unwrap_err().to_string();
+    assert!(
+        err.contains("block 'a' has no start_address and [settings.auto_place] is not set"),
+        "{err}"
+    );
+}
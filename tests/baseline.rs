@@ -0,0 +1,82 @@
+use mint_cli::commands;
+use mint_cli::output::args::OutputFormat;
+
+#[path = "common/mod.rs"]
+mod common;
+
+fn layout(second: u8) -> String {
+    format!(
+        r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x8
+
+[block.data]
+a = {{ value = 1, type = "u8" }}
+b = {{ value = {second}, type = "u8" }}
+c = {{ value = 0x0303, type = "u16" }}
+"#
+    )
+}
+
+fn build(
+    stem: &str,
+    second: u8,
+    format: OutputFormat,
+    baseline: Option<&str>,
+    granularity: u32,
+) -> String {
+    let ext = match format {
+        OutputFormat::Hex => "hex",
+        OutputFormat::Mot => "mot",
+    };
+    let out = format!("out/{}.{}", stem, ext);
+    common::build_layout_with(stem, &layout(second), "block", |args| {
+        args.output.out = out.clone().into();
+        args.output.format = format;
+        args.output.baseline = baseline.map(Into::into);
+        args.output.baseline_granularity = granularity;
+    })
+    .expect("build");
+    out
+}
+
+#[test]
+fn baseline_keeps_only_changed_records() {
+    let base = build("baseline_hex_base", 2, OutputFormat::Hex, None, 1);
+
+    let out = build("baseline_hex_delta", 9, OutputFormat::Hex, Some(&base), 1);
+    let hex = std::fs::read_to_string(out).unwrap();
+    assert_eq!(hex, ":0110010009E5\n:00000001FF");
+
+    let out = build("baseline_hex_wide", 9, OutputFormat::Hex, Some(&base), 4);
+    let hex = std::fs::read_to_string(out).unwrap();
+    assert!(hex.starts_with(":0410000001090303"), "{hex}");
+    assert_eq!(hex.lines().count(), 2, "{hex}");
+}
+
+#[test]
+fn baseline_accepts_srec_and_skips_identical_output() {
+    let base = build("baseline_mot_base", 2, OutputFormat::Mot, None, 1);
+    let out = build("baseline_mot_same", 2, OutputFormat::Mot, Some(&base), 1);
+    let mot = std::fs::read_to_string(out).unwrap();
+    assert!(!mot.lines().any(|l| l.starts_with("S1")), "{mot}");
+}
+
+#[test]
+fn baseline_build_reports_changed_fields() {
+    let base = build("baseline_fields_base", 2, OutputFormat::Hex, None, 1);
+    let mut args = common::layout_args("baseline_fields", &layout(9), "block");
+    args.output.baseline = Some(base.into());
+    let stats = commands::build(&args, None).expect("build");
+
+    assert_eq!(stats.changes.len(), 1);
+    let change = &stats.changes[0];
+    assert_eq!((change.field.as_str(), change.address), ("b", 0x1001));
+    assert_eq!(
+        (&change.old[..], &change.new[..]),
+        (&[Some(2)][..], &[9][..])
+    );
+}
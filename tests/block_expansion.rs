@@ -23,15 +23,14 @@ fn test_file_expands_all_blocks() {
                 file: layout_path.to_string(),
             }],
             strict: false,
+            ..Default::default()
         },
         data: Default::default(),
         output: mint_cli::output::args::OutputArgs {
             out: PathBuf::from("out/expand_test.hex"),
-            record_width: 32,
             format: mint_cli::output::args::OutputFormat::Hex,
-            export_json: None,
-            stats: false,
             quiet: true,
+            ..Default::default()
         },
     };
 
@@ -71,15 +70,14 @@ fn test_deduplication_file_and_specific() {
                 },
             ],
             strict: false,
+            ..Default::default()
         },
         data: Default::default(),
         output: mint_cli::output::args::OutputArgs {
             out: PathBuf::from("out/dedup_test.hex"),
-            record_width: 32,
             format: mint_cli::output::args::OutputFormat::Hex,
-            export_json: None,
-            stats: false,
             quiet: true,
+            ..Default::default()
         },
     };
 
@@ -110,15 +108,14 @@ fn test_file_expansion_builds_all_blocks() {
                 file: layout_path.to_string(),
             }],
             strict: false,
+            ..Default::default()
         },
         data: Default::default(),
         output: mint_cli::output::args::OutputArgs {
             out: PathBuf::from("out/all_blocks.hex"),
-            record_width: 32,
             format: mint_cli::output::args::OutputFormat::Hex,
-            export_json: None,
-            stats: false,
             quiet: true,
+            ..Default::default()
         },
     };
 
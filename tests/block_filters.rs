@@ -0,0 +1,69 @@
+use mint_cli::layout::error::LayoutError;
+use mint_cli::output::image::Image;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"
+[settings]
+endianness = "little"
+
+[eeprom_cal.header]
+start_address = 0x1000
+length = 0x4
+tags = ["eeprom"]
+
+[eeprom_cal.data]
+id = { value = 1, type = "u32" }
+
+[eeprom_debug.header]
+start_address = 0x2000
+length = 0x4
+tags = ["eeprom", "debug"]
+
+[eeprom_debug.data]
+id = { value = 2, type = "u32" }
+
+[flash_app.header]
+start_address = 0x3000
+length = 0x4
+
+[flash_app.data]
+id = { value = 3, type = "u32" }
+"#;
+
+fn build(stem: &str, configure: impl FnOnce(&mut mint_cli::args::Args)) -> Result<Image, String> {
+    common::build_layout_with(stem, LAYOUT, "", configure).map_err(|e| e.to_string())?;
+    Ok(Image::read(format!("out/{}.hex", stem).as_ref()).unwrap())
+}
+
+#[test]
+fn only_and_exclude_filter_by_name() {
+    let image = build("filter_globs", |args| {
+        args.layout.only = vec!["eeprom_*".to_string()];
+        args.layout.exclude = vec!["*_debug".to_string()];
+    })
+    .unwrap();
+    assert_eq!(image.byte_at(0x1000), Some(1));
+    assert_eq!(image.byte_at(0x2000), None);
+    assert_eq!(image.byte_at(0x3000), None);
+}
+
+#[test]
+fn tag_selects_tagged_blocks() {
+    let image = build("filter_tags", |args| {
+        args.layout.tag = vec!["debug".to_string()];
+    })
+    .unwrap();
+    assert_eq!(image.byte_at(0x1000), None);
+    assert_eq!(image.byte_at(0x2000), Some(2));
+}
+
+#[test]
+fn filters_that_match_nothing_are_an_error() {
+    let err = build("filter_none", |args| {
+        args.layout.only = vec!["missing_*".to_string()];
+    })
+    .unwrap_err();
+    assert_eq!(err, LayoutError::NoBlocksSelected.to_string());
+}
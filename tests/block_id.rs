@@ -0,0 +1,80 @@
+use mint_cli::data::args::DataArgs;
+use mint_cli::data::create_data_source;
+use mint_cli::layout::used_values::NoopValueSink;
+
+#[path = "common/mod.rs"]
+mod common;
+
+fn fnv1a_32(text: &str) -> u32 {
+    text.bytes().fold(0x811c_9dc5, |hash, b| {
+        (hash ^ u32::from(b)).wrapping_mul(0x0100_0193)
+    })
+}
+
+fn build(id_entry: &str, version: Option<&str>) -> Result<Vec<u8>, String> {
+    let layout = format!(
+        r#"[settings]
+endianness = "little"
+
+[cal.header]
+start_address = 0x1000
+length = 0x20
+
+[cal.data]
+variant_id = {id_entry}
+"#
+    );
+    let path = common::write_layout_file("block_id", &layout);
+    let cfg = mint_cli::layout::load_layout(&path).map_err(|e| e.to_string())?;
+    let ds = version.map(|v| {
+        let args = DataArgs {
+            json: Some(r#"{"Debug": {}, "Default": {}}"#.to_string()),
+            version: Some(v.to_string()),
+            ..Default::default()
+        };
+        create_data_source(&args).unwrap().unwrap()
+    });
+    cfg.blocks["cal"]
+        .build_bytestream(ds.as_deref(), &cfg.settings, false, &mut NoopValueSink)
+        .map(|(bytes, _)| bytes)
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn block_id_hashes_block_name_and_version_stack() {
+    let entry = r#"{ auto = { block_id = {} }, type = "u32" }"#;
+    let debug = build(entry, Some("Debug/Default")).unwrap();
+    assert_eq!(debug, fnv1a_32("cal@Debug/Default").to_le_bytes());
+
+    let default = build(entry, Some("Default")).unwrap();
+    assert_eq!(default, fnv1a_32("cal@Default").to_le_bytes());
+
+    let no_source = build(entry, None).unwrap();
+    assert_eq!(no_source, fnv1a_32("cal@").to_le_bytes());
+}
+
+#[test]
+fn block_id_salt_hash_and_width() {
+    let salted = build(
+        r#"{ auto = { block_id = { salt = "acme:" } }, type = "u16" }"#,
+        Some("Default"),
+    )
+    .unwrap();
+    let expected = (fnv1a_32("acme:cal@Default") & 0xFFFF) as u16;
+    assert_eq!(salted, expected.to_le_bytes());
+
+    let xx = build(
+        r#"{ auto = { block_id = { hash = "xxhash" } }, type = "u64" }"#,
+        Some("Default"),
+    )
+    .unwrap();
+    assert_eq!(xx.len(), 8);
+    assert_ne!(xx, [0; 8]);
+
+    let err = build(
+        r#"{ auto = { block_id = { hash = "md5" } }, type = "u32" }"#,
+        None,
+    )
+    .unwrap_err();
+    assert!(err.contains("md5"), "{err}");
+}
@@ -0,0 +1,65 @@
+use mint_cli::commands;
+use std::path::PathBuf;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[boot.header]
+start_address = 0x1000
+length = 0x4
+
+[boot.data]
+id = { value = 0x11, type = "u8" }
+
+[app.header]
+start_address = 0x2000
+length = 0x4
+
+[app.data]
+id = { value = 0x22, type = "u8" }
+"#;
+
+#[test]
+fn block_out_dir_writes_each_block_and_combined_output() {
+    let dir = PathBuf::from("out/block_out_dir");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let mut args = common::layout_args("block_out_dir", LAYOUT, "");
+    args.output.out = "out/block_out_dir_combined.hex".into();
+    args.output.block_out_dir = Some(dir.clone());
+    commands::build(&args, None).expect("build");
+
+    let combined = std::fs::read_to_string("out/block_out_dir_combined.hex").unwrap();
+    assert!(combined.contains(":0110000011"), "{combined}");
+    assert!(combined.contains(":0120000022"), "{combined}");
+
+    let boot = std::fs::read_to_string(dir.join("boot.hex")).expect("boot output");
+    assert!(boot.contains(":0110000011"), "{boot}");
+    assert!(!boot.contains(":01200000"), "{boot}");
+
+    let app = std::fs::read_to_string(dir.join("app.hex")).expect("app output");
+    assert!(app.contains(":0120000022"), "{app}");
+    assert!(!app.contains(":01100000"), "{app}");
+}
+
+#[test]
+fn header_format_overrides_format_for_its_block_file() {
+    let layout = LAYOUT.replace("[app.header]\n", "[app.header]\nformat = \"mot\"\n");
+    let dir = PathBuf::from("out/block_out_dir_format");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let mut args = common::layout_args("block_out_dir_format", &layout, "");
+    args.output.block_out_dir = Some(dir.clone());
+    commands::build(&args, None).expect("build");
+
+    let combined = std::fs::read_to_string("out/block_out_dir_format.hex").unwrap();
+    assert!(combined.contains(":0120000022"), "{combined}");
+
+    assert!(dir.join("boot.hex").exists());
+    assert!(!dir.join("app.hex").exists());
+    let app = std::fs::read_to_string(dir.join("app.mot")).expect("app output");
+    assert!(app.contains("S104200022"), "{app}");
+}
@@ -0,0 +1,215 @@
+use mint_cli::error::MintError;
+use mint_cli::layout::error::LayoutError;
+
+#[path = "common/mod.rs"]
+mod common;
+
+#[test]
+fn container_embeds_referenced_block_with_crc() {
+    let layout = r#"[settings]
+endianness = "big"
+
+[settings.crc]
+polynomial = 0x04C11DB7
+start = 0xFFFFFFFF
+xor_out = 0xFFFFFFFF
+ref_in = true
+ref_out = true
+area = "data"
+
+[inner.header]
+start_address = 0x2000
+length = 0x10
+crc = { location = "end_data" }
+
+[inner.data]
+magic = { value = 0x31323334, type = "u32" }
+
+[container.header]
+start_address = 0x1000
+length = 0x20
+
+[container.data]
+version = { value = 1, type = "u16" }
+payload = { block = "inner", size = 12 }
+"#;
+    let hex = common::build_layout("block_refs_container", layout, "container").unwrap();
+
+    // "1234" big-endian followed by CRC32("1234"), then padding up to size.
+    let expected = "0001313233349BE3E0A3FFFFFFFF";
+    assert!(hex.contains(expected), "{hex}");
+    assert!(
+        !hex.contains(":0800200031"),
+        "inner block should not be output: {hex}"
+    );
+}
+
+#[test]
+fn container_embeds_word_addressed_block_swapped_once() {
+    let layout = r#"[settings]
+endianness = "big"
+word_addressing = true
+
+[settings.crc]
+polynomial = 0x04C11DB7
+start = 0xFFFFFFFF
+xor_out = 0xFFFFFFFF
+ref_in = true
+ref_out = true
+area = "data"
+
+[inner.header]
+start_address = 0x2000
+length = 0x10
+crc = { location = "end_data" }
+
+[inner.data]
+magic = { value = 0x31323334, type = "u32" }
+
+[container.header]
+start_address = 0x1000
+length = 0x20
+
+[container.data]
+version = { value = 1, type = "u16" }
+payload = { block = "inner", size = 12 }
+"#;
+    let inner = common::build_layout("block_refs_word_inner", layout, "inner").unwrap();
+    let hex = common::build_layout("block_refs_word_container", layout, "container").unwrap();
+
+    // The payload reads as the inner block does on its own.
+    assert!(inner.contains("32313433355A70F2"), "{inner}");
+    assert!(hex.contains("010032313433355A70F2FFFF"), "{hex}");
+}
+
+#[test]
+fn cyclic_block_references_are_rejected() {
+    let layout = r#"[settings]
+endianness = "little"
+
+[a.header]
+start_address = 0x1000
+length = 0x10
+
+[a.data]
+b = { block = "b" }
+
+[b.header]
+start_address = 0x2000
+length = 0x10
+
+[b.data]
+a = { block = "a" }
+"#;
+    let err = common::build_layout("block_refs_cycle", layout, "a").unwrap_err();
+    assert!(
+        matches!(err, MintError::Layout(LayoutError::DependencyCycle(_))),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn cycle_error_names_only_blocks_on_the_cycle() {
+    let layout = r#"[settings]
+endianness = "little"
+
+[app.header]
+start_address = 0x3000
+length = 0x20
+
+[app.data]
+a = { block = "a" }
+
+[a.header]
+start_address = 0x1000
+length = 0x10
+
+[a.data]
+b = { block = "b" }
+
+[b.header]
+start_address = 0x2000
+length = 0x10
+
+[b.data]
+a = { block = "a" }
+"#;
+    let err = common::build_layout("block_refs_cycle_names", layout, "app").unwrap_err();
+    assert_eq!(err.to_string(), "Block dependency cycle between: a, b.");
+}
+
+#[test]
+fn crc_of_stores_another_blocks_crc() {
+    let layout = r#"[settings]
+endianness = "big"
+
+[settings.crc]
+polynomial = 0x04C11DB7
+start = 0xFFFFFFFF
+xor_out = 0xFFFFFFFF
+ref_in = true
+ref_out = true
+area = "data"
+
+[app.header]
+start_address = 0x2000
+length = 0x10
+crc = { location = "end_data" }
+
+[app.data]
+magic = { value = 0x31323334, type = "u32" }
+
+[boot.header]
+start_address = 0x1000
+length = 0x10
+
+[boot.data]
+app_crc = { auto = { crc_of = "app" }, type = "u32" }
+"#;
+    let hex = common::build_layout("block_refs_crc_of", layout, "boot").unwrap();
+    assert!(hex.starts_with(":041000009BE3E0A3"), "{hex}");
+
+    let err = common::build_layout(
+        "block_refs_crc_of_missing",
+        &layout.replace("crc = { location = \"end_data\" }\n", ""),
+        "boot",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("has no CRC"), "{err}");
+}
+
+#[test]
+fn crc_of_reads_crc_before_word_swap() {
+    let layout = r#"[settings]
+endianness = "big"
+word_addressing = true
+
+[settings.crc]
+polynomial = 0x04C11DB7
+start = 0xFFFFFFFF
+xor_out = 0xFFFFFFFF
+ref_in = true
+ref_out = true
+area = "data"
+
+[app.header]
+start_address = 0x2000
+length = 0x10
+crc = { location = "end_data" }
+
+[app.data]
+magic = { value = 0x31323334, type = "u32" }
+
+[boot.header]
+start_address = 0x1000
+length = 0x10
+
+[boot.data]
+app_crc = { auto = { crc_of = "app" }, type = "u32" }
+"#;
+    let app = common::build_layout("block_refs_crc_of_word_app", layout, "app").unwrap();
+    let boot = common::build_layout("block_refs_crc_of_word_boot", layout, "boot").unwrap();
+    // Both store the CRC swapped the same way; the value is not swapped twice.
+    assert!(app.starts_with(":0840000032313433355A70F2"), "{app}");
+    assert!(boot.starts_with(":04200000355A70F2"), "{boot}");
+}
@@ -0,0 +1,58 @@
+use mint_cli::commands;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[boot.header]
+start_address = 0x1000
+length = 0x4
+
+[boot.data]
+id = { value = 0x11, type = "u8" }
+
+[app.header]
+start_address = 0x2000
+length = 0x4
+
+[app.data]
+id = { value = 0x22, type = "u8" }
+"#;
+
+fn sha256_hex(path: &std::path::Path) -> String {
+    Sha256::digest(std::fs::read(path).unwrap())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[test]
+fn checksums_cover_every_generated_file() {
+    let dir = PathBuf::from("out/checksums");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let mut args = common::layout_args("checksums", LAYOUT, "");
+    args.output.out = "out/checksums/fw.hex".into();
+    args.output.block_out_dir = Some(dir.join("blocks"));
+    args.output.export_json = Some(dir.join("values.json"));
+    args.output.checksums = Some(dir.join("SHA256SUMS"));
+    commands::build(&args, None).expect("build");
+
+    let sums = std::fs::read_to_string(dir.join("SHA256SUMS")).expect("checksums");
+    let entries: Vec<(&str, &str)> = sums
+        .lines()
+        .map(|line| line.split_once("  ").expect("sha256sum line"))
+        .collect();
+    let names: Vec<&str> = entries.iter().map(|(_, name)| *name).collect();
+    assert_eq!(
+        names,
+        ["values.json", "blocks/boot.hex", "blocks/app.hex", "fw.hex"]
+    );
+    for (hash, name) in entries {
+        assert_eq!(hash, sha256_hex(&dir.join(name)), "{name}");
+    }
+}
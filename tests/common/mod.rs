@@ -1,10 +1,15 @@
 #![allow(dead_code)]
 
 use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
 use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
 
 use mint_cli::args::Args;
+use mint_cli::commands;
 use mint_cli::data::{self, DataSource};
+use mint_cli::error::MintError;
 use mint_cli::layout::args::{BlockNames, LayoutArgs};
 use mint_cli::output::args::{OutputArgs, OutputFormat};
 
@@ -32,6 +37,7 @@ pub fn build_args(layout_path: &str, block_name: &str, format: OutputFormat) ->
                 file: layout_path.to_string(),
             }],
             strict: false,
+            ..Default::default()
         },
         data: data::args::DataArgs {
             xlsx: Some("tests/data/data.xlsx".to_string()),
@@ -40,11 +46,8 @@ pub fn build_args(layout_path: &str, block_name: &str, format: OutputFormat) ->
         },
         output: OutputArgs {
             out: PathBuf::from(format!("out/{}.{}", block_name, ext)),
-            record_width: 32,
             format,
-            export_json: None,
-            stats: false,
-            quiet: false,
+            ..Default::default()
         },
     }
 }
@@ -84,6 +87,7 @@ pub fn build_args_for_layouts(
         layout: LayoutArgs {
             blocks: layouts,
             strict: false,
+            ..Default::default()
         },
         data: data::args::DataArgs {
             xlsx: Some("tests/data/data.xlsx".to_string()),
@@ -92,11 +96,75 @@ pub fn build_args_for_layouts(
         },
         output: OutputArgs {
             out: PathBuf::from(out_path),
-            record_width: 32,
             format,
-            export_json: None,
-            stats: false,
-            quiet: false,
+            ..Default::default()
         },
     }
 }
+
+/// Writes `layout` to out/{stem}.toml and returns args building its `block`
+/// to out/{stem}.hex. An empty `block` builds every block in the file.
+pub fn layout_args(stem: &str, layout: &str, block: &str) -> Args {
+    let path = write_layout_file(stem, layout);
+    build_args_for_layouts(
+        vec![BlockNames {
+            name: block.to_string(),
+            file: path,
+        }],
+        OutputFormat::Hex,
+        &format!("out/{}.hex", stem),
+    )
+}
+
+/// Builds `block` of `layout` to out/{stem}.hex and returns the output text.
+pub fn build_layout(stem: &str, layout: &str, block: &str) -> Result<String, MintError> {
+    build_layout_with(stem, layout, block, |_| {})
+}
+
+/// Like [`build_layout`], with `configure` adjusting the args first. The data
+/// source is created from the configured data args, and the output is read
+/// from the configured output path.
+pub fn build_layout_with(
+    stem: &str,
+    layout: &str,
+    block: &str,
+    configure: impl FnOnce(&mut Args),
+) -> Result<String, MintError> {
+    let mut args = layout_args(stem, layout, block);
+    configure(&mut args);
+    let ds = data::create_data_source(&args.data)?;
+    commands::build(&args, ds.as_deref())?;
+    Ok(fs::read_to_string(&args.output.out).unwrap())
+}
+
+/// Answers one HTTP request with the JSON `body`. Returns the server address
+/// and a handle yielding the request body it received.
+pub fn serve_once(body: &'static str) -> (String, JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let handle = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(&stream);
+        let mut line = String::new();
+        let mut content_length = 0;
+        while reader.read_line(&mut line).unwrap() > 2 {
+            if let Some((name, value)) = line.split_once(':')
+                && name.eq_ignore_ascii_case("content-length")
+            {
+                content_length = value.trim().parse().unwrap();
+            }
+            line.clear();
+        }
+        let mut request = vec![0; content_length];
+        reader.read_exact(&mut request).unwrap();
+        write!(
+            &stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+        String::from_utf8(request).unwrap()
+    });
+    (address, handle)
+}
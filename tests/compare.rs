@@ -0,0 +1,113 @@
+use mint_cli::commands;
+use mint_cli::layout::used_values::NoopValueSink;
+use mint_cli::output;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[settings.crc]
+polynomial = 0x04C11DB7
+start = 0xFFFFFFFF
+xor_out = 0xFFFFFFFF
+ref_in = true
+ref_out = true
+area = "block_zero_crc"
+
+[block.header]
+start_address = 0x1000
+length = 0x20
+padding = 0xFF
+
+[block.header.crc]
+location = "end_block"
+
+[block.data]
+id = { value = 0x11, type = "u8" }
+limits = { value = [0x0102, 0x0304], type = "u16", size = 2 }
+name = { value = "ok", type = "u8", size = 4 }
+"#;
+
+/// Raw dump of the built block, starting 0x10 bytes before it.
+fn reference_dump(path: &str) -> Vec<u8> {
+    let cfg = mint_cli::layout::load_layout(path).expect("parse");
+    let block = &cfg.blocks["block"];
+    let (bytes, padding) = block
+        .build_bytestream(None, &cfg.settings, false, &mut NoopValueSink)
+        .expect("build");
+    let range = output::bytestream_to_datarange(bytes, &block.header, &cfg.settings, padding)
+        .expect("range");
+    let mut dump = vec![0xFF; 0x10];
+    dump.extend(range.image());
+    dump
+}
+
+fn compare(stem: &str, dump: &[u8]) -> Vec<commands::BlockComparison> {
+    let image = format!("out/{}.bin", stem);
+    std::fs::write(&image, dump).unwrap();
+    let mut args = common::layout_args(stem, LAYOUT, "block");
+    args.output.compare = Some(image.into());
+    args.output.base_address = 0x0FF0;
+    commands::compare(&args, None).expect("compare")
+}
+
+#[test]
+fn compare_matches_reference_image() {
+    let path = common::write_layout_file("compare_match", LAYOUT);
+    let result = compare("compare_match", &reference_dump(&path));
+    assert_eq!(result.len(), 1);
+    assert!(result[0].matches(), "{:?}", result[0]);
+    assert!(result[0].crc.is_some());
+}
+
+#[test]
+fn compare_reports_differing_fields_and_crc() {
+    let path = common::write_layout_file("compare_diff", LAYOUT);
+    let mut dump = reference_dump(&path);
+    dump[0x10 + 3] = 0x99;
+
+    let result = compare("compare_diff", &dump);
+    let block = &result[0];
+    assert!(!block.matches());
+    assert!(!block.crc.unwrap().matches());
+    assert_eq!(block.differences.len(), 1);
+    let diff = &block.differences[0];
+    assert_eq!(diff.field, "limits");
+    assert_eq!(diff.address, 0x1002);
+    assert_eq!(diff.expected, [0x02, 0x01, 0x04, 0x03]);
+    assert_eq!(diff.actual, [0x02, 0x99, 0x04, 0x03]);
+}
+
+#[test]
+fn compare_flags_blocks_outside_image() {
+    let path = common::write_layout_file("compare_short", LAYOUT);
+    let dump = reference_dump(&path);
+    let result = compare("compare_short", &dump[..0x18]);
+    assert!(!result[0].covered);
+    assert!(result[0].crc.is_none());
+    let names: Vec<&str> = result[0]
+        .differences
+        .iter()
+        .map(|d| d.field.as_str())
+        .collect();
+    assert_eq!(names, ["name"]);
+}
+
+#[test]
+fn compare_reads_byte_swapped_fields_both_ways() {
+    let path = common::write_layout_file("compare_swapped", LAYOUT);
+    let mut dump = reference_dump(&path);
+    dump[0x12..0x16].copy_from_slice(&[0x01, 0x02, 0x03, 0x04]);
+    dump[0x10] = 0x12;
+
+    let result = compare("compare_swapped", &dump);
+    let diffs = &result[0].differences;
+    assert_eq!(diffs.len(), 2);
+    // A single byte cannot be byte-swapped.
+    assert_eq!(diffs[0].byte_swapped, None);
+    let readings = diffs[1].byte_swapped.as_ref().expect("byte-swapped");
+    assert_eq!(readings.little, "[513, 1027]");
+    assert_eq!(readings.big, "[258, 772]");
+}
@@ -0,0 +1,76 @@
+#[path = "common/mod.rs"]
+mod common;
+
+fn build(file_stem: &str, constants: &str, start: &str, data: &str) -> Result<String, String> {
+    let layout = format!(
+        r#"[settings]
+endianness = "little"
+
+[constants]
+{}
+
+[block.header]
+start_address = {}
+length = 0x10
+padding = 0x00
+
+[block.data]
+{}
+"#,
+        constants, start, data
+    );
+    common::build_layout(file_stem, &layout, "block").map_err(|e| e.to_string())
+}
+
+#[test]
+fn constants_fill_values_and_header_expressions() {
+    let hex = build(
+        "constants_resolved",
+        "BASE = 0x2000\nMAGIC = 0xCAFE\nGAINS = [1, 2]",
+        r#""BASE + 0x10""#,
+        r#"magic = { value = "$MAGIC", type = "u16" }
+gains = { value = "$GAINS", type = "u8", size = 2 }
+mixed = { value = ["$MAGIC", 3], type = "u16", size = 2 }"#,
+    )
+    .unwrap();
+    assert!(hex.contains(":08201000FECA0102FECA0300"), "{hex}");
+}
+
+#[test]
+fn unknown_and_non_integer_constants_are_reported() {
+    let err = build(
+        "constants_unknown",
+        "BASE = 0x2000",
+        r#""BASE""#,
+        r#"x = { value = "$NOPE", type = "u8" }"#,
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("In field 'block.x'") && err.contains("unknown constant 'NOPE'"),
+        "{err}"
+    );
+
+    let err = build(
+        "constants_array",
+        "GAINS = [1, 2]",
+        "0x2000",
+        r#"flags = { type = "u8", bitmap = [{ bits = 4, value = "$GAINS" }] }"#,
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("constant 'GAINS' is an array where a single value is expected"),
+        "{err}"
+    );
+
+    let err = build(
+        "constants_float",
+        "GAIN = 1.5",
+        r#""GAIN * 2""#,
+        r#"x = { value = 1, type = "u8" }"#,
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("constant 'GAIN' is not an unsigned integer"),
+        "{err}"
+    );
+}
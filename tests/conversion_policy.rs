@@ -0,0 +1,186 @@
+use mint_cli::commands;
+use mint_cli::data::DataSource;
+use mint_cli::data::error::DataError;
+use mint_cli::layout::policy::{ConversionPolicy, NonFinite, Policy};
+use mint_cli::layout::refs::NoBlockRefs;
+use mint_cli::layout::used_values::NoopValueSink;
+use mint_cli::layout::value::{DataValue, ValueSource};
+
+#[path = "common/mod.rs"]
+mod common;
+
+fn layout(policy: &str) -> String {
+    format!(
+        r#"[settings]
+endianness = "little"
+{policy}
+
+[block.header]
+start_address = 0x1000
+length = 0x20
+
+[block.data]
+lossy = {{ value = 1.5, type = "u8" }}
+range = {{ value = 300, type = "u8" }}
+short = {{ value = [1, 2], type = "u8", size = 4 }}
+"#
+    )
+}
+
+#[test]
+fn settings_policy_rejects_selected_issue() {
+    let err = common::build_layout(
+        "policy_settings",
+        &layout("\n[settings.policy]\nshort_array = \"error\""),
+        "block",
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("smaller than defined size"), "{err}");
+}
+
+#[test]
+fn cli_policy_overrides_strict_and_settings() {
+    let mut args = common::layout_args(
+        "policy_cli",
+        &layout("\n[settings.policy]\nshort_array = \"error\""),
+        "block",
+    );
+    args.layout.strict = true;
+    args.layout.on_lossy_cast = Some(Policy::Warn);
+    args.layout.on_out_of_range = Some(Policy::Allow);
+    args.layout.on_short_array = Some(Policy::Allow);
+    commands::build(&args, None).expect("policies allow every issue");
+
+    let hex = std::fs::read_to_string("out/policy_cli.hex").expect("read hex");
+    assert!(hex.contains("012C0102FFFF"), "{hex}");
+
+    args.layout.on_out_of_range = None;
+    let err = commands::build(&args, None).unwrap_err().to_string();
+    assert!(err.contains("out of range"), "{err}");
+}
+
+const HEADER_STRICT_LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[safe.header]
+start_address = 0x1000
+length = 0x10
+strict = true
+
+[safe.data]
+lossy = { value = 1.5, type = "u8" }
+
+[scratch.header]
+start_address = 0x2000
+length = 0x10
+strict = false
+
+[scratch.data]
+lossy = { value = 1.5, type = "u8" }
+"#;
+
+#[test]
+fn header_strict_overrides_cli_strict_for_its_block() {
+    let block_args = |name: &str, strict: bool| {
+        let stem = format!("policy_header_strict_{}", name);
+        let mut args = common::layout_args(&stem, HEADER_STRICT_LAYOUT, name);
+        args.layout.strict = strict;
+        args
+    };
+
+    let err = commands::build(&block_args("safe", false), None)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("exact integer"), "{err}");
+    commands::build(&block_args("scratch", true), None).expect("scratch block is lax");
+
+    // Explicit policies still apply to a block that turns strict off
+    let mut args = block_args("scratch", true);
+    args.layout.on_lossy_cast = Some(Policy::Error);
+    assert!(commands::build(&args, None).is_err());
+}
+
+const NAN_LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[settings.policy]
+non_finite = { sentinel = 0xBEEF }
+
+[block.header]
+start_address = 0x1000
+length = 0x10
+
+[block.data]
+reading = { name = "Reading", type = "u16" }
+limit = { name = "Limit", type = "f32" }
+"#;
+
+/// Data source returning NaN for `Reading` and +Inf for everything else.
+struct NonFiniteSource;
+
+impl DataSource for NonFiniteSource {
+    fn retrieve_single_value(&self, name: &str) -> Result<DataValue, DataError> {
+        Ok(DataValue::F64(if name == "Reading" {
+            f64::NAN
+        } else {
+            f64::INFINITY
+        }))
+    }
+
+    fn retrieve_1d_array_or_string(&self, _name: &str) -> Result<ValueSource, DataError> {
+        unimplemented!()
+    }
+
+    fn retrieve_2d_array(&self, _name: &str) -> Result<Vec<Vec<DataValue>>, DataError> {
+        unimplemented!()
+    }
+}
+
+#[test]
+fn non_finite_policy_from_settings_and_cli() {
+    let path = common::write_layout_file("policy_nan", NAN_LAYOUT);
+    let cfg = mint_cli::layout::load_layout(&path).expect("parse");
+    let block = &cfg.blocks["block"];
+    let build = |policy: &ConversionPolicy| {
+        block.build_bytestream_with_refs(
+            Some(&NonFiniteSource),
+            &cfg.settings,
+            policy,
+            &mut NoopValueSink,
+            &NoBlockRefs,
+        )
+    };
+
+    let (bytes, _) = build(&ConversionPolicy::default()).expect("sentinel from settings");
+    assert_eq!(bytes, [0xEF, 0xBE, 0xFF, 0xFF, 0xEF, 0xBE, 0x00, 0x00]);
+
+    let raw = ConversionPolicy {
+        non_finite: Some(NonFinite::Raw),
+        ..Default::default()
+    };
+    let err = build(&raw).unwrap_err().to_string();
+    assert!(err.contains("cannot be stored raw"), "{err}");
+}
+
+#[test]
+fn non_finite_without_policy_follows_out_of_range() {
+    let layout = NAN_LAYOUT.replace("non_finite = { sentinel = 0xBEEF }\n", "");
+    let path = common::write_layout_file("policy_nan_default", &layout);
+    let cfg = mint_cli::layout::load_layout(&path).expect("parse");
+    let block = &cfg.blocks["block"];
+    let build = |policy: &ConversionPolicy| {
+        block.build_bytestream_with_refs(
+            Some(&NonFiniteSource),
+            &cfg.settings,
+            policy,
+            &mut NoopValueSink,
+            &NoBlockRefs,
+        )
+    };
+
+    let (bytes, _) = build(&ConversionPolicy::default()).expect("lax cast by default");
+    assert_eq!(bytes, [0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00, 0x80, 0x7F]);
+
+    assert!(build(&ConversionPolicy::strict()).is_err());
+}
@@ -0,0 +1,59 @@
+use mint_cli::commands;
+use mint_cli::layout::args::BlockNames;
+use mint_cli::layout::convert::{LayoutFormat, convert_layout};
+use mint_cli::output::args::OutputFormat;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "big"
+
+[config.header]
+start_address = 0x1000
+length = 0x40
+padding = 0xFF
+crc = { location = "end_data", polynomial = 0x04C11DB7, start = 0xFFFFFFFF, xor_out = 0xFFFFFFFF, ref_in = true, ref_out = true, area = "data" }
+
+[config.data]
+version = { value = 3, type = "u16" }
+gain = { value = 1.5, type = "f32" }
+flags = { type = "u8", bitmap = [{ bits = 1, value = true }, { bits = 7, value = 0 }] }
+channel = { repeat = 2, offset = { value = 7, type = "i16" } }
+device.name = { value = "ecu", type = "u8", size = 8 }
+"#;
+
+fn build(path: &str, out: &str) -> String {
+    let args = common::build_args_for_layouts(
+        vec![BlockNames {
+            name: "config".to_string(),
+            file: path.to_string(),
+        }],
+        OutputFormat::Hex,
+        out,
+    );
+    commands::build(&args, None).expect("build");
+    std::fs::read_to_string(out).unwrap()
+}
+
+#[test]
+fn conversion_round_trip_builds_identical_image() {
+    let toml_path = common::write_layout_file("convert", LAYOUT);
+    let reference = build(&toml_path, "out/convert_ref.hex");
+
+    let yaml = convert_layout(&toml_path, LayoutFormat::Yaml).unwrap();
+    std::fs::write("out/convert.yaml", &yaml).unwrap();
+    assert_eq!(build("out/convert.yaml", "out/convert_yaml.hex"), reference);
+
+    let json = convert_layout("out/convert.yaml", LayoutFormat::Json).unwrap();
+    std::fs::write("out/convert.json", &json).unwrap();
+    assert_eq!(build("out/convert.json", "out/convert_json.hex"), reference);
+
+    let toml = convert_layout("out/convert.json", LayoutFormat::Toml).unwrap();
+    std::fs::write("out/convert_back.toml", &toml).unwrap();
+    assert_eq!(
+        build("out/convert_back.toml", "out/convert_toml.hex"),
+        reference
+    );
+    assert!(toml.contains("device.name = { value = \"ecu\", type = \"u8\", size = 8 }"));
+}
@@ -0,0 +1,58 @@
+#![cfg(unix)]
+
+use mint_cli::layout::used_values::NoopValueSink;
+use mint_cli::output;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "big"
+
+[settings.crc]
+location = "end_data"
+area = "data"
+command = "printf '%x' $(wc -c)"
+
+[block.header]
+start_address = 0x1000
+length = 0x10
+
+[block.data]
+id = { value = 0x11223344, type = "u32" }
+name = { value = "abc", type = "u8", size = 6 }
+
+[header_override.header]
+start_address = 0x2000
+length = 0x10
+
+[header_override.header.crc]
+command = "exit 2"
+
+[header_override.data]
+id = { value = 1, type = "u8" }
+"#;
+
+#[test]
+fn crc_command_supplies_checksum_bytes() {
+    let path = common::write_layout_file("crc_command", LAYOUT);
+    let cfg = mint_cli::layout::load_layout(&path).expect("parse");
+
+    let block = &cfg.blocks["block"];
+    let (bytes, padding) = block
+        .build_bytestream(None, &cfg.settings, false, &mut NoopValueSink)
+        .expect("build");
+    let range = output::bytestream_to_datarange(bytes, &block.header, &cfg.settings, padding)
+        .expect("range");
+    assert_eq!(range.crc_address, 0x100C);
+    assert_eq!(range.crc_bytestream, [0x00, 0x00, 0x00, 0x0C]);
+
+    let block = &cfg.blocks["header_override"];
+    let (bytes, padding) = block
+        .build_bytestream(None, &cfg.settings, false, &mut NoopValueSink)
+        .expect("build");
+    let err = output::bytestream_to_datarange(bytes, &block.header, &cfg.settings, padding)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Checksum command 'exit 2' failed"), "{err}");
+}
@@ -0,0 +1,45 @@
+use mint_cli::commands;
+use mint_cli::output::image::Image;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[settings.crc]
+polynomial = 0x04C11DB7
+start = 0xFFFFFFFF
+xor_out = 0xFFFFFFFF
+ref_in = true
+ref_out = true
+area = "data"
+
+[block.header]
+start_address = 0x1000
+length = 0x20
+
+[block.header.crc]
+location = "end_block"
+
+[block.data]
+id = { value = 0x11223344, type = "u32" }
+"#;
+
+#[test]
+fn crc_out_holds_only_crc_bytes() {
+    let mut args = common::layout_args("crc_out", LAYOUT, "block");
+    args.output.out = "out/crc_out_full.hex".into();
+    args.output.crc_out = Some("out/crc_out.hex".into());
+    let stats = commands::build(&args, None).expect("build");
+
+    let full = Image::read("out/crc_out_full.hex".as_ref()).unwrap();
+    let crc = Image::read("out/crc_out.hex".as_ref()).unwrap();
+    assert_eq!(crc.byte_at(0x1000), None);
+    let stored: Vec<u8> = (0x101C..0x1020).map(|a| crc.byte_at(a).unwrap()).collect();
+    let expected: Vec<u8> = (0x101C..0x1020).map(|a| full.byte_at(a).unwrap()).collect();
+    assert_eq!(stored, expected);
+    let value = stats.block_stats[0].crc_value.unwrap();
+    assert_eq!(stored, value.to_le_bytes());
+    assert_eq!(crc.byte_at(0x1020), None);
+}
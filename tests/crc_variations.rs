@@ -1,4 +1,6 @@
 use mint_cli::commands;
+use mint_cli::layout::used_values::NoopValueSink;
+use mint_cli::output::bytestream_to_datarange;
 
 #[path = "common/mod.rs"]
 mod common;
@@ -488,3 +490,63 @@ value = { value = 0x33333333, type = "u32" }
 
     common::assert_out_file_exists(std::path::Path::new("out/crc_combined.hex"));
 }
+
+/// CRC at "start" reserves the first 4 bytes; "end-N" places it N bytes from the end.
+#[test]
+fn crc_start_and_end_relative_locations() {
+    let layout = r#"
+[settings]
+endianness = "little"
+
+[settings.crc]
+polynomial = 0x04C11DB7
+start = 0xFFFFFFFF
+xor_out = 0xFFFFFFFF
+ref_in = true
+ref_out = true
+area = "data"
+
+[block_start.header]
+start_address = 0x1000
+length = 0x40
+padding = 0xFF
+
+[block_start.header.crc]
+location = "start"
+
+[block_start.data]
+value1 = { value = 0x12345678, type = "u32" }
+
+[block_end_rel.header]
+start_address = 0x2000
+length = 0x40
+padding = 0xFF
+
+[block_end_rel.header.crc]
+location = "end-16"
+
+[block_end_rel.data]
+value1 = { value = 0x12345678, type = "u32" }
+"#;
+
+    let layout_path = common::write_layout_file("crc_start_end_rel", layout);
+    let cfg = mint_cli::layout::load_layout(&layout_path).expect("layout loads");
+
+    let block = &cfg.blocks["block_start"];
+    let (bytes, padding) = block
+        .build_bytestream(None, &cfg.settings, false, &mut NoopValueSink)
+        .expect("build block_start");
+    assert_eq!(bytes, [0xFF, 0xFF, 0xFF, 0xFF, 0x78, 0x56, 0x34, 0x12]);
+    let range = bytestream_to_datarange(bytes, &block.header, &cfg.settings, padding)
+        .expect("block_start datarange");
+    assert_eq!(range.crc_address, 0x1000);
+    assert_eq!(&range.image()[4..8], [0x78, 0x56, 0x34, 0x12]);
+
+    let block = &cfg.blocks["block_end_rel"];
+    let (bytes, padding) = block
+        .build_bytestream(None, &cfg.settings, false, &mut NoopValueSink)
+        .expect("build block_end_rel");
+    let range = bytestream_to_datarange(bytes, &block.header, &cfg.settings, padding)
+        .expect("block_end_rel datarange");
+    assert_eq!(range.crc_address, 0x2000 + 0x40 - 16);
+}
@@ -0,0 +1,101 @@
+use mint_cli::layout::used_values::NoopValueSink;
+
+#[path = "common/mod.rs"]
+mod common;
+
+fn build(file_stem: &str, entry: &str) -> Result<Vec<u8>, String> {
+    common::ensure_out_dir();
+    std::fs::write("out/csv_values_jagged.csv", "1,2,3\n7\n").unwrap();
+    std::fs::write("out/csv_values_comma.csv", "1,5;\"2,5\"\n").unwrap();
+    std::fs::write("out/csv_values_table.csv", "x,y,z\n1,2,3\n0x10,0b101,1k\n").unwrap();
+    let layout = format!(
+        r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x40
+padding = 0x00
+
+[block.data]
+table = {entry}
+"#
+    );
+    let path = common::write_layout_file(file_stem, &layout);
+    let cfg = mint_cli::layout::load_layout(&path).map_err(|e| e.to_string())?;
+    cfg.blocks["block"]
+        .build_bytestream(None, &cfg.settings, false, &mut NoopValueSink)
+        .map(|(bytes, _)| bytes)
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn csv_reference_fills_2d_array() {
+    let bytes = build(
+        "csv_values_2d",
+        r#"{ csv = "csv_values_table.csv", type = "u16", size = [3, 3] }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        bytes,
+        [
+            1, 0, 2, 0, 3, 0, 0x10, 0, 5, 0, 0xE8, 0x03, 0, 0, 0, 0, 0, 0
+        ]
+    );
+}
+
+#[test]
+fn csv_reference_flattens_into_1d_array() {
+    let bytes = build(
+        "csv_values_1d",
+        r#"{ csv = "csv_values_table.csv", type = "u16", size = 8 }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        bytes,
+        [1, 0, 2, 0, 3, 0, 0x10, 0, 5, 0, 0xE8, 0x03, 0, 0, 0, 0]
+    );
+}
+
+#[test]
+fn missing_csv_reference_fails() {
+    let err = build(
+        "csv_values_missing",
+        r#"{ csv = "no_such_table.csv", type = "u16", size = [2, 2] }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("failed to read CSV"), "{err}");
+}
+
+#[test]
+fn jagged_csv_is_rejected() {
+    let err = build(
+        "csv_values_jagged",
+        r#"{ csv = "csv_values_jagged.csv", type = "u16", size = 8 }"#,
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("line 2 has 1 cells where earlier rows have 3"),
+        "{err}"
+    );
+}
+
+#[test]
+fn csv_table_sets_decimal_separator() {
+    let bytes = build(
+        "csv_values_comma",
+        r#"{ csv = { path = "csv_values_comma.csv", decimal_separator = "comma" }, type = "u8", size = 2, transform = ["scale(2)"] }"#,
+    )
+    .unwrap();
+    assert_eq!(bytes, [3, 5]);
+}
+
+#[test]
+fn at_prefixed_value_is_a_plain_string() {
+    let bytes = build(
+        "csv_values_at",
+        r#"{ value = "@home", type = "u8", size = 5 }"#,
+    )
+    .unwrap();
+    assert_eq!(bytes, *b"@home");
+}
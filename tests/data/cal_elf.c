@@ -0,0 +1,23 @@
+/* Source of cal.elf, a fixture for the --import-elf tests.
+ * Build: gcc -g -O0 -ffreestanding -nostdlib -static -Wl,--build-id=none -o cal.elf cal_elf.c
+ */
+#include <stdint.h>
+
+typedef struct {
+    int16_t gain;
+    uint8_t trim;
+} Channel;
+
+typedef struct {
+    uint8_t id;
+    uint32_t serial;
+    Channel channel[2];
+    unsigned enable : 1;
+    unsigned mode : 3;
+    float map[2][3];
+    uint8_t tail;
+} CalBlock;
+
+const CalBlock cal = {.id = 7};
+
+void _start(void) {}
@@ -0,0 +1,54 @@
+use mint_cli::commands;
+use serde_json::json;
+
+#[path = "common/mod.rs"]
+mod common;
+
+#[test]
+fn contract_covers_selected_and_referenced_blocks() {
+    let layout = r#"
+[settings]
+endianness = "little"
+
+[boot.header]
+start_address = 0x1000
+length = 0x10
+
+[boot.data]
+version = { name = "BootVersion", type = "u16" }
+
+[app.header]
+start_address = 0x2000
+length = 0x40
+
+[app.data]
+coeffs = { name = "Coeffs", type = "f32", size = 4 }
+boot = { block = "boot" }
+
+[debug.header]
+start_address = 0x3000
+length = 0x10
+
+[debug.data]
+level = { name = "DebugLevel", type = "u8" }
+"#;
+    common::ensure_out_dir();
+    let args = common::layout_args("data_contract", layout, "app");
+
+    let schema = commands::data_contract(&args).unwrap();
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["required"], json!(["Coeffs", "BootVersion"]));
+    assert_eq!(
+        schema["properties"]["Coeffs"],
+        json!({
+            "type": "array",
+            "items": { "type": "number" },
+            "maxItems": 4,
+            "x-mint-fields": ["app.coeffs"],
+        })
+    );
+    assert_eq!(
+        schema["properties"]["BootVersion"]["x-mint-fields"],
+        json!(["boot.version"])
+    );
+}
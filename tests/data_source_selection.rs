@@ -0,0 +1,67 @@
+use mint_cli::data::args::DataArgs;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[cal.header]
+start_address = 0x1000
+length = 0x10
+
+[cal.data]
+max = { name = "TemperatureMax", type = "u16" }
+
+[ident.header]
+start_address = 0x2000
+length = 0x10
+data_source = "json"
+
+[ident.data]
+max = { name = "TemperatureMax", type = "u16" }
+serial = { name = "Serial", type = "u32" }
+"#;
+
+fn build(file_stem: &str, layout: &str, data: DataArgs) -> Result<String, String> {
+    common::build_layout_with(file_stem, layout, "", |args| args.data = data)
+        .map_err(|e| e.to_string())
+}
+
+fn excel_and_json() -> DataArgs {
+    DataArgs {
+        xlsx: Some("tests/data/data.xlsx".to_string()),
+        json: Some(r#"{"Default": {"TemperatureMax": 7, "Serial": 4660}}"#.to_string()),
+        version: Some("Default".to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn blocks_read_from_the_source_their_header_selects() {
+    let hex = build("source_selection", LAYOUT, excel_and_json()).unwrap();
+    // cal reads the workbook (the default source), ident the JSON.
+    assert!(hex.contains(":021000003200"), "{hex}");
+    assert!(hex.contains(":082000000700FFFF34120000"), "{hex}");
+}
+
+#[test]
+fn overrides_still_apply_to_a_selected_source() {
+    let data = DataArgs {
+        overrides: Some(r#"{"Serial": 1}"#.to_string()),
+        ..excel_and_json()
+    };
+    let hex = build("source_selection_override", LAYOUT, data).unwrap();
+    assert!(hex.contains(":082000000700FFFF01000000"), "{hex}");
+}
+
+#[test]
+fn selecting_an_unconfigured_source_fails() {
+    let layout = LAYOUT.replace("data_source = \"json\"", "data_source = \"postgres\"");
+    let err = build("source_selection_missing", &layout, excel_and_json()).unwrap_err();
+    assert!(
+        err.contains("data source --postgres is not configured"),
+        "{err}"
+    );
+    assert!(err.contains("ident"), "{err}");
+}
@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use mint_cli::commands;
+use mint_cli::data;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"
+[settings]
+endianness = "little"
+
+[cal.header]
+start_address = 0x1000
+length = 0x20
+
+[cal.data]
+gains = { names = "Gain_*", type = "u8" }
+
+[info.header]
+start_address = 0x2000
+length = 0x10
+
+[info.data]
+id = { value = 7, type = "u32" }
+"#;
+
+#[test]
+fn deterministic_builds_are_byte_identical_without_durations() {
+    let ds_args = data::args::DataArgs {
+        json: Some(r#"{"Default":{"Gain_1":1,"Gain_01":2,"Gain_2":3}}"#.to_string()),
+        version: Some("Default".to_string()),
+        ..Default::default()
+    };
+    let ds = data::create_data_source(&ds_args).unwrap().unwrap();
+
+    let mut outputs = Vec::new();
+    for run in 0..2 {
+        let mut args = common::layout_args(&format!("deterministic_{}", run), LAYOUT, "");
+        args.output.deterministic = true;
+        let stats = commands::build(&args, Some(ds.as_ref())).expect("build should succeed");
+        assert_eq!(stats.total_duration, Duration::ZERO);
+        assert!(stats.block_stats.iter().all(|b| b.emit_duration.is_zero()));
+        outputs.push(std::fs::read(&args.output.out).unwrap());
+    }
+    assert_eq!(outputs[0], outputs[1]);
+
+    // Gain_01 and Gain_1 tie in natural order and fall back to byte order.
+    let image = mint_cli::output::image::Image::read("out/deterministic_0.hex".as_ref()).unwrap();
+    let gains: Vec<u8> = (0x1000..0x1003)
+        .map(|a| image.byte_at(a).unwrap())
+        .collect();
+    assert_eq!(gains, [2, 1, 3]);
+}
@@ -0,0 +1,60 @@
+use mint_cli::layout::settings::EccScheme;
+use mint_cli::output::ecc;
+use mint_cli::output::image::Image;
+
+#[path = "common/mod.rs"]
+mod common;
+
+fn layout(placement: &str) -> String {
+    format!(
+        r#"[settings]
+endianness = "little"
+
+[settings.ecc]
+scheme = "secded"
+word_size = 4
+placement = {placement}
+base = 0x1000
+
+[cal.header]
+start_address = 0x1010
+length = 0x10
+
+[cal.data]
+a = {{ value = 0x12345678, type = "u32" }}
+b = {{ value = 0x0BADF00D, type = "u32" }}
+"#
+    )
+}
+
+fn build(stem: &str, placement: &str) -> Image {
+    common::build_layout(stem, &layout(placement), "cal").expect("build");
+    Image::read(format!("out/{}.hex", stem).as_ref()).unwrap()
+}
+
+#[test]
+fn ecc_companion_range_and_interleave() {
+    let a = 0x12345678u32.to_le_bytes();
+    let b = 0x0BADF00Du32.to_le_bytes();
+    let codes = [
+        ecc::code(EccScheme::Secded, &a),
+        ecc::code(EccScheme::Secded, &b),
+    ];
+
+    let image = build("ecc_companion", "0x9000");
+    assert_eq!(image.byte_at(0x1010), Some(0x78));
+    // (0x1010 - 0x1000) / 4 words past the companion base.
+    assert_eq!(image.byte_at(0x9004), Some(codes[0]));
+    assert_eq!(image.byte_at(0x9005), Some(codes[1]));
+
+    let image = build("ecc_interleave", "\"interleave\"");
+    let bytes: Vec<u8> = (0x1010..0x101A)
+        .map(|a| image.byte_at(a).unwrap())
+        .collect();
+    assert_eq!(
+        bytes,
+        [
+            a[0], a[1], a[2], a[3], codes[0], b[0], b[1], b[2], b[3], codes[1]
+        ]
+    );
+}
@@ -0,0 +1,75 @@
+use mint_cli::commands;
+use mint_cli::output::DataRange;
+use mint_cli::output::emitter::{EmitOptions, EmitterRegistry, ImageEmitter};
+use mint_cli::output::error::OutputError;
+use std::io::Write;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x4
+
+[block.data]
+id = { value = 0x11, type = "u8" }
+gain = { value = 0x22, type = "u8" }
+"#;
+
+/// Length-prefixed container with a text sidecar listing block addresses.
+struct Container;
+
+impl ImageEmitter for Container {
+    fn sidecars(&self) -> Vec<String> {
+        vec!["idx".to_string()]
+    }
+
+    fn emit(
+        &self,
+        ranges: &[DataRange],
+        _opts: &EmitOptions,
+        sidecar: Option<&str>,
+        out: &mut dyn Write,
+    ) -> Result<(), OutputError> {
+        let io = |e: std::io::Error| OutputError::FileError(e.to_string());
+        for range in ranges {
+            match sidecar {
+                None => {
+                    let image = range.image();
+                    out.write_all(&(image.len() as u16).to_be_bytes())
+                        .map_err(io)?;
+                    out.write_all(&image).map_err(io)?;
+                }
+                Some(_) => writeln!(out, "{:#X}", range.start_address).map_err(io)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn custom_emitter_writes_output_and_sidecar() {
+    let mut args = common::layout_args("emitter", LAYOUT, "block");
+    args.output.out = "out/emitter.oem".into();
+    args.output.checksums = Some("out/emitter.sha256".into());
+
+    let mut registry = EmitterRegistry::default();
+    registry.register("oem", Container);
+    commands::build_with_emitter(&args, None, registry.get("oem").unwrap()).expect("build");
+
+    assert_eq!(
+        std::fs::read("out/emitter.oem").unwrap(),
+        [0x00, 0x02, 0x11, 0x22]
+    );
+    assert_eq!(
+        std::fs::read_to_string("out/emitter.idx").unwrap(),
+        "0x1000\n"
+    );
+
+    let sums = std::fs::read_to_string("out/emitter.sha256").unwrap();
+    assert!(sums.contains("  emitter.oem\n"), "{sums}");
+    assert!(sums.contains("  emitter.idx\n"), "{sums}");
+}
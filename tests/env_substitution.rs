@@ -0,0 +1,57 @@
+use mint_cli::commands;
+
+#[path = "common/mod.rs"]
+mod common;
+
+#[test]
+fn layout_values_expand_environment_variables() {
+    // SAFETY: the variable name is unique to this test binary.
+    unsafe { std::env::set_var("MINT_ENV_TEST_ID", "a\"b") };
+
+    let args = common::layout_args(
+        "env_substitution",
+        r#"[settings]
+endianness = "little"
+
+[block.header]
+# ${MINT_ENV_TEST_UNSET} in a comment is left alone.
+start_address = "${MINT_ENV_TEST_BASE:-0x3000}"
+length = 0x10
+
+[block.data]
+id = { value = "${MINT_ENV_TEST_ID}", type = "u8", size = 3 }
+"#,
+        "block",
+    );
+    let stats = commands::build(&args, None).expect("build succeeds");
+    assert_eq!(stats.block_stats[0].start_address, 0x3000);
+
+    let hex = std::fs::read_to_string("out/env_substitution.hex").unwrap();
+    assert!(
+        hex.starts_with(":03300000612262E8"),
+        "unexpected output: {hex}"
+    );
+}
+
+#[test]
+fn unset_variable_without_fallback_fails_to_load() {
+    let path = common::write_layout_file(
+        "env_substitution_missing",
+        r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x10
+
+[block.data]
+id = { name = "${MINT_ENV_TEST_UNSET}", type = "u8" }
+"#,
+    );
+
+    let err = mint_cli::layout::load_layout(&path).expect_err("load should fail");
+    assert!(
+        err.to_string().contains("'MINT_ENV_TEST_UNSET' is not set"),
+        "unexpected error: {err}"
+    );
+}
@@ -0,0 +1,53 @@
+use mint_cli::error::MintError;
+
+#[path = "common/mod.rs"]
+mod common;
+
+fn layout(padding: u8) -> String {
+    format!(
+        r#"[settings]
+endianness = "little"
+erased_value = 0xFF
+
+[cal.header]
+start_address = 0x8000
+length = 0x40
+padding = 0x{padding:02X}
+
+[cal.data]
+id = {{ value = 7, type = "u8" }}
+limit = {{ value = 300, type = "u32" }}
+"#
+    )
+}
+
+// Guard bytes fill the block exactly, leaving no padding.
+const GUARDED: &str = r#"[settings]
+endianness = "little"
+erased_value = 0xFF
+
+[cal.header]
+start_address = 0x8000
+length = 0x10
+padding = 0x00
+guard = { before = 4, after = 4, value = 0xA5 }
+
+[cal.data]
+id = { value = 7, type = "u32" }
+limit = { value = 300, type = "u32" }
+"#;
+
+fn build(stem: &str, layout: &str) -> Result<(), MintError> {
+    common::build_layout_with(stem, layout, "cal", |args| args.output.deny_warnings = true)
+        .map(|_| ())
+}
+
+// One test, as the warning count is shared by the whole process.
+#[test]
+fn padding_other_than_erased_value_is_warned() {
+    build("erased_value_match", &layout(0xFF)).expect("padding matches erased flash");
+    build("erased_value_guarded", GUARDED).expect("guard bytes are not padding");
+
+    let err = build("erased_value_mismatch", &layout(0x00)).unwrap_err();
+    assert!(matches!(err, MintError::WarningsDenied(_)), "{err}");
+}
@@ -0,0 +1,36 @@
+use mint_cli::data::args::{DataArgs, DateFormat};
+use mint_cli::data::create_data_source;
+use mint_cli::layout::value::{DataValue, ValueSource};
+
+fn dates_source(date_format: DateFormat) -> Box<dyn mint_cli::data::DataSource> {
+    let args = DataArgs {
+        xlsx: Some("tests/data/dates.xlsx".to_string()),
+        version: Some("Default".to_string()),
+        date_format,
+        ..Default::default()
+    };
+    create_data_source(&args)
+        .expect("open workbook")
+        .expect("source")
+}
+
+#[test]
+fn excel_date_cells_convert_per_date_format() {
+    let value = dates_source(DateFormat::Epoch)
+        .retrieve_single_value("ManufactureDate")
+        .expect("epoch");
+    assert!(matches!(value, DataValue::I64(1_710_460_800)), "{value:?}");
+
+    let value = dates_source(DateFormat::Bcd)
+        .retrieve_single_value("ManufactureDate")
+        .expect("bcd");
+    assert!(matches!(value, DataValue::U64(0x2024_0315)), "{value:?}");
+
+    let value = dates_source(DateFormat::Iso)
+        .retrieve_1d_array_or_string("ManufactureDate")
+        .expect("iso");
+    assert!(
+        matches!(&value, ValueSource::Single(DataValue::Str(s)) if s == "2024-03-15"),
+        "{value:?}"
+    );
+}
@@ -0,0 +1,70 @@
+use mint_cli::commands;
+use mint_cli::data;
+use mint_cli::error::exit_code;
+use mint_cli::layout::args::BlockNames;
+use mint_cli::output::args::OutputFormat;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"
+[settings]
+endianness = "little"
+
+[cal.header]
+start_address = 0x1000
+length = 0x4
+
+[cal.data]
+gain = { name = "Gain", type = "u32" }
+"#;
+
+#[test]
+fn missing_value_and_overflow_have_distinct_codes() {
+    let ds_args = data::args::DataArgs {
+        json: Some(r#"{"Default":{"Other":1}}"#.to_string()),
+        version: Some("Default".to_string()),
+        ..Default::default()
+    };
+    let ds = data::create_data_source(&ds_args).unwrap().unwrap();
+    let args = common::layout_args("exit_missing", LAYOUT, "cal");
+    let err = commands::build(&args, Some(ds.as_ref())).unwrap_err();
+    assert_eq!(err.exit_code(), exit_code::DATA, "{err}");
+    assert_eq!(err.code(), "MINT-D003");
+
+    let overflow = LAYOUT.replace(
+        r#"{ name = "Gain", type = "u32" }"#,
+        r#"{ value = [1, 2], type = "u32", size = 2 }"#,
+    );
+    let args = common::layout_args("exit_overflow", &overflow, "cal");
+    let err = commands::build(&args, None).unwrap_err();
+    assert_eq!(err.exit_code(), exit_code::BUDGET, "{err}");
+    assert_eq!(err.code(), "MINT-O003");
+    assert!(mint_cli::error::explain(err.code()).is_some());
+}
+
+#[test]
+fn deny_warnings_fails_before_writing_outputs() {
+    let first = common::write_layout_file("exit_warn_a", LAYOUT);
+    let second = common::write_layout_file("exit_warn_b", LAYOUT);
+    let layout = LAYOUT.replace(
+        r#"{ name = "Gain", type = "u32" }"#,
+        r#"{ value = 1, type = "u32" }"#,
+    );
+    std::fs::write(&first, &layout).unwrap();
+    std::fs::write(&second, &layout).unwrap();
+    let out = "out/exit_warn.hex";
+    let _ = std::fs::remove_file(out);
+
+    let blocks = [first, second]
+        .map(|file| BlockNames {
+            name: "cal".to_string(),
+            file,
+        })
+        .to_vec();
+    let mut args = common::build_args_for_layouts(blocks, OutputFormat::Hex, out);
+    args.output.deny_warnings = true;
+    let err = commands::build(&args, None).unwrap_err();
+    assert_eq!(err.exit_code(), exit_code::WARNINGS, "{err}");
+    assert!(!std::path::Path::new(out).exists());
+}
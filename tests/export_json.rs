@@ -3,7 +3,7 @@ use std::path::PathBuf;
 use mint_cli::commands;
 use mint_cli::data;
 use mint_cli::layout::args::{BlockNames, LayoutArgs};
-use mint_cli::output::args::{OutputArgs, OutputFormat};
+use mint_cli::output::args::{ExportFormat, OutputArgs, OutputFormat};
 
 #[path = "common/mod.rs"]
 mod common;
@@ -61,6 +61,7 @@ message = { value = "Hi", type = "u8", size = 4 }
                 file: layout_path,
             }],
             strict: false,
+            ..Default::default()
         },
         data: data_args,
         output: OutputArgs {
@@ -68,8 +69,8 @@ message = { value = "Hi", type = "u8", size = 4 }
             record_width: 16,
             format: OutputFormat::Hex,
             export_json: Some(PathBuf::from("out/export.json")),
-            stats: false,
             quiet: true,
+            ..Default::default()
         },
     };
 
@@ -102,3 +103,68 @@ message = { value = "Hi", type = "u8", size = 4 }
     assert_eq!(json[&layout_key]["data"]["counter"].as_u64(), Some(99));
     assert_eq!(json[&layout_key]["data"]["message"].as_str(), Some("Hi"));
 }
+
+#[test]
+fn export_per_block_writes_one_document_per_block() {
+    let layout = r#"
+[settings]
+endianness = "little"
+
+[settings.crc]
+polynomial = 0x04C11DB7
+start = 0xFFFFFFFF
+xor_out = 0xFFFFFFFF
+ref_in = true
+ref_out = true
+area = "data"
+
+[cal.header]
+start_address = 0x1000
+length = 0x20
+
+[cal.header.crc]
+location = "end_block"
+
+[cal.data]
+gain = { value = 7, type = "u16" }
+
+[info.header]
+start_address = 0x2000
+length = 0x10
+
+[info.data]
+name = { value = "mint", type = "u8", size = 8 }
+"#;
+    let path = common::write_layout_file("export_per_block", layout);
+    let blocks = ["cal", "info"]
+        .map(|name| BlockNames {
+            name: name.to_string(),
+            file: path.clone(),
+        })
+        .to_vec();
+    let mut args =
+        common::build_args_for_layouts(blocks, OutputFormat::Hex, "out/export_per_block.hex");
+    let dir = PathBuf::from("out/export_per_block");
+    let _ = std::fs::remove_dir_all(&dir);
+    args.output.export_json = Some(dir.clone());
+    args.output.export_format = ExportFormat::Yaml;
+    args.output.export_per_block = true;
+    let stats = commands::build(&args, None).expect("build should succeed");
+
+    let text = std::fs::read_to_string(dir.join("cal.yaml")).expect("read cal document");
+    let cal: serde_yaml::Value = serde_yaml::from_str(&text).expect("parse cal document");
+    assert_eq!(cal["block"].as_str(), Some("cal"));
+    assert_eq!(cal["start_address"].as_u64(), Some(0x1000));
+    assert_eq!(cal["crc_address"].as_u64(), Some(0x101C));
+    assert_eq!(
+        cal["crc"].as_u64(),
+        stats.block_stats[0].crc_value.map(u64::from)
+    );
+    assert_eq!(cal["values"]["gain"].as_u64(), Some(7));
+
+    let text = std::fs::read_to_string(dir.join("info.yaml")).expect("read info document");
+    let info: serde_yaml::Value = serde_yaml::from_str(&text).expect("parse info document");
+    assert_eq!(info["start_address"].as_u64(), Some(0x2000));
+    assert!(info.get("crc").is_none());
+    assert_eq!(info["values"]["name"].as_str(), Some("mint"));
+}
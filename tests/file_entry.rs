@@ -0,0 +1,66 @@
+use std::path::PathBuf;
+
+#[path = "common/mod.rs"]
+mod common;
+
+fn build(file_stem: &str, entry: &str, strict: bool) -> Result<(String, String), String> {
+    common::ensure_out_dir();
+    std::fs::write("out/file_entry_asset.bin", [1u8, 2, 3, 4, 5, 6]).unwrap();
+    let layout = format!(
+        r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x20
+padding = 0x00
+
+[block.data]
+asset = {}
+"#,
+        entry
+    );
+    let report = format!("out/{}.json", file_stem);
+    let hex = common::build_layout_with(file_stem, &layout, "block", |args| {
+        args.layout.strict = strict;
+        args.output.export_json = Some(PathBuf::from(&report));
+    })
+    .map_err(|e| e.to_string())?;
+    Ok((hex, std::fs::read_to_string(report).unwrap()))
+}
+
+#[test]
+fn file_entry_is_embedded_relative_to_layout() {
+    let (hex, report) = build(
+        "file_entry_padded",
+        r#"{ file = "file_entry_asset.bin", type = "u8", size = 8 }"#,
+        false,
+    )
+    .unwrap();
+    assert!(hex.starts_with(":0810000001020304050600"), "{hex}");
+    assert!(
+        report.contains(
+            "\"sha256\": \"7192385c3c0605de55bb9476ce1d90748190ecb32a8eed7f5207b30cf6a1fe89\""
+        ),
+        "{report}"
+    );
+}
+
+#[test]
+fn file_entry_truncates_unless_strict() {
+    let (hex, _) = build(
+        "file_entry_truncated",
+        r#"{ file = "file_entry_asset.bin", type = "u8", size = 4 }"#,
+        false,
+    )
+    .unwrap();
+    assert!(hex.starts_with(":0410000001020304"), "{hex}");
+
+    let err = build(
+        "file_entry_strict",
+        r#"{ file = "file_entry_asset.bin", type = "u8", size = 4 }"#,
+        true,
+    )
+    .unwrap_err();
+    assert!(err.contains("larger than defined size"), "{err}");
+}
@@ -0,0 +1,71 @@
+use mint_cli::commands;
+use mint_cli::output::args::OutputFormat;
+use mint_cli::output::flash_script::FlashTool;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[boot.header]
+start_address = 0x1000
+length = 0x100
+
+[boot.data]
+id = { value = 0x11, type = "u8" }
+
+[app.header]
+start_address = 0x2000
+length = 0x400
+
+[app.data]
+id = { value = 0x22, type = "u8" }
+"#;
+
+fn args(stem: &str, format: OutputFormat, out: &str) -> mint_cli::args::Args {
+    let mut args = common::layout_args(stem, LAYOUT, "");
+    args.output.out = out.into();
+    args.output.format = format;
+    args
+}
+
+#[test]
+fn jlink_script_erases_blocks_and_loads_output() {
+    let mut args = args(
+        "flash_script_jlink",
+        OutputFormat::Hex,
+        "out/flash_script_jlink/fw.hex",
+    );
+    args.output.flash_script = Some("out/flash_script_jlink/flash.jlink".into());
+    commands::build(&args, None).expect("build");
+
+    let script = std::fs::read_to_string("out/flash_script_jlink/flash.jlink").unwrap();
+    assert!(
+        script.contains("// boot\nerase 0x1000 0x10FF\n"),
+        "{script}"
+    );
+    assert!(script.contains("// app\nerase 0x2000 0x23FF\n"), "{script}");
+    assert!(script.contains("loadfile fw.hex\n"), "{script}");
+}
+
+#[test]
+fn trace32_script_from_flag_and_errors_without_tool() {
+    let mut args = args(
+        "flash_script_t32",
+        OutputFormat::Mot,
+        "out/flash_script_t32.mot",
+    );
+    args.output.flash_script = Some("out/flash_script_t32.script".into());
+    let err = commands::build(&args, None).unwrap_err().to_string();
+    assert!(err.contains("--flash-tool"), "{err}");
+
+    args.output.flash_tool = Some(FlashTool::Trace32);
+    commands::build(&args, None).expect("build");
+    let script = std::fs::read_to_string("out/flash_script_t32.script").unwrap();
+    assert!(script.contains("FLASH.Erase 0x1000--0x10FF\n"), "{script}");
+    assert!(
+        script.contains("Data.LOAD.S3record \"~~~~/flash_script_t32.mot\"\n"),
+        "{script}"
+    );
+}
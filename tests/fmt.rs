@@ -0,0 +1,55 @@
+use mint_cli::commands;
+use mint_cli::layout::args::BlockNames;
+use mint_cli::layout::canonical::format_files;
+use mint_cli::layout::error::LayoutError;
+use mint_cli::output::args::OutputFormat;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness="little"
+
+[config.header]
+  start_address = 0x1a00
+  length = 0x20
+
+[config.data]
+version = {type="u16",value=0xbeef}
+gains = { type = "f32", value = [1.0,2.0], size = 2 }
+ch = { repeat = 2, off = {type="i8", value=-1} }
+"#;
+
+fn build(path: &str, out: &str) -> String {
+    let args = common::build_args_for_layouts(
+        vec![BlockNames {
+            name: "config".to_string(),
+            file: path.to_string(),
+        }],
+        OutputFormat::Hex,
+        out,
+    );
+    commands::build(&args, None).expect("build");
+    std::fs::read_to_string(out).unwrap()
+}
+
+#[test]
+fn fmt_check_fails_then_rewrite_keeps_the_image() {
+    let path = common::write_layout_file("fmt", LAYOUT);
+    let before = build(&path, "out/fmt_before.hex");
+
+    let err = format_files(&[&path], true).unwrap_err();
+    assert!(
+        matches!(err, LayoutError::NotFormatted(ref files) if files == std::slice::from_ref(&path))
+    );
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), LAYOUT);
+
+    assert_eq!(format_files(&[&path], false).unwrap(), [path.as_str()]);
+    let formatted = std::fs::read_to_string(&path).unwrap();
+    assert!(formatted.contains("start_address = 0x1A00\n"));
+    assert!(formatted.contains("version = { value = 0xBEEF, type = \"u16\" }\n"));
+    assert!(formatted.contains("gains = { value = [1.0, 2.0], type = \"f32\", size = 2 }\n"));
+    assert!(format_files(&[&path], true).unwrap().is_empty());
+
+    assert_eq!(build(&path, "out/fmt_after.hex"), before);
+}
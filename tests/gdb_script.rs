@@ -0,0 +1,41 @@
+use mint_cli::commands;
+use mint_cli::data::create_data_source;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[cal.header]
+start_address = 0x8000
+length = 0x40
+
+[cal.data]
+id = { value = 7, type = "u8" }
+TemperatureMax = { value = 85, type = "i16" }
+name = { value = "ecu", type = "u8", size = 8 }
+map = { name = "Map", type = "f32", size = [2, 3] }
+"#;
+
+#[test]
+fn gdb_script_lists_every_field_with_type_and_address() {
+    let mut args = common::layout_args("gdb_script", LAYOUT, "cal");
+    args.data.xlsx = None;
+    args.data.json = Some(r#"{"Default": {"Map": [[1, 2, 3], [4, 5, 6]]}}"#.to_string());
+    args.output.gdb_script = Some("out/gdb_script.gdb".into());
+    let ds = create_data_source(&args.data).unwrap();
+    commands::build(&args, ds.as_deref()).expect("build");
+
+    let script = std::fs::read_to_string("out/gdb_script.gdb").unwrap();
+    let lines: Vec<&str> = script.lines().filter(|l| l.starts_with("set")).collect();
+    assert_eq!(
+        lines,
+        [
+            "set $cal_id = (uint8_t *) 0x8000",
+            "set $cal_TemperatureMax = (int16_t *) 0x8002",
+            "set $cal_name = (uint8_t (*)[8]) 0x8004",
+            "set $cal_map = (float (*)[2][3]) 0x800C",
+        ]
+    );
+}
@@ -0,0 +1,69 @@
+use mint_cli::layout::used_values::ValueCollector;
+use mint_cli::output::checksum::compute_checksum;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[settings.crc]
+polynomial = 0x04C11DB7
+start = 0xFFFFFFFF
+xor_out = 0xFFFFFFFF
+ref_in = true
+ref_out = true
+area = "data"
+
+[image.header]
+start_address = 0x1000
+length = 0x40
+
+[image.data]
+hdr.length = { auto = "data_length", type = "u16" }
+hdr.count = { auto = "field_count", type = "u16" }
+hdr.crc = { auto = "data_crc", type = "u32" }
+payload.id = { auto = "values_hash", type = "u32" }
+payload.gain = { value = 1.5, type = "f32" }
+payload.table = { value = [1, 2, 3], type = "u8", size = 4 }
+"#;
+
+#[test]
+fn leading_header_is_patched_from_following_data() {
+    let path = common::write_layout_file("generated_header", LAYOUT);
+    let cfg = mint_cli::layout::load_layout(&path).unwrap();
+    let mut values = ValueCollector::new();
+    let (bytes, _) = cfg.blocks["image"]
+        .build_bytestream(None, &cfg.settings, false, &mut values)
+        .unwrap();
+
+    assert_eq!(bytes.len(), 20);
+    // Each covers what follows it: the length 18 bytes, the count 4 fields
+    assert_eq!(bytes[0..2], 18u16.to_le_bytes());
+    assert_eq!(bytes[2..4], 4u16.to_le_bytes());
+
+    // The CRC covers the final payload, including the patched values_hash
+    assert_ne!(bytes[8..12], [0; 4]);
+    let crc = compute_checksum(&bytes[8..], cfg.settings.crc.as_ref().unwrap()).unwrap();
+    assert_eq!(bytes[4..8], crc.to_le_bytes());
+
+    let values = values.into_value();
+    assert_eq!(values["hdr"]["length"], 18);
+    assert_eq!(values["hdr"]["crc"], crc);
+}
+
+#[test]
+fn data_crc_requires_crc_parameters() {
+    let layout = LAYOUT.replace("polynomial = 0x04C11DB7\n", "");
+    let path = common::write_layout_file("generated_header_no_crc", &layout);
+    let cfg = mint_cli::layout::load_layout(&path).unwrap();
+    let err = cfg.blocks["image"]
+        .build_bytestream(
+            None,
+            &cfg.settings,
+            false,
+            &mut mint_cli::layout::used_values::NoopValueSink,
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("data_crc requires CRC parameters"));
+}
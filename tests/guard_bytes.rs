@@ -0,0 +1,111 @@
+use mint_cli::layout::load_layout;
+use mint_cli::layout::used_values::NoopValueSink;
+use mint_cli::output::{DataRange, bytestream_to_datarange};
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[settings.crc]
+location = "end_block"
+polynomial = 0x04C11DB7
+start = 0xFFFFFFFF
+xor_out = 0xFFFFFFFF
+ref_in = true
+ref_out = true
+area = "data"
+
+[plain.header]
+start_address = 0x1000
+length = 0x20
+
+[plain.data]
+id = { value = 0x1234, type = "u16" }
+flag = { value = 1, type = "u8" }
+
+[guarded.header]
+start_address = 0x2000
+length = 0x20
+guard = { before = 4, after = 4, value = 0xA5 }
+
+[guarded.data]
+id = { value = 0x1234, type = "u16" }
+flag = { value = 1, type = "u8" }
+
+[excluded.header]
+start_address = 0x3000
+length = 0x20
+guard = { before = 4, after = 4, value = 0xA5, exclude_from_crc = true }
+
+[excluded.data]
+id = { value = 0x1234, type = "u16" }
+flag = { value = 1, type = "u8" }
+"#;
+
+fn build(file_stem: &str, layout: &str, name: &str) -> DataRange {
+    let path = common::write_layout_file(file_stem, layout);
+    let cfg = load_layout(&path).unwrap();
+    let block = &cfg.blocks[name];
+    let (bytestream, padding) = block
+        .build_bytestream(None, &cfg.settings, false, &mut NoopValueSink)
+        .unwrap();
+    bytestream_to_datarange(bytestream, &block.header, &cfg.settings, padding).unwrap()
+}
+
+#[test]
+fn guard_bytes_surround_the_data() {
+    let dr = build("guard_bytes", LAYOUT, "guarded");
+    assert_eq!(
+        &dr.bytestream[..11],
+        &[
+            0xA5, 0xA5, 0xA5, 0xA5, 0x34, 0x12, 0x01, 0xA5, 0xA5, 0xA5, 0xA5
+        ]
+    );
+    assert_eq!(dr.used_size, 15);
+}
+
+#[test]
+fn guard_bytes_can_be_left_out_of_the_crc() {
+    let plain = build("guard_bytes_crc", LAYOUT, "plain");
+    let guarded = build("guard_bytes_crc", LAYOUT, "guarded");
+    let excluded = build("guard_bytes_crc", LAYOUT, "excluded");
+    assert_ne!(guarded.crc_bytestream, plain.crc_bytestream);
+    assert_eq!(excluded.crc_bytestream, plain.crc_bytestream);
+}
+
+#[test]
+fn guard_follows_a_crc_at_start() {
+    let layout = LAYOUT.replace("location = \"end_block\"", "location = \"start\"");
+    let dr = build("guard_bytes_start", &layout, "excluded");
+    assert_eq!(&dr.bytestream[4..8], &[0xA5; 4]);
+    assert_eq!(&dr.bytestream[8..11], &[0x34, 0x12, 0x01]);
+    assert_eq!(
+        dr.crc_bytestream,
+        build("guard_bytes_start", &layout, "plain").crc_bytestream
+    );
+}
+
+#[test]
+fn guard_sizes_must_fill_whole_words() {
+    let layout = r#"[settings]
+endianness = "little"
+word_addressing = 2
+
+[block.header]
+start_address = 0x1000
+length = 0x20
+guard = { before = 3, value = 0xA5 }
+
+[block.data]
+id = { value = 1, type = "u16" }
+"#;
+    let path = common::write_layout_file("guard_bytes_words", layout);
+    let cfg = load_layout(&path).unwrap();
+    let err = cfg.blocks["block"]
+        .build_bytestream(None, &cfg.settings, false, &mut NoopValueSink)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("multiples of the 2-byte word"), "{err}");
+}
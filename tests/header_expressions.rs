@@ -0,0 +1,83 @@
+use mint_cli::layout::error::LayoutError;
+use mint_cli::layout::load_layout;
+
+#[path = "common/mod.rs"]
+mod common;
+
+#[test]
+fn header_expressions_follow_other_blocks() {
+    let path = common::write_layout_file(
+        "header_expressions",
+        r#"[settings]
+endianness = "little"
+
+[boot.header]
+start_address = "0x8000_0000 + 3*0x400"
+length = 0x400
+
+[app.header]
+start_address = "END_OF(boot)"
+length = "2 * LENGTH_OF(boot)"
+
+[boot.data]
+id = { value = 1, type = "u8" }
+
+[app.data]
+id = { value = 2, type = "u8" }
+"#,
+    );
+    let cfg = load_layout(&path).unwrap();
+    assert_eq!(cfg.blocks["boot"].header.start_address, 0x8000_0C00);
+    assert_eq!(cfg.blocks["app"].header.start_address, 0x8000_1000);
+    assert_eq!(cfg.blocks["app"].header.length, 0x800);
+}
+
+#[test]
+fn header_expression_cycles_and_missing_blocks_are_errors() {
+    let path = common::write_layout_file(
+        "header_expressions_cycle",
+        r#"[settings]
+endianness = "little"
+
+[a.header]
+start_address = "END_OF(b)"
+length = 0x10
+
+[b.header]
+start_address = "END_OF(a)"
+length = 0x10
+
+[a.data]
+id = { value = 1, type = "u8" }
+
+[b.data]
+id = { value = 2, type = "u8" }
+"#,
+    );
+    let err = load_layout(&path).unwrap_err();
+    assert!(matches!(err, LayoutError::Expression(_)), "{err:?}");
+    assert!(
+        err.to_string()
+            .contains("cycle: a.start_address -> b.start_address -> a.start_address"),
+        "{err}"
+    );
+
+    let path = common::write_layout_file(
+        "header_expressions_missing",
+        r#"[settings]
+endianness = "little"
+
+[a.header]
+start_address = "END_OF(boot) + 4"
+length = 0x10
+
+[a.data]
+id = { value = 1, type = "u8" }
+"#,
+    );
+    let err = load_layout(&path).unwrap_err().to_string();
+    assert!(
+        err.contains("block 'a' start_address: no block 'boot' in this layout file"),
+        "{err}"
+    );
+}
@@ -0,0 +1,100 @@
+use mint_cli::data::args::DataArgs;
+
+#[path = "common/mod.rs"]
+mod common;
+
+fn build(file_stem: &str, data: &str, json: Option<&str>) -> Result<String, String> {
+    let layout = format!(
+        r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x20
+padding = 0x00
+
+[block.data]
+{}
+"#,
+        data
+    );
+    common::build_layout_with(file_stem, &layout, "block", |args| {
+        args.data = DataArgs {
+            json: json.map(str::to_string),
+            version: json.map(|_| "Default".to_string()),
+            ..Default::default()
+        };
+    })
+    .map_err(|e| e.to_string())
+}
+
+#[test]
+fn hex_blob_is_emitted_verbatim_and_padded() {
+    let hex = build(
+        "hex_blob_padded",
+        r#"blob = { hex = "DE AD BE EF", type = "u8", size = 6 }"#,
+        None,
+    )
+    .unwrap();
+    assert!(hex.starts_with(":06100000DEADBEEF0000"), "{hex}");
+}
+
+#[test]
+fn hex_blob_respects_strict_size() {
+    let err = build(
+        "hex_blob_strict",
+        r#"blob = { hex = "DEADBEEF", type = "u8", SIZE = 6 }"#,
+        None,
+    )
+    .unwrap_err();
+    assert!(err.contains("smaller than defined size"), "{err}");
+
+    let err = build(
+        "hex_blob_long",
+        r#"blob = { hex = "DEADBEEF", type = "u8", size = 2 }"#,
+        None,
+    )
+    .unwrap_err();
+    assert!(err.contains("larger than defined size"), "{err}");
+}
+
+#[test]
+fn prefixed_data_source_string_is_decoded_only_with_bytes() {
+    let hex = build(
+        "hex_blob_datasource",
+        r#"key = { name = "Key", type = "u8", size = 4, bytes = true }"#,
+        Some(r#"{"Default": {"Key": "0x01020304"}}"#),
+    )
+    .unwrap();
+    assert!(hex.starts_with(":0410000001020304"), "{hex}");
+
+    let hex = build(
+        "hex_blob_datasource_text",
+        r#"key = { name = "Key", type = "u8", size = 10 }"#,
+        Some(r#"{"Default": {"Key": "0x01020304"}}"#),
+    )
+    .unwrap();
+    // "0x01020304" as text.
+    assert!(hex.starts_with(":0A10000030783031303230333034"), "{hex}");
+}
+
+#[test]
+fn bytea_and_base64_strings_are_decoded_as_bytes() {
+    let hex = build(
+        "hex_blob_bytea",
+        r#"key = { name = "Key", type = "u8", size = 4 }
+blob = { name = "Blob", type = "u8", size = 4 }"#,
+        Some(r#"{"Default": {"Key": "\\x31323334", "Blob": "base64:AQIDBA=="}}"#),
+    )
+    .unwrap();
+    // "1234" as bytes, not parsed as the number 1234.
+    assert!(hex.starts_with(":08100000313233340102030"), "{hex}");
+
+    let err = build(
+        "hex_blob_bad_base64",
+        r#"blob = { name = "Blob", type = "u8", size = 4 }"#,
+        Some(r#"{"Default": {"Blob": "base64:AQ*D"}}"#),
+    )
+    .unwrap_err();
+    assert!(err.contains("Invalid base64"), "{err}");
+}
@@ -0,0 +1,61 @@
+#![cfg(unix)]
+
+use mint_cli::commands;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x4
+
+[block.data]
+id = { value = 0x11, type = "u8" }
+"#;
+
+fn args(stem: &str) -> mint_cli::args::Args {
+    common::layout_args(stem, LAYOUT, "block")
+}
+
+#[test]
+fn post_build_hook_sees_output_and_checksums() {
+    let mut args = args("hook_post");
+    args.output.checksums = Some("out/hook_post.sha256".into());
+    args.output.post_build = Some(
+        "test -f \"$MINT_OUTPUT\" && echo \"$MINT_OUTPUT $MINT_CHECKSUMS\" > out/hook_post.txt"
+            .to_string(),
+    );
+    commands::build(&args, None).expect("build");
+
+    let seen = std::fs::read_to_string("out/hook_post.txt").expect("hook ran");
+    assert_eq!(seen.trim(), "out/hook_post.hex out/hook_post.sha256");
+}
+
+#[test]
+fn post_build_hook_sees_written_manifest() {
+    let mut args = args("hook_manifest");
+    args.output.manifest = Some("out/hook_manifest.json".into());
+    args.output.post_build = Some(
+        "test -f \"$MINT_BUILD_MANIFEST\" && cp \"$MINT_BUILD_MANIFEST\" out/hook_manifest.seen"
+            .to_string(),
+    );
+    let invocation = commands::Invocation {
+        argv: Vec::new(),
+        project: None,
+    };
+    commands::build_recorded(&args, None, &invocation).expect("build");
+
+    let seen = std::fs::read_to_string("out/hook_manifest.seen").expect("hook ran");
+    assert!(seen.contains("out/hook_manifest.hex"), "{seen}");
+}
+
+#[test]
+fn failing_post_build_hook_fails_build() {
+    let mut args = args("hook_fail");
+    args.output.post_build = Some("exit 1".to_string());
+    let err = commands::build(&args, None).unwrap_err().to_string();
+    assert!(err.contains("Hook 'exit 1' failed"), "{err}");
+}
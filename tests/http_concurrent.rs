@@ -0,0 +1,129 @@
+//! Version stacks from an HTTP source are fetched concurrently over pooled connections.
+
+use mint_cli::data::args::DataArgs;
+use mint_cli::data::create_data_source;
+use mint_cli::layout::value::DataValue;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+/// Serves `{"Gain": <n>}` for `/item?version=V<n>`, but only once `expected`
+/// requests are open at the same time. Returns the server address and a
+/// handle yielding whether all requests were open together.
+fn start_server(expected: usize) -> (String, std::thread::JoinHandle<bool>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let handle = std::thread::spawn(move || {
+        listener.set_nonblocking(true).unwrap();
+        // A sequential client waits on its first request, so after this the
+        // server answers as requests arrive and the test fails instead of hanging.
+        let give_up = Instant::now() + Duration::from_secs(2);
+        let mut open: Vec<(TcpStream, String)> = Vec::new();
+        let mut together = false;
+        let mut served = 0;
+        while served < expected {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    stream.set_nonblocking(false).unwrap();
+                    let mut line = String::new();
+                    BufReader::new(&stream).read_line(&mut line).unwrap();
+                    open.push((stream, line));
+                    together |= open.len() == expected;
+                }
+                Err(_) => std::thread::sleep(Duration::from_millis(10)),
+            }
+            if together || Instant::now() > give_up {
+                served += open.len();
+                open.drain(..)
+                    .for_each(|(stream, line)| respond(stream, &line));
+            }
+        }
+        together
+    });
+    (address, handle)
+}
+
+fn respond(mut stream: TcpStream, request_line: &str) {
+    let n = request_line.split("version=V").nth(1).unwrap().as_bytes()[0] as char;
+    let body = format!("{{\"Gain\": {}}}", n);
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    )
+    .unwrap();
+}
+
+#[test]
+fn version_stack_is_fetched_concurrently() {
+    let (address, server) = start_server(3);
+    let args = DataArgs {
+        http: Some(format!(
+            r#"{{"url": "http://{}/item?version=$VERSION"}}"#,
+            address
+        )),
+        version: Some("V1/V2/V3".to_string()),
+        ..Default::default()
+    };
+    let ds = create_data_source(&args).unwrap().unwrap();
+
+    assert!(
+        server.join().unwrap(),
+        "requests were not made concurrently"
+    );
+    // The first version in the stack still wins.
+    assert!(matches!(
+        ds.retrieve_single_value("Gain").unwrap(),
+        DataValue::U64(1)
+    ));
+}
+
+#[test]
+fn pooled_connections_are_reused_across_versions() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let server = std::thread::spawn(move || {
+        let mut connections = 0;
+        let mut served = 0;
+        while served < 3 {
+            let (stream, _) = listener.accept().unwrap();
+            connections += 1;
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            loop {
+                let mut request_line = String::new();
+                if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+                    break;
+                }
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+                let body = "{\"Gain\": 1}";
+                write!(
+                    &stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .unwrap();
+                served += 1;
+                if served == 3 {
+                    break;
+                }
+            }
+        }
+        connections
+    });
+
+    let args = DataArgs {
+        http: Some(format!(
+            r#"{{"url": "http://{}/item?version=$VERSION", "pool_size": 1}}"#,
+            address
+        )),
+        version: Some("V1/V2/V3".to_string()),
+        ..Default::default()
+    };
+    create_data_source(&args).unwrap().unwrap();
+    assert_eq!(server.join().unwrap(), 1);
+}
@@ -0,0 +1,104 @@
+//! HTTP responses are reshaped by `map` expressions and checked against
+//! `schema` types, and GraphQL requests built from `graphql` configs.
+
+use mint_cli::data::args::DataArgs;
+use mint_cli::data::create_data_source;
+use mint_cli::layout::value::{DataValue, ValueSource};
+
+#[path = "common/mod.rs"]
+mod common;
+
+#[test]
+fn map_turns_record_arrays_into_names() {
+    let (address, _) = common::serve_once(
+        r#"{"results": [{"kind": "cal", "rows": [
+            {"name": "Gain", "value": 3},
+            {"name": "Table", "value": [1, 2, 3]}
+        ]}]}"#,
+    );
+    let args = DataArgs {
+        http: Some(format!(
+            r#"{{"url": "http://{}/item?version=$VERSION",
+                "map": ".results[?kind=='cal'].rows[] | {{(.name): .value}}"}}"#,
+            address
+        )),
+        version: Some("Default".to_string()),
+        ..Default::default()
+    };
+    let ds = create_data_source(&args).unwrap().unwrap();
+
+    assert!(matches!(
+        ds.retrieve_single_value("Gain").unwrap(),
+        DataValue::U64(3)
+    ));
+    let ValueSource::Array(table) = ds.retrieve_1d_array_or_string("Table").unwrap() else {
+        panic!("expected array");
+    };
+    assert_eq!(table.len(), 3);
+}
+
+#[test]
+fn malformed_map_is_a_config_error() {
+    let args = DataArgs {
+        http: Some(r#"{"url": "http://127.0.0.1:9/", "map": ".rows | .name"}"#.to_string()),
+        version: Some("Default".to_string()),
+        ..Default::default()
+    };
+    let err = create_data_source(&args).err().unwrap().to_string();
+    assert!(err.contains("invalid map '.rows | .name'"), "{err}");
+}
+
+#[test]
+fn graphql_posts_persisted_queries() {
+    let (address, server) = common::serve_once(r#"{"data": {"calibration": {"Gain": 4}}}"#);
+    let args = DataArgs {
+        http: Some(format!(
+            r#"{{"url": "http://{}/graphql",
+                "graphql": {{"persisted_query": "abc123", "operation_name": "Calibration",
+                             "variables": {{"variant": "$VERSION"}}}},
+                "data_path": ["data", "calibration"]}}"#,
+            address
+        )),
+        version: Some("Debug".to_string()),
+        ..Default::default()
+    };
+    let ds = create_data_source(&args).unwrap().unwrap();
+    assert!(matches!(
+        ds.retrieve_single_value("Gain").unwrap(),
+        DataValue::U64(4)
+    ));
+
+    let request: serde_json::Value = serde_json::from_str(&server.join().unwrap()).unwrap();
+    assert_eq!(request["operationName"], "Calibration");
+    assert_eq!(request["variables"]["variant"], "Debug");
+    assert_eq!(
+        request["extensions"]["persistedQuery"]["sha256Hash"],
+        "abc123"
+    );
+    assert!(request.get("query").is_none());
+}
+
+#[test]
+fn schema_mismatches_are_reported_together() {
+    let (address, _) = common::serve_once(r#"{"TemperatureMax": "hot", "Curve": [0.5, "x"]}"#);
+    let args = DataArgs {
+        http: Some(format!(
+            r#"{{"url": "http://{}/item?version=$VERSION",
+                "schema": {{"TemperatureMax": "int", "Curve": "array<float>"}}}}"#,
+            address
+        )),
+        version: Some("Default".to_string()),
+        ..Default::default()
+    };
+    let err = create_data_source(&args).err().unwrap();
+    assert_eq!(err.code(), "MINT-D007");
+    let message = err.to_string();
+    assert!(
+        message.contains("'TemperatureMax' in version 'Default' should be int"),
+        "{message}"
+    );
+    assert!(
+        message.contains("'Curve' in version 'Default' should be array<float>"),
+        "{message}"
+    );
+}
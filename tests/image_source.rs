@@ -0,0 +1,181 @@
+use mint_cli::args::Args;
+use mint_cli::commands;
+use mint_cli::data::{self, args::DataArgs};
+use std::path::Path;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x40
+
+[block.data]
+id = { name = "Id", type = "u16" }
+offset = { name = "Offset", type = "i16" }
+gain = { name = "Gain", type = "f32" }
+serial = { name = "Serial", type = "u8", size = 4 }
+table = { name = "Table", type = "u8", size = [2, 3], order = "column" }
+scaled = { name = "Scaled", type = "u16", transform = ["scale(10)"] }
+"#;
+
+const DATA: &str = r#"{"Default": {"Id": 513, "Offset": -3, "Gain": 1.5,
+    "Serial": [1, 2, 3, 4], "Table": [[1, 2, 3], [4, 5, 6]], "Scaled": 7}}"#;
+
+fn args(file_stem: &str, data: DataArgs) -> Args {
+    layout_args(file_stem, LAYOUT, data)
+}
+
+fn layout_args(file_stem: &str, layout: &str, data: DataArgs) -> Args {
+    let mut args = common::layout_args(file_stem, layout, "block");
+    args.data = data;
+    args
+}
+
+fn build(args: &Args) -> String {
+    let ds = match &args.data.image_source {
+        Some(path) => data::create_image_source(&args.data, |source| {
+            commands::image_values(args, path, source)
+        })
+        .unwrap(),
+        None => data::create_data_source(&args.data).unwrap(),
+    };
+    commands::build(args, ds.as_deref()).expect("build succeeds");
+    std::fs::read_to_string(&args.output.out).unwrap()
+}
+
+fn json_data() -> DataArgs {
+    DataArgs {
+        json: Some(DATA.to_string()),
+        version: Some("Default".to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn rebuild_from_image_reproduces_it() {
+    let original = build(&args("image_source_original", json_data()));
+
+    // Transformed fields are not read back, so they still need the data source.
+    let rebuilt = build(&args(
+        "image_source_rebuilt",
+        DataArgs {
+            image_source: Some("out/image_source_original.hex".into()),
+            ..json_data()
+        },
+    ));
+    assert_eq!(rebuilt, original);
+}
+
+#[test]
+fn image_values_decode_each_leaf_in_source_shape() {
+    build(&args("image_source_values", json_data()));
+    let args = args("image_source_values_read", json_data());
+    let base = data::create_base_source(&args.data).unwrap();
+    let values = commands::image_values(
+        &args,
+        Path::new("out/image_source_values.hex"),
+        base.as_deref(),
+    )
+    .unwrap();
+
+    assert_eq!(values["Id"], 513);
+    assert_eq!(values["Offset"], -3);
+    assert_eq!(values["Gain"], 1.5);
+    assert_eq!(values["Serial"], serde_json::json!([1, 2, 3, 4]));
+    assert_eq!(values["Table"], serde_json::json!([[1, 2, 3], [4, 5, 6]]));
+    assert!(!values.contains_key("Scaled"));
+}
+
+#[test]
+fn overrides_take_priority_over_image_values() {
+    build(&args("image_source_base", json_data()));
+    let hex = build(&args(
+        "image_source_override",
+        DataArgs {
+            image_source: Some("out/image_source_base.hex".into()),
+            overrides: Some(r#"{"Id": 4660, "Scaled": 1}"#.to_string()),
+            ..Default::default()
+        },
+    ));
+    // Id is overridden; Offset still comes from the image.
+    assert!(
+        hex.starts_with(":141000003412FDFF"),
+        "unexpected output: {hex}"
+    );
+}
+
+const LAYOUT_V1: &str = r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x10
+schema_version = 1
+
+[block.data]
+id = { name = "Id", type = "u16" }
+gain = { name = "Gain", type = "u16" }
+"#;
+
+const LAYOUT_V2: &str = r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x10
+schema_version = 2
+
+[block.data]
+id = { name = "Id", type = "u16" }
+trim = { name = "Trim", type = "u16" }
+gain_q8 = { name = "GainQ8", type = "u16" }
+
+[[block.migrations]]
+from = 1
+renamed = { gain_q8 = "gain" }
+offsets = { gain_q8 = 2 }
+absent = ["trim"]
+"#;
+
+#[test]
+fn migration_reads_older_images_into_the_new_layout() {
+    build(&layout_args(
+        "image_source_v1",
+        LAYOUT_V1,
+        DataArgs {
+            overrides: Some(r#"{"Id": 1, "Gain": 256}"#.to_string()),
+            ..Default::default()
+        },
+    ));
+    let data = DataArgs {
+        image_source: Some("out/image_source_v1.hex".into()),
+        image_schema: Some(1),
+        overrides: Some(r#"{"Trim": 2}"#.to_string()),
+        ..Default::default()
+    };
+    let args = layout_args("image_source_v2", LAYOUT_V2, data);
+    let values = commands::image_values(&args, Path::new("out/image_source_v1.hex"), None).unwrap();
+    assert_eq!(values["Id"], 1);
+    assert_eq!(values["GainQ8"], 256);
+    assert!(!values.contains_key("Trim"));
+
+    let hex = build(&args);
+    assert!(
+        hex.starts_with(":06100000010002000001E6"),
+        "unexpected output: {hex}"
+    );
+
+    let unknown = DataArgs {
+        image_schema: Some(0),
+        ..args.data.clone()
+    };
+    let args = layout_args("image_source_v0", LAYOUT_V2, unknown);
+    let error = commands::image_values(&args, Path::new("out/image_source_v1.hex"), None)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("no migration from 0"), "{error}");
+}
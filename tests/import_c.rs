@@ -0,0 +1,59 @@
+use mint_cli::commands;
+use mint_cli::data::create_data_source;
+use mint_cli::import;
+use mint_cli::layout::settings::Endianness;
+use mint_cli::output::image::Image;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const HEADER: &str = r#"#include <stdint.h>
+#define CHANNELS 2
+
+typedef struct {
+    int16_t gain;
+    uint8_t trim;
+} Channel;
+
+typedef struct {
+    uint8_t id;
+    uint32_t serial;
+    Channel channel[CHANNELS];
+    unsigned enable : 1;
+    unsigned mode : 3;
+    uint8_t tail;
+} CalBlock;
+"#;
+
+#[test]
+fn imported_skeleton_builds_with_c_struct_layout() {
+    std::fs::create_dir_all("out").unwrap();
+    std::fs::write("out/import_c.h", HEADER).unwrap();
+    let layout = import::c::import_header("out/import_c.h".as_ref(), "CalBlock").expect("import");
+    assert_eq!(layout.size, 24);
+    let text = import::render_layout(&layout, "import_c.h", 0, Endianness::Little).expect("render");
+    assert!(text.contains("[CalBlock.header]"));
+    assert!(text.contains("channel = { repeat = 2, gain = { name = \"channel[{i}].gain\""));
+
+    let mut args = common::layout_args("import_c", &text, "CalBlock");
+    args.data.xlsx = None;
+    args.data.json = Some(
+        r#"{"Default": {"id": 7, "serial": 287454020,
+            "channel[0].gain": -2, "channel[0].trim": 5,
+            "channel[1].gain": 3, "channel[1].trim": 6,
+            "enable": 1, "mode": 5, "tail": 9}}"#
+            .to_string(),
+    );
+    let ds = create_data_source(&args.data).unwrap();
+    commands::build(&args, ds.as_deref()).expect("build");
+
+    let image = Image::read("out/import_c.hex".as_ref()).unwrap();
+    let bytes: Vec<u8> = (0..24).map(|a| image.byte_at(a).unwrap()).collect();
+    assert_eq!(
+        bytes,
+        [
+            0x07, 0, 0, 0, 0x44, 0x33, 0x22, 0x11, 0xFE, 0xFF, 0x05, 0, 0x03, 0, 0x06, 0, 0x0B, 0,
+            0, 0, 0x09, 0, 0, 0,
+        ]
+    );
+}
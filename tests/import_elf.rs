@@ -0,0 +1,71 @@
+use mint_cli::commands;
+use mint_cli::data::create_data_source;
+use mint_cli::import;
+use mint_cli::import::elf::ElfTarget;
+use mint_cli::output::image::Image;
+use object::{Object, ObjectSection, ObjectSymbol};
+
+#[path = "common/mod.rs"]
+mod common;
+
+// Built from tests/data/cal_elf.c; see the build line there.
+const ELF: &str = "tests/data/cal.elf";
+
+#[test]
+fn imported_symbol_layout_matches_compiled_bytes() {
+    let imported = import::elf::import_elf(ELF.as_ref(), ElfTarget::Symbol("cal")).unwrap();
+    let offsets: Vec<(&str, usize)> = imported
+        .layout
+        .members
+        .iter()
+        .map(|m| (m.name.as_str(), m.offset))
+        .collect();
+    assert_eq!(
+        offsets,
+        [
+            ("id", 0),
+            ("serial", 4),
+            ("channel", 8),
+            ("enable", 16),
+            ("map", 20),
+            ("tail", 44)
+        ]
+    );
+    assert_eq!(imported.layout.size, 48);
+
+    let text = import::render_layout(&imported.layout, ELF, imported.address, imported.endianness)
+        .unwrap();
+    let mut args = common::layout_args("import_elf", &text, "cal");
+    args.data.xlsx = None;
+    args.data.json = Some(
+        r#"{"Default": {"id": 7, "serial": 0,
+            "channel[0].gain": 0, "channel[0].trim": 0,
+            "channel[1].gain": 0, "channel[1].trim": 0,
+            "enable": 0, "mode": 0,
+            "map": [[0, 0, 0], [0, 0, 0]], "tail": 0}}"#
+            .to_string(),
+    );
+    let ds = create_data_source(&args.data).unwrap();
+    commands::build(&args, ds.as_deref()).expect("build");
+
+    let data = std::fs::read(ELF).unwrap();
+    let file = object::File::parse(&*data).unwrap();
+    let symbol = file.symbol_by_name("cal").unwrap();
+    let section = file
+        .section_by_index(symbol.section_index().unwrap())
+        .unwrap();
+    let start = (symbol.address() - section.address()) as usize;
+    let compiled = &section.data().unwrap()[start..start + 48];
+
+    let image = Image::read("out/import_elf.hex".as_ref()).unwrap();
+    let built: Vec<u8> = (0..48)
+        .map(|i| image.byte_at(imported.address + i).unwrap())
+        .collect();
+    assert_eq!(built, compiled);
+}
+
+#[test]
+fn missing_struct_is_reported() {
+    let err = import::elf::import_elf(ELF.as_ref(), ElfTarget::Struct("Missing")).unwrap_err();
+    assert_eq!(err.to_string(), "Struct 'Missing' not found.");
+}
@@ -1,6 +1,6 @@
 //! Integration tests for JsonDataSource.
 
-use mint_cli::data::args::DataArgs;
+use mint_cli::data::args::{DataArgs, DuplicatePolicy};
 use mint_cli::data::create_data_source;
 use mint_cli::layout::value::{DataValue, ValueSource};
 
@@ -328,3 +328,162 @@ fn json_from_file() {
 
     fs::remove_file(test_file).ok();
 }
+
+#[test]
+fn json_parses_radix_and_suffix_literals() {
+    let json_data = r#"{
+        "Default": {
+            "hexList": "0x10 0x20 0b11",
+            "baud": "115.2k",
+            "mask": "0xFF00"
+        }
+    }"#;
+
+    let args = build_json_args("Default", json_data);
+    let ds = create_data_source(&args).unwrap().unwrap();
+
+    let ValueSource::Array(arr) = ds.retrieve_1d_array_or_string("hexList").unwrap() else {
+        panic!("expected array");
+    };
+    assert!(matches!(
+        arr.as_slice(),
+        [DataValue::U64(16), DataValue::U64(32), DataValue::U64(3)]
+    ));
+    assert!(matches!(
+        ds.retrieve_single_value("baud").unwrap(),
+        DataValue::U64(115_200)
+    ));
+    assert!(matches!(
+        ds.retrieve_single_value("mask").unwrap(),
+        DataValue::U64(0xFF00)
+    ));
+}
+
+#[test]
+fn json_parses_comma_decimal_strings() {
+    let json_data = r#"{
+        "$decimal_separator": "comma",
+        "Default": {
+            "gains": "0,5; 1.250,75 3",
+            "offset": "-12,5"
+        }
+    }"#;
+
+    let args = build_json_args("Default", json_data);
+    let ds = create_data_source(&args).unwrap().unwrap();
+
+    let ValueSource::Array(arr) = ds.retrieve_1d_array_or_string("gains").unwrap() else {
+        panic!("expected array");
+    };
+    assert!(matches!(
+        arr.as_slice(),
+        [DataValue::F64(a), DataValue::F64(b), DataValue::U64(3)] if *a == 0.5 && *b == 1250.75
+    ));
+    assert!(matches!(
+        ds.retrieve_single_value("offset").unwrap(),
+        DataValue::F64(v) if v == -12.5
+    ));
+}
+
+#[test]
+fn json_duplicate_name_policy() {
+    let json_data = r#"{
+        "Default": {
+            "Gain": 1,
+            "Gain": 2
+        }
+    }"#;
+
+    let mut args = build_json_args("Default", json_data);
+    let ds = create_data_source(&args).unwrap().unwrap();
+    assert!(matches!(
+        ds.retrieve_single_value("Gain").unwrap(),
+        DataValue::U64(2)
+    ));
+
+    args.on_duplicate_name = DuplicatePolicy::FirstWins;
+    let ds = create_data_source(&args).unwrap().unwrap();
+    assert!(matches!(
+        ds.retrieve_single_value("Gain").unwrap(),
+        DataValue::U64(1)
+    ));
+
+    args.on_duplicate_name = DuplicatePolicy::Error;
+    let err = create_data_source(&args)
+        .err()
+        .expect("duplicate error")
+        .to_string();
+    assert!(err.contains("Default.Gain"), "{err}");
+}
+
+#[test]
+fn json_normalized_name_lookup_is_opt_in() {
+    let json_data = r#"{
+        "Default": {
+            "Engine  GAIN ": 7
+        }
+    }"#;
+
+    let mut args = build_json_args("Default", json_data);
+    let ds = create_data_source(&args).unwrap().unwrap();
+    assert!(ds.retrieve_single_value("engine gain").is_err());
+
+    args.normalize_names = true;
+    let ds = create_data_source(&args).unwrap().unwrap();
+    assert!(matches!(
+        ds.retrieve_single_value("engine gain").unwrap(),
+        DataValue::U64(7)
+    ));
+}
+
+#[test]
+fn json_versions_merge_included_files() {
+    let dir = std::path::Path::new("out/json_include");
+    std::fs::create_dir_all(dir.join("shared")).unwrap();
+    std::fs::write(
+        dir.join("shared/base.json"),
+        r#"{"Gain": 1, "Offset": 2, "Curve": [1, 2, 3]}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("shared/fast.json"),
+        r#"{"$include": "base.json", "Gain": 5}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("data.json"),
+        r#"{
+            "Default": {"$include": "shared/base.json"},
+            "Fast": {"$include": ["shared/base.json", "shared/fast.json"], "Offset": 9}
+        }"#,
+    )
+    .unwrap();
+
+    let args = build_json_args("Fast", "out/json_include/data.json");
+    let ds = create_data_source(&args).unwrap().unwrap();
+    assert!(matches!(
+        ds.retrieve_single_value("Gain").unwrap(),
+        DataValue::U64(5)
+    ));
+    assert!(matches!(
+        ds.retrieve_single_value("Offset").unwrap(),
+        DataValue::U64(9)
+    ));
+    assert!(matches!(
+        ds.retrieve_1d_array_or_string("Curve").unwrap(),
+        ValueSource::Array(values) if values.len() == 3
+    ));
+    assert!(ds.retrieve_single_value("$include").is_err());
+
+    std::fs::write(dir.join("shared/loop.json"), r#"{"$include": "loop.json"}"#).unwrap();
+    std::fs::write(
+        dir.join("cycle.json"),
+        r#"{"Default": {"$include": ["shared/loop.json"]}}"#,
+    )
+    .unwrap();
+    let err = create_data_source(&build_json_args("Default", "out/json_include/cycle.json"))
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("$include cycle through"), "{err}");
+}
@@ -0,0 +1,178 @@
+use mint_cli::layout::error::LayoutError;
+
+#[path = "common/mod.rs"]
+mod common;
+
+fn write_out_file(name: &str, contents: &str) -> String {
+    common::ensure_out_dir();
+    let path = format!("out/{}", name);
+    std::fs::write(&path, contents).expect("write layout file");
+    path
+}
+
+#[test]
+fn toml_unknown_leaf_key_reports_location() {
+    let layout = r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x100
+
+[block.data]
+device.id = { value = 1, type = "u8" }
+device.name = { nmae = "DeviceName", type = "u8", size = 8 }
+"#;
+    let path = write_out_file("parse_error_unknown_key.toml", layout);
+
+    let err = mint_cli::layout::load_layout(&path).expect_err("layout should fail");
+    let LayoutError::Parse {
+        line,
+        column,
+        ref message,
+        ref snippet,
+        ..
+    } = err
+    else {
+        panic!("expected parse error, got {err:?}");
+    };
+    assert_eq!((line, column), (10, 15));
+    assert!(message.contains("device.name"), "message: {message}");
+    assert!(
+        message.contains("unknown field `nmae`"),
+        "message: {message}"
+    );
+    assert!(snippet.contains("10 | device.name"), "snippet: {snippet}");
+    assert!(err.to_string().contains(&path));
+}
+
+#[test]
+fn toml_header_type_error_points_at_value() {
+    let layout = r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = "0x1000 +"
+length = 0x100
+
+[block.data]
+id = { value = 1, type = "u8" }
+"#;
+    let path = write_out_file("parse_error_header.toml", layout);
+
+    let err = mint_cli::layout::load_layout(&path).expect_err("layout should fail");
+    let LayoutError::Parse {
+        line,
+        column,
+        ref message,
+        ..
+    } = err
+    else {
+        panic!("expected parse error, got {err:?}");
+    };
+    assert_eq!((line, column), (5, 17));
+    assert!(message.contains("unexpected end"), "message: {message}");
+}
+
+#[test]
+fn toml_leaf_value_error_points_at_attribute() {
+    let layout = r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x100
+
+[block.data]
+id = { value = 1, type = "u8" }
+table = { value = 1, type = "u8", size = "two" }
+"#;
+    let path = write_out_file("parse_error_leaf_value.toml", layout);
+
+    let err = mint_cli::layout::load_layout(&path).expect_err("layout should fail");
+    let LayoutError::Parse {
+        line,
+        column,
+        ref message,
+        ..
+    } = err
+    else {
+        panic!("expected parse error, got {err:?}");
+    };
+    assert_eq!((line, column), (10, 42));
+    assert!(message.contains("table.size"), "message: {message}");
+}
+
+#[test]
+fn yaml_missing_type_reports_location() {
+    let layout = r#"settings:
+  endianness: little
+block:
+  header:
+    start_address: 0x1000
+    length: 0x100
+  data:
+    id: { value: 1, type: u8 }
+    name: { name: DeviceName, size: 8 }
+"#;
+    let path = write_out_file("parse_error_missing_type.yaml", layout);
+
+    let err = mint_cli::layout::load_layout(&path).expect_err("layout should fail");
+    let LayoutError::Parse {
+        line, ref message, ..
+    } = err
+    else {
+        panic!("expected parse error, got {err:?}");
+    };
+    assert_eq!(line, 9);
+    assert!(
+        message.contains("missing field `type`"),
+        "message: {message}"
+    );
+}
+
+#[test]
+fn json_missing_source_reports_location() {
+    let layout = r#"{
+  "settings": { "endianness": "little" },
+  "block": {
+    "header": { "start_address": 4096, "length": 256 },
+    "data": {
+      "id": { "type": "u8" }
+    }
+  }
+}"#;
+    let path = write_out_file("parse_error_missing_source.json", layout);
+
+    let err = mint_cli::layout::load_layout(&path).expect_err("layout should fail");
+    let LayoutError::Parse {
+        line, ref message, ..
+    } = err
+    else {
+        panic!("expected parse error, got {err:?}");
+    };
+    assert_eq!(line, 6);
+    assert!(message.contains("missing source"), "message: {message}");
+}
+
+#[test]
+fn duplicate_leaf_paths_are_reported_together() {
+    let layout = r#"{
+  "settings": { "endianness": "little" },
+  "block": {
+    "header": { "start_address": 4096, "length": 256 },
+    "data": {
+      "device.id": { "value": 1, "type": "u8" },
+      "device": { "id": { "value": 2, "type": "u8" }, "rev": { "value": 3, "type": "u8" } },
+      "device.rev": { "value": 4, "type": "u8" }
+    }
+  }
+}"#;
+    let path = write_out_file("duplicate_paths.json", layout);
+
+    let err = mint_cli::layout::load_layout(&path).expect_err("layout should fail");
+    let LayoutError::DuplicatePaths { ref paths, .. } = err else {
+        panic!("expected duplicate paths error, got {err:?}");
+    };
+    assert_eq!(paths, &["block.device.id", "block.device.rev"]);
+}
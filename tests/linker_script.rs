@@ -0,0 +1,41 @@
+use mint_cli::commands;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+virtual_offset = 0x08000000
+
+[boot.header]
+start_address = 0x1000
+length = 0x100
+
+[boot.data]
+id = { value = 0x11, type = "u8" }
+
+[cal.header]
+start_address = 0x4000
+length = 0x800
+
+[cal.data]
+id = { value = 0x22, type = "u8" }
+"#;
+
+#[test]
+fn linker_script_declares_every_block_region() {
+    let mut args = common::layout_args("linker_script", LAYOUT, "");
+    args.output.linker_script = Some("out/linker_script.ld".into());
+    commands::build(&args, None).expect("build");
+
+    let script = std::fs::read_to_string("out/linker_script.ld").unwrap();
+    for expected in [
+        "__mint_boot_start = 0x8001000;",
+        "__mint_boot_end = 0x8001100;",
+        "__mint_cal_length = 0x800;",
+        ".mint_boot 0x8001000 (NOLOAD) :",
+        ".mint_cal 0x8004000 (NOLOAD) :",
+    ] {
+        assert!(script.contains(expected), "{expected}\n{script}");
+    }
+}
@@ -92,15 +92,14 @@ arr2.i16 = { value = [10, -20, 30, -40], type = "i16", size = 4 }
                 file: be_path.clone(),
             }],
             strict: false,
+            ..Default::default()
         },
         data: data_args.clone(),
         output: OutputArgs {
             out: PathBuf::from("out/mix_a.hex"),
             record_width: 64,
             format: OutputFormat::Hex,
-            export_json: None,
-            stats: false,
-            quiet: false,
+            ..Default::default()
         },
     };
     commands::build(&args_be_hex, ds.as_deref()).expect("be-hex");
@@ -114,15 +113,14 @@ arr2.i16 = { value = [10, -20, 30, -40], type = "i16", size = 4 }
                 file: be_path.clone(),
             }],
             strict: false,
+            ..Default::default()
         },
         data: data_args.clone(),
         output: OutputArgs {
             out: PathBuf::from("out/mix_b.mot"),
             record_width: 16,
             format: OutputFormat::Mot,
-            export_json: None,
-            stats: false,
-            quiet: false,
+            ..Default::default()
         },
     };
     commands::build(&args_be_mot, ds.as_deref()).expect("be-mot");
@@ -136,15 +134,14 @@ arr2.i16 = { value = [10, -20, 30, -40], type = "i16", size = 4 }
                 file: le_path.clone(),
             }],
             strict: true, // exercise strict path on numeric arrays
+            ..Default::default()
         },
         data: data_args.clone(),
         output: OutputArgs {
             out: PathBuf::from("out/mix_c.hex"),
             record_width: 16,
             format: OutputFormat::Hex,
-            export_json: None,
-            stats: false,
-            quiet: false,
+            ..Default::default()
         },
     };
     commands::build(&args_le_hex, ds.as_deref()).expect("le-hex");
@@ -158,15 +155,14 @@ arr2.i16 = { value = [10, -20, 30, -40], type = "i16", size = 4 }
                 file: le_path.clone(),
             }],
             strict: true,
+            ..Default::default()
         },
         data: data_args,
         output: OutputArgs {
             out: PathBuf::from("out/mix_d.mot"),
             record_width: 64,
             format: OutputFormat::Mot,
-            export_json: None,
-            stats: false,
-            quiet: false,
+            ..Default::default()
         },
     };
     commands::build(&args_le_mot, ds.as_deref()).expect("le-mot");
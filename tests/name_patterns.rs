@@ -0,0 +1,68 @@
+use mint_cli::data::args::DataArgs;
+use mint_cli::data::create_data_source;
+use mint_cli::layout::used_values::NoopValueSink;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const DATA: &str = r#"{
+    "Default": {
+        "Gain_10": 10,
+        "Gain_2": 2,
+        "Gain_1": 1,
+        "Offset_1": 99
+    }
+}"#;
+
+fn build(entry: &str) -> Result<Vec<u8>, String> {
+    let layout = format!(
+        r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x20
+padding = 0x00
+
+[block.data]
+gains = {entry}
+"#
+    );
+    let path = common::write_layout_file("name_patterns", &layout);
+    let cfg = mint_cli::layout::load_layout(&path).map_err(|e| e.to_string())?;
+    let args = DataArgs {
+        json: Some(DATA.to_string()),
+        version: Some("Default".to_string()),
+        ..Default::default()
+    };
+    let ds = create_data_source(&args).unwrap();
+    cfg.blocks["block"]
+        .build_bytestream(ds.as_deref(), &cfg.settings, false, &mut NoopValueSink)
+        .map(|(bytes, _)| bytes)
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn names_pattern_collects_matches_in_natural_order() {
+    let bytes = build(r#"{ names = "Gain_*", type = "u8" }"#).unwrap();
+    assert_eq!(bytes, [1, 2, 10]);
+
+    let bytes = build(r#"{ names = "Gain_*", type = "u16", size = 4 }"#).unwrap();
+    assert_eq!(bytes, [1, 0, 2, 0, 10, 0, 0, 0]);
+}
+
+#[test]
+fn names_pattern_without_matches_fails() {
+    let err = build(r#"{ names = "Trim_*", type = "u8" }"#).unwrap_err();
+    assert!(err.contains("No data source names match 'Trim_*'"), "{err}");
+}
+
+#[test]
+fn names_list_keeps_given_order() {
+    let bytes =
+        build(r#"{ names = ["Offset_1", "Gain_1", "Gain_10"], type = "u8", size = 4 }"#).unwrap();
+    assert_eq!(bytes, [99, 1, 10, 0]);
+
+    let err = build(r#"{ names = ["Gain_1", "Missing"], type = "u8" }"#).unwrap_err();
+    assert!(err.contains("Missing"), "{err}");
+}
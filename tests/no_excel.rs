@@ -20,15 +20,14 @@ fn test_build_without_excel() {
                 file: layout_path.to_string(),
             }],
             strict: false,
+            ..Default::default()
         },
         data: Default::default(),
         output: mint_cli::output::args::OutputArgs {
             out: PathBuf::from("out/simple_block.hex"),
-            record_width: 32,
             format: mint_cli::output::args::OutputFormat::Hex,
-            export_json: None,
-            stats: false,
             quiet: true,
+            ..Default::default()
         },
     };
 
@@ -59,15 +58,14 @@ fn test_error_when_name_without_excel() {
         layout: mint_cli::layout::args::LayoutArgs {
             blocks: vec![input.clone()],
             strict: false,
+            ..Default::default()
         },
         data: Default::default(),
         output: mint_cli::output::args::OutputArgs {
             out: PathBuf::from("out/error_test.hex"),
-            record_width: 32,
             format: mint_cli::output::args::OutputFormat::Hex,
-            export_json: None,
-            stats: false,
             quiet: true,
+            ..Default::default()
         },
     };
 
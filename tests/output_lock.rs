@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use mint_cli::commands;
+use mint_cli::output::args::LockMode;
+use mint_cli::output::lock;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"
+[settings]
+endianness = "little"
+
+[cal.header]
+start_address = 0x1000
+length = 0x10
+
+[cal.data]
+id = { value = 1, type = "u32" }
+"#;
+
+fn args(out: &str, mode: LockMode) -> mint_cli::args::Args {
+    let mut args = common::layout_args("output_lock", LAYOUT, "cal");
+    args.output.out = out.into();
+    args.output.lock = mode;
+    args
+}
+
+#[test]
+fn locked_output_directory_fails_fast_or_queues() {
+    let out = "out/output_lock/fw.hex";
+    let held = lock::acquire(vec!["out/output_lock".into()], LockMode::Fail).unwrap();
+
+    let err = commands::build(&args(out, LockMode::Fail), None).unwrap_err();
+    assert!(err.to_string().contains("another mint build"), "{err}");
+
+    let release = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(100));
+        drop(held);
+    });
+    commands::build(&args(out, LockMode::Wait), None).expect("build waits for the lock");
+    release.join().unwrap();
+
+    commands::build(&args(out, LockMode::Fail), None).expect("lock is free again");
+}
+
+#[test]
+fn every_output_directory_is_locked_when_asked() {
+    let out = "out/output_lock_all/fw.hex";
+    let _held = lock::acquire(vec!["out/output_lock_reports".into()], LockMode::Fail).unwrap();
+
+    let mut locked = args(out, LockMode::Fail);
+    locked.output.export_json = Some("out/output_lock_reports/values.json".into());
+    let err = commands::build(&locked, None).unwrap_err();
+    assert!(err.to_string().contains("output_lock_reports"), "{err}");
+
+    locked.output.lock = LockMode::default();
+    commands::build(&locked, None).expect("locking is off by default");
+}
+
+#[test]
+fn equivalent_directory_spellings_are_locked_once() {
+    let held = lock::acquire(
+        vec![
+            "out/output_lock_same".into(),
+            "./out/output_lock_same".into(),
+            "out/output_lock_same/../output_lock_same/.".into(),
+        ],
+        LockMode::Fail,
+    )
+    .expect("one directory is locked once");
+    assert_eq!(held.len(), 1);
+}
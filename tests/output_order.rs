@@ -0,0 +1,54 @@
+use mint_cli::layout::args::BlockNames;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[low.header]
+start_address = 0x1000
+length = 0x4
+
+[low.data]
+id = { value = 0x11, type = "u8" }
+
+[high.header]
+start_address = 0x2000
+length = 0x4
+
+[high.data]
+id = { value = 0x22, type = "u8" }
+"#;
+
+fn build(stem: &str, order: &[&str], preserve: bool) -> String {
+    common::build_layout_with(stem, LAYOUT, "", |args| {
+        let file = args.layout.blocks[0].file.clone();
+        args.layout.blocks = order
+            .iter()
+            .map(|name| BlockNames {
+                name: name.to_string(),
+                file: file.clone(),
+            })
+            .collect();
+        args.output.preserve_block_order = preserve;
+    })
+    .expect("build")
+}
+
+#[test]
+fn combined_output_is_sorted_by_address_regardless_of_block_order() {
+    let forward = build("output_order_forward", &["low", "high"], false);
+    let reverse = build("output_order_reverse", &["high", "low"], false);
+    assert_eq!(forward, reverse);
+    assert!(forward.find(":01100000").unwrap() < forward.find(":01200000").unwrap());
+}
+
+#[test]
+fn preserve_block_order_keeps_command_line_order() {
+    let hex = build("output_order_preserved", &["high", "low"], true);
+    assert!(
+        hex.find(":01200000").unwrap() < hex.find(":01100000").unwrap(),
+        "{hex}"
+    );
+}
@@ -0,0 +1,55 @@
+use mint_cli::data::args::DataArgs;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x10
+
+[block.data]
+a = { name = "A", type = "u8" }
+b = { name = "B", type = "u8" }
+"#;
+
+fn build_hex(file_stem: &str, data_args: &DataArgs) -> String {
+    common::build_layout_with(file_stem, LAYOUT, "block", |args| {
+        args.data = data_args.clone();
+    })
+    .expect("build succeeds")
+}
+
+#[test]
+fn override_layer_wins_over_data_source() {
+    let hex = build_hex(
+        "overrides_layered",
+        &DataArgs {
+            json: Some(r#"{"Default": {"A": 1, "B": 2}}"#.to_string()),
+            version: Some("Default".to_string()),
+            overrides: Some(r#"{"B": 34}"#.to_string()),
+            ..Default::default()
+        },
+    );
+    assert!(
+        hex.starts_with(":021000000122CB"),
+        "unexpected output: {hex}"
+    );
+}
+
+#[test]
+fn override_layer_works_without_data_source() {
+    let hex = build_hex(
+        "overrides_only",
+        &DataArgs {
+            overrides: Some(r#"{"A": 1, "B": 2}"#.to_string()),
+            ..Default::default()
+        },
+    );
+    assert!(
+        hex.starts_with(":021000000102EB"),
+        "unexpected output: {hex}"
+    );
+}
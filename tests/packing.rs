@@ -0,0 +1,43 @@
+use mint_cli::commands::{self, PackingSuggestion};
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[cal.header]
+start_address = 0x8000
+length = 0x40
+
+[cal.data]
+id = { value = 1, type = "u8" }
+serial = { value = 2, type = "u32" }
+limits.min = { value = 3, type = "u8" }
+limits.max = { value = 4, type = "u16" }
+gain = { value = 1.5, type = "f64" }
+"#;
+
+#[test]
+fn suggest_packing_reports_padding_and_a_better_order() {
+    let mut args = common::layout_args("packing", LAYOUT, "cal");
+    args.layout.suggest_packing = true;
+
+    let reports = commands::suggest_packing(&args, None).expect("packing");
+    assert_eq!(reports.len(), 1);
+    // id @0, serial @4, limits.min @8, limits.max @10, gain @16.
+    assert_eq!(reports[0].padding, 3 + 1 + 4);
+    assert_eq!(
+        reports[0].suggestion,
+        Some(PackingSuggestion {
+            order: vec![
+                "gain".to_string(),
+                "serial".to_string(),
+                "limits".to_string(),
+                "id".to_string()
+            ],
+            padding: 1,
+        })
+    );
+    assert!(!std::path::Path::new("out/packing.hex").exists());
+}
@@ -0,0 +1,50 @@
+use mint_cli::commands;
+use mint_cli::layout::error::LayoutError;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[profiles.slot_b.settings]
+endianness = "big"
+virtual_offset = 0x10000
+
+[block.header]
+start_address = 0x1000
+length = 0x10
+
+[block.data]
+id = { value = 0x1234, type = "u16" }
+"#;
+
+fn args_for(stem: &str, profile: Option<&str>) -> mint_cli::args::Args {
+    let mut args = common::layout_args(stem, LAYOUT, "block");
+    args.layout.profile = profile.map(str::to_string);
+    args
+}
+
+#[test]
+fn profile_overrides_global_settings() {
+    let stats = commands::build(&args_for("profile_default", None), None).expect("default build");
+    assert_eq!(stats.block_stats[0].start_address, 0x1000);
+
+    let stats =
+        commands::build(&args_for("profile_b", Some("slot_b")), None).expect("profile build");
+    assert_eq!(stats.block_stats[0].start_address, 0x11000);
+
+    let hex = std::fs::read_to_string("out/profile_b.hex").expect("read hex");
+    assert!(hex.contains("1234"), "expected big-endian bytes: {hex}");
+}
+
+#[test]
+fn unknown_profile_is_an_error() {
+    let err = commands::build(&args_for("profile_c", Some("slot_c")), None)
+        .expect_err("unknown profile should fail");
+    let mint_cli::error::MintError::Layout(LayoutError::ProfileNotFound { available, .. }) = err
+    else {
+        panic!("expected profile error, got {err:?}");
+    };
+    assert_eq!(available, "slot_b");
+}
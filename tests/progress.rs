@@ -0,0 +1,23 @@
+use mint_cli::commands;
+
+#[path = "common/mod.rs"]
+mod common;
+
+#[test]
+fn large_build_without_terminal_succeeds_with_progress_enabled() {
+    let count = 30;
+    let mut layout = String::from("[settings]\nendianness = \"little\"\n");
+    for i in 0..count {
+        layout.push_str(&format!(
+            "\n[b{i}.header]\nstart_address = 0x{:X}\nlength = 0x10\n\n[b{i}.data]\nid = {{ value = {i}, type = \"u32\" }}\n",
+            0x1000 + i * 0x10
+        ));
+    }
+    let mut args = common::layout_args("progress", &layout, "");
+    args.output.quiet = false;
+    args.output.block_out_dir = Some("out/progress_blocks".into());
+
+    let stats = commands::build(&args, None).expect("build should succeed");
+    assert_eq!(stats.blocks_processed, count);
+    assert!(std::path::Path::new("out/progress_blocks/b29.hex").exists());
+}
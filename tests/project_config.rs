@@ -0,0 +1,61 @@
+use mint_cli::args::Args;
+use mint_cli::commands;
+use mint_cli::data;
+use mint_cli::project::{PROJECT_FILE, ProjectConfig};
+
+#[path = "common/mod.rs"]
+mod common;
+
+#[test]
+fn project_file_supplies_build_defaults() {
+    common::ensure_out_dir();
+    let dir = std::path::Path::new("out/project_config");
+    std::fs::create_dir_all(dir).unwrap();
+    std::fs::write(
+        dir.join(PROJECT_FILE),
+        r#"json = '{"Default": {"Id": 7}}'
+version = "Default"
+out = "out/project_config/firmware.mot"
+format = "mot"
+record_width = 16
+"#,
+    )
+    .unwrap();
+
+    let layout = common::write_layout_file(
+        "project_config",
+        r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x20
+
+[block.data]
+id = { name = "Id", type = "u8" }
+text = { value = "project defaults", type = "u8", size = 16 }
+"#,
+    );
+
+    let project = ProjectConfig::discover(dir)
+        .expect("project file parses")
+        .expect("project file found");
+    let matches = Args::command_with_project(Some(&project))
+        .try_get_matches_from(["mint", layout.as_str()])
+        .expect("args parse");
+    let args = Args::from_project_matches(&matches).expect("args resolve");
+
+    let ds = data::create_data_source(&args.data)
+        .expect("data source")
+        .expect("data source configured");
+    commands::build(&args, Some(ds.as_ref())).expect("build succeeds");
+
+    let mot = std::fs::read_to_string("out/project_config/firmware.mot").expect("output written");
+    assert!(mot.starts_with("S113100007"), "unexpected output: {mot}");
+}
+
+#[test]
+fn missing_project_file_is_not_an_error() {
+    let found = ProjectConfig::discover(std::path::Path::new("tests/data")).expect("no error");
+    assert!(found.is_none());
+}
@@ -0,0 +1,77 @@
+use mint_cli::commands;
+use mint_cli::layout::load_layout;
+use mint_cli::layout::used_values::ValueCollector;
+use mint_cli::output::checksum::compute_checksum;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[settings.crc]
+polynomial = 0x04C11DB7
+start = 0xFFFFFFFF
+xor_out = 0xFFFFFFFF
+ref_in = true
+ref_out = true
+area = "data"
+
+[image.header]
+start_address = 0x1000
+length = 0x40
+
+[image.prologue]
+magic = { value = 0x4D494E54, type = "u32" }
+length = { auto = "data_length", type = "u16" }
+count = { auto = "field_count", type = "u16" }
+crc = { auto = "data_crc", type = "u32" }
+
+[image.data]
+gain = { value = 1.5, type = "f32" }
+table = { value = [1, 2, 3], type = "u8", size = 4 }
+"#;
+
+#[test]
+fn prologue_precedes_and_describes_the_data() {
+    let path = common::write_layout_file("prologue", LAYOUT);
+    let cfg = load_layout(&path).unwrap();
+    let block = &cfg.blocks["image"];
+    let mut values = ValueCollector::new();
+    let (bytes, _) = block
+        .build_bytestream(None, &cfg.settings, false, &mut values)
+        .unwrap();
+
+    assert_eq!(bytes.len(), 20);
+    assert_eq!(bytes[0..4], 0x4D494E54u32.to_le_bytes());
+    // Auto leaves in the prologue cover only the data section
+    assert_eq!(bytes[4..6], 8u16.to_le_bytes());
+    assert_eq!(bytes[6..8], 2u16.to_le_bytes());
+    let crc = compute_checksum(&bytes[12..], cfg.settings.crc.as_ref().unwrap()).unwrap();
+    assert_eq!(bytes[8..12], crc.to_le_bytes());
+    assert_eq!(bytes[12..16], 1.5f32.to_le_bytes());
+
+    let values = values.into_value();
+    assert_eq!(values["prologue"]["length"], 8);
+    assert_eq!(values["gain"], 1.5);
+    assert_eq!(
+        block.leaf_paths().unwrap(),
+        [
+            "prologue.magic",
+            "prologue.length",
+            "prologue.count",
+            "prologue.crc",
+            "gain",
+            "table"
+        ]
+    );
+}
+
+#[test]
+fn stats_report_the_prologue_separately() {
+    let args = common::layout_args("prologue_stats", LAYOUT, "image");
+    let stats = commands::build(&args, None).unwrap();
+    let stat = &stats.block_stats[0];
+    assert_eq!(stat.prologue_size, 12);
+    assert_eq!(stat.used_size, 20);
+}
@@ -0,0 +1,104 @@
+//! `--manifest` records a build that `--rebuild` replays and verifies.
+
+use mint_cli::args::Args;
+use mint_cli::commands;
+use mint_cli::error::MintError;
+use std::path::Path;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[cal.header]
+start_address = 0x1000
+length = 0x8
+
+[cal.data]
+gain = { name = "Gain", type = "u32" }
+"#;
+
+/// Builds with `argv` and writes the manifest at `manifest`.
+fn build_with_manifest(argv: &[String], manifest: &Path) {
+    let matches = Args::command_with_project(None)
+        .try_get_matches_from(std::iter::once("mint".to_string()).chain(argv.iter().cloned()))
+        .unwrap();
+    let mut args = Args::from_project_matches(&matches).unwrap();
+    args.output.manifest = Some(manifest.to_path_buf());
+    let ds = commands::open_data_source(&args).unwrap();
+    let invocation = commands::Invocation {
+        argv: argv.to_vec(),
+        project: None,
+    };
+    commands::build_recorded(&args, ds.as_deref(), &invocation).unwrap();
+}
+
+fn argv(text: &str) -> Vec<String> {
+    text.split_whitespace().map(str::to_string).collect()
+}
+
+#[test]
+fn rebuild_verifies_inputs_and_outputs() {
+    let layout = common::write_layout_file("rebuild_file", LAYOUT);
+    std::fs::write("out/rebuild_data.json", r#"{"Default": {"Gain": 7}}"#).unwrap();
+    let manifest = Path::new("out/rebuild_file.manifest.json");
+    build_with_manifest(
+        &argv(&format!(
+            "{} --json out/rebuild_data.json -v Default -o out/rebuild_file.hex --checksums out/rebuild_file.sha256",
+            layout
+        )),
+        manifest,
+    );
+
+    let text = std::fs::read_to_string(manifest).unwrap();
+    let recorded: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(recorded["outputs"].as_object().unwrap().len(), 2);
+    assert!(recorded["inputs"]["out/rebuild_data.json"].is_string());
+    assert!(recorded["data"].is_null());
+
+    assert_eq!(commands::rebuild(manifest).unwrap(), 2);
+
+    let tampered = text.replacen(
+        "\"outputs\": {\n    \"out/rebuild_file.hex\": \"",
+        "\"outputs\": {\n    \"out/rebuild_file.hex\": \"0",
+        1,
+    );
+    assert_ne!(tampered, text);
+    std::fs::write("out/rebuild_tampered.manifest.json", tampered).unwrap();
+    let err = commands::rebuild(Path::new("out/rebuild_tampered.manifest.json")).unwrap_err();
+    assert!(
+        matches!(&err, MintError::RebuildMismatch(files) if files == &["out/rebuild_file.hex"]),
+        "{err}"
+    );
+
+    std::fs::write("out/rebuild_data.json", r#"{"Default": {"Gain": 8}}"#).unwrap();
+    let err = commands::rebuild(manifest).unwrap_err().to_string();
+    assert!(
+        err.contains("inputs changed since the manifest was written: out/rebuild_data.json"),
+        "{err}"
+    );
+}
+
+#[test]
+fn rebuild_replays_fetched_values() {
+    let layout = common::write_layout_file("rebuild_http", LAYOUT);
+    let (address, _) = common::serve_once(r#"{"Gain": 9}"#);
+    let manifest = Path::new("out/rebuild_http.manifest.json");
+    let mut args = argv(&format!(
+        "{} -v Default -o out/rebuild_http.hex --http",
+        layout
+    ));
+    args.push(format!(r#"{{"url": "http://{}/values"}}"#, address));
+    build_with_manifest(&args, manifest);
+
+    let recorded: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(manifest).unwrap()).unwrap();
+    assert_eq!(
+        recorded["data"],
+        serde_json::json!({"Default": {"Gain": 9}})
+    );
+
+    // The server has gone; the rebuild reads the recorded values instead.
+    assert_eq!(commands::rebuild(manifest).unwrap(), 1);
+}
@@ -0,0 +1,73 @@
+use mint_cli::commands;
+use mint_cli::output::args::{HexCase, LineEnding, OutputFormat};
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x10
+
+[block.data]
+a = { value = 0xABCDEF01, type = "u32" }
+b = { value = 0x2345, type = "u16" }
+"#;
+
+fn args(stem: &str, format: OutputFormat) -> mint_cli::args::Args {
+    let mut args = common::layout_args(stem, LAYOUT, "block");
+    args.output.out = format!("out/{}.out", stem).into();
+    args.output.format = format;
+    args
+}
+
+#[test]
+fn per_format_width_case_and_line_ending() {
+    let mut args = args("record_style", OutputFormat::Mot);
+    args.output.record_width = 2;
+    args.output.hex_record_width = Some(1);
+    args.output.mot_record_width = Some(4);
+    args.output.hex_case = HexCase::Lower;
+    args.output.line_ending = LineEnding::Crlf;
+    commands::build(&args, None).expect("build");
+
+    let text = std::fs::read_to_string(&args.output.out).unwrap();
+    let lines: Vec<&str> = text.split("\r\n").collect();
+    assert_eq!(
+        lines,
+        ["S107100001efcdab80", "S105100445237e", "S5030002fa"]
+    );
+}
+
+#[test]
+fn srec_width_above_the_record_limit_is_rejected() {
+    let mut args = args("record_style_wide", OutputFormat::Mot);
+    args.output.record_width = 253;
+    let err = commands::build(&args, None).unwrap_err().to_string();
+    assert!(
+        err.contains("exceeds the S1 record maximum of 252 bytes"),
+        "{err}"
+    );
+
+    args.output.format = OutputFormat::Hex;
+    commands::build(&args, None).expect("Intel HEX allows 253-byte records");
+}
+
+#[test]
+fn srec_entry_address_and_offset() {
+    let mut args = args("record_entry", OutputFormat::Mot);
+    args.output.record_width = 8;
+    args.output.mot_entry_address = Some(0x2_0000);
+    args.output.mot_address_offset = -0x800;
+    commands::build(&args, None).expect("build");
+
+    let text = std::fs::read_to_string(&args.output.out).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    // The entry address needs 3 address bytes, so data uses S2 and ends with S8.
+    assert_eq!(
+        lines,
+        ["S20A00080001EFCDAB45231D", "S5030001FB", "S804020000F9"]
+    );
+}
@@ -0,0 +1,58 @@
+use mint_cli::commands;
+use mint_cli::output::image::Image;
+use mint_cli::output::regions::parse_region;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[flash.header]
+start_address = 0x8000
+length = 0x10
+
+[flash.data]
+a = { value = 0x11, type = "u16" }
+
+[eeprom.header]
+start_address = 0x100
+length = 0x10
+
+[eeprom.data]
+b = { value = 0x22, type = "u16" }
+"#;
+
+fn args(stem: &str) -> mint_cli::args::Args {
+    let mut args = common::layout_args(stem, LAYOUT, "");
+    args.output.out = format!("out/{}/combined.hex", stem).into();
+    args
+}
+
+#[test]
+fn regions_write_one_file_per_address_range() {
+    let mut args = args("regions");
+    args.output.regions = vec![
+        parse_region("flash_a=0x8000..0x10000").unwrap(),
+        parse_region("eeprom=0..0x1000").unwrap(),
+    ];
+    commands::build(&args, None).expect("build");
+
+    let flash = Image::read("out/regions/flash_a.hex".as_ref()).unwrap();
+    assert_eq!(flash.byte_at(0x8000), Some(0x11));
+    assert_eq!(flash.byte_at(0x100), None);
+    let eeprom = Image::read("out/regions/eeprom.hex".as_ref()).unwrap();
+    assert_eq!(eeprom.byte_at(0x100), Some(0x22));
+    assert!(!std::path::Path::new("out/regions/combined.hex").exists());
+}
+
+#[test]
+fn block_outside_every_region_is_an_error() {
+    let mut args = args("regions_missing");
+    args.output.regions = vec![parse_region("flash_a=0x8000..0x10000").unwrap()];
+    let err = commands::build(&args, None).unwrap_err().to_string();
+    assert!(
+        err.contains("0x100..0x102 is not inside any --region"),
+        "{err}"
+    );
+}
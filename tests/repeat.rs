@@ -0,0 +1,62 @@
+use mint_cli::data::args::DataArgs;
+use mint_cli::data::create_data_source;
+use mint_cli::layout::used_values::NoopValueSink;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const DATA: &str = r#"{
+    "Default": {
+        "Chan0_Offset": 10,
+        "Chan1_Offset": 11,
+        "Chan2_Offset": 12,
+        "Chan0_Enable": 1,
+        "Chan1_Enable": 0,
+        "Chan2_Enable": 1
+    }
+}"#;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x20
+padding = 0x00
+
+[block.data.channel]
+repeat = 3
+offset = { name = "Chan{i}_Offset", type = "u16" }
+flags = { type = "u8", bitmap = [
+    { bits = 1, name = "Chan{i}_Enable" },
+    { bits = 7, value = 0 },
+] }
+"#;
+
+#[test]
+fn repeat_emits_branch_once_per_index() {
+    let path = common::write_layout_file("repeat_channels", LAYOUT);
+    let cfg = mint_cli::layout::load_layout(&path).expect("parse");
+    let args = DataArgs {
+        json: Some(DATA.to_string()),
+        version: Some("Default".to_string()),
+        ..Default::default()
+    };
+    let ds = create_data_source(&args).unwrap();
+    let (bytes, _) = cfg.blocks["block"]
+        .build_bytestream(ds.as_deref(), &cfg.settings, false, &mut NoopValueSink)
+        .expect("build");
+    assert_eq!(bytes, [10, 0, 1, 0, 11, 0, 0, 0, 12, 0, 1]);
+}
+
+#[test]
+fn repeat_requires_positive_integer_count() {
+    for (name, count) in [("repeat_invalid", "\"three\""), ("repeat_zero", "0")] {
+        let layout = LAYOUT.replace("repeat = 3", &format!("repeat = {count}"));
+        let path = common::write_layout_file(name, &layout);
+        let err = mint_cli::layout::load_layout(&path)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("`repeat` must be a positive integer"), "{err}");
+    }
+}
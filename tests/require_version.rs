@@ -0,0 +1,63 @@
+use mint_cli::data::args::DataArgs;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x20
+
+[block.data]
+trim = { name = "Trim", type = "u16", require_version = "VarA" }
+gains = { names = "Gain_*", type = "u8", require_version = "VarA" }
+shared = { name = "Shared", type = "u16" }
+"#;
+
+const DATA: &str = r#"{
+    "VarA": {"Trim": 7, "Gain_1": 1, "Gain_2": 2},
+    "Default": {"Trim": 5, "Gain_1": 0, "Gain_2": 0, "Shared": 9}
+}"#;
+
+fn build(file_stem: &str, data: DataArgs) -> Result<(), String> {
+    common::build_layout_with(file_stem, LAYOUT, "block", |args| args.data = data)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+fn json(version: &str) -> DataArgs {
+    DataArgs {
+        json: Some(DATA.to_string()),
+        version: Some(version.to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn values_from_the_required_version_build() {
+    build("require_version", json("VarA/Default")).expect("values come from VarA");
+}
+
+#[test]
+fn fallback_to_another_version_fails_the_build() {
+    let err = build("require_version_fallback", json("Default")).unwrap_err();
+    assert!(
+        err.contains("'Trim' comes from 'Default', not the required version 'VarA'"),
+        "{err}"
+    );
+}
+
+#[test]
+fn every_name_of_a_names_entry_is_checked() {
+    let data = DataArgs {
+        overrides: Some(r#"{"Gain_2": 3}"#.to_string()),
+        ..json("VarA/Default")
+    };
+    let err = build("require_version_override", data).unwrap_err();
+    assert!(
+        err.contains("'Gain_2' comes from 'overrides', not the required version 'VarA'"),
+        "{err}"
+    );
+}
@@ -0,0 +1,73 @@
+//! `${secret:NAME}` references in source configs are read from the `--secrets` file.
+
+use mint_cli::data::args::DataArgs;
+use mint_cli::data::create_data_source;
+use mint_cli::layout::value::DataValue;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+
+/// Answers one request with `{"Gain": 1}`, returning the request headers.
+fn start_server() -> (String, std::thread::JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let handle = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let headers: Vec<String> = BufReader::new(&stream)
+            .lines()
+            .map(|line| line.unwrap())
+            .take_while(|line| !line.is_empty())
+            .collect();
+        let body = r#"{"Gain": 1}"#;
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+        headers
+    });
+    (address, handle)
+}
+
+#[test]
+fn http_header_reads_secret_from_file() {
+    std::fs::create_dir_all("out").unwrap();
+    let secrets = "out/secrets_http.json";
+    std::fs::write(secrets, r#"{"api_token": "s3cr3t"}"#).unwrap();
+
+    let (address, server) = start_server();
+    let args = DataArgs {
+        http: Some(format!(
+            r#"{{"url": "http://{}/cal?v=$VERSION", "headers": {{"Authorization": "Bearer ${{secret:api_token}}"}}}}"#,
+            address
+        )),
+        version: Some("Default".to_string()),
+        secrets: Some(secrets.to_string()),
+        ..Default::default()
+    };
+    let ds = create_data_source(&args).unwrap().unwrap();
+
+    let headers = server.join().unwrap();
+    assert!(
+        headers
+            .iter()
+            .any(|h| h.eq_ignore_ascii_case("authorization: Bearer s3cr3t")),
+        "{headers:?}"
+    );
+    assert!(matches!(
+        ds.retrieve_single_value("Gain").unwrap(),
+        DataValue::U64(1)
+    ));
+}
+
+#[test]
+fn missing_secret_is_an_error() {
+    let args = DataArgs {
+        http: Some(r#"{"url": "http://127.0.0.1:9/${secret:missing}"}"#.to_string()),
+        version: Some("Default".to_string()),
+        ..Default::default()
+    };
+    let err = create_data_source(&args).err().unwrap().to_string();
+    assert!(err.contains("no --secrets file"), "{err}");
+}
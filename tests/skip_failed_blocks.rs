@@ -0,0 +1,50 @@
+use mint_cli::commands;
+use mint_cli::output::image::Image;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"
+[settings]
+endianness = "little"
+
+[cal.header]
+start_address = 0x1000
+length = 0x4
+
+[cal.data]
+gain = { name = "Gain", type = "u32" }
+
+[info.header]
+start_address = 0x2000
+length = 0x4
+
+[info.data]
+id = { value = 7, type = "u32" }
+
+[copy.header]
+start_address = 0x3000
+length = 0x4
+
+[copy.data]
+crc = { auto = { crc_of = "cal" }, type = "u32" }
+"#;
+
+#[test]
+fn skip_failed_blocks_writes_the_rest() {
+    let mut args = common::layout_args("skip_failed", LAYOUT, "");
+    args.layout.skip_failed_blocks = true;
+    let stats = commands::build(&args, None).expect("failed blocks are skipped");
+
+    let skipped: Vec<&str> = stats.skipped.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(skipped, ["cal", "copy"]);
+    assert!(
+        stats.skipped[0].error.contains("data source"),
+        "{}",
+        stats.skipped[0].error
+    );
+    assert_eq!(stats.blocks_processed, 1);
+    let image = Image::read("out/skip_failed.hex".as_ref()).unwrap();
+    assert_eq!(image.byte_at(0x2000), Some(7));
+    assert_eq!(image.byte_at(0x1000), None);
+}
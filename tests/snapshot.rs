@@ -0,0 +1,75 @@
+use mint_cli::data::args::DataArgs;
+use mint_cli::data::{create_data_source, write_snapshot};
+use mint_cli::layout::value::DataValue;
+use std::path::Path;
+
+#[path = "common/mod.rs"]
+mod common;
+
+fn json_args(json: &str, version: &str) -> DataArgs {
+    DataArgs {
+        json: Some(json.to_string()),
+        version: Some(version.to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn snapshot_round_trips_through_json() {
+    common::ensure_out_dir();
+    std::fs::write("out/snapshot_base.json", r#"{"Gain": 1, "Curve": [1, 2]}"#).unwrap();
+    std::fs::write(
+        "out/snapshot_source.json",
+        r#"{
+            "Debug": {"Gain": 7},
+            "Default": {"$include": "snapshot_base.json", "Offset": 3},
+            "Unused": {"Gain": 9}
+        }"#,
+    )
+    .unwrap();
+
+    let path = Path::new("out/snapshot_values.json");
+    let count = write_snapshot(
+        &json_args("out/snapshot_source.json", "Debug/Default"),
+        path,
+    )
+    .unwrap();
+    assert_eq!(count, 2);
+
+    let text = std::fs::read_to_string(path).unwrap();
+    let snapshot: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(
+        snapshot,
+        serde_json::json!({
+            "Debug": {"Gain": 7},
+            "Default": {"Curve": [1, 2], "Gain": 1, "Offset": 3}
+        })
+    );
+    assert!(text.find("\"Curve\"").unwrap() < text.find("\"Offset\"").unwrap());
+
+    let ds = create_data_source(&json_args("out/snapshot_values.json", "Debug/Default"))
+        .unwrap()
+        .unwrap();
+    assert!(matches!(
+        ds.retrieve_single_value("Gain").unwrap(),
+        DataValue::U64(7)
+    ));
+    assert!(matches!(
+        ds.retrieve_single_value("Offset").unwrap(),
+        DataValue::U64(3)
+    ));
+}
+
+#[test]
+fn snapshot_needs_a_json_shaped_default_source() {
+    let args = DataArgs {
+        xlsx: Some("tests/data/data.xlsx".to_string()),
+        version: Some("Default".to_string()),
+        ..Default::default()
+    };
+    let err = write_snapshot(&args, Path::new("out/snapshot_xlsx.json")).unwrap_err();
+    assert!(
+        err.to_string().contains("--snapshot needs a --json"),
+        "{err}"
+    );
+}
@@ -0,0 +1,32 @@
+use mint_cli::commands;
+use mint_cli::output::image::Image;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[cal.header]
+start_address = 0x8000
+length = 0x10
+
+[cal.data]
+a = { value = 0x33221100, type = "u32" }
+b = { value = 0x77665544, type = "u32" }
+"#;
+
+#[test]
+fn split_writes_even_and_odd_lanes() {
+    let mut args = common::layout_args("split", LAYOUT, "cal");
+    args.output.split = Some(1);
+    args.output.split_odd_base = 0x4000;
+    commands::build(&args, None).expect("build");
+
+    let lane = |path: &str, base: u64| -> Vec<u8> {
+        let image = Image::read(path.as_ref()).unwrap();
+        (0..4).map(|i| image.byte_at(base + i).unwrap()).collect()
+    };
+    assert_eq!(lane("out/split.even.hex", 0), [0x00, 0x22, 0x44, 0x66]);
+    assert_eq!(lane("out/split.odd.hex", 0x4000), [0x11, 0x33, 0x55, 0x77]);
+}
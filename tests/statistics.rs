@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use mint_cli::commands::{
     self,
     stats::{BlockStat, BuildStats},
@@ -89,17 +91,27 @@ fn test_space_efficiency_calculation() {
     stats.add_block(BlockStat {
         name: "test1".to_string(),
         start_address: 0x1000,
+        auto_placed: false,
         allocated_size: 100,
         used_size: 80,
+        prologue_size: 0,
+        base_fallbacks: Vec::new(),
         crc_value: Some(0x12345678),
+        fetch_duration: Duration::ZERO,
+        emit_duration: Duration::ZERO,
     });
 
     stats.add_block(BlockStat {
         name: "test2".to_string(),
         start_address: 0x2000,
+        auto_placed: false,
         allocated_size: 200,
         used_size: 120,
+        prologue_size: 0,
+        base_fallbacks: Vec::new(),
         crc_value: Some(0x9ABCDEF0),
+        fetch_duration: Duration::ZERO,
+        emit_duration: Duration::ZERO,
     });
 
     assert_eq!(stats.blocks_processed, 2);
@@ -160,9 +172,14 @@ fn test_space_efficiency_edge_cases() {
     stats.add_block(BlockStat {
         name: "full".to_string(),
         start_address: 0x1000,
+        auto_placed: false,
         allocated_size: 100,
         used_size: 100,
+        prologue_size: 0,
+        base_fallbacks: Vec::new(),
         crc_value: Some(0x12345678),
+        fetch_duration: Duration::ZERO,
+        emit_duration: Duration::ZERO,
     });
 
     let efficiency = stats.space_efficiency();
@@ -206,3 +223,229 @@ device.name = { value = "TestDevice", type = "u8", size = 16 }
         "CRC value should be None when no crc section is present"
     );
 }
+
+#[test]
+fn test_block_name_collision_across_files_is_qualified() {
+    let layout_a = common::write_layout_file(
+        "collision_a",
+        r#"[settings]
+endianness = "little"
+
+[config.header]
+start_address = 0x1000
+length = 0x10
+
+[config.data]
+id = { value = 1, type = "u8" }
+"#,
+    );
+    let layout_b = common::write_layout_file(
+        "collision_b",
+        r#"[settings]
+endianness = "little"
+
+[config.header]
+start_address = 0x2000
+length = 0x10
+
+[config.data]
+id = { value = 2, type = "u8" }
+"#,
+    );
+
+    let blocks = [&layout_a, &layout_b]
+        .iter()
+        .map(|file| mint_cli::layout::args::BlockNames {
+            name: "config".to_string(),
+            file: file.to_string(),
+        })
+        .collect();
+    let args = common::build_args_for_layouts(
+        blocks,
+        mint_cli::output::args::OutputFormat::Hex,
+        "out/collision.hex",
+    );
+
+    let stats = commands::build(&args, None).expect("build should succeed");
+    let names: Vec<&str> = stats.block_stats.iter().map(|b| b.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            format!("{}:config", layout_a),
+            format!("{}:config", layout_b)
+        ]
+    );
+}
+
+#[test]
+fn stats_fields_lists_largest_leaf_entries() {
+    let layout = r#"
+[settings]
+endianness = "little"
+
+[cal.header]
+start_address = 0x1000
+length = 0x40
+
+[cal.data]
+id = { value = 1, type = "u32" }
+map = { value = [1, 2, 3, 4, 5, 6], type = "u16", size = 6 }
+flag = { value = 1, type = "u8" }
+"#;
+    let path = common::write_layout_file("stats_fields", layout);
+    let args = common::build_args_for_layouts(
+        vec![mint_cli::layout::args::BlockNames {
+            name: "cal".to_string(),
+            file: path,
+        }],
+        mint_cli::output::args::OutputFormat::Hex,
+        "out/stats_fields.hex",
+    );
+    let stats = commands::build(&args, None).expect("build should succeed");
+
+    let largest: Vec<(&str, &str, usize)> = stats
+        .largest_fields("cal", 2)
+        .iter()
+        .map(|f| (f.path.as_str(), f.type_name.as_str(), f.size))
+        .collect();
+    assert_eq!(largest, [("map", "u16[6]", 12), ("id", "u32", 4)]);
+}
+
+#[test]
+fn padding_gaps_report_alignment_between_entries() {
+    let layout = r#"
+[settings]
+endianness = "little"
+
+[cal.header]
+start_address = 0x1000
+length = 0x40
+
+[cal.data]
+flag = { value = 1, type = "u8" }
+id = { value = 1, type = "u32" }
+mode = { value = 2, type = "u8" }
+gain = { value = 3, type = "u16" }
+"#;
+    let path = common::write_layout_file("stats_padding", layout);
+    let args = common::build_args_for_layouts(
+        vec![mint_cli::layout::args::BlockNames {
+            name: "cal".to_string(),
+            file: path,
+        }],
+        mint_cli::output::args::OutputFormat::Hex,
+        "out/stats_padding.hex",
+    );
+    let stats = commands::build(&args, None).expect("build should succeed");
+
+    let gaps: Vec<(u32, usize, Option<&str>, &str)> = stats
+        .gaps
+        .iter()
+        .map(|g| (g.address, g.size, g.after.as_deref(), g.before.as_str()))
+        .collect();
+    assert_eq!(
+        gaps,
+        [
+            (0x1001, 3, Some("flag"), "id"),
+            (0x1009, 1, Some("mode"), "gain")
+        ]
+    );
+    assert_eq!(stats.padding_of("cal"), 4);
+}
+
+#[test]
+fn block_stats_split_fetch_and_emit_time() {
+    let layout = r#"
+[settings]
+endianness = "little"
+
+[fetched.header]
+start_address = 0x1000
+length = 0x10
+
+[fetched.data]
+gain = { name = "Gain", type = "u32" }
+
+[literal.header]
+start_address = 0x2000
+length = 0x10
+
+[literal.data]
+id = { value = 1, type = "u32" }
+"#;
+    let path = common::write_layout_file("stats_timing", layout);
+    let ds_args = mint_cli::data::args::DataArgs {
+        json: Some(r#"{"Default":{"Gain":3}}"#.to_string()),
+        version: Some("Default".to_string()),
+        ..Default::default()
+    };
+    let ds = mint_cli::data::create_data_source(&ds_args)
+        .unwrap()
+        .unwrap();
+    let blocks = ["fetched", "literal"]
+        .map(|name| mint_cli::layout::args::BlockNames {
+            name: name.to_string(),
+            file: path.clone(),
+        })
+        .to_vec();
+    let args = common::build_args_for_layouts(
+        blocks,
+        mint_cli::output::args::OutputFormat::Hex,
+        "out/stats_timing.hex",
+    );
+    let stats = commands::build(&args, Some(ds.as_ref())).expect("build should succeed");
+
+    assert!(stats.block_stats[0].fetch_duration > Duration::ZERO);
+    assert_eq!(stats.block_stats[1].fetch_duration, Duration::ZERO);
+    assert!(stats.block_stats[1].emit_duration > Duration::ZERO);
+    assert_eq!(stats.slowest_blocks(5).len(), 2);
+}
+
+#[test]
+fn base_fallbacks_list_values_only_the_base_version_provides() {
+    let layout = r#"
+[settings]
+endianness = "little"
+
+[tuned.header]
+start_address = 0x1000
+length = 0x10
+
+[tuned.data]
+gain = { name = "Gain", type = "u32" }
+trim = { name = "Trim", type = "u32" }
+
+[stock.header]
+start_address = 0x2000
+length = 0x10
+
+[stock.data]
+id = { name = "Id", type = "u32" }
+"#;
+    let path = common::write_layout_file("stats_fallbacks", layout);
+    let ds_args = mint_cli::data::args::DataArgs {
+        json: Some(r#"{"VarA":{"Gain":3},"Default":{"Gain":1,"Trim":2,"Id":4}}"#.to_string()),
+        version: Some("VarA/Default".to_string()),
+        ..Default::default()
+    };
+    let ds = mint_cli::data::create_data_source(&ds_args)
+        .unwrap()
+        .unwrap();
+    let blocks = ["tuned", "stock"]
+        .map(|name| mint_cli::layout::args::BlockNames {
+            name: name.to_string(),
+            file: path.clone(),
+        })
+        .to_vec();
+    let args = common::build_args_for_layouts(
+        blocks,
+        mint_cli::output::args::OutputFormat::Hex,
+        "out/stats_fallbacks.hex",
+    );
+    let stats = commands::build(&args, Some(ds.as_ref())).expect("build should succeed");
+
+    assert_eq!(stats.base_version.as_deref(), Some("Default"));
+    assert_eq!(stats.block_stats[0].base_fallbacks, ["Trim"]);
+    assert_eq!(stats.block_stats[1].base_fallbacks, ["Id"]);
+    assert_eq!(stats.base_fallback_count(), 2);
+}
@@ -0,0 +1,60 @@
+use mint_cli::commands;
+use mint_cli::layout::args::BlockNames;
+use mint_cli::layout::template::parse_define;
+use mint_cli::output::args::OutputFormat;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const TEMPLATE: &str = r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x10
+padding = 0x00
+
+[block.data]
+{% for i in range(CHANNELS) %}
+chan{{ i }} = { value = {{ i + 1 }}, type = "u8" }
+{% endfor %}
+{% if FAMILY == "pro" %}
+extra = { value = 0xAA, type = "u8" }
+{% endif %}
+"#;
+
+fn build(file_stem: &str, defines: &[&str]) -> Result<String, String> {
+    common::ensure_out_dir();
+    let path = format!("out/{}.toml.j2", file_stem);
+    std::fs::write(&path, TEMPLATE).unwrap();
+    let out = format!("out/{}.hex", file_stem);
+    let mut args = common::build_args_for_layouts(
+        vec![BlockNames {
+            name: "block".to_string(),
+            file: path,
+        }],
+        OutputFormat::Hex,
+        &out,
+    );
+    args.layout.define = defines
+        .iter()
+        .map(|define| parse_define(define).unwrap())
+        .collect();
+    commands::build(&args, None).map_err(|e| e.to_string())?;
+    Ok(std::fs::read_to_string(out).unwrap())
+}
+
+#[test]
+fn j2_layout_is_rendered_with_defines() {
+    let hex = build("template_pro", &["CHANNELS:int=3", "FAMILY=pro"]).unwrap();
+    assert!(hex.contains(":04100000010203AA"), "{hex}");
+
+    let hex = build("template_lite", &["CHANNELS:int=2", "FAMILY=lite"]).unwrap();
+    assert!(hex.contains(":021000000102"), "{hex}");
+}
+
+#[test]
+fn j2_layout_with_undefined_variable_fails() {
+    let err = build("template_undefined", &["CHANNELS:int=1"]).unwrap_err();
+    assert!(err.contains("template error"), "{err}");
+}
@@ -0,0 +1,93 @@
+use mint_cli::data::args::DataArgs;
+use mint_cli::data::create_data_source;
+use mint_cli::layout::used_values::NoopValueSink;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const DATA: &str = r#"{
+    "Default": {
+        "Percent": 123.4,
+        "Temps": [21.6, -3.2, 250.0],
+        "Label": "abc"
+    }
+}"#;
+
+fn build(stem: &str, entry: &str) -> Result<Vec<u8>, String> {
+    let layout = format!(
+        r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x20
+
+[block.data]
+field = {entry}
+"#
+    );
+    let path = common::write_layout_file(stem, &layout);
+    let cfg = mint_cli::layout::load_layout(&path).map_err(|e| e.to_string())?;
+    let args = DataArgs {
+        json: Some(DATA.to_string()),
+        version: Some("Default".to_string()),
+        ..Default::default()
+    };
+    let ds = create_data_source(&args).unwrap();
+    cfg.blocks["block"]
+        .build_bytestream(ds.as_deref(), &cfg.settings, false, &mut NoopValueSink)
+        .map(|(bytes, _)| bytes)
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn transform_chain_runs_before_conversion() {
+    let bytes = build(
+        "transform_scalar",
+        r#"{ name = "Percent", type = "u8", transform = ["round", "clamp(0, 100)"] }"#,
+    )
+    .unwrap();
+    assert_eq!(bytes, [100]);
+
+    let bytes = build(
+        "transform_array",
+        r#"{ name = "Temps", type = "i16", size = 3, transform = ["scale(10)", "round", "clamp(-400, 1250)"] }"#,
+    )
+    .unwrap();
+    let expected: Vec<u8> = [216i16, -32, 1250]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect();
+    assert_eq!(bytes, expected);
+
+    let bytes = build(
+        "transform_value",
+        r#"{ value = 7, type = "u8", transform = ["offset(-10)", "abs"] }"#,
+    )
+    .unwrap();
+    assert_eq!(bytes, [3]);
+}
+
+#[test]
+fn invalid_transforms_are_rejected() {
+    let err = build(
+        "transform_unknown",
+        r#"{ value = 1, type = "u8", transform = ["trunc"] }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("unknown transform 'trunc'"), "{err}");
+
+    let err = build(
+        "transform_string",
+        r#"{ name = "Label", type = "u8", size = 4, transform = ["round"] }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("string"), "{err}");
+
+    let err = build(
+        "transform_hex",
+        r#"{ hex = "0102", type = "u8", transform = ["round"] }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("transform requires"), "{err}");
+}
@@ -0,0 +1,86 @@
+use mint_cli::data::args::DataArgs;
+use mint_cli::data::create_data_source;
+use mint_cli::layout::used_values::NoopValueSink;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const DATA: &str = r#"{
+    "Default": {
+        "States": { "Off": 0, "Standby": 1, "Run": 2, "Fault": 255 },
+        "PowerState": "Run",
+        "Sequence": ["Off", "Standby", "Run"],
+        "Unknown": "Sleep"
+    }
+}"#;
+
+fn build(stem: &str, entry: &str) -> Result<Vec<u8>, String> {
+    let layout = format!(
+        r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x20
+
+[block.data]
+field = {entry}
+"#
+    );
+    let path = common::write_layout_file(stem, &layout);
+    let cfg = mint_cli::layout::load_layout(&path).map_err(|e| e.to_string())?;
+    let args = DataArgs {
+        json: Some(DATA.to_string()),
+        version: Some("Default".to_string()),
+        ..Default::default()
+    };
+    let ds = create_data_source(&args).unwrap();
+    cfg.blocks["block"]
+        .build_bytestream(ds.as_deref(), &cfg.settings, false, &mut NoopValueSink)
+        .map(|(bytes, _)| bytes)
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn strings_are_mapped_through_the_table() {
+    let bytes = build(
+        "value_maps_scalar",
+        r##"{ name = "PowerState", type = "u16", map = "#States" }"##,
+    )
+    .unwrap();
+    assert_eq!(bytes, [2, 0]);
+
+    let bytes = build(
+        "value_maps_array",
+        r##"{ name = "Sequence", type = "u8", size = 4, map = "#States" }"##,
+    )
+    .unwrap();
+    assert_eq!(bytes, [0, 1, 2, 0xFF]);
+
+    let bytes = build(
+        "value_maps_literal",
+        r##"{ value = "Fault", type = "u8", map = "#States" }"##,
+    )
+    .unwrap();
+    assert_eq!(bytes, [255]);
+}
+
+#[test]
+fn unmapped_strings_are_rejected() {
+    let err = build(
+        "value_maps_unknown",
+        r##"{ name = "Unknown", type = "u8", map = "#States" }"##,
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("'Sleep' is not in mapping table 'States'"),
+        "{err}"
+    );
+
+    let err = build(
+        "value_maps_prefix",
+        r#"{ name = "PowerState", type = "u8", map = "States" }"#,
+    )
+    .unwrap_err();
+    assert!(err.contains("'#Table'"), "{err}");
+}
@@ -0,0 +1,75 @@
+use mint_cli::data::args::DataArgs;
+use mint_cli::data::create_data_source;
+use mint_cli::layout::used_values::{ValueCollector, canonical_json};
+use sha2::{Digest, Sha256};
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[cal.header]
+start_address = 0x1000
+length = 0x40
+
+[cal.data]
+params_id = { auto = "values_hash", type = "u64" }
+gain = { name = "Gain", type = "f32" }
+limits = { name = "Limits", type = "u16", size = 3 }
+"#;
+
+fn build(data: &str) -> (Vec<u8>, serde_json::Value) {
+    let path = common::write_layout_file("values_hash", LAYOUT);
+    let cfg = mint_cli::layout::load_layout(&path).unwrap();
+    let args = DataArgs {
+        json: Some(data.to_string()),
+        version: Some("Default".to_string()),
+        ..Default::default()
+    };
+    let ds = create_data_source(&args).unwrap();
+    let mut values = ValueCollector::new();
+    let (bytes, _) = cfg.blocks["cal"]
+        .build_bytestream(ds.as_deref(), &cfg.settings, false, &mut values)
+        .unwrap();
+    (bytes, values.into_value())
+}
+
+#[test]
+fn values_hash_matches_exported_values() {
+    let (bytes, mut values) = build(r#"{"Default": {"Gain": 1.5, "Limits": [1, 2, 3]}}"#);
+
+    let embedded = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+    assert_eq!(values["params_id"], embedded);
+
+    // Verify as a back-office tool would: hash the export without the hash field.
+    values.as_object_mut().unwrap().remove("params_id");
+    let digest = Sha256::digest(canonical_json(&values));
+    assert_eq!(
+        embedded,
+        u64::from_be_bytes(digest[..8].try_into().unwrap())
+    );
+
+    let (other, _) = build(r#"{"Default": {"Gain": 1.5, "Limits": [1, 2, 4]}}"#);
+    assert_ne!(bytes[..8], other[..8]);
+    assert_eq!(bytes[8..14], other[8..14]);
+}
+
+#[test]
+fn values_hash_requires_unsigned_type() {
+    let layout = LAYOUT.replace(
+        r#"{ auto = "values_hash", type = "u64" }"#,
+        r#"{ auto = "values_hash", type = "i32" }"#,
+    );
+    let path = common::write_layout_file("values_hash_signed", &layout);
+    let cfg = mint_cli::layout::load_layout(&path).unwrap();
+    let err = cfg.blocks["cal"]
+        .build_bytestream(
+            None,
+            &cfg.settings,
+            false,
+            &mut mint_cli::layout::used_values::NoopValueSink,
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("unsigned integer"), "{err}");
+}
@@ -87,3 +87,15 @@ fn boolean_cell_retrieves_vara_false() {
 
     assert!(matches!(value, DataValue::Bool(false)));
 }
+
+#[test]
+fn version_of_names_the_providing_column() {
+    let args = build_args("VarA/Debug/Default");
+    let ds = create_data_source(&args)
+        .expect("datasource load")
+        .expect("datasource exists");
+
+    assert_eq!(ds.version_of("TemperatureMax").as_deref(), Some("VarA"));
+    assert_eq!(ds.version_of("Value 2").as_deref(), Some("Debug"));
+    assert_eq!(ds.version_of("NoSuchName"), None);
+}
@@ -0,0 +1,103 @@
+use mint_cli::commands;
+use mint_cli::data::create_data_source;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const DATA: &str = r#"{
+    "Default": { "Id": 1, "Gain": 10 },
+    "VarA": { "Id": 2 },
+    "VarB": { "Gain": 30 }
+}"#;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[block.header]
+start_address = 0x1000
+length = 0x10
+padding = 0x00
+
+[block.data]
+id = { name = "Id", type = "u8" }
+gain = { name = "Gain", type = "u8" }
+"#;
+
+fn matrix_args(stem: &str, matrix: &str) -> mint_cli::args::Args {
+    let mut args = common::layout_args(stem, LAYOUT, "block");
+    args.output.out = format!("out/{}/{{version}}.hex", stem).into();
+    args.data.xlsx = None;
+    args.data.version = None;
+    args.data.json = Some(DATA.to_string());
+    args.data.version_matrix = Some(matrix.to_string());
+    args
+}
+
+#[test]
+fn version_matrix_builds_each_stack() {
+    let args = matrix_args("version_matrix", "Default;VarA/Default;VarB/Default");
+    let ds = create_data_source(&args.data).unwrap();
+    let results = commands::build_matrix(&args, ds.as_deref()).expect("matrix build");
+
+    let stacks: Vec<&str> = results.iter().map(|(s, _)| s.as_str()).collect();
+    assert_eq!(stacks, ["Default", "VarA/Default", "VarB/Default"]);
+
+    for (label, data) in [
+        ("Default", "010A"),
+        ("VarA-Default", "020A"),
+        ("VarB-Default", "011E"),
+    ] {
+        let hex = std::fs::read_to_string(format!("out/version_matrix/{}.hex", label))
+            .expect("per-stack output");
+        assert!(
+            hex.contains(&format!(":02100000{}", data)),
+            "{label}: {hex}"
+        );
+    }
+}
+
+#[test]
+fn version_matrix_reports_failing_stack() {
+    let args = matrix_args("version_matrix_missing", "Default;VarA");
+    let ds = create_data_source(&args.data).unwrap();
+    let err = commands::build_matrix(&args, ds.as_deref())
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("version stack 'VarA'"), "{err}");
+}
+
+#[test]
+fn version_matrix_writes_block_files_per_stack() {
+    let mut args = matrix_args("version_matrix_blocks", "Default;VarA/Default");
+    args.output.block_out_dir = Some("out/version_matrix_blocks/{version}-blocks".into());
+    let ds = create_data_source(&args.data).unwrap();
+    commands::build_matrix(&args, ds.as_deref()).expect("matrix build");
+
+    for (label, data) in [("Default", "010A"), ("VarA-Default", "020A")] {
+        let path = format!("out/version_matrix_blocks/{}-blocks/block.hex", label);
+        let hex = std::fs::read_to_string(&path).expect("per-stack block file");
+        assert!(
+            hex.contains(&format!(":02100000{}", data)),
+            "{label}: {hex}"
+        );
+    }
+}
+
+#[test]
+fn version_matrix_writes_crc_file_per_stack() {
+    let layout = LAYOUT.replace(
+        "padding = 0x00\n",
+        "padding = 0x00\n\n[block.header.crc]\nlocation = \"end_block\"\npolynomial = 0x04C11DB7\nstart = 0xFFFFFFFF\nxor_out = 0xFFFFFFFF\nref_in = true\nref_out = true\narea = \"data\"\n",
+    );
+    let mut args = matrix_args("version_matrix_crc", "Default;VarA/Default");
+    args.layout.blocks[0].file = common::write_layout_file("version_matrix_crc", &layout);
+    args.output.crc_out = Some("out/version_matrix_crc/crc.hex".into());
+    let ds = create_data_source(&args.data).unwrap();
+    commands::build_matrix(&args, ds.as_deref()).expect("matrix build");
+
+    let crc = |label: &str| {
+        std::fs::read_to_string(format!("out/version_matrix_crc/crc_{}.hex", label))
+            .expect("per-stack CRC file")
+    };
+    assert_ne!(crc("Default"), crc("VarA-Default"));
+}
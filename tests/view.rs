@@ -0,0 +1,49 @@
+use mint_cli::commands;
+use mint_cli::output::image::Image;
+use mint_cli::visuals::viewer::Viewer;
+
+#[path = "common/mod.rs"]
+mod common;
+
+const LAYOUT: &str = r#"[settings]
+endianness = "little"
+
+[cal.header]
+start_address = 0x8000
+length = 0x40
+
+[cal.data]
+id = { value = 7, type = "u8" }
+gain = { value = 0x1234, type = "u16" }
+name = { value = "ecu", type = "u8", size = 4 }
+"#;
+
+#[test]
+fn viewer_highlights_selected_field_in_built_image() {
+    let args = common::layout_args("view", LAYOUT, "cal");
+    commands::build(&args, None).expect("build");
+
+    let fields = commands::view_fields(&args, None).expect("fields");
+    let located: Vec<(&str, u64, usize)> = fields
+        .iter()
+        .map(|f| (f.path.as_str(), f.address, f.len))
+        .collect();
+    assert_eq!(
+        located,
+        [("id", 0x8000, 1), ("gain", 0x8002, 2), ("name", 0x8004, 4)]
+    );
+
+    let image = Image::read("out/view.hex".as_ref()).unwrap();
+    let mut viewer = Viewer::new(image, fields);
+    viewer.move_selection(1);
+    let row = &viewer.hex_rows(1)[0];
+    assert_eq!(row.address, 0x8000);
+    let highlighted: Vec<u8> = row
+        .bytes
+        .iter()
+        .zip(&row.selected)
+        .filter(|(_, selected)| **selected)
+        .map(|(byte, _)| byte.unwrap())
+        .collect();
+    assert_eq!(highlighted, [0x34, 0x12]);
+}
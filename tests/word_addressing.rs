@@ -34,15 +34,14 @@ val2 = { value = 0x5678, type = "u16" }
                 file: path,
             }],
             strict: false,
+            ..Default::default()
         },
         data: mint_cli::data::args::DataArgs::default(),
         output: OutputArgs {
             out: PathBuf::from("out/word_addr.hex"),
             record_width: 16,
             format: OutputFormat::Hex,
-            export_json: None,
-            stats: false,
-            quiet: false,
+            ..Default::default()
         },
     };
 
@@ -95,15 +94,14 @@ val2 = { value = 0x5678, type = "u16" }
                 file: path,
             }],
             strict: false,
+            ..Default::default()
         },
         data: mint_cli::data::args::DataArgs::default(),
         output: OutputArgs {
             out: PathBuf::from("out/word_len_words.hex"),
             record_width: 16,
             format: OutputFormat::Hex,
-            export_json: None,
-            stats: false,
-            quiet: false,
+            ..Default::default()
         },
     };
 
@@ -151,15 +149,14 @@ val = { value = 0xABCD, type = "u16" }
                 file: path,
             }],
             strict: false,
+            ..Default::default()
         },
         data: mint_cli::data::args::DataArgs::default(),
         output: OutputArgs {
             out: PathBuf::from("out/word_crc.hex"),
             record_width: 16,
             format: OutputFormat::Hex,
-            export_json: None,
-            stats: false,
-            quiet: false,
+            ..Default::default()
         },
     };
 
@@ -195,15 +192,14 @@ byte_val = { value = 42, type = "u8" }
                 file: path,
             }],
             strict: false,
+            ..Default::default()
         },
         data: mint_cli::data::args::DataArgs::default(),
         output: OutputArgs {
             out: PathBuf::from("out/word_u8_reject.hex"),
             record_width: 16,
             format: OutputFormat::Hex,
-            export_json: None,
-            stats: false,
-            quiet: false,
+            ..Default::default()
         },
     };
 
@@ -243,15 +239,14 @@ text = { value = "HELLO", type = "u8", size = 8 }
                 file: path,
             }],
             strict: false,
+            ..Default::default()
         },
         data: mint_cli::data::args::DataArgs::default(),
         output: OutputArgs {
             out: PathBuf::from("out/word_str_reject.hex"),
             record_width: 16,
             format: OutputFormat::Hex,
-            export_json: None,
-            stats: false,
-            quiet: false,
+            ..Default::default()
         },
     };
 
@@ -286,15 +281,14 @@ val = { value = 0x1234, type = "u16" }
                 file: path,
             }],
             strict: false,
+            ..Default::default()
         },
         data: mint_cli::data::args::DataArgs::default(),
         output: OutputArgs {
             out: PathBuf::from("out/word_voff.hex"),
             record_width: 16,
             format: OutputFormat::Hex,
-            export_json: None,
-            stats: false,
-            quiet: false,
+            ..Default::default()
         },
     };
 
@@ -311,3 +305,44 @@ val = { value = 0x1234, type = "u16" }
         "address should be (0x1000 * 2) + 0x100 = 0x2100"
     );
 }
+
+/// Verifies that 4-byte words quadruple addresses and reverse each word.
+#[test]
+fn word_addressing_32bit_words() {
+    let layout = r#"
+[settings]
+endianness = "little"
+word_addressing = 4
+
+[block.header]
+start_address = 0x1000
+length = 0x10
+padding = 0xFF
+
+[block.data]
+val1 = { value = 0x12345678, type = "u32" }
+val2 = { value = 0x0A0B0C0D, type = "i32" }
+"#;
+    let content =
+        common::build_layout("word_addr_32", layout, "block").expect("build should succeed");
+
+    // start_address 0x1000 * 4 = 0x4000.
+    assert!(content.contains(":08400000"), "{content}");
+    // [78 56 34 12] reversed per 4-byte word.
+    assert!(content.contains("123456780A0B0C0D"), "{content}");
+
+    let narrow = layout.replace(r#"type = "i32""#, r#"type = "i16""#);
+    let err = common::build_layout("word_addr_32_u16", &narrow, "block")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("u16/i16 types are not supported"), "{err}");
+
+    let odd = layout.replace("word_addressing = 4", "word_addressing = 3");
+    let err = common::build_layout("word_addr_odd", &odd, "block")
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("word_addressing must be true, false, 2, or 4"),
+        "{err}"
+    );
+}